@@ -1,3 +1,5 @@
 fn main() {
+    // Library mode has no Tauri context to generate.
+    #[cfg(feature = "app")]
     tauri_build::build()
 }
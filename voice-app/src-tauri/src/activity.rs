@@ -0,0 +1,239 @@
+//! Spoken digest of recent GitHub activity in the configured repositories:
+//! new and closed issues, opened and merged pull requests, and failed
+//! workflow runs, read aloud through TTS. Available as a command and as the
+//! `repo_activity` voice command ("what happened overnight in <repo>?").
+
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::error::CommandError;
+use crate::github::{self, GithubClient};
+use crate::intents::{self, HandlerFuture, Intent};
+use crate::settings::SettingsState;
+use crate::tts;
+
+/// Titles read out per category; the rest are only counted.
+const MAX_TITLES: usize = 3;
+const PER_PAGE: u32 = 100;
+
+#[derive(Default, Serialize)]
+pub struct RepoActivity {
+    pub repo: String,
+    pub opened_issues: Vec<String>,
+    pub closed_issues: usize,
+    pub opened_pulls: Vec<String>,
+    pub merged_pulls: usize,
+    /// `workflow on branch`, once per pair.
+    pub failed_runs: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ActivityReport {
+    pub hours: u32,
+    pub repos: Vec<RepoActivity>,
+    /// What was spoken.
+    pub text: String,
+}
+
+fn since(hours: u32) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    github::iso8601(now.saturating_sub(u64::from(hours) * 3_600))
+}
+
+fn at_or_after(value: &serde_json::Value, since: &str) -> bool {
+    value.as_str().is_some_and(|t| t >= since)
+}
+
+async fn fetch(
+    client: &GithubClient,
+    repo: &str,
+    since: &str,
+) -> Result<RepoActivity, CommandError> {
+    let mut activity = RepoActivity {
+        repo: repo.to_string(),
+        ..Default::default()
+    };
+
+    // The issues endpoint lists pull requests too, marked by `pull_request`.
+    let issues = client
+        .get(&format!(
+            "/repos/{}/issues?state=all&since={}&per_page={}",
+            repo, since, PER_PAGE
+        ))
+        .await?;
+    for item in issues.as_array().into_iter().flatten() {
+        let title = item["title"].as_str().unwrap_or_default().to_string();
+        let opened = at_or_after(&item["created_at"], since);
+        match item.get("pull_request") {
+            Some(pull) => {
+                if opened {
+                    activity.opened_pulls.push(title);
+                }
+                if at_or_after(&pull["merged_at"], since) {
+                    activity.merged_pulls += 1;
+                }
+            }
+            None => {
+                if opened {
+                    activity.opened_issues.push(title);
+                }
+                if at_or_after(&item["closed_at"], since) {
+                    activity.closed_issues += 1;
+                }
+            }
+        }
+    }
+
+    let runs = client
+        .get(&format!(
+            "/repos/{}/actions/runs?status=failure&created=%3E%3D{}&per_page={}",
+            repo, since, PER_PAGE
+        ))
+        .await?;
+    for run in runs["workflow_runs"].as_array().into_iter().flatten() {
+        let name = run["name"].as_str().unwrap_or("a workflow");
+        let failed = match run["head_branch"].as_str() {
+            Some(branch) => format!("{} on {}", name, branch),
+            None => name.to_string(),
+        };
+        if !activity.failed_runs.contains(&failed) {
+            activity.failed_runs.push(failed);
+        }
+    }
+    Ok(activity)
+}
+
+fn count(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+fn titles(titles: &[String]) -> String {
+    let quoted: Vec<String> = titles
+        .iter()
+        .take(MAX_TITLES)
+        .map(|t| format!("\"{}\"", t))
+        .collect();
+    quoted.join(", ")
+}
+
+/// One sentence per repository, e.g. "agentic-devops-loop: 2 new issues,
+/// "Fix login", "Crash on start"; 1 pull request merged; failed: CI on main."
+fn digest(report: &[RepoActivity], hours: u32) -> String {
+    let mut text = format!("In the last {}.", count(hours as usize, "hour", "hours"));
+    for activity in report {
+        let name = activity
+            .repo
+            .split_once('/')
+            .map_or(activity.repo.as_str(), |(_, name)| name);
+        let mut parts = Vec::new();
+        if !activity.opened_issues.is_empty() {
+            let n = activity.opened_issues.len();
+            parts.push(format!(
+                "{}, {}",
+                count(n, "new issue", "new issues"),
+                titles(&activity.opened_issues)
+            ));
+        }
+        if activity.closed_issues > 0 {
+            parts.push(format!(
+                "{} closed",
+                count(activity.closed_issues, "issue", "issues")
+            ));
+        }
+        if !activity.opened_pulls.is_empty() {
+            let n = activity.opened_pulls.len();
+            parts.push(format!(
+                "{} opened, {}",
+                count(n, "pull request", "pull requests"),
+                titles(&activity.opened_pulls)
+            ));
+        }
+        if activity.merged_pulls > 0 {
+            parts.push(format!(
+                "{} merged",
+                count(activity.merged_pulls, "pull request", "pull requests")
+            ));
+        }
+        if !activity.failed_runs.is_empty() {
+            parts.push(format!("failed: {}", activity.failed_runs.join(", ")));
+        }
+        if parts.is_empty() {
+            text.push_str(&format!(" {}: nothing new.", name));
+        } else {
+            text.push_str(&format!(" {}: {}.", name, parts.join("; ")));
+        }
+    }
+    text
+}
+
+/// The configured repository a spoken name refers to: "agentic devops
+/// loop" finds `owner/agentic-devops-loop`. A full `owner/name` is taken as
+/// is.
+fn resolve(repos: &[String], spoken: &str) -> Result<String, String> {
+    let found = repos.iter().find(|repo| {
+        let name = repo.split_once('/').map_or(repo.as_str(), |(_, name)| name);
+        intents::same_name(spoken, name) || intents::same_name(spoken, repo)
+    });
+    match found {
+        Some(repo) => Ok(repo.clone()),
+        None if github::check_repo(spoken).is_ok() => Ok(spoken.to_string()),
+        None => Err(format!("No configured repository called {:?}", spoken)),
+    }
+}
+
+/// Fetch and speak the activity of `repo` (spoken or `owner/name`), or of
+/// every configured repository.
+pub async fn report(
+    app: &AppHandle,
+    repo: Option<&str>,
+    hours: Option<u32>,
+) -> Result<ActivityReport, CommandError> {
+    let settings = app.state::<SettingsState>().get();
+    let repos = match repo {
+        Some(spoken) => vec![resolve(&settings.github.repos, spoken)?],
+        None if settings.github.repos.is_empty() => {
+            return Err("No repositories configured in github.repos".into())
+        }
+        None => settings.github.repos.clone(),
+    };
+    let hours = hours.unwrap_or(settings.github.activity_hours);
+
+    let client = GithubClient::new(&settings.github)?;
+    let since = since(hours);
+    let mut activity = Vec::new();
+    for repo in &repos {
+        github::check_repo(repo)?;
+        activity.push(fetch(&client, repo, &since).await?);
+    }
+
+    let text = digest(&activity, hours);
+    tts::speak(&settings.tts, &text, None);
+    Ok(ActivityReport {
+        hours,
+        repos: activity,
+        text,
+    })
+}
+
+/// Handler for the `repo_activity` action; the `repo` slot is optional.
+pub fn repo_activity_intent(app: AppHandle, intent: Intent, _server_url: String) -> HandlerFuture {
+    Box::pin(async move {
+        let repo = intent.slots.get("repo").map(String::as_str);
+        let report = report(&app, repo, None).await?;
+        Ok(serde_json::json!({ "text": intent.text, "_activity": report }))
+    })
+}
+
+/// Read recent activity aloud. `hours` defaults to `github.activity_hours`.
+#[tauri::command]
+pub async fn read_repo_activity(
+    app: AppHandle,
+    repo: Option<String>,
+    hours: Option<u32>,
+) -> Result<ActivityReport, CommandError> {
+    report(&app, repo.as_deref(), hours).await
+}
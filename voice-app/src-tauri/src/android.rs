@@ -0,0 +1,106 @@
+//! Android glue for the mic path. cpal captures through Oboe, whose input
+//! streams default to the `VOICE_RECOGNITION` preset (tuned for speech,
+//! without the AGC and noise suppression of `VOICE_COMMUNICATION`), so the
+//! capture code is shared with desktop. What Android adds is `RECORD_AUDIO`,
+//! a runtime permission: it is requested at launch and checked again before
+//! every capture. While recording or sending, a foreground service keeps
+//! the process (and its mic access) alive in the background.
+
+use jni::objects::{JObject, JValue};
+use jni::{JNIEnv, JavaVM};
+use tauri::AppHandle;
+use tauri_plugin_background_capture::BackgroundCaptureExt;
+use voice_core::capture::MicError;
+
+use crate::controller::AppState;
+
+const RECORD_AUDIO: &str = "android.permission.RECORD_AUDIO";
+/// For the foreground service's notification (Android 13+). Without it the
+/// service still runs, just without a visible notification.
+const POST_NOTIFICATIONS: &str = "android.permission.POST_NOTIFICATIONS";
+/// `PackageManager.PERMISSION_GRANTED`.
+const PERMISSION_GRANTED: i32 = 0;
+/// Handed back to `onRequestPermissionsResult`, which nothing overrides;
+/// the permission is checked again instead.
+const REQUEST_CODE: i32 = 1;
+
+/// Run `f` against the app's activity on a thread attached to the JVM.
+fn with_activity<T>(
+    f: impl FnOnce(&mut JNIEnv, &JObject) -> jni::errors::Result<T>,
+) -> Result<T, String> {
+    let context = ndk_context::android_context();
+    // SAFETY: both pointers are set by the mobile entry point before any
+    // command or setup code runs, and stay valid for the app's lifetime.
+    let vm = unsafe { JavaVM::from_raw(context.vm().cast()) }
+        .map_err(|e| format!("Java VM unavailable: {}", e))?;
+    let activity = unsafe { JObject::from_raw(context.context().cast()) };
+    let mut env = vm
+        .attach_current_thread()
+        .map_err(|e| format!("Failed to attach to the Java VM: {}", e))?;
+    f(&mut env, &activity).map_err(|e| format!("Android call failed: {}", e))
+}
+
+fn has_record_permission() -> Result<bool, String> {
+    with_activity(|env, activity| {
+        let permission = env.new_string(RECORD_AUDIO)?;
+        let granted = env
+            .call_method(
+                activity,
+                "checkSelfPermission",
+                "(Ljava/lang/String;)I",
+                &[JValue::Object(&permission)],
+            )?
+            .i()?;
+        Ok(granted == PERMISSION_GRANTED)
+    })
+}
+
+/// Show the system prompt for `RECORD_AUDIO` (and `POST_NOTIFICATIONS`)
+/// unless recording is already allowed. Returns without waiting for the
+/// answer.
+pub fn request_record_permission() -> Result<(), String> {
+    if has_record_permission()? {
+        return Ok(());
+    }
+    with_activity(|env, activity| {
+        let record = env.new_string(RECORD_AUDIO)?;
+        let permissions = env.new_object_array(2, "java/lang/String", &record)?;
+        let notify = env.new_string(POST_NOTIFICATIONS)?;
+        env.set_object_array_element(&permissions, 1, notify)?;
+        env.call_method(
+            activity,
+            "requestPermissions",
+            "([Ljava/lang/String;I)V",
+            &[JValue::Object(&permissions), JValue::Int(REQUEST_CODE)],
+        )?;
+        Ok(())
+    })
+}
+
+/// Fail unless `RECORD_AUDIO` is granted, prompting for it again so the
+/// next attempt can succeed. Without it Oboe opens fine but records silence.
+pub fn ensure_record_permission() -> Result<(), MicError> {
+    if has_record_permission().map_err(MicError::Failed)? {
+        return Ok(());
+    }
+    request_record_permission().map_err(MicError::Failed)?;
+    Err(MicError::PermissionDenied(
+        "Permission denied: allow microphone access and try again".into(),
+    ))
+}
+
+/// Run the foreground service while `state` needs the process alive:
+/// recording (including a WebRTC stream) and sending.
+pub fn follow_state(app: &AppHandle, state: AppState) {
+    let service = app.background_capture();
+    let result = match state {
+        AppState::Recording => service.start("Recording", "Listening to the microphone"),
+        AppState::Encoding | AppState::Uploading => {
+            service.start("Sending", "Uploading the recording")
+        }
+        AppState::Idle | AppState::Done | AppState::Error => service.stop(),
+    };
+    if let Err(e) = result {
+        log!("{}", e);
+    }
+}
@@ -1,105 +1,171 @@
-use hound::{SampleFormat, WavSpec, WavWriter};
-use reqwest::multipart;
-use reqwest::StatusCode;
-use std::io::Cursor;
-
-const SAMPLE_RATE: u32 = 16_000;
-
-fn encode_wav(samples: &[i16]) -> Result<Vec<u8>, String> {
-    let spec = WavSpec {
-        channels: 1,
-        sample_rate: SAMPLE_RATE,
-        bits_per_sample: 16,
-        sample_format: SampleFormat::Int,
-    };
-
-    let mut cursor = Cursor::new(Vec::new());
-    {
-        let mut writer =
-            WavWriter::new(&mut cursor, spec).map_err(|e| format!("WAV write error: {}", e))?;
-        for &sample in samples {
-            writer
-                .write_sample(sample)
-                .map_err(|e| format!("WAV sample error: {}", e))?;
+//! The pipeline-server client from `voice_core` with the bearer token and
+//! signing key read from the keychain, and the upload commands.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use voice_core::client::{
+    run_pipeline_text_with_options, transcribe_with_options, upload_wav_hedged_with_options,
+    upload_wav_with_options,
+};
+pub use voice_core::client::{HedgeSettings, RouteCache, TranscribeOptions, UploadError};
+pub use voice_core::wav::{encode_wav, read_wav_file, WavEncoder};
+
+use crate::controller;
+use crate::error::CommandError;
+use crate::secrets;
+use crate::signing::{self, Signer, SigningSettings};
+
+/// Encode on the blocking pool so long recordings don't stall the async
+/// runtime. Setting `cancel` aborts with a `Cancelled` error.
+pub async fn encode_wav_blocking(
+    samples: Vec<i16>,
+    cancel: Arc<AtomicBool>,
+) -> Result<Vec<u8>, CommandError> {
+    let stop = Arc::clone(&cancel);
+    let encoded = tauri::async_runtime::spawn_blocking(move || {
+        voice_core::wav::encode_wav_cancellable(&samples, &stop)
+    })
+    .await
+    .map_err(|e| format!("Encoder task failed: {}", e))?;
+    encoded.map_err(|e| {
+        if cancel.load(Ordering::Relaxed) {
+            CommandError::cancelled(e)
+        } else {
+            e.into()
         }
-        writer
-            .finalize()
-            .map_err(|e| format!("WAV finalize error: {}", e))?;
-    }
+    })
+}
+
+/// The stored bearer token. A keychain that can't be read, e.g. no Secret
+/// Service on a headless Linux box, is logged and counts as no key, so
+/// servers without auth keep working.
+pub fn api_key() -> Option<String> {
+    secrets::get(secrets::PIPELINE_API_KEY).unwrap_or_else(|e| {
+        log!("{}; sending without an API key", e);
+        None
+    })
+}
 
-    Ok(cursor.into_inner())
+/// The bearer token and signer every request carries.
+fn credentials(signing: &SigningSettings) -> Result<(Option<String>, Option<Signer>), UploadError> {
+    let api_key = api_key();
+    let signer = signing::load(signing).map_err(UploadError::Credentials)?;
+    Ok((api_key, signer))
 }
 
-async fn post_audio(
+pub async fn upload_wav(
     client: &reqwest::Client,
-    url: &str,
+    routes: &RouteCache,
     wav_bytes: &[u8],
-) -> Result<reqwest::Response, String> {
-    let part = multipart::Part::bytes(wav_bytes.to_vec())
-        .file_name("recording.wav")
-        .mime_str("audio/wav")
-        .map_err(|e| format!("MIME error: {}", e))?;
-
-    let form = multipart::Form::new().part("audio", part);
-    client
-        .post(url)
-        .multipart(form)
-        .send()
-        .await
-        .map_err(|e| format!("HTTP request failed for {}: {}", url, e))
+    server_url: &str,
+    signing: &SigningSettings,
+    options: &TranscribeOptions,
+) -> Result<serde_json::Value, UploadError> {
+    let (api_key, signer) = credentials(signing)?;
+    upload_wav_with_options(
+        client,
+        routes,
+        wav_bytes,
+        server_url,
+        api_key.as_deref(),
+        signer.as_ref(),
+        options,
+    )
+    .await
 }
 
-async fn parse_json_response(
-    response: reqwest::Response,
-    endpoint_label: &str,
-) -> Result<serde_json::Value, String> {
-    let mut result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("JSON parse error from {}: {}", endpoint_label, e))?;
+/// `upload_wav`, racing a second request per `hedge` (see
+/// `upload_wav_hedged_with_key`).
+pub async fn upload_wav_hedged(
+    client: &reqwest::Client,
+    routes: &RouteCache,
+    wav_bytes: &[u8],
+    server_url: &str,
+    signing: &SigningSettings,
+    hedge: &HedgeSettings,
+    options: &TranscribeOptions,
+) -> Result<serde_json::Value, UploadError> {
+    let (api_key, signer) = credentials(signing)?;
+    upload_wav_hedged_with_options(
+        client,
+        routes,
+        wav_bytes,
+        server_url,
+        api_key.as_deref(),
+        signer.as_ref(),
+        hedge,
+        options,
+    )
+    .await
+}
 
-    if let Some(obj) = result.as_object_mut() {
-        obj.insert(
-            "_endpoint_used".to_string(),
-            serde_json::Value::String(endpoint_label.to_string()),
-        );
-    }
+/// Transcription only, for callers that decide themselves what happens to
+/// the text next.
+pub async fn transcribe_wav(
+    client: &reqwest::Client,
+    wav_bytes: &[u8],
+    server_url: &str,
+    signing: &SigningSettings,
+    options: &TranscribeOptions,
+) -> Result<serde_json::Value, UploadError> {
+    let (api_key, signer) = credentials(signing)?;
+    transcribe_with_options(
+        client,
+        wav_bytes,
+        server_url,
+        api_key.as_deref(),
+        signer.as_ref(),
+        "transcribe",
+        options,
+    )
+    .await
+}
 
-    Ok(result)
+/// Run the server pipeline on text that is already transcribed; `None` when
+/// the server has no pipeline route. The `options` context goes along.
+pub async fn run_pipeline_text(
+    client: &reqwest::Client,
+    text: &str,
+    server_url: &str,
+    signing: &SigningSettings,
+    options: &TranscribeOptions,
+) -> Result<Option<serde_json::Value>, UploadError> {
+    let (api_key, signer) = credentials(signing)?;
+    run_pipeline_text_with_options(
+        client,
+        text,
+        server_url,
+        api_key.as_deref(),
+        signer.as_ref(),
+        options,
+    )
+    .await
 }
 
 #[tauri::command]
-pub async fn send_audio(samples: Vec<i16>, server_url: String) -> Result<serde_json::Value, String> {
-    let wav_bytes = encode_wav(&samples)?;
-    let base_url = server_url.trim_end_matches('/');
-    let pipeline_url = format!("{}/api/pipeline/run/audio", base_url);
-    let transcribe_url = format!("{}/api/transcribe", base_url);
-    let client = reqwest::Client::new();
-
-    let pipeline_response = post_audio(&client, &pipeline_url, &wav_bytes).await?;
-
-    if pipeline_response.status().is_success() {
-        return parse_json_response(pipeline_response, "pipeline_run_audio").await;
-    }
-
-    if pipeline_response.status() != StatusCode::NOT_FOUND
-        && pipeline_response.status() != StatusCode::METHOD_NOT_ALLOWED
-    {
-        let status = pipeline_response.status();
-        let body = pipeline_response.text().await.unwrap_or_default();
-        return Err(format!(
-            "Server error {} on /api/pipeline/run/audio: {}",
-            status, body
-        ));
-    }
-
-    let transcribe_response = post_audio(&client, &transcribe_url, &wav_bytes).await?;
+pub async fn send_audio(
+    app: tauri::AppHandle,
+    samples: Vec<i16>,
+    server_url: String,
+) -> Result<serde_json::Value, CommandError> {
+    controller::send(&app, samples.into(), &server_url).await
+}
 
-    if !transcribe_response.status().is_success() {
-        let status = transcribe_response.status();
-        let body = transcribe_response.text().await.unwrap_or_default();
-        return Err(format!("Server error {}: {}", status, body));
-    }
+/// Stop recording and upload at once, reporting `send-progress` as it goes.
+#[tauri::command]
+pub async fn stop_and_send(
+    app: tauri::AppHandle,
+    server_url: String,
+) -> Result<serde_json::Value, CommandError> {
+    controller::stop_and_send(&app, &server_url).await
+}
 
-    parse_json_response(transcribe_response, "transcribe_fallback").await
+/// Upload the recording kept by the last `stop_mic`.
+#[tauri::command]
+pub async fn send_last_recording(
+    app: tauri::AppHandle,
+    server_url: String,
+) -> Result<serde_json::Value, CommandError> {
+    controller::send_last(&app, &server_url).await
 }
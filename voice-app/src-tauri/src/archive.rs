@@ -0,0 +1,187 @@
+//! Portable backups of the voice-note history: a `.tar.gz` holding a
+//! snapshot of the history database and the saved recordings, for moving
+//! to another machine or keeping a copy. Importing merges into what is
+//! already there instead of replacing it.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::error::CommandError;
+use crate::history::HistoryState;
+use crate::queue;
+
+/// Bumped when the layout changes; newer archives are refused.
+const FORMAT_VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DB_ENTRY: &str = "history.sqlite3";
+const RECORDINGS_ENTRY: &str = "recordings";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    format: u32,
+    /// Unix time in milliseconds.
+    exported_at: u128,
+}
+
+#[derive(Serialize)]
+pub struct ArchiveSummary {
+    transcripts: usize,
+    recordings: usize,
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default()
+}
+
+/// Scratch directory for one export or import, removed on drop.
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn new(purpose: &str) -> Result<Self, String> {
+        let dir =
+            std::env::temp_dir().join(format!("agentic-devops-voice-{}-{}", purpose, now_millis()));
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn recording_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| queue::RECORDING_EXTENSIONS.iter().any(|e| ext == *e))
+        })
+        .collect()
+}
+
+pub fn export(app: &AppHandle, path: &Path) -> Result<ArchiveSummary, String> {
+    let scratch = Scratch::new("export")?;
+    let db = scratch.0.join(DB_ENTRY);
+    let history = app.state::<HistoryState>();
+    history.snapshot(&db)?;
+    let transcripts = history.count()?;
+
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let write_err = |e: std::io::Error| format!("Failed to write {}: {}", path.display(), e);
+
+    let manifest = serde_json::to_vec_pretty(&Manifest {
+        format: FORMAT_VERSION,
+        exported_at: now_millis(),
+    })
+    .map_err(|e| e.to_string())?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, MANIFEST_ENTRY, manifest.as_slice())
+        .map_err(write_err)?;
+    tar.append_path_with_name(&db, DB_ENTRY)
+        .map_err(write_err)?;
+
+    let recordings = recording_files(&queue::recordings_dir(app)?);
+    for recording in &recordings {
+        let Some(name) = recording.file_name() else {
+            continue;
+        };
+        tar.append_path_with_name(recording, Path::new(RECORDINGS_ENTRY).join(name))
+            .map_err(write_err)?;
+    }
+
+    tar.into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(write_err)?;
+    Ok(ArchiveSummary {
+        transcripts,
+        recordings: recordings.len(),
+    })
+}
+
+pub fn import(app: &AppHandle, path: &Path) -> Result<ArchiveSummary, String> {
+    let scratch = Scratch::new("import")?;
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    // `unpack` refuses entries that would land outside the scratch dir.
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(&scratch.0)
+        .map_err(|e| format!("Not a history archive ({}): {}", path.display(), e))?;
+
+    let manifest: Manifest = std::fs::read(scratch.0.join(MANIFEST_ENTRY))
+        .map_err(|e| e.to_string())
+        .and_then(|raw| serde_json::from_slice(&raw).map_err(|e| e.to_string()))
+        .map_err(|e| format!("Archive has no valid manifest: {}", e))?;
+    if manifest.format > FORMAT_VERSION {
+        return Err(format!(
+            "Archive format {} is newer than this app supports ({})",
+            manifest.format, FORMAT_VERSION
+        ));
+    }
+
+    let transcripts = app
+        .state::<HistoryState>()
+        .merge_from(&scratch.0.join(DB_ENTRY))?;
+
+    // Recordings are named by timestamp, so an existing name is the same
+    // recording and is kept as is.
+    let target = queue::recordings_dir(app)?;
+    let mut recordings = 0;
+    for recording in recording_files(&scratch.0.join(RECORDINGS_ENTRY)) {
+        let Some(name) = recording.file_name() else {
+            continue;
+        };
+        let dest = target.join(name);
+        if dest.exists() {
+            continue;
+        }
+        std::fs::copy(&recording, &dest)
+            .map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+        recordings += 1;
+    }
+
+    #[cfg(feature = "embeddings")]
+    crate::embeddings::index_in_background(app);
+
+    Ok(ArchiveSummary {
+        transcripts,
+        recordings,
+    })
+}
+
+/// Write the history and recordings to a `.tar.gz` at `path`.
+#[tauri::command]
+pub async fn export_history(app: AppHandle, path: PathBuf) -> Result<ArchiveSummary, CommandError> {
+    let summary = tauri::async_runtime::spawn_blocking(move || export(&app, &path))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))??;
+    Ok(summary)
+}
+
+/// Merge an archive made by `export_history` into this machine's history.
+/// Returns what was new.
+#[tauri::command]
+pub async fn import_history(app: AppHandle, path: PathBuf) -> Result<ArchiveSummary, CommandError> {
+    let summary = tauri::async_runtime::spawn_blocking(move || import(&app, &path))
+        .await
+        .map_err(|e| format!("Import task failed: {}", e))??;
+    Ok(summary)
+}
@@ -0,0 +1,457 @@
+//! OAuth2 device authorization flow (RFC 8628) for the pipeline server and
+//! GitHub, so nobody has to paste a raw token into settings: the app shows a
+//! short code, the user approves it in a browser, and the token is polled
+//! for and stored in the keychain under the name the rest of the app
+//! already reads (`pipeline_api_key` or `github_token`). Tokens that expire
+//! are refreshed shortly before they do, and again when the server rejects
+//! one; `reauth-required` means the refresh token was refused and the user
+//! has to sign in again.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::{CommandError, ErrorCode};
+use crate::events;
+use crate::net;
+use crate::secrets;
+use crate::settings::{Settings, SettingsState};
+
+const DEVICE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+/// RFC 8628 §3.5: poll every 5 s unless told otherwise, 5 s slower per
+/// `slow_down`.
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+const SLOW_DOWN_SECS: u64 = 5;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Tokens expiring within this are refreshed by the background check.
+const REFRESH_MARGIN_SECS: u64 = 300;
+const REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const PROVIDERS: [AuthProvider; 2] = [AuthProvider::Pipeline, AuthProvider::Github];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthProvider {
+    Pipeline,
+    Github,
+}
+
+impl AuthProvider {
+    /// Where the access token goes.
+    pub fn token_secret(self) -> &'static str {
+        match self {
+            AuthProvider::Pipeline => secrets::PIPELINE_API_KEY,
+            AuthProvider::Github => secrets::GITHUB_TOKEN,
+        }
+    }
+
+    /// Where the refresh token and expiry go.
+    pub fn session_secret(self) -> &'static str {
+        match self {
+            AuthProvider::Pipeline => secrets::PIPELINE_OAUTH,
+            AuthProvider::Github => secrets::GITHUB_OAUTH,
+        }
+    }
+}
+
+/// One provider's OAuth client. Unset fields fall back to the provider's
+/// defaults; URLs starting with `/` are relative to `server_url`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceFlowSettings {
+    pub client_id: Option<String>,
+    pub device_url: Option<String>,
+    pub token_url: Option<String>,
+    pub scope: Option<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthSettings {
+    /// Defaults to the `agentic-devops-voice` client at the server's
+    /// `/oauth/device/code` and `/oauth/token`.
+    pub pipeline: DeviceFlowSettings,
+    /// Needs the client id of a GitHub OAuth app with device flow enabled.
+    pub github: DeviceFlowSettings,
+}
+
+/// A provider's settings with the defaults filled in.
+struct Client {
+    client_id: String,
+    device_url: String,
+    token_url: String,
+    scope: String,
+}
+
+fn client(settings: &Settings, provider: AuthProvider) -> Result<Client, String> {
+    let (flow, client_id, device_url, token_url, scope) = match provider {
+        AuthProvider::Pipeline => (
+            &settings.auth.pipeline,
+            Some("agentic-devops-voice"),
+            "/oauth/device/code",
+            "/oauth/token",
+            "",
+        ),
+        AuthProvider::Github => (
+            &settings.auth.github,
+            None,
+            "https://github.com/login/device/code",
+            "https://github.com/login/oauth/access_token",
+            "repo",
+        ),
+    };
+    let absolute = |url: &str| match url.strip_prefix('/') {
+        Some(path) => format!("{}/{}", settings.server_url.trim_end_matches('/'), path),
+        None => url.to_string(),
+    };
+    Ok(Client {
+        client_id: flow
+            .client_id
+            .clone()
+            .or(client_id.map(str::to_string))
+            .ok_or("No OAuth client id; set auth.github.client_id")?,
+        device_url: absolute(flow.device_url.as_deref().unwrap_or(device_url)),
+        token_url: absolute(flow.token_url.as_deref().unwrap_or(token_url)),
+        scope: flow.scope.clone().unwrap_or_else(|| scope.to_string()),
+    })
+}
+
+/// What the user needs to approve the login.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeviceCode {
+    #[serde(skip_serializing)]
+    device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    /// The URI with the code filled in, when the provider offers one.
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    #[serde(default)]
+    pub interval: Option<u64>,
+}
+
+/// Stored as JSON under the provider's `session_secret`.
+#[derive(Default, Serialize, Deserialize)]
+struct Session {
+    refresh_token: Option<String>,
+    /// Unix seconds; `None` for tokens that don't expire.
+    expires_at: Option<u64>,
+}
+
+/// Any token endpoint response. Errors come back as JSON too, with 400 per
+/// the RFC and with 200 from GitHub.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+impl TokenResponse {
+    fn error_message(&self) -> String {
+        match (&self.error, &self.error_description) {
+            (Some(error), Some(description)) => format!("{}: {}", error, description),
+            (Some(error), None) => error.clone(),
+            _ => "No access token in response".into(),
+        }
+    }
+}
+
+/// A device-flow login finished, successfully or not.
+#[derive(Clone, Serialize)]
+pub struct AuthComplete {
+    provider: AuthProvider,
+    error: Option<String>,
+}
+
+impl events::Event for AuthComplete {
+    const NAME: &'static str = "auth-complete";
+}
+
+/// A stored token was refused or could not be refreshed.
+#[derive(Clone, Serialize)]
+pub struct ReauthRequired {
+    provider: AuthProvider,
+    error: String,
+}
+
+impl events::Event for ReauthRequired {
+    const NAME: &'static str = "reauth-required";
+}
+
+#[derive(Default)]
+pub struct AuthState {
+    /// Logins waiting for approval, one per provider.
+    pending: Mutex<HashMap<AuthProvider, JoinHandle<()>>>,
+    /// Held while refreshing, so a rotated refresh token is never used
+    /// twice.
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// POST a form with `Accept: application/json`, which GitHub needs to
+/// answer in JSON rather than urlencoded.
+async fn post_form<T: for<'de> Deserialize<'de>>(
+    http: &reqwest::Client,
+    url: &str,
+    form: &[(&str, &str)],
+) -> Result<T, CommandError> {
+    let response = http
+        .post(url)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(form)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| {
+            let code = if e.is_timeout() {
+                ErrorCode::ServerTimeout
+            } else {
+                ErrorCode::ServerUnreachable
+            };
+            CommandError::new(code, format!("HTTP request failed for {}: {}", url, e))
+        })?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    serde_json::from_str(&body).map_err(|_| {
+        let code = if status.is_server_error() {
+            ErrorCode::ServerUnavailable
+        } else {
+            ErrorCode::ServerRejected
+        };
+        CommandError::new(
+            code,
+            format!("Auth error {} from {}: {}", status, url, body),
+        )
+    })
+}
+
+fn session(provider: AuthProvider) -> Result<Option<Session>, String> {
+    match secrets::get(provider.session_secret())? {
+        Some(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Bad {} secret: {}", provider.session_secret(), e)),
+        None => Ok(None),
+    }
+}
+
+/// Keep the tokens from a successful token response.
+fn store(provider: AuthProvider, token: &TokenResponse) -> Result<(), String> {
+    let access_token = token
+        .access_token
+        .as_deref()
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| token.error_message())?;
+    secrets::set(provider.token_secret(), access_token)?;
+    let session = Session {
+        refresh_token: token.refresh_token.clone(),
+        expires_at: token.expires_in.map(|secs| now_secs() + secs),
+    };
+    let json = serde_json::to_string(&session).map_err(|e| e.to_string())?;
+    Ok(secrets::set(provider.session_secret(), &json)?)
+}
+
+async fn poll(
+    http: &reqwest::Client,
+    client: &Client,
+    code: &DeviceCode,
+) -> Result<TokenResponse, String> {
+    let deadline = now_secs() + code.expires_in;
+    let mut interval = code.interval.unwrap_or(DEFAULT_INTERVAL_SECS).max(1);
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        if now_secs() >= deadline {
+            return Err("The login code expired before it was approved".into());
+        }
+        let token: TokenResponse = post_form(
+            http,
+            &client.token_url,
+            &[
+                ("grant_type", DEVICE_GRANT),
+                ("device_code", &code.device_code),
+                ("client_id", &client.client_id),
+            ],
+        )
+        .await?;
+        match token.error.as_deref() {
+            None => return Ok(token),
+            Some("authorization_pending") => {}
+            Some("slow_down") => interval += SLOW_DOWN_SECS,
+            Some(_) => return Err(token.error_message()),
+        }
+    }
+}
+
+/// Request a code, open the verification page, and poll for the token in
+/// the background. The result arrives as `auth-complete`, with `error` set
+/// if the login failed.
+pub async fn start(app: &AppHandle, provider: AuthProvider) -> Result<DeviceCode, CommandError> {
+    let settings = app.state::<SettingsState>().get();
+    let client = client(&settings, provider)?;
+    let mut form = vec![("client_id", client.client_id.as_str())];
+    if !client.scope.is_empty() {
+        form.push(("scope", &client.scope));
+    }
+    let http = net::client(app);
+    let code: DeviceCode = post_form(&http, &client.device_url, &form).await?;
+
+    let page = code
+        .verification_uri_complete
+        .as_ref()
+        .unwrap_or(&code.verification_uri);
+    if let Err(e) = open::that_detached(page) {
+        log!("Failed to open {}: {}", page, e);
+    }
+    // For headless mode, where nothing shows the code.
+    log!(
+        "To sign in, enter {} at {}",
+        code.user_code,
+        code.verification_uri
+    );
+
+    let polling = {
+        let app = app.clone();
+        let code = code.clone();
+        tauri::async_runtime::spawn(async move {
+            let result = poll(&http, &client, &code)
+                .await
+                .and_then(|token| store(provider, &token));
+            if let Err(e) = &result {
+                log!("Device login failed: {}", e);
+            }
+            events::emit(
+                &app,
+                &AuthComplete {
+                    provider,
+                    error: result.err(),
+                },
+            );
+        })
+    };
+    let state = app.state::<AuthState>();
+    if let Ok(mut pending) = state.pending.lock() {
+        if let Some(previous) = pending.insert(provider, polling) {
+            previous.abort();
+        }
+    }
+    Ok(code)
+}
+
+/// Refresh `provider`'s access token if it expires soon or, with
+/// `rejected`, if it is still the token the server just refused. `Ok(true)`
+/// when there is a newer token to retry with. Without a device-flow login
+/// there is nothing to refresh and this is `Ok(false)`.
+pub async fn refresh(
+    app: &AppHandle,
+    provider: AuthProvider,
+    rejected: Option<&str>,
+) -> Result<bool, String> {
+    let state = app.state::<AuthState>();
+    let _refreshing = state.refreshing.lock().await;
+    let Some(session) = session(provider)? else {
+        return Ok(false);
+    };
+    let current = secrets::get(provider.token_secret())?;
+    match rejected {
+        // Someone else refreshed while the request was in flight.
+        Some(rejected) if current.as_deref() != Some(rejected) => return Ok(current.is_some()),
+        Some(_) => {}
+        None if session
+            .expires_at
+            .is_none_or(|at| at > now_secs() + REFRESH_MARGIN_SECS) =>
+        {
+            return Ok(false)
+        }
+        None => {}
+    }
+
+    let reauth = |error: String| {
+        log!("Sign-in needed: {}", error);
+        events::emit(
+            app,
+            &ReauthRequired {
+                provider,
+                error: error.clone(),
+            },
+        );
+        Err(error)
+    };
+    let Some(refresh_token) = session.refresh_token else {
+        return reauth("The login has no refresh token".into());
+    };
+    let settings = app.state::<SettingsState>().get();
+    let client = client(&settings, provider)?;
+    // Transport errors are left to the next attempt; only a refusal from
+    // the token endpoint means signing in again.
+    let mut token: TokenResponse = post_form(
+        &net::client(app),
+        &client.token_url,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", &refresh_token),
+            ("client_id", &client.client_id),
+        ],
+    )
+    .await?;
+    if token.error.is_some() || token.access_token.is_none() {
+        return reauth(token.error_message());
+    }
+    // Providers that don't rotate refresh tokens leave it out.
+    token.refresh_token.get_or_insert(refresh_token);
+    store(provider, &token)?;
+    Ok(true)
+}
+
+/// Refresh expiring tokens in the background for as long as the app runs.
+pub fn start_refresh(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            for provider in PROVIDERS {
+                if let Err(e) = refresh(&app, provider, None).await {
+                    log!("Token refresh failed: {}", e);
+                }
+            }
+            tokio::time::sleep(REFRESH_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Start a device-flow login; returns the code to show the user.
+#[tauri::command]
+pub async fn start_device_login(
+    app: AppHandle,
+    provider: AuthProvider,
+) -> Result<DeviceCode, CommandError> {
+    start(&app, provider).await
+}
+
+/// Stop waiting for a login started with `start_device_login`.
+#[tauri::command]
+pub fn cancel_device_login(auth: State<'_, AuthState>, provider: AuthProvider) {
+    if let Some(polling) = auth
+        .pending
+        .lock()
+        .ok()
+        .and_then(|mut p| p.remove(&provider))
+    {
+        polling.abort();
+    }
+}
+
+/// Forget the provider's tokens.
+#[tauri::command]
+pub fn logout(provider: AuthProvider) -> Result<(), CommandError> {
+    secrets::delete(provider.token_secret())?;
+    secrets::delete(provider.session_secret())
+}
@@ -0,0 +1,32 @@
+use tauri::{App, AppHandle};
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+
+/// Login launches go straight to the tray so the hotkey loop is available
+/// without a window popping up after every reboot.
+const LOGIN_ARGS: &[&str] = &["--headless"];
+
+pub fn register(app: &App, enabled: bool) -> Result<(), Box<dyn std::error::Error>> {
+    app.handle().plugin(tauri_plugin_autostart::init(
+        MacosLauncher::LaunchAgent,
+        Some(LOGIN_ARGS.to_vec()),
+    ))?;
+
+    // The OS entry can drift from settings (removed by the user, or a moved
+    // binary), so reconcile on every start.
+    if let Err(e) = apply(app.handle(), enabled) {
+        log!("{}", e);
+    }
+    Ok(())
+}
+
+pub fn apply(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let launcher = app.autolaunch();
+    let result = if enabled {
+        launcher.enable()
+    } else if launcher.is_enabled().unwrap_or(false) {
+        launcher.disable()
+    } else {
+        Ok(())
+    };
+    result.map_err(|e| format!("Failed to update autostart: {}", e))
+}
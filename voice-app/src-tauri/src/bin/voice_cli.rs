@@ -0,0 +1,111 @@
+//! Record from the default microphone, send it to the pipeline, print the
+//! JSON response.
+//!
+//!     voice-cli [--seconds N] [--server URL] [--api-key KEY]
+//!
+//! Captures and uploads with the same `voice_core` code as the desktop app:
+//! the pipeline route first, falling back to `/api/transcribe` on servers
+//! without it. Unlike the app, requests are neither signed nor hedged and
+//! no vocabulary or context goes along. Builds without the `app` feature.
+//! `VOICE_SERVER_URL` and `VOICE_API_KEY` set the default server and bearer
+//! token.
+
+use std::process::ExitCode;
+use std::time::Duration;
+use voice_core::capture::Capture;
+use voice_core::client::{upload_wav_with_options, RouteCache, TranscribeOptions};
+
+const DEFAULT_SERVER_URL: &str = "http://localhost:8000";
+const DEFAULT_SECONDS: f64 = 5.0;
+
+const USAGE: &str = "usage: voice-cli [--seconds N] [--server URL] [--api-key KEY]";
+
+struct Args {
+    seconds: f64,
+    server_url: String,
+    api_key: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        seconds: DEFAULT_SECONDS,
+        server_url: std::env::var("VOICE_SERVER_URL")
+            .unwrap_or_else(|_| DEFAULT_SERVER_URL.to_string()),
+        api_key: std::env::var("VOICE_API_KEY").ok(),
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-s" | "--seconds" => {
+                let value = iter.next().ok_or("--seconds needs a value")?;
+                args.seconds = value
+                    .parse()
+                    .map_err(|_| format!("Invalid --seconds value: {}", value))?;
+                if !args.seconds.is_finite() || args.seconds <= 0.0 {
+                    return Err("--seconds must be positive".into());
+                }
+            }
+            "-u" | "--server" => {
+                args.server_url = iter.next().ok_or("--server needs a value")?;
+            }
+            "-k" | "--api-key" => {
+                args.api_key = Some(iter.next().ok_or("--api-key needs a value")?);
+            }
+            "-h" | "--help" => return Err(USAGE.into()),
+            other => return Err(format!("Unknown argument: {}\n{}", other, USAGE)),
+        }
+    }
+    Ok(args)
+}
+
+async fn run(args: Args) -> Result<serde_json::Value, String> {
+    let mic = Capture::default();
+
+    mic.start(&Default::default(), &Default::default())
+        .map_err(|e| e.to_string())?;
+    eprintln!("Recording for {:.1}s...", args.seconds);
+    tokio::time::sleep(Duration::from_secs_f64(args.seconds)).await;
+    let samples = mic.stop().map_err(|e| e.to_string())?.samples;
+
+    eprintln!("Sending {} samples to {}", samples.len(), args.server_url);
+    let wav_bytes = voice_core::wav::encode_wav(&samples)?;
+    // Unsigned: request signing is configured in the app's settings.
+    let client = reqwest::Client::new();
+    upload_wav_with_options(
+        &client,
+        &RouteCache::default(),
+        &wav_bytes,
+        &args.server_url,
+        args.api_key.as_deref(),
+        None,
+        &TranscribeOptions::default(),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args).await {
+        Ok(result) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&result).unwrap_or_else(|_| result.to_string())
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
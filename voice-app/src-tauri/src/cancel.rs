@@ -0,0 +1,43 @@
+//! One cancellation hierarchy for everything in flight. Each recording,
+//! send and WebRTC stream runs under a child of the current root token, so
+//! `cancel_all` reaches all of them at once; a fresh root is started for
+//! whatever comes after.
+
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tokio_util::sync::CancellationToken;
+
+use crate::uploads::UploadWorker;
+
+#[derive(Default)]
+pub struct Cancellation(Mutex<CancellationToken>);
+
+impl Cancellation {
+    /// A token cancelled by the next `cancel_all`.
+    pub fn child(&self) -> CancellationToken {
+        self.0
+            .lock()
+            .map(|root| root.child_token())
+            .unwrap_or_default()
+    }
+
+    fn cancel_root(&self) {
+        if let Ok(mut root) = self.0.lock() {
+            std::mem::take(&mut *root).cancel();
+        }
+    }
+}
+
+/// Drop queued sends, then cancel the recording, the send in progress and
+/// any stream. Each of them settles its own state as it unwinds.
+pub fn cancel_everything(app: &AppHandle) {
+    app.state::<UploadWorker>().cancel_queued();
+    app.state::<Cancellation>().cancel_root();
+}
+
+/// Abort everything in flight: the recording (its audio is discarded),
+/// encoding, uploads queued or on the wire, and a WebRTC stream.
+#[tauri::command]
+pub fn cancel_all(app: AppHandle) {
+    cancel_everything(&app);
+}
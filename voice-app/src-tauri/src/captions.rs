@@ -0,0 +1,246 @@
+//! Live captions in a small always-on-top window that floats over other
+//! apps, e.g. during a meeting: the partial transcripts the server sends
+//! over the WebRTC data channel as they arrive, and the transcript of each
+//! upload once it is back. Uploads have no interim results, so captions
+//! that follow the speaker need the `webrtc` feature; without it only
+//! finished lines show. The window is managed from
+//! Rust: a self-contained page loaded from a data URL and updated with
+//! `eval`, so it works in headless mode without loading the frontend.
+//! Shown and hidden with `toggle_captions` or `captions.hotkey`.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use crate::error::CommandError;
+use crate::settings::SettingsState;
+
+const LABEL: &str = "captions";
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 120.0;
+
+/// Dragged by its background; `setCaptions` replaces the text.
+const PAGE: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+html, body { margin: 0; height: 100%; overflow: hidden; cursor: move;
+  background: #111; color: #fff; font-family: system-ui, sans-serif; }
+#captions { position: absolute; left: 0; right: 0; bottom: 0;
+  padding: 8px 16px; line-height: 1.3; }
+.done { opacity: 0.6; }
+</style>
+</head>
+<body data-tauri-drag-region>
+<div id="captions" data-tauri-drag-region></div>
+<script>
+window.setCaptions = function (lines, partial, size) {
+  var root = document.getElementById("captions");
+  root.style.fontSize = size + "px";
+  root.textContent = "";
+  lines.concat(partial ? [partial] : []).forEach(function (text, i) {
+    var line = document.createElement("div");
+    line.textContent = text;
+    if (i < lines.length) line.className = "done";
+    line.setAttribute("data-tauri-drag-region", "");
+    root.appendChild(line);
+  });
+};
+</script>
+</body>
+</html>"#;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptionSettings {
+    /// Global shortcut that shows or hides the captions window.
+    pub hotkey: Option<String>,
+    /// Finished lines kept above the one being spoken.
+    pub history: usize,
+    pub font_size: u32,
+}
+
+impl Default for CaptionSettings {
+    fn default() -> Self {
+        Self {
+            hotkey: None,
+            history: 1,
+            font_size: 24,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Captions {
+    /// Finished lines, oldest first.
+    lines: Vec<String>,
+    /// The line still being spoken.
+    partial: Option<String>,
+}
+
+#[derive(Default)]
+pub struct CaptionState {
+    captions: Mutex<Captions>,
+    hotkey: Mutex<Option<Shortcut>>,
+}
+
+/// `html` as a `data:` URL, percent-encoding everything but unreserved
+/// characters.
+fn data_url(html: &str) -> String {
+    let mut url = String::from("data:text/html;charset=utf-8,");
+    for byte in html.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            url.push(byte as char);
+        } else {
+            url.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    url
+}
+
+/// Push the current captions to the window, if it is open.
+fn render(app: &AppHandle, settings: &CaptionSettings) {
+    let Some(window) = app.get_webview_window(LABEL) else {
+        return;
+    };
+    let script = {
+        let state = app.state::<CaptionState>();
+        let Ok(captions) = state.captions.lock() else {
+            return;
+        };
+        format!(
+            "window.setCaptions && window.setCaptions({}, {}, {})",
+            serde_json::Value::from(captions.lines.clone()),
+            serde_json::Value::from(captions.partial.clone()),
+            settings.font_size
+        )
+    };
+    if let Err(e) = window.eval(&script) {
+        log!("Failed to update captions: {}", e);
+    }
+}
+
+/// Show a data-channel message: its `text` (or the message itself, when it
+/// is a string) as the line being spoken, moved up into the finished lines
+/// when `is_final` or `final` is set. Kept while the window is hidden, so
+/// showing it picks up where the conversation is.
+#[cfg(feature = "webrtc")]
+pub fn show_partial(app: &AppHandle, data: &serde_json::Value) {
+    let Some(text) = data["text"].as_str().or(data.as_str()) else {
+        return;
+    };
+    let is_final = data["is_final"]
+        .as_bool()
+        .or(data["final"].as_bool())
+        .unwrap_or(false);
+    push(app, text, is_final);
+}
+
+/// Show the transcript of an upload as a finished line.
+pub fn show_final(app: &AppHandle, text: &str) {
+    push(app, text, true);
+}
+
+fn push(app: &AppHandle, text: &str, is_final: bool) {
+    let settings = app.state::<SettingsState>().get().captions;
+    {
+        let state = app.state::<CaptionState>();
+        let Ok(mut captions) = state.captions.lock() else {
+            return;
+        };
+        if is_final {
+            captions.partial = None;
+            captions.lines.push(text.to_string());
+            let excess = captions.lines.len().saturating_sub(settings.history);
+            captions.lines.drain(..excess);
+        } else {
+            captions.partial = Some(text.to_string());
+        }
+    }
+    render(app, &settings);
+}
+
+fn open(app: &AppHandle) -> Result<(), String> {
+    let url = data_url(PAGE)
+        .parse()
+        .map_err(|e| format!("Invalid captions page: {}", e))?;
+    WebviewWindowBuilder::new(app, LABEL, WebviewUrl::External(url))
+        .title("Captions")
+        .inner_size(WIDTH, HEIGHT)
+        .always_on_top(true)
+        .visible_on_all_workspaces(true)
+        .decorations(false)
+        .skip_taskbar(true)
+        .focused(false)
+        .build()
+        .map_err(|e| format!("Failed to open captions window: {}", e))?;
+    Ok(())
+}
+
+/// Show the captions window, creating it on first use, or hide it.
+/// Returns whether it is now shown.
+pub fn toggle(app: &AppHandle) -> Result<bool, String> {
+    let err = |e: tauri::Error| format!("Captions window: {}", e);
+    match app.get_webview_window(LABEL) {
+        Some(window) if window.is_visible().map_err(err)? => {
+            window.hide().map_err(err)?;
+            return Ok(false);
+        }
+        Some(window) => window.show().map_err(err)?,
+        None => open(app)?,
+    }
+    render(app, &app.state::<SettingsState>().get().captions);
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn toggle_captions(app: AppHandle) -> Result<bool, CommandError> {
+    Ok(toggle(&app)?)
+}
+
+fn bind(app: &AppHandle, key: Option<&str>) {
+    let shortcut = key.and_then(|key| match key.parse::<Shortcut>() {
+        Ok(shortcut) => Some(shortcut),
+        Err(e) => {
+            log!("Invalid captions hotkey {}: {}", key, e);
+            None
+        }
+    });
+    if let Some(shortcut) = shortcut {
+        if let Err(e) = app.global_shortcut().register(shortcut) {
+            log!("Failed to register captions hotkey: {}", e);
+        }
+    }
+    if let Ok(mut hotkey) = app.state::<CaptionState>().hotkey.lock() {
+        *hotkey = shortcut;
+    }
+}
+
+pub fn register(app: &AppHandle, settings: &CaptionSettings) {
+    bind(app, settings.hotkey.as_deref());
+}
+
+/// Rebind the hotkey and redraw with the new settings.
+pub fn apply(app: &AppHandle, previous: &CaptionSettings, settings: &CaptionSettings) {
+    if previous.hotkey != settings.hotkey {
+        if let Some(key) = &previous.hotkey {
+            let _ = app.global_shortcut().unregister(key.as_str());
+        }
+        bind(app, settings.hotkey.as_deref());
+    }
+    render(app, settings);
+}
+
+/// Whether `shortcut` is the captions hotkey rather than a recording one.
+pub fn is_hotkey(app: &AppHandle, shortcut: &Shortcut) -> bool {
+    let Some(state) = app.try_state::<CaptionState>() else {
+        return false;
+    };
+    let matches = state
+        .hotkey
+        .lock()
+        .is_ok_and(|hotkey| hotkey.as_ref() == Some(shortcut));
+    matches
+}
@@ -0,0 +1,161 @@
+//! Circuit breaker per server, so a server that is down fails sends at once
+//! instead of making every attempt wait out the full request timeout. After
+//! `circuit.failure_threshold` outages in a row (transport errors and 5xx;
+//! a 4xx means the server is up) the circuit opens and calls fail with
+//! `CircuitOpen`. Every `circuit.open_secs` one call is let through as a
+//! probe: success closes the circuit, another outage keeps it open.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::CommandError;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitSettings {
+    pub enabled: bool,
+    /// Outages in a row that open the circuit.
+    pub failure_threshold: u32,
+    /// How long an open circuit fails fast before the next probe.
+    pub open_secs: u64,
+}
+
+impl Default for CircuitSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failure_threshold: 3,
+            open_secs: 30,
+        }
+    }
+}
+
+/// The error for a call that was not attempted.
+#[derive(Debug)]
+pub struct CircuitOpen {
+    pub endpoint: String,
+    /// Until the next probe; zero while one is in flight.
+    pub retry_in: Duration,
+}
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.retry_in.is_zero() {
+            write!(f, "{} is not responding; checking again", self.endpoint)
+        } else {
+            write!(
+                f,
+                "{} is not responding; retrying in {}s",
+                self.endpoint,
+                self.retry_in.as_secs().max(1)
+            )
+        }
+    }
+}
+
+enum Circuit {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A probe is in flight; one that never finishes (its future dropped)
+    /// is given up on after `open_secs`.
+    HalfOpen {
+        since: Instant,
+    },
+}
+
+#[derive(Default)]
+pub struct CircuitBreakers(Mutex<HashMap<String, Circuit>>);
+
+fn key(endpoint: &str) -> String {
+    endpoint.trim_end_matches('/').to_string()
+}
+
+impl CircuitBreakers {
+    /// `Ok` if a call to `endpoint` may go ahead, possibly as the probe.
+    fn check(&self, settings: &CircuitSettings, endpoint: &str) -> Result<(), CircuitOpen> {
+        if !settings.enabled {
+            return Ok(());
+        }
+        let Ok(mut circuits) = self.0.lock() else {
+            return Ok(());
+        };
+        let open_for = Duration::from_secs(settings.open_secs);
+        let now = Instant::now();
+        let Some(circuit) = circuits.get_mut(&key(endpoint)) else {
+            return Ok(());
+        };
+        match *circuit {
+            Circuit::Closed { .. } => Ok(()),
+            Circuit::Open { until } if now < until => Err(CircuitOpen {
+                endpoint: key(endpoint),
+                retry_in: until - now,
+            }),
+            Circuit::HalfOpen { since } if now < since + open_for => Err(CircuitOpen {
+                endpoint: key(endpoint),
+                retry_in: Duration::ZERO,
+            }),
+            Circuit::Open { .. } | Circuit::HalfOpen { .. } => {
+                *circuit = Circuit::HalfOpen { since: now };
+                Ok(())
+            }
+        }
+    }
+
+    /// Record how a call that `check` let through went.
+    fn record(&self, settings: &CircuitSettings, endpoint: &str, outage: bool) {
+        let Ok(mut circuits) = self.0.lock() else {
+            return;
+        };
+        if !outage {
+            circuits.remove(&key(endpoint));
+            return;
+        }
+        let circuit = circuits
+            .entry(key(endpoint))
+            .or_insert(Circuit::Closed { failures: 0 });
+        let failures = match *circuit {
+            Circuit::Closed { failures } => failures + 1,
+            // A failed probe reopens at once.
+            Circuit::Open { .. } | Circuit::HalfOpen { .. } => u32::MAX,
+        };
+        *circuit = if failures >= settings.failure_threshold.max(1) {
+            log!(
+                "{} is failing; pausing requests for {}s",
+                key(endpoint),
+                settings.open_secs
+            );
+            Circuit::Open {
+                until: Instant::now() + Duration::from_secs(settings.open_secs),
+            }
+        } else {
+            Circuit::Closed { failures }
+        };
+    }
+
+    /// Run `call` against `endpoint` through its breaker.
+    pub async fn call<T, F>(
+        &self,
+        settings: &CircuitSettings,
+        endpoint: &str,
+        call: F,
+    ) -> Result<T, CommandError>
+    where
+        F: Future<Output = Result<T, CommandError>>,
+    {
+        self.check(settings, endpoint)?;
+        let result = call.await;
+        let outage = result.as_ref().is_err_and(|e| e.code.is_outage());
+        if settings.enabled {
+            self.record(settings, endpoint, outage);
+        }
+        result
+    }
+}
@@ -0,0 +1,55 @@
+use tauri::AppHandle;
+use zbus::fdo;
+
+use super::Command;
+
+const BUS_NAME: &str = "dev.agentic.devops.Voice";
+const OBJECT_PATH: &str = "/dev/agentic/devops/Voice";
+
+struct VoiceControl {
+    app: AppHandle,
+}
+
+impl VoiceControl {
+    async fn run(&self, command: Command) -> fdo::Result<String> {
+        super::execute(&self.app, command)
+            .await
+            .map(|result| result.to_string())
+            .map_err(|e| fdo::Error::Failed(e.message))
+    }
+}
+
+/// `busctl --user call dev.agentic.devops.Voice /dev/agentic/devops/Voice
+/// dev.agentic.devops.Voice1 Start`. Every method returns a JSON string.
+#[zbus::interface(name = "dev.agentic.devops.Voice1")]
+impl VoiceControl {
+    async fn start(&self) -> fdo::Result<String> {
+        self.run(Command::Start).await
+    }
+
+    async fn stop(&self) -> fdo::Result<String> {
+        self.run(Command::Stop).await
+    }
+
+    async fn status(&self) -> fdo::Result<String> {
+        self.run(Command::Status).await
+    }
+
+    async fn send(&self) -> fdo::Result<String> {
+        self.run(Command::Send).await
+    }
+}
+
+pub async fn serve(app: AppHandle) -> Result<(), String> {
+    let _connection = zbus::connection::Builder::session()
+        .and_then(|b| b.name(BUS_NAME))
+        .and_then(|b| b.serve_at(OBJECT_PATH, VoiceControl { app }))
+        .map_err(|e| format!("D-Bus setup failed: {}", e))?
+        .build()
+        .await
+        .map_err(|e| format!("D-Bus connection failed: {}", e))?;
+
+    // The object is served for as long as the connection lives.
+    std::future::pending::<()>().await;
+    Ok(())
+}
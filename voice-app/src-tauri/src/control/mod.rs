@@ -0,0 +1,89 @@
+//! External control of recording for window managers, Stream Decks and
+//! scripts: D-Bus on Linux, a local socket (named pipe on Windows) elsewhere.
+//! Both transports accept the same four commands: start, stop, status, send.
+
+#[cfg(target_os = "linux")]
+mod dbus;
+#[cfg(not(target_os = "linux"))]
+mod socket;
+
+use serde_json::json;
+use std::str::FromStr;
+use tauri::{AppHandle, Manager};
+
+use crate::controller::{self, AppState, Controller};
+use crate::error::CommandError;
+use crate::settings::SettingsState;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Command {
+    Start,
+    Stop,
+    Status,
+    Send,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "start" => Ok(Command::Start),
+            "stop" => Ok(Command::Stop),
+            "status" => Ok(Command::Status),
+            "send" => Ok(Command::Send),
+            other => Err(format!("Unknown command: {}", other)),
+        }
+    }
+}
+
+/// Stop and keep the samples for a following `send`.
+fn stop(app: &AppHandle) -> Result<usize, CommandError> {
+    let recording = controller::stop_recording(app)?;
+    let count = recording.samples.len();
+    app.state::<Controller>().keep_recording(Some(recording));
+    Ok(count)
+}
+
+pub async fn execute(app: &AppHandle, command: Command) -> Result<serde_json::Value, CommandError> {
+    match command {
+        Command::Start => {
+            controller::start_recording(app)?;
+            Ok(json!({ "recording": true }))
+        }
+        Command::Stop => {
+            let samples = stop(app)?;
+            Ok(json!({ "recording": false, "samples": samples }))
+        }
+        Command::Status => {
+            let controller = app.state::<Controller>();
+            Ok(json!({
+                "state": controller.state(),
+                "has_recording": controller.has_recording(),
+            }))
+        }
+        Command::Send => {
+            // `send` while recording means "stop and send".
+            if app.state::<Controller>().state() == AppState::Recording {
+                stop(app)?;
+            }
+            let server_url = app.state::<SettingsState>().get().server_url;
+            controller::send_last(app, &server_url).await
+        }
+    }
+}
+
+/// Start the platform transport in the background.
+pub fn spawn(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        #[cfg(target_os = "linux")]
+        let result = dbus::serve(app).await;
+        #[cfg(not(target_os = "linux"))]
+        let result = socket::serve(app).await;
+
+        if let Err(e) = result {
+            log!("Control interface unavailable: {}", e);
+        }
+    });
+}
@@ -0,0 +1,88 @@
+//! Line protocol: one command per line, one JSON reply per line
+//! (`{"ok":true,"result":{..}}` or `{"ok":false,"error":".."}`).
+
+use serde_json::json;
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use super::Command;
+
+async fn serve_client<S>(app: AppHandle, stream: S)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match line.parse::<Command>() {
+            Ok(command) => match super::execute(&app, command).await {
+                Ok(result) => json!({ "ok": true, "result": result }),
+                Err(e) => json!({ "ok": false, "error": e.message, "code": e.code }),
+            },
+            Err(e) => json!({ "ok": false, "error": e }),
+        };
+        if writer
+            .write_all(format!("{}\n", reply).as_bytes())
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// `echo start | nc -U "~/Library/Application Support/dev.agentic.devops.voice/control.sock"`
+#[cfg(unix)]
+pub async fn serve(app: AppHandle) -> Result<(), String> {
+    use tauri::Manager;
+    use tokio::net::UnixListener;
+
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("No data directory: {}", e))?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    let path = dir.join("control.sock");
+    // A socket left behind by a crashed instance would make bind fail.
+    let _ = std::fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path)
+        .map_err(|e| format!("Failed to bind {}: {}", path.display(), e))?;
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(serve_client(app.clone(), stream));
+            }
+            Err(e) => log!("Control socket accept failed: {}", e),
+        }
+    }
+}
+
+/// `\\.\pipe\agentic-devops-voice`
+#[cfg(windows)]
+pub async fn serve(app: AppHandle) -> Result<(), String> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    const PIPE_NAME: &str = r"\\.\pipe\agentic-devops-voice";
+
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(PIPE_NAME)
+        .map_err(|e| format!("Failed to create {}: {}", PIPE_NAME, e))?;
+    loop {
+        server
+            .connect()
+            .await
+            .map_err(|e| format!("Named pipe connect failed: {}", e))?;
+        // Hand the connected instance off and open the next one for new clients.
+        let connected = server;
+        server = ServerOptions::new()
+            .create(PIPE_NAME)
+            .map_err(|e| format!("Failed to create {}: {}", PIPE_NAME, e))?;
+        tokio::spawn(serve_client(app.clone(), connected));
+    }
+}
@@ -0,0 +1,583 @@
+//! Single owner of the app lifecycle. Every trigger (UI commands, tray,
+//! hotkey, control socket, deep links) goes through these functions so that
+//! overlapping requests — stop during upload, a second start — are rejected
+//! by the state machine instead of racing on separate flags. The one overlap
+//! allowed is recording while a send runs: the send's phase is tracked apart
+//! from the state, and a send that starts during a recording, or is
+//! overtaken by one, leaves the state to it and reports through
+//! `send-progress` and `get_upload_status` alone.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
+use tokio_util::sync::CancellationToken;
+use voice_core::controller::can_transition;
+
+pub use voice_core::controller::AppState;
+
+use crate::api::{self, RouteCache};
+use crate::auth::{self, AuthProvider};
+use crate::cancel::Cancellation;
+use crate::circuit::CircuitBreakers;
+use crate::crash;
+use crate::cues::{self, Cue};
+use crate::devices::AudioSource;
+use crate::ducking;
+use crate::error::{CommandError, ErrorCode};
+use crate::events;
+use crate::history;
+use crate::incidents;
+use crate::input_volume;
+use crate::intents;
+use crate::metrics;
+use crate::mic::{self, CaptureSettings, MicError, MicState, Recording, TARGET_SAMPLE_RATE};
+use crate::net;
+use crate::review::{self, ReviewState};
+use crate::secrets;
+use crate::settings::SettingsState;
+use crate::summary;
+use crate::terminal;
+use crate::transcription;
+use crate::uploads;
+
+/// Phases of a send, reported as `send-progress` with the time since the
+/// send began, so the UI can show where the wait goes.
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum SendStage {
+    /// Stream teardown and resampling; only for `stop_and_send`.
+    Stopping,
+    Encoding,
+    Uploading,
+    Done,
+    Failed,
+}
+
+#[derive(Clone, Serialize)]
+pub struct SendProgressPayload {
+    stage: SendStage,
+    elapsed_ms: u64,
+}
+
+impl events::Event for SendProgressPayload {
+    const NAME: &'static str = "send-progress";
+}
+
+fn progress(app: &AppHandle, stage: SendStage, started: Instant) {
+    events::emit(
+        app,
+        &SendProgressPayload {
+            stage,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        },
+    );
+}
+
+#[derive(Clone, Serialize)]
+pub struct StateChangedPayload {
+    state: AppState,
+    previous: AppState,
+    error: Option<String>,
+    /// `error`'s code, for a localized message.
+    error_code: Option<ErrorCode>,
+}
+
+impl events::Event for StateChangedPayload {
+    const NAME: &'static str = "app-state-changed";
+}
+
+pub struct Controller {
+    state: Mutex<AppState>,
+    /// Phase of the send the upload worker is running: `Encoding` or
+    /// `Uploading`, `None` between sends.
+    send_phase: Mutex<Option<AppState>>,
+    /// The running send left `state` to a recording. Written under the
+    /// `state` lock.
+    send_detached: AtomicBool,
+    /// Encoded audio of the upload in flight, so shutdown can queue it.
+    pending_upload: Mutex<Option<Arc<Vec<u8>>>>,
+    /// The send in progress; cancelled by `cancel_send` or `cancel_all`.
+    send_token: Mutex<CancellationToken>,
+    /// The recording in progress. Taken by `stop_recording`, so a
+    /// `cancel_all` that finds it here still has a recording to discard.
+    recording_token: Mutex<Option<CancellationToken>>,
+    /// The language the recording in progress was started for; moved onto
+    /// the `Recording` when it stops.
+    language: Mutex<Option<String>>,
+    /// The last stop that asked to keep its audio, so a later send can
+    /// upload without the audio crossing IPC again.
+    last_recording: Mutex<Option<Recording>>,
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(AppState::Idle),
+            send_phase: Mutex::new(None),
+            send_detached: AtomicBool::new(false),
+            pending_upload: Mutex::new(None),
+            send_token: Mutex::new(CancellationToken::new()),
+            recording_token: Mutex::new(None),
+            language: Mutex::new(None),
+            last_recording: Mutex::new(None),
+        }
+    }
+}
+
+impl Controller {
+    pub fn state(&self) -> AppState {
+        self.state.lock().map(|s| *s).unwrap_or(AppState::Error)
+    }
+
+    /// Phase of the running send, whether or not it drives the state.
+    pub fn send_phase(&self) -> Option<AppState> {
+        self.send_phase.lock().ok().and_then(|p| *p)
+    }
+
+    fn transition(
+        &self,
+        app: &AppHandle,
+        to: AppState,
+        error: Option<&CommandError>,
+    ) -> Result<(), CommandError> {
+        self.change(app, to, error, false)
+    }
+
+    /// Move the running send to `to`: `Encoding` as it starts, `Uploading`,
+    /// then `Done`, `Idle` or `Error`. The state follows unless a recording
+    /// holds it.
+    fn send_transition(
+        &self,
+        app: &AppHandle,
+        to: AppState,
+        error: Option<&CommandError>,
+    ) -> Result<(), CommandError> {
+        if let Ok(mut phase) = self.send_phase.lock() {
+            *phase = Some(to).filter(|s| matches!(s, AppState::Encoding | AppState::Uploading));
+        }
+        self.change(app, to, error, true)
+    }
+
+    fn change(
+        &self,
+        app: &AppHandle,
+        to: AppState,
+        error: Option<&CommandError>,
+        send: bool,
+    ) -> Result<(), CommandError> {
+        let previous = {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            if send {
+                if to == AppState::Encoding {
+                    self.send_detached
+                        .store(*state == AppState::Recording, Ordering::Relaxed);
+                }
+                if self.send_detached.load(Ordering::Relaxed) {
+                    #[cfg(target_os = "android")]
+                    crate::android::follow_state(app, self.service_state(*state));
+                    return Ok(());
+                }
+            }
+            // A recording over a running send, which carries on unseen.
+            let overtakes = !send
+                && to == AppState::Recording
+                && matches!(*state, AppState::Encoding | AppState::Uploading);
+            if !can_transition(*state, to) && !overtakes {
+                return Err(CommandError::invalid_state(format!(
+                    "Cannot go from {:?} to {:?}",
+                    *state, to
+                )));
+            }
+            if overtakes {
+                self.send_detached.store(true, Ordering::Relaxed);
+            }
+            std::mem::replace(&mut *state, to)
+        };
+        crash::note("app_state", format!("{:?}", to));
+        ducking::follow_state(to);
+        #[cfg(target_os = "android")]
+        crate::android::follow_state(app, self.service_state(to));
+        if let Some(error) = error {
+            crash::note("last_error", error.message.clone());
+        }
+
+        events::emit(
+            app,
+            &StateChangedPayload {
+                state: to,
+                previous,
+                error: error.map(|e| e.message.clone()),
+                error_code: error.map(|e| e.code),
+            },
+        );
+        Ok(())
+    }
+
+    /// Token for a new send, under the app's cancellation root.
+    fn new_send_token(&self, app: &AppHandle) -> CancellationToken {
+        let token = app.state::<Cancellation>().child();
+        if let Ok(mut current) = self.send_token.lock() {
+            *current = token.clone();
+        }
+        token
+    }
+
+    fn take_recording_token(&self) -> Option<CancellationToken> {
+        self.recording_token.lock().ok().and_then(|mut t| t.take())
+    }
+
+    fn set_pending_upload(&self, wav_bytes: Option<Arc<Vec<u8>>>) {
+        if let Ok(mut pending) = self.pending_upload.lock() {
+            *pending = wav_bytes;
+        }
+    }
+
+    pub fn take_pending_upload(&self) -> Option<Arc<Vec<u8>>> {
+        self.pending_upload.lock().ok().and_then(|mut p| p.take())
+    }
+
+    pub fn keep_recording(&self, recording: Option<Recording>) {
+        if let Ok(mut last) = self.last_recording.lock() {
+            *last = recording;
+        }
+    }
+
+    pub fn has_recording(&self) -> bool {
+        self.last_recording
+            .lock()
+            .map(|r| r.is_some())
+            .unwrap_or(false)
+    }
+
+    /// A copy of the kept samples, for playing them back.
+    pub fn kept_samples(&self) -> Option<Vec<i16>> {
+        self.last_recording
+            .lock()
+            .ok()
+            .and_then(|r| r.as_ref().map(|r| r.samples.clone()))
+    }
+
+    fn take_recording(&self) -> Option<Recording> {
+        self.last_recording.lock().ok().and_then(|mut r| r.take())
+    }
+
+    /// What keeps the process alive: the recording, else a send still
+    /// running behind the state.
+    #[cfg(target_os = "android")]
+    fn service_state(&self, state: AppState) -> AppState {
+        if state == AppState::Recording {
+            return state;
+        }
+        self.send_phase().unwrap_or(state)
+    }
+
+    fn fail(&self, app: &AppHandle, error: &CommandError) {
+        if let Err(e) = self.transition(app, AppState::Error, Some(error)) {
+            log!("Failed to report {}: {}", error, e);
+        }
+    }
+
+    fn fail_send(&self, app: &AppHandle, error: &CommandError) {
+        if let Err(e) = self.send_transition(app, AppState::Error, Some(error)) {
+            log!("Failed to report {}: {}", error, e);
+        }
+    }
+}
+
+/// Record from the source configured in settings.
+pub fn start_recording(app: &AppHandle) -> Result<(), CommandError> {
+    let settings = app.state::<SettingsState>().get();
+    start_recording_with(app, &settings.source, &settings.capture)
+}
+
+/// Record from `source` with `capture` in place of the configured ones.
+pub fn start_recording_with(
+    app: &AppHandle,
+    source: &AudioSource,
+    capture: &CaptureSettings,
+) -> Result<(), CommandError> {
+    start_recording_in(app, source, capture, None)
+}
+
+/// `start_recording_with`, transcribed in `language` (e.g. `sv`) instead of
+/// the one the server detects.
+pub fn start_recording_in(
+    app: &AppHandle,
+    source: &AudioSource,
+    capture: &CaptureSettings,
+    language: Option<String>,
+) -> Result<(), CommandError> {
+    mic::ensure_access()?;
+    let controller = app.state::<Controller>();
+    controller.transition(app, AppState::Recording, None)?;
+
+    app.state::<MicState>()
+        .main()
+        .start(source, capture)
+        .map_err(CommandError::from)
+        .inspect_err(|e| controller.fail(app, e))?;
+    let token = app.state::<Cancellation>().child();
+    if let Ok(mut slot) = controller.recording_token.lock() {
+        *slot = Some(token.clone());
+    }
+    if let Ok(mut slot) = controller.language.lock() {
+        *slot = language;
+    }
+    watch_recording(app, token);
+    input_volume::warn_if_silent(app, source);
+    Ok(())
+}
+
+/// Stop and discard the recording when `cancel_all` reaches it. A normal
+/// stop cancels the token too, after taking it, which only ends the wait.
+fn watch_recording(app: &AppHandle, token: CancellationToken) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        token.cancelled().await;
+        let cancelled = app
+            .state::<Controller>()
+            .recording_token
+            .lock()
+            .ok()
+            .and_then(|mut t| t.take_if(|t| t.is_cancelled()));
+        if cancelled.is_some() {
+            let _ = stop_recording(&app);
+        }
+    });
+}
+
+pub fn stop_recording(app: &AppHandle) -> Result<Recording, CommandError> {
+    let recording = stop(app)?;
+    // A recording from another source than the standby one closed it.
+    mic::apply_standby(app);
+    Ok(recording)
+}
+
+/// `stop_recording` without reopening the standby stream, for exit.
+pub fn stop_recording_on_exit(app: &AppHandle) -> Result<Recording, CommandError> {
+    stop(app)
+}
+
+/// Stop the capture, then leave `Recording`: for `Idle` with the audio, or
+/// for `Error` when the device failed to stop.
+fn stop(app: &AppHandle) -> Result<Recording, CommandError> {
+    let controller = app.state::<Controller>();
+    let state = controller.state();
+    if state != AppState::Recording {
+        return Err(CommandError::invalid_state(format!(
+            "Cannot stop while {:?}",
+            state
+        )));
+    }
+    if let Some(token) = controller.take_recording_token() {
+        token.cancel();
+    }
+
+    let mut recording = match app.state::<MicState>().main().stop() {
+        Ok(recording) => recording,
+        // A concurrent stop got there first and moves the state itself.
+        Err(MicError::NotRecording) => return Err(MicError::NotRecording.into()),
+        Err(e) => {
+            let e = CommandError::from(e);
+            controller.fail(app, &e);
+            return Err(e);
+        }
+    };
+    controller.transition(app, AppState::Idle, None)?;
+    recording.language = controller.language.lock().ok().and_then(|mut l| l.take());
+    Ok(recording)
+}
+
+async fn upload(
+    app: &AppHandle,
+    wav_bytes: &[u8],
+    server_url: &str,
+    language: Option<&str>,
+) -> Result<serde_json::Value, CommandError> {
+    let settings = app.state::<SettingsState>().get();
+    let mut options = transcription::options_with_context(app).await;
+    options.language = language.map(str::to_string);
+    let upload = async {
+        if app.state::<ReviewState>().is_armed() {
+            review::upload(app, wav_bytes, server_url, &options).await
+        } else if settings.intents.enabled {
+            intents::upload(app, wav_bytes, server_url, &options).await
+        } else {
+            api::upload_wav_hedged(
+                &net::client(app),
+                &app.state::<RouteCache>(),
+                wav_bytes,
+                server_url,
+                &settings.signing,
+                &settings.hedge,
+                &options,
+            )
+            .await
+            .map_err(CommandError::from)
+        }
+    };
+    app.state::<CircuitBreakers>()
+        .call(&settings.circuit, server_url, upload)
+        .await
+}
+
+/// Encode and upload a finished recording, reporting each phase. The send
+/// waits behind any earlier ones on the upload worker. Encoding is skipped
+/// when the recording was already encoded during capture.
+pub async fn send(
+    app: &AppHandle,
+    recording: Recording,
+    server_url: &str,
+) -> Result<serde_json::Value, CommandError> {
+    uploads::submit(app, recording, server_url, Instant::now()).await
+}
+
+/// One send, as run by the upload worker.
+pub async fn run_send(
+    app: &AppHandle,
+    recording: Recording,
+    server_url: &str,
+    started: Instant,
+) -> Result<serde_json::Value, CommandError> {
+    let controller = app.state::<Controller>();
+    controller.send_transition(app, AppState::Encoding, None)?;
+    progress(app, SendStage::Encoding, started);
+    let cancel = controller.new_send_token(app);
+    let duration_secs = (recording.samples.len() / TARGET_SAMPLE_RATE as usize) as u32;
+    let language = recording.language;
+
+    let encoded = match recording.wav {
+        Some(wav_bytes) => Ok(wav_bytes),
+        None => {
+            let stop_encoder = Arc::new(AtomicBool::new(false));
+            let encode = api::encode_wav_blocking(recording.samples, Arc::clone(&stop_encoder));
+            tokio::select! {
+                encoded = encode => encoded,
+                _ = cancel.cancelled() => {
+                    // The encoder stops at its next chunk.
+                    stop_encoder.store(true, Ordering::Relaxed);
+                    Err(CommandError::cancelled("Encoding cancelled"))
+                }
+            }
+        }
+    };
+    let mut result = match encoded {
+        Ok(wav_bytes) => {
+            let wav_bytes = Arc::new(wav_bytes);
+            controller.set_pending_upload(Some(Arc::clone(&wav_bytes)));
+            controller.send_transition(app, AppState::Uploading, None)?;
+            progress(app, SendStage::Uploading, started);
+            let token = secrets::get(AuthProvider::Pipeline.token_secret()).unwrap_or_default();
+            let uploaded = async {
+                match upload(app, &wav_bytes, server_url, language.as_deref()).await {
+                    // One retry when a device-flow token was refused and
+                    // could be refreshed.
+                    Err(e) if e.code == ErrorCode::AuthExpired => {
+                        match auth::refresh(app, AuthProvider::Pipeline, token.as_deref()).await {
+                            Ok(true) => {
+                                upload(app, &wav_bytes, server_url, language.as_deref()).await
+                            }
+                            _ => Err(e),
+                        }
+                    }
+                    result => result,
+                }
+            };
+            tokio::select! {
+                result = uploaded => result,
+                _ = cancel.cancelled() => Err(CommandError::cancelled("Upload cancelled")),
+            }
+        }
+        Err(e) => Err(e),
+    };
+    controller.set_pending_upload(None);
+
+    match &mut result {
+        Ok(result) => {
+            incidents::record_success(app);
+            metrics::record_upload(app, result);
+            history::attach_confidence(result);
+            transcription::attach_alternatives(result);
+            transcription::format(app, result);
+            #[cfg(desktop)]
+            if let Some(text) = history::transcript_text(result) {
+                crate::captions::show_final(app, text);
+            }
+            let transcript_id = history::record(app, result);
+            summary::attach(app, result, transcript_id, duration_secs).await;
+            terminal::inject(app, result).await;
+            transcription::remember(app, result, transcript_id);
+            controller.send_transition(app, AppState::Done, None)?;
+            progress(app, SendStage::Done, started);
+        }
+        Err(_) if cancel.is_cancelled() => {
+            controller.send_transition(app, AppState::Idle, None)?;
+        }
+        Err(e) => {
+            controller.fail_send(app, e);
+            progress(app, SendStage::Failed, started);
+            cues::play(app, Cue::Error);
+            incidents::record_failure(app, &e.message);
+        }
+    }
+    result
+}
+
+/// Upload the kept recording. It is consumed either way, matching the
+/// frontend, which drops its preview copy before sending.
+pub async fn send_last(
+    app: &AppHandle,
+    server_url: &str,
+) -> Result<serde_json::Value, CommandError> {
+    let recording = app
+        .state::<Controller>()
+        .take_recording()
+        .ok_or_else(|| CommandError::invalid_state("Nothing recorded to send"))?;
+    send(app, recording, server_url).await
+}
+
+/// Stop recording and send in one go, for push-to-talk. The connection to
+/// the server is opened while the stream is torn down and the audio
+/// resampled and encoded, so the upload starts on a warm connection.
+pub async fn stop_and_send(
+    app: &AppHandle,
+    server_url: &str,
+) -> Result<serde_json::Value, CommandError> {
+    let started = Instant::now();
+    progress(app, SendStage::Stopping, started);
+    net::connect(app, server_url);
+
+    let handle = app.clone();
+    let recording = tauri::async_runtime::spawn_blocking(move || stop_recording(&handle))
+        .await
+        .map_err(|e| format!("Stop task failed: {}", e))?
+        .inspect_err(|_| progress(app, SendStage::Failed, started))?;
+    uploads::submit(app, recording, server_url, started).await
+}
+
+/// Abort a send that is still encoding. Uploads already on the wire are not
+/// affected.
+#[tauri::command]
+pub fn cancel_send(controller: State<'_, Controller>) -> Result<(), CommandError> {
+    if controller.send_phase() != Some(AppState::Encoding) {
+        return Err(CommandError::invalid_state("Nothing is being encoded"));
+    }
+    controller
+        .send_token
+        .lock()
+        .map_err(|e| e.to_string())?
+        .cancel();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn discard_recording(controller: State<'_, Controller>) {
+    controller.keep_recording(None);
+}
+
+#[tauri::command]
+pub fn get_app_state(controller: State<'_, Controller>) -> AppState {
+    controller.state()
+}
@@ -0,0 +1,216 @@
+//! Crash reports. `install` sets a panic hook before the app is built, so a
+//! panic in setup is caught too, that writes the panic, its backtrace, a
+//! snapshot of the app state and the recent log lines to `crashes/` in the
+//! data directory. Later launches offer the reports through
+//! `get_crash_reports` until the frontend dismisses them.
+//!
+//! The hook runs on the panicking thread, which may hold any lock, so it
+//! only `try_lock`s and reads nothing through the `AppHandle`.
+
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::collections::{BTreeMap, VecDeque};
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::CommandError;
+
+const CRASH_DIR: &str = "crashes";
+/// Log lines kept for the next report.
+const LOG_LINES: usize = 200;
+/// Reports kept on disk; older ones are deleted at launch.
+const REPORTS_KEPT: usize = 10;
+/// Suffix of reports the user has dismissed.
+const SEEN_SUFFIX: &str = ".seen.json";
+
+static DIR: OnceLock<PathBuf> = OnceLock::new();
+static LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static SNAPSHOT: Mutex<BTreeMap<&'static str, String>> = Mutex::new(BTreeMap::new());
+
+#[derive(Serialize, Deserialize)]
+struct CrashReport {
+    /// When it panicked, in Unix milliseconds.
+    time_ms: u64,
+    version: String,
+    thread: String,
+    message: String,
+    location: Option<String>,
+    backtrace: String,
+    state: BTreeMap<String, String>,
+    log: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct CrashSummary {
+    /// The full report; pass it to `dismiss_crash_report`.
+    path: String,
+    time_ms: u64,
+    message: String,
+    location: Option<String>,
+}
+
+/// Print `line` to stderr and keep it for a crash report. Use through
+/// `log!`.
+pub fn log(line: String) {
+    eprintln!("{}", line);
+    if let Ok(mut log) = LOG.lock() {
+        if log.len() == LOG_LINES {
+            log.pop_front();
+        }
+        log.push_back(line);
+    }
+}
+
+/// Record `value` under `key` in the state snapshot written with a crash.
+pub fn note(key: &'static str, value: impl Into<String>) {
+    if let Ok(mut snapshot) = SNAPSHOT.lock() {
+        snapshot.insert(key, value.into());
+    }
+}
+
+/// Install the panic hook, writing reports under the data directory of the
+/// app `identifier` (the same directory `app_data_dir` resolves to later).
+pub fn install(identifier: &str) {
+    if let Some(dir) = dirs::data_dir() {
+        let _ = DIR.set(dir.join(identifier).join(CRASH_DIR));
+    }
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write(info) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+        previous(info);
+    }));
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn write(info: &PanicHookInfo) -> Result<PathBuf, String> {
+    let dir = DIR.get().ok_or("No data directory")?;
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string());
+    let report = CrashReport {
+        time_ms: now_ms(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        thread: std::thread::current()
+            .name()
+            .unwrap_or("<unnamed>")
+            .to_string(),
+        message,
+        location: info.location().map(|l| l.to_string()),
+        backtrace: Backtrace::force_capture().to_string(),
+        state: SNAPSHOT
+            .try_lock()
+            .map(|s| s.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+            .unwrap_or_default(),
+        log: LOG
+            .try_lock()
+            .map(|log| log.iter().cloned().collect())
+            .unwrap_or_default(),
+    };
+
+    let path = dir.join(format!("crash-{}.json", report.time_ms));
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Reports in `dir`, oldest first.
+fn reports(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("crash-") && name.ends_with(".json"))
+        })
+        .collect();
+    // Names carry the timestamp.
+    paths.sort_by_key(|path| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.trim_start_matches("crash-").split('.').next())
+            .and_then(|millis| millis.parse::<u64>().ok())
+    });
+    paths
+}
+
+fn is_seen(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(SEEN_SUFFIX)
+}
+
+/// Delete all but the last few reports left by earlier runs.
+pub fn prune() {
+    let Some(dir) = DIR.get() else {
+        return;
+    };
+    let paths = reports(dir);
+    for path in paths.iter().take(paths.len().saturating_sub(REPORTS_KEPT)) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Reports left by earlier runs and not dismissed yet, oldest first.
+#[tauri::command]
+pub fn get_crash_reports() -> Vec<CrashSummary> {
+    let Some(dir) = DIR.get() else {
+        return Vec::new();
+    };
+    reports(dir)
+        .into_iter()
+        .filter(|path| !is_seen(path))
+        .filter_map(|path| {
+            let report = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| {
+                    serde_json::from_str::<CrashReport>(&json).map_err(|e| e.to_string())
+                });
+            match report {
+                Ok(report) => Some(CrashSummary {
+                    path: path.display().to_string(),
+                    time_ms: report.time_ms,
+                    message: report.message,
+                    location: report.location,
+                }),
+                Err(e) => {
+                    log!("Unreadable crash report {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Mark a report from `get_crash_reports` seen once the user has been shown
+/// it, so it isn't offered again.
+#[tauri::command]
+pub fn dismiss_crash_report(path: String) -> Result<(), CommandError> {
+    let dir = DIR.get().ok_or("No data directory")?;
+    let path = reports(dir)
+        .into_iter()
+        .find(|p| !is_seen(p) && p.display().to_string() == path)
+        .ok_or_else(|| CommandError::invalid_state(format!("No crash report {}", path)))?;
+    let seen = path.with_extension("seen.json");
+    std::fs::rename(&path, &seen)
+        .map_err(|e| format!("Failed to mark {} seen: {}", path.display(), e))?;
+    Ok(())
+}
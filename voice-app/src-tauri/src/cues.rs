@@ -0,0 +1,106 @@
+//! Short earcons for eyes-free use: recording started, stopped, upload
+//! failed, wake word heard.
+
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::api;
+use crate::mic::TARGET_SAMPLE_RATE;
+use crate::playback;
+use crate::settings::SettingsState;
+
+const CUE_SAMPLE_RATE: u32 = 48_000;
+const FADE_MS: f32 = 5.0; // Avoids clicks at tone edges
+
+#[derive(Clone, Copy)]
+pub enum Cue {
+    Start,
+    Stop,
+    Error,
+    /// A wake word was accepted.
+    Wake,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CueSettings {
+    pub enabled: bool,
+    /// 0.0–1.0, applied to built-in and custom sounds alike.
+    pub volume: f32,
+    /// Optional WAV files replacing the built-in tones.
+    pub start_sound: Option<PathBuf>,
+    pub stop_sound: Option<PathBuf>,
+    pub error_sound: Option<PathBuf>,
+    pub wake_sound: Option<PathBuf>,
+}
+
+impl Default for CueSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 0.4,
+            start_sound: None,
+            stop_sound: None,
+            error_sound: None,
+            wake_sound: None,
+        }
+    }
+}
+
+fn tone(freq: f32, ms: f32) -> Vec<f32> {
+    let len = (CUE_SAMPLE_RATE as f32 * ms / 1000.0) as usize;
+    let fade = (CUE_SAMPLE_RATE as f32 * FADE_MS / 1000.0) as usize;
+    (0..len)
+        .map(|n| {
+            let envelope = (n.min(len - n) as f32 / fade as f32).min(1.0);
+            (TAU * freq * n as f32 / CUE_SAMPLE_RATE as f32).sin() * envelope
+        })
+        .collect()
+}
+
+fn builtin(cue: Cue) -> Vec<f32> {
+    match cue {
+        // Rising for start, falling for stop, low and long for errors.
+        Cue::Start => [tone(660.0, 80.0), tone(880.0, 80.0)].concat(),
+        Cue::Stop => [tone(880.0, 80.0), tone(660.0, 80.0)].concat(),
+        Cue::Error => [tone(220.0, 150.0), vec![0.0; 2400], tone(220.0, 150.0)].concat(),
+        // One short high blip, distinct from the start cue that follows.
+        Cue::Wake => tone(1320.0, 60.0),
+    }
+}
+
+pub fn play(app: &AppHandle, cue: Cue) {
+    let Some(state) = app.try_state::<SettingsState>() else {
+        return;
+    };
+    let settings = state.get().cues;
+    if !settings.enabled {
+        return;
+    }
+
+    let custom = match cue {
+        Cue::Start => settings.start_sound,
+        Cue::Stop => settings.stop_sound,
+        Cue::Error => settings.error_sound,
+        Cue::Wake => settings.wake_sound,
+    };
+    let custom_samples = custom.and_then(|path| match api::read_wav_file(&path) {
+        Ok(samples) => Some(samples),
+        Err(e) => {
+            log!("Falling back to built-in cue: {}", e);
+            None
+        }
+    });
+
+    let volume = settings.volume.clamp(0.0, 1.0);
+    let (samples, rate) = match custom_samples {
+        Some(samples) => (
+            samples.iter().map(|&s| s as f32 / i16::MAX as f32).collect(),
+            TARGET_SAMPLE_RATE,
+        ),
+        None => (builtin(cue), CUE_SAMPLE_RATE),
+    };
+    playback::play(samples.into_iter().map(|s: f32| s * volume).collect(), rate);
+}
@@ -0,0 +1,95 @@
+use serde::Serialize;
+use tauri::{App, AppHandle, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::controller;
+use crate::error::CommandError;
+use crate::events;
+use crate::processing;
+use crate::settings::SettingsState;
+
+const SCHEME: &str = "voiceapp";
+
+/// Options carried by `voiceapp://record?profile=..&language=..`: the
+/// capture preset for this recording (`quiet_office`, `noisy_cafe`, ...)
+/// and the language it is transcribed in.
+#[derive(Clone, Default, Serialize)]
+pub struct RecordRequest {
+    profile: Option<String>,
+    language: Option<String>,
+}
+
+impl events::Event for RecordRequest {
+    const NAME: &'static str = "deep-link-record";
+}
+
+fn parse_record_url(url: &Url) -> Option<RecordRequest> {
+    if url.scheme() != SCHEME || url.host_str() != Some("record") {
+        return None;
+    }
+
+    let mut request = RecordRequest::default();
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "profile" => request.profile = Some(value.into_owned()),
+            "language" => request.language = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    Some(request)
+}
+
+fn handle_urls(app: &AppHandle, urls: &[Url]) {
+    for url in urls {
+        let Some(request) = parse_record_url(url) else {
+            log!("Ignoring unsupported deep link: {}", url);
+            continue;
+        };
+
+        // Without a window (headless launch) we just record.
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+
+        match start(app, &request) {
+            Ok(()) => {
+                events::emit(app, &request);
+            }
+            Err(e) => log!("Deep link could not start recording: {}", e),
+        }
+    }
+}
+
+/// Record with the request's preset in place of the configured one.
+fn start(app: &AppHandle, request: &RecordRequest) -> Result<(), CommandError> {
+    let settings = app.state::<SettingsState>().get();
+    let mut capture = settings.capture;
+    if let Some(profile) = &request.profile {
+        match processing::find(profile) {
+            Some(preset) => capture.preset = preset,
+            None => log!(
+                "Unknown deep link profile {}, using the configured one",
+                profile
+            ),
+        }
+    }
+    controller::start_recording_in(app, &settings.source, &capture, request.language.clone())
+}
+
+pub fn register(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    // Linux and Windows only learn about the scheme at runtime in dev builds;
+    // installers register it for release builds.
+    #[cfg(all(debug_assertions, any(target_os = "linux", windows)))]
+    app.deep_link().register_all()?;
+
+    let handle = app.handle().clone();
+    app.deep_link()
+        .on_open_url(move |event| handle_urls(&handle, &event.urls()));
+
+    // The URL that cold-started the app is not delivered through on_open_url.
+    if let Some(urls) = app.deep_link().get_current()? {
+        handle_urls(app.handle(), &urls);
+    }
+    Ok(())
+}
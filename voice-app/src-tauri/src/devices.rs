@@ -0,0 +1,24 @@
+//! Audio source commands over `voice_core::devices`.
+
+use tauri::State;
+
+pub use voice_core::devices::*;
+
+use crate::error::CommandError;
+use crate::settings::SettingsState;
+
+#[tauri::command]
+pub fn list_audio_hosts() -> Vec<HostInfo> {
+    list_hosts()
+}
+
+/// `host` defaults to the one in the configured source.
+#[tauri::command]
+pub fn list_audio_sources(
+    settings: State<'_, SettingsState>,
+    host: Option<String>,
+) -> Result<Vec<SourceInfo>, CommandError> {
+    let source = settings.get().source;
+    let host = host.or(source.host);
+    Ok(list_sources(host.as_deref(), &source.hidden)?)
+}
@@ -0,0 +1,336 @@
+//! Lowering other applications' volume while recording or speaking, so
+//! background music doesn't end up in a dictation or drown out a spoken
+//! answer, and putting it back afterwards. Per-application streams are
+//! lowered where the OS has them: PulseAudio/PipeWire sink inputs via
+//! `pactl` on Linux, WASAPI audio sessions on Windows. macOS only has the
+//! output volume, which would lower our own speech too, so there only
+//! recording ducks.
+//!
+//! Recording and speech each hold a `Duck`; the volumes are lowered when
+//! the first is taken and restored when the last is dropped. Streams that
+//! start while ducked keep their volume.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::controller::AppState;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DuckingSettings {
+    /// Duck from start to stop of a recording.
+    pub recording: bool,
+    /// Duck while a response is spoken (not on macOS).
+    pub speaking: bool,
+    /// What other applications are lowered to, as a fraction of their
+    /// volume (0.0–1.0).
+    pub level: f32,
+}
+
+impl Default for DuckingSettings {
+    fn default() -> Self {
+        Self {
+            recording: false,
+            speaking: false,
+            level: 0.2,
+        }
+    }
+}
+
+/// Volumes to put back: per sink input or process, or the output volume
+/// under key 0 on macOS.
+type Saved = Vec<(u32, f32)>;
+
+#[derive(Default)]
+struct Ducked {
+    holders: usize,
+    saved: Saved,
+}
+
+static SETTINGS: Mutex<Option<DuckingSettings>> = Mutex::new(None);
+static DUCKED: Mutex<Ducked> = Mutex::new(Ducked {
+    holders: 0,
+    saved: Vec::new(),
+});
+/// The recording's hold, taken on `Recording` and dropped on leaving it.
+static RECORDING: Mutex<Option<Duck>> = Mutex::new(None);
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::Saved;
+    use std::process::Command;
+
+    fn pactl(args: &[&str]) -> Result<String, String> {
+        let output = Command::new("pactl")
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run pactl: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "pactl {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn set(index: u32, volume: f32) -> Result<(), String> {
+        let percent = format!("{}%", (volume * 100.0).round() as u32);
+        pactl(&["set-sink-input-volume", &index.to_string(), &percent]).map(|_| ())
+    }
+
+    /// Every sink input except our own process's, at its first channel's
+    /// volume.
+    fn sink_inputs() -> Result<Saved, String> {
+        let raw = pactl(&["-f", "json", "list", "sink-inputs"])?;
+        let inputs: Vec<serde_json::Value> =
+            serde_json::from_str(&raw).map_err(|e| format!("Unexpected pactl output: {}", e))?;
+        let own_pid = std::process::id().to_string();
+        Ok(inputs
+            .iter()
+            .filter(|input| {
+                input["properties"]["application.process.id"].as_str() != Some(&own_pid)
+            })
+            .filter_map(|input| {
+                let index = input["index"].as_u64()? as u32;
+                let percent = input["volume"]
+                    .as_object()?
+                    .values()
+                    .next()?["value_percent"]
+                    .as_str()?
+                    .trim_end_matches('%')
+                    .parse::<f32>()
+                    .ok()?;
+                Some((index, percent / 100.0))
+            })
+            .collect())
+    }
+
+    pub fn lower(level: f32, _speaking: bool) -> Result<Saved, String> {
+        let saved = sink_inputs()?;
+        for &(index, volume) in &saved {
+            set(index, volume * level)?;
+        }
+        Ok(saved)
+    }
+
+    /// Streams that have gone away since are skipped.
+    pub fn restore(saved: Saved) {
+        for (index, volume) in saved {
+            let _ = set(index, volume);
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::Saved;
+    use std::process::Command;
+
+    fn osascript(script: &str) -> Result<String, String> {
+        let output = Command::new("osascript")
+            .args(["-e", script])
+            .output()
+            .map_err(|e| format!("Failed to run osascript: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "osascript failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn set(volume: f32) -> Result<(), String> {
+        let percent = (volume * 100.0).round() as u32;
+        osascript(&format!("set volume output volume {}", percent)).map(|_| ())
+    }
+
+    /// Lowering the output volume would also lower our own speech.
+    pub fn lower(level: f32, speaking: bool) -> Result<Saved, String> {
+        if speaking {
+            return Ok(Vec::new());
+        }
+        let raw = osascript("output volume of (get volume settings)")?;
+        let percent: f32 = raw
+            .parse()
+            .map_err(|_| format!("No output volume available ({})", raw))?;
+        let volume = percent / 100.0;
+        set(volume * level)?;
+        Ok(vec![(0, volume)])
+    }
+
+    pub fn restore(saved: Saved) {
+        for (_, volume) in saved {
+            let _ = set(volume);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::Saved;
+    use windows::core::Interface;
+    use windows::Win32::Media::Audio::{
+        eConsole, eRender, IAudioSessionControl2, IAudioSessionManager2, IMMDeviceEnumerator,
+        ISimpleAudioVolume, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+
+    /// The audio sessions on the default output, other than our own, with
+    /// their process ids.
+    fn sessions() -> Result<Vec<(u32, ISimpleAudioVolume)>, String> {
+        let err = |e: windows::core::Error| format!("Windows audio error: {}", e);
+        let own_pid = std::process::id();
+        // SAFETY: plain COM calls on this thread; initializing an already
+        // initialized thread is harmless (S_FALSE / RPC_E_CHANGED_MODE).
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).map_err(err)?;
+            let device = enumerator
+                .GetDefaultAudioEndpoint(eRender, eConsole)
+                .map_err(err)?;
+            let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None).map_err(err)?;
+            let list = manager.GetSessionEnumerator().map_err(err)?;
+            let mut sessions = Vec::new();
+            for i in 0..list.GetCount().map_err(err)? {
+                let Ok(control) = list.GetSession(i) else {
+                    continue;
+                };
+                let Ok(control) = control.cast::<IAudioSessionControl2>() else {
+                    continue;
+                };
+                let Ok(pid) = control.GetProcessId() else {
+                    continue;
+                };
+                if pid == own_pid || pid == 0 {
+                    continue;
+                }
+                if let Ok(volume) = control.cast::<ISimpleAudioVolume>() {
+                    sessions.push((pid, volume));
+                }
+            }
+            Ok(sessions)
+        }
+    }
+
+    pub fn lower(level: f32, _speaking: bool) -> Result<Saved, String> {
+        let mut saved = Vec::new();
+        for (pid, session) in sessions()? {
+            // SAFETY: COM interfaces obtained above, used on the same
+            // thread; a null event context is allowed.
+            unsafe {
+                if let Ok(volume) = session.GetMasterVolume() {
+                    let _ = session.SetMasterVolume(volume * level, std::ptr::null());
+                    saved.push((pid, volume));
+                }
+            }
+        }
+        Ok(saved)
+    }
+
+    /// Sessions are found again by process id; COM interfaces can't be kept
+    /// across threads.
+    pub fn restore(saved: Saved) {
+        let Ok(sessions) = sessions() else {
+            return;
+        };
+        for (pid, session) in sessions {
+            if let Some(&(_, volume)) = saved.iter().find(|(p, _)| *p == pid) {
+                // SAFETY: as in `lower`.
+                unsafe {
+                    let _ = session.SetMasterVolume(volume, std::ptr::null());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::Saved;
+
+    pub fn lower(_level: f32, _speaking: bool) -> Result<Saved, String> {
+        Err("Ducking is not supported on this platform".into())
+    }
+
+    pub fn restore(_saved: Saved) {}
+}
+
+/// A hold on the ducked volume; the last one dropped restores it.
+pub struct Duck(());
+
+impl Drop for Duck {
+    fn drop(&mut self) {
+        let Ok(mut ducked) = DUCKED.lock() else {
+            return;
+        };
+        ducked.holders = ducked.holders.saturating_sub(1);
+        if ducked.holders == 0 {
+            platform::restore(std::mem::take(&mut ducked.saved));
+        }
+    }
+}
+
+fn hold(level: f32, speaking: bool) -> Option<Duck> {
+    let mut ducked = DUCKED.lock().ok()?;
+    if ducked.holders == 0 {
+        match platform::lower(level.clamp(0.0, 1.0), speaking) {
+            Ok(saved) => ducked.saved = saved,
+            Err(e) => log!("Ducking failed: {}", e),
+        }
+    }
+    ducked.holders += 1;
+    Some(Duck(()))
+}
+
+pub fn apply(settings: &DuckingSettings) {
+    if let Ok(mut current) = SETTINGS.lock() {
+        *current = Some(settings.clone());
+    }
+}
+
+fn settings() -> DuckingSettings {
+    SETTINGS
+        .lock()
+        .ok()
+        .and_then(|s| s.clone())
+        .unwrap_or_default()
+}
+
+/// Duck for the length of a recording, when `ducking.recording` is on.
+pub fn follow_state(state: AppState) {
+    let Ok(mut recording) = RECORDING.lock() else {
+        return;
+    };
+    let settings = settings();
+    if state == AppState::Recording && settings.recording {
+        if recording.is_none() {
+            *recording = hold(settings.level, false);
+        }
+    } else {
+        *recording = None;
+    }
+}
+
+/// Put the volumes back now, for exit: a response still being spoken
+/// would otherwise leave other applications lowered.
+pub fn restore_now() {
+    if let Ok(mut ducked) = DUCKED.lock() {
+        platform::restore(std::mem::take(&mut ducked.saved));
+    }
+}
+
+/// A hold for the caller to keep while speaking, when `ducking.speaking`
+/// is on.
+pub fn while_speaking() -> Option<Duck> {
+    let settings = settings();
+    if !settings.speaking {
+        return None;
+    }
+    hold(settings.level, true)
+}
@@ -0,0 +1,215 @@
+//! Offline semantic search over transcript history. A small sentence
+//! embedding model (e.g. all-MiniLM-L6-v2 exported to ONNX) is loaded from
+//! `<app data>/models/embeddings/{model.onnx,tokenizer.json}`; transcripts
+//! are embedded in the background and their vectors kept next to the text
+//! in the history database. Search is a brute-force cosine scan, which is
+//! plenty for a few thousand notes.
+
+use ort::session::Session;
+use ort::value::Tensor;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tokenizers::{Tokenizer, TruncationParams};
+
+use crate::error::CommandError;
+use crate::history::HistoryState;
+
+const MODEL_DIR: &str = "models/embeddings";
+const MODEL_FILE: &str = "model.onnx";
+const TOKENIZER_FILE: &str = "tokenizer.json";
+/// MiniLM-style models are trained on short passages; longer notes are
+/// embedded by their opening.
+const MAX_TOKENS: usize = 256;
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+pub struct Embedder {
+    session: Mutex<Session>,
+    tokenizer: Tokenizer,
+    /// BERT exports take `token_type_ids`; some slimmed-down ones don't.
+    wants_token_types: bool,
+}
+
+impl Embedder {
+    fn load(dir: PathBuf) -> Result<Self, String> {
+        let model = dir.join(MODEL_FILE);
+        let session = Session::builder()
+            .and_then(|b| b.commit_from_file(&model))
+            .map_err(|e| format!("Failed to load {}: {}", model.display(), e))?;
+        let tokenizer_path = dir.join(TOKENIZER_FILE);
+        let mut tokenizer = Tokenizer::from_file(&tokenizer_path)
+            .map_err(|e| format!("Failed to load {}: {}", tokenizer_path.display(), e))?;
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_TOKENS,
+                ..Default::default()
+            }))
+            .map_err(|e| format!("Failed to configure tokenizer: {}", e))?;
+        let wants_token_types = session.inputs.iter().any(|i| i.name == "token_type_ids");
+        Ok(Self {
+            session: Mutex::new(session),
+            tokenizer,
+            wants_token_types,
+        })
+    }
+
+    /// Mean-pooled, L2-normalized sentence vector, so cosine similarity is a
+    /// plain dot product.
+    pub fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+        let encoding = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|e| format!("Tokenization failed: {}", e))?;
+        let len = encoding.get_ids().len();
+        let as_tensor = |values: &[u32]| {
+            let values: Vec<i64> = values.iter().map(|&v| i64::from(v)).collect();
+            Tensor::from_array(([1, len], values)).map_err(|e| e.to_string())
+        };
+        let mask = encoding.get_attention_mask();
+
+        let mut session = self.session.lock().map_err(|e| e.to_string())?;
+        let outputs = if self.wants_token_types {
+            session.run(ort::inputs![
+                "input_ids" => as_tensor(encoding.get_ids())?,
+                "attention_mask" => as_tensor(mask)?,
+                "token_type_ids" => as_tensor(encoding.get_type_ids())?,
+            ])
+        } else {
+            session.run(ort::inputs![
+                "input_ids" => as_tensor(encoding.get_ids())?,
+                "attention_mask" => as_tensor(mask)?,
+            ])
+        }
+        .map_err(|e| format!("Embedding model failed: {}", e))?;
+
+        // [1, tokens, dim] token embeddings. Summing the unmasked ones is
+        // enough: dividing by the count would not change the direction.
+        let (shape, hidden) = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| format!("Unexpected embedding output: {}", e))?;
+        let dim = *shape.last().ok_or("Embedding output has no dimensions")? as usize;
+        let mut pooled = vec![0.0f32; dim];
+        for (token, _) in hidden.chunks_exact(dim).zip(mask).filter(|(_, &m)| m != 0) {
+            for (sum, &v) in pooled.iter_mut().zip(token) {
+                *sum += v;
+            }
+        }
+        let norm = pooled.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            pooled.iter_mut().for_each(|v| *v /= norm);
+        }
+        Ok(pooled)
+    }
+}
+
+/// The model is loaded on first use and retried after a failure, so
+/// dropping the files in place works without a restart.
+#[derive(Default)]
+pub struct EmbeddingsState {
+    embedder: Mutex<Option<Arc<Embedder>>>,
+    indexing: AtomicBool,
+}
+
+impl EmbeddingsState {
+    fn embedder(&self, app: &AppHandle) -> Result<Arc<Embedder>, String> {
+        let mut slot = self.embedder.lock().map_err(|e| e.to_string())?;
+        if let Some(embedder) = slot.as_ref() {
+            return Ok(Arc::clone(embedder));
+        }
+        let dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("No data directory: {}", e))?
+            .join(MODEL_DIR);
+        if !dir.join(MODEL_FILE).exists() {
+            return Err(format!(
+                "No embedding model; put {} and {} in {}",
+                MODEL_FILE,
+                TOKENIZER_FILE,
+                dir.display()
+            ));
+        }
+        let embedder = Arc::new(Embedder::load(dir)?);
+        *slot = Some(Arc::clone(&embedder));
+        Ok(embedder)
+    }
+}
+
+/// Embed every transcript that has no vector yet, off the calling thread.
+/// Called after each new transcript and once at startup for the backlog;
+/// a run already in progress picks up new rows, so overlapping calls return.
+pub fn index_in_background(app: &AppHandle) {
+    let state = app.state::<EmbeddingsState>();
+    if state.indexing.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<EmbeddingsState>();
+        if let Err(e) = index_missing(&app, &state) {
+            log!("Transcript indexing stopped: {}", e);
+        }
+        state.indexing.store(false, Ordering::Release);
+    });
+}
+
+fn index_missing(app: &AppHandle, state: &EmbeddingsState) -> Result<(), String> {
+    let history = app.state::<HistoryState>();
+    loop {
+        let pending = history.unindexed()?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let embedder = state.embedder(app)?;
+        for (id, text) in pending {
+            history.store_vector(id, &embedder.embed(&text)?)?;
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct SemanticHit {
+    id: i64,
+    /// Unix time in milliseconds.
+    created_at: i64,
+    text: String,
+    /// Cosine similarity, -1.0–1.0; higher is closer.
+    score: f32,
+}
+
+fn search(app: &AppHandle, query: &str, limit: usize) -> Result<Vec<SemanticHit>, String> {
+    let query = app.state::<EmbeddingsState>().embedder(app)?.embed(query)?;
+    let mut hits: Vec<SemanticHit> = app
+        .state::<HistoryState>()
+        .vectors()?
+        .into_iter()
+        // Vectors from a previously installed model can't be compared.
+        .filter(|(_, vector)| vector.len() == query.len())
+        .map(|(transcript, vector)| SemanticHit {
+            score: vector.iter().zip(&query).map(|(a, b)| a * b).sum(),
+            id: transcript.id,
+            created_at: transcript.created_at,
+            text: transcript.text,
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Transcripts closest in meaning to `query`, best first. Transcripts still
+/// waiting to be indexed are not included.
+#[tauri::command]
+pub async fn semantic_search(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SemanticHit>, CommandError> {
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    let hits = tauri::async_runtime::spawn_blocking(move || search(&app, &query, limit))
+        .await
+        .map_err(|e| format!("Search task failed: {}", e))??;
+    Ok(hits)
+}
@@ -0,0 +1,173 @@
+//! Errors returned by commands. They reach the frontend as
+//! `{ code, message, retryable }`, so the UI branches on `code`, and shows
+//! its own (localized) text for it, instead of matching English messages
+//! from reqwest or cpal. The code is set where the error is made: from
+//! `MicError` and `UploadError` kinds, or by the caller that knows what
+//! went wrong. Plain `String` errors are `Internal`.
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use voice_core::capture::MicError;
+use voice_core::client::UploadError;
+
+use crate::circuit::CircuitOpen;
+
+/// What went wrong, as sent to the frontend. Stable: codes are added, never
+/// renamed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// No input device is connected.
+    MicNoDevice,
+    /// The configured input device is gone.
+    MicDeviceNotFound,
+    /// The device or source can't be captured in this build or format.
+    MicUnsupported,
+    /// The microphone failed to open or while recording.
+    MicFailed,
+    /// The request doesn't fit what the app is doing, e.g. stop while idle
+    /// or send with nothing recorded.
+    InvalidState,
+    /// The user or shutdown cancelled it.
+    Cancelled,
+    /// No connection to the server could be made.
+    ServerUnreachable,
+    /// The server didn't answer in time.
+    ServerTimeout,
+    /// The server failed with a 5xx, or is skipped while its circuit
+    /// breaker is open.
+    ServerUnavailable,
+    /// The server rejected the request itself.
+    ServerRejected,
+    /// The server or GitHub refused the credentials; sign in again.
+    AuthExpired,
+    /// The OS keychain couldn't be read or written.
+    KeychainUnavailable,
+    /// A setting is missing or malformed, e.g. the server URL.
+    ConfigInvalid,
+    /// The OS refused access to a file or folder.
+    PermissionDenied,
+    /// Anything else: encoding, files, settings.
+    Internal,
+}
+
+impl ErrorCode {
+    /// Whether the same request may succeed if simply tried again: the
+    /// server may come back, a device may be plugged in.
+    pub fn retryable(self) -> bool {
+        matches!(
+            self,
+            Self::MicNoDevice
+                | Self::MicDeviceNotFound
+                | Self::MicFailed
+                | Self::ServerUnreachable
+                | Self::ServerTimeout
+                | Self::ServerUnavailable
+        )
+    }
+
+    /// The server is down or unreachable, as opposed to refusing the
+    /// request; what the circuit breaker counts.
+    pub fn is_outage(self) -> bool {
+        matches!(
+            self,
+            Self::ServerUnreachable | Self::ServerTimeout | Self::ServerUnavailable
+        )
+    }
+}
+
+/// A failed command: a stable code and the original message, for logs.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_state(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidState, message)
+    }
+
+    pub fn cancelled(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Cancelled, message)
+    }
+}
+
+impl From<MicError> for CommandError {
+    fn from(error: MicError) -> Self {
+        let code = match error {
+            MicError::NoDevice => ErrorCode::MicNoDevice,
+            MicError::DeviceNotFound(_) => ErrorCode::MicDeviceNotFound,
+            MicError::Unsupported(_) => ErrorCode::MicUnsupported,
+            MicError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            MicError::Misconfigured(_) => ErrorCode::ConfigInvalid,
+            MicError::AlreadyRecording
+            | MicError::RecordingOtherSource
+            | MicError::NotRecording => ErrorCode::InvalidState,
+            MicError::Failed(_) => ErrorCode::MicFailed,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<UploadError> for CommandError {
+    fn from(error: UploadError) -> Self {
+        let code = match &error {
+            UploadError::Timeout { .. } => ErrorCode::ServerTimeout,
+            UploadError::Unreachable { .. } => ErrorCode::ServerUnreachable,
+            e if e.is_unauthorized() => ErrorCode::AuthExpired,
+            UploadError::Status { status, .. } if status.is_server_error() => {
+                ErrorCode::ServerUnavailable
+            }
+            UploadError::Status { .. } => ErrorCode::ServerRejected,
+            UploadError::InvalidResponse { .. } | UploadError::Encode(_) => ErrorCode::Internal,
+            UploadError::Credentials(_) => ErrorCode::KeychainUnavailable,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<CircuitOpen> for CommandError {
+    fn from(error: CircuitOpen) -> Self {
+        Self::new(ErrorCode::ServerUnavailable, error.to_string())
+    }
+}
+
+/// A failure nothing more is known about.
+impl From<String> for CommandError {
+    fn from(error: String) -> Self {
+        Self::new(ErrorCode::Internal, error)
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(error: &str) -> Self {
+        error.to_string().into()
+    }
+}
+
+/// For callers that only log or show the message.
+impl From<CommandError> for String {
+    fn from(error: CommandError) -> Self {
+        error.message
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("CommandError", 3)?;
+        error.serialize_field("code", &self.code)?;
+        error.serialize_field("message", &self.message)?;
+        error.serialize_field("retryable", &self.code.retryable())?;
+        error.end()
+    }
+}
@@ -0,0 +1,124 @@
+//! Every event for the webview goes through `emit`, so the names can be
+//! namespaced when several embedded instances or plugins share a webview.
+//! Names get `events.prefix` in front, and with `events.envelope` each event
+//! is also sent on one versioned channel as `{ version, type, payload }`.
+//!
+//! Each payload is a struct implementing [`Event`] and carries its
+//! `schema_version`, so the frontend can tell when the backend sends a shape
+//! it doesn't know yet instead of silently reading missing fields.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// The single channel carrying every event when `events.envelope` is on.
+pub const ENVELOPE_EVENT: &str = "voiceapp://events";
+/// Bumped when the envelope itself changes shape.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// A payload sent to the webview. Fields may be added within a schema
+/// version; bump `SCHEMA_VERSION` when one is renamed, removed or changes
+/// meaning.
+pub trait Event: Serialize + Clone {
+    /// Event name before `events.prefix`.
+    const NAME: &'static str;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// The payload as sent: the event's own fields plus `schema_version`.
+#[derive(Clone, Serialize)]
+struct Versioned<'a, E> {
+    schema_version: u32,
+    #[serde(flatten)]
+    event: &'a E,
+}
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventSettings {
+    /// Put in front of every event name, e.g. `"voice:"` for `voice:mic-level`.
+    pub prefix: String,
+    /// Also send every event on `voiceapp://events`.
+    pub envelope: bool,
+}
+
+#[derive(Default)]
+pub struct EventChannel(RwLock<EventSettings>);
+
+#[derive(Clone, Serialize)]
+struct Envelope<'a, S> {
+    version: u32,
+    #[serde(rename = "type")]
+    kind: &'a str,
+    payload: S,
+}
+
+pub fn apply(app: &AppHandle, settings: &EventSettings) {
+    if let Ok(mut current) = app.state::<EventChannel>().0.write() {
+        *current = settings.clone();
+    }
+}
+
+/// Emit `event` to the webview under the configured prefix.
+pub fn emit<E: Event>(app: &AppHandle, event: &E) {
+    let settings = app
+        .try_state::<EventChannel>()
+        .and_then(|channel| channel.0.read().ok().map(|s| s.clone()))
+        .unwrap_or_default();
+    let name = format!("{}{}", settings.prefix, E::NAME);
+    let payload = Versioned {
+        schema_version: E::SCHEMA_VERSION,
+        event,
+    };
+    if settings.envelope {
+        let envelope = Envelope {
+            version: ENVELOPE_VERSION,
+            kind: &name,
+            payload: &payload,
+        };
+        let _ = app.emit(ENVELOPE_EVENT, envelope);
+    }
+    let _ = app.emit(&name, payload);
+}
+
+fn schema<E: Event>() -> (&'static str, u32) {
+    (E::NAME, E::SCHEMA_VERSION)
+}
+
+/// Schema version of every event by name, for a frontend checking at
+/// startup that it knows them all.
+#[tauri::command]
+pub fn get_event_schemas() -> BTreeMap<&'static str, u32> {
+    [
+        schema::<crate::auth::AuthComplete>(),
+        schema::<crate::auth::ReauthRequired>(),
+        schema::<crate::controller::SendProgressPayload>(),
+        schema::<crate::controller::StateChangedPayload>(),
+        schema::<crate::deep_link::RecordRequest>(),
+        schema::<crate::health::HealthStatus>(),
+        schema::<crate::input_volume::InputVolume>(),
+        schema::<crate::mic::MicLatencyPayload>(),
+        schema::<crate::mic::MicLevelPayload>(),
+        schema::<crate::mic::MicOverrunPayload>(),
+        schema::<crate::mic::MicMutedPayload>(),
+        schema::<crate::mic::MicRestartedPayload>(),
+        schema::<crate::mic::MicSessionPayload>(),
+        schema::<crate::mic::MicSuspendedPayload>(),
+        schema::<crate::playback::ReviewStatus>(),
+        schema::<crate::processing::PresetChanged>(),
+        schema::<crate::review::ReviewDraft>(),
+        schema::<crate::self_check::StartupIssues>(),
+        #[cfg(feature = "webrtc")]
+        schema::<crate::rtc::TranscriptPartial>(),
+        #[cfg(feature = "webrtc")]
+        schema::<crate::rtc::RtcStateChanged>(),
+        #[cfg(all(desktop, feature = "updater"))]
+        schema::<crate::updater::UpdateStagedPayload>(),
+        schema::<crate::voice_loop::PipelineResult>(),
+        schema::<crate::voice_loop::PipelineError>(),
+        schema::<crate::webhooks::WebhookEvent>(),
+    ]
+    .into_iter()
+    .collect()
+}
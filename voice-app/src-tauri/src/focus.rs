@@ -0,0 +1,62 @@
+//! The title of the window the user is working in, as context for a
+//! transcript. Read with `xdotool` on Linux (X11 only; Wayland has no way
+//! to ask), System Events on macOS (needs the accessibility permission) and
+//! `GetForegroundWindow` on Windows. `None` wherever it can't be read.
+
+#[cfg(target_os = "linux")]
+fn read() -> Result<String, String> {
+    let output = std::process::Command::new("xdotool")
+        .args(["getactivewindow", "getwindowname"])
+        .output()
+        .map_err(|e| format!("Failed to run xdotool: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "xdotool failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(target_os = "macos")]
+fn read() -> Result<String, String> {
+    const SCRIPT: &str = "tell application \"System Events\" to tell \
+        (first application process whose frontmost is true) to \
+        return name & \" - \" & name of front window";
+    let output = std::process::Command::new("osascript")
+        .args(["-e", SCRIPT])
+        .output()
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "osascript failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(windows)]
+fn read() -> Result<String, String> {
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowTextW};
+
+    let mut title = [0u16; 512];
+    // SAFETY: the buffer outlives the call and its length is passed along.
+    let len = unsafe { GetWindowTextW(GetForegroundWindow(), &mut title) };
+    if len <= 0 {
+        return Err("The foreground window has no title".into());
+    }
+    Ok(String::from_utf16_lossy(&title[..len as usize]))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn read() -> Result<String, String> {
+    Err("Window titles are not available on this platform".into())
+}
+
+/// The focused window's title, or `None` when it can't be read or is empty.
+pub fn window_title() -> Option<String> {
+    let title = read().inspect_err(|e| log!("{}", e)).ok()?;
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
@@ -0,0 +1,166 @@
+//! Minimal GitHub REST client for the voice flows that talk to GitHub
+//! directly instead of through the pipeline server. Authenticates with a
+//! token kept in the OS keychain under `github_token`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CommandError, ErrorCode};
+use crate::secrets;
+
+const API_VERSION: &str = "2022-11-28";
+const USER_AGENT: &str = "agentic-devops-voice";
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GithubSettings {
+    /// REST API root; change for GitHub Enterprise Server.
+    pub api_url: String,
+    /// `owner/name` repositories whose activity is read aloud.
+    pub repos: Vec<String>,
+    /// How far back "what happened" looks.
+    pub activity_hours: u32,
+}
+
+impl Default for GithubSettings {
+    fn default() -> Self {
+        Self {
+            api_url: "https://api.github.com".into(),
+            repos: Vec::new(),
+            activity_hours: 24,
+        }
+    }
+}
+
+/// `owner/name`, checked before it is put into a URL path.
+pub fn check_repo(repo: &str) -> Result<(), String> {
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part != "."
+            && part != ".."
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    match repo.split_once('/') {
+        Some((owner, name)) if valid_part(owner) && valid_part(name) => Ok(()),
+        _ => Err(format!("Not an owner/name repository: {:?}", repo)),
+    }
+}
+
+/// Unix seconds as the UTC `YYYY-MM-DDTHH:MM:SSZ` GitHub uses, which also
+/// makes its timestamps comparable as plain strings.
+pub fn iso8601(secs: u64) -> String {
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+    // Days to civil date, after Howard Hinnant's `civil_from_days`.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+pub struct GithubClient {
+    http: reqwest::Client,
+    api_url: String,
+    token: String,
+}
+
+impl GithubClient {
+    pub fn new(settings: &GithubSettings) -> Result<Self, CommandError> {
+        let token = secrets::get(secrets::GITHUB_TOKEN)?.ok_or_else(|| {
+            CommandError::new(
+                ErrorCode::ConfigInvalid,
+                "No GitHub token; store one as the github_token secret",
+            )
+        })?;
+        Ok(Self {
+            http: reqwest::Client::new(),
+            api_url: settings.api_url.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<serde_json::Value, CommandError> {
+        let response = request
+            .bearer_auth(&self.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .header("X-GitHub-Api-Version", API_VERSION)
+            .send()
+            .await
+            .map_err(|e| {
+                let code = if e.is_timeout() {
+                    ErrorCode::ServerTimeout
+                } else {
+                    ErrorCode::ServerUnreachable
+                };
+                CommandError::new(code, format!("GitHub request failed: {}", e))
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let code = match status {
+                reqwest::StatusCode::UNAUTHORIZED => ErrorCode::AuthExpired,
+                s if s.is_server_error() => ErrorCode::ServerUnavailable,
+                _ => ErrorCode::ServerRejected,
+            };
+            return Err(CommandError::new(
+                code,
+                format!("GitHub error {}: {}", status, body),
+            ));
+        }
+        Ok(response
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected GitHub response: {}", e))?)
+    }
+
+    /// `path` is relative to the API root, e.g. `/repos/owner/name/pulls/1`.
+    pub async fn get(&self, path: &str) -> Result<serde_json::Value, CommandError> {
+        self.send(self.http.get(format!("{}{}", self.api_url, path)))
+            .await
+    }
+
+    pub async fn get_query(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<serde_json::Value, CommandError> {
+        self.send(
+            self.http
+                .get(format!("{}{}", self.api_url, path))
+                .query(query),
+        )
+        .await
+    }
+
+    pub async fn post(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value, CommandError> {
+        self.send(
+            self.http
+                .post(format!("{}{}", self.api_url, path))
+                .json(body),
+        )
+        .await
+    }
+}
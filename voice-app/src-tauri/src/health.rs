@@ -0,0 +1,273 @@
+//! Health monitoring from inside the app: every `health.interval_secs` the
+//! pipeline server's `/health` endpoint and the latest run of the Jules
+//! health-check workflow are checked. Each result is emitted as
+//! `health-status`, appended to `health.jsonl` in the app data dir, and new
+//! failures are announced through TTS.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use crate::events;
+use crate::github::{self, GithubClient};
+use crate::net;
+use crate::settings::{Settings, SettingsState};
+use crate::tts;
+
+const LOG_FILE: &str = "health.jsonl";
+/// The log is rotated to `health.jsonl.1` past this size.
+const LOG_MAX_BYTES: u64 = 1024 * 1024;
+const MIN_INTERVAL_SECS: u64 = 30;
+const SERVER_TIMEOUT: Duration = Duration::from_secs(10);
+/// Workflow conclusions that mean the check itself failed.
+const FAILED_CONCLUSIONS: &[&str] = &["failure", "timed_out", "startup_failure"];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthSettings {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Ping `<server_url>/health`.
+    pub check_server: bool,
+    /// `owner/name` running `workflow`; the first of `github.repos` when
+    /// unset. No workflow check without either.
+    pub repo: Option<String>,
+    /// Workflow file name, as in `.github/workflows/`.
+    pub workflow: String,
+    /// Speak failures as they start.
+    pub announce: bool,
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 300,
+            check_server: true,
+            repo: None,
+            workflow: "jules_health_check.yml".into(),
+            announce: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ServerHealth {
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct WorkflowHealth {
+    pub repo: String,
+    pub run_id: Option<u64>,
+    /// `queued`, `in_progress`, `completed`, ...
+    pub status: Option<String>,
+    pub conclusion: Option<String>,
+    pub url: Option<String>,
+    /// Set when GitHub couldn't be asked; that alone isn't a failure.
+    pub error: Option<String>,
+}
+
+/// Whether a workflow run conclusion means the run failed.
+pub fn is_failure(conclusion: &str) -> bool {
+    FAILED_CONCLUSIONS.contains(&conclusion)
+}
+
+impl WorkflowHealth {
+    fn failed(&self) -> bool {
+        self.conclusion.as_deref().is_some_and(is_failure)
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct HealthStatus {
+    /// Unix time in milliseconds.
+    pub checked_at: u128,
+    pub healthy: bool,
+    pub server: Option<ServerHealth>,
+    pub workflow: Option<WorkflowHealth>,
+}
+
+impl events::Event for HealthStatus {
+    const NAME: &'static str = "health-status";
+}
+
+/// The latest check, for `get_health_status`.
+#[derive(Default)]
+pub struct HealthState(Mutex<Option<HealthStatus>>);
+
+pub async fn check_server(client: &reqwest::Client, server_url: &str) -> ServerHealth {
+    let url = format!("{}/health", server_url.trim_end_matches('/'));
+    let started = Instant::now();
+    let response = client.get(&url).timeout(SERVER_TIMEOUT).send().await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let error = match response {
+        Ok(response) if response.status().is_success() => None,
+        Ok(response) => Some(format!("Server error {}", response.status())),
+        Err(e) => Some(format!("HTTP request failed for {}: {}", url, e)),
+    };
+    ServerHealth {
+        ok: error.is_none(),
+        latency_ms,
+        error,
+    }
+}
+
+async fn check_workflow(settings: &Settings, repo: &str) -> WorkflowHealth {
+    let mut health = WorkflowHealth {
+        repo: repo.to_string(),
+        ..Default::default()
+    };
+    let workflow = &settings.health.workflow;
+    let valid_workflow = !workflow.is_empty()
+        && workflow
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid_workflow {
+        health.error = Some(format!("Not a workflow file name: {:?}", workflow));
+        return health;
+    }
+
+    let runs = async {
+        github::check_repo(repo)?;
+        GithubClient::new(&settings.github)?
+            .get(&format!(
+                "/repos/{}/actions/workflows/{}/runs?per_page=1",
+                repo, workflow
+            ))
+            .await
+    };
+    match runs.await {
+        Ok(runs) => {
+            let run = &runs["workflow_runs"][0];
+            health.run_id = run["id"].as_u64();
+            health.status = run["status"].as_str().map(str::to_string);
+            health.conclusion = run["conclusion"].as_str().map(str::to_string);
+            health.url = run["html_url"].as_str().map(str::to_string);
+        }
+        Err(e) => health.error = Some(e.message),
+    }
+    health
+}
+
+/// What to say about `current` given the check before it: only failures
+/// that are new, not the same outage every interval.
+fn announcements(previous: Option<&HealthStatus>, current: &HealthStatus) -> Vec<String> {
+    let mut spoken = Vec::new();
+    let server_was_ok = previous
+        .and_then(|p| p.server.as_ref())
+        .is_none_or(|s| s.ok);
+    if current.server.as_ref().is_some_and(|s| !s.ok) && server_was_ok {
+        spoken.push("The pipeline server is not responding.".to_string());
+    }
+    if let Some(workflow) = current.workflow.as_ref().filter(|w| w.failed()) {
+        let already_reported = previous
+            .and_then(|p| p.workflow.as_ref())
+            .is_some_and(|w| w.failed() && w.run_id == workflow.run_id);
+        if !already_reported {
+            spoken.push(format!(
+                "The health check workflow failed in {}.",
+                workflow.repo
+            ));
+        }
+    }
+    spoken
+}
+
+fn log(app: &AppHandle, status: &HealthStatus) -> Result<(), String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("No data directory: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(LOG_FILE);
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() > LOG_MAX_BYTES) {
+        let _ = std::fs::rename(&path, dir.join(format!("{}.1", LOG_FILE)));
+    }
+    let line = serde_json::to_string(status).map_err(|e| e.to_string())?;
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Run one check now, then log, emit and announce it.
+pub async fn check(app: &AppHandle) -> HealthStatus {
+    let settings = app.state::<SettingsState>().get();
+    let server = if settings.health.check_server {
+        Some(check_server(&net::client(app), &settings.server_url).await)
+    } else {
+        None
+    };
+    let repo = settings
+        .health
+        .repo
+        .clone()
+        .or_else(|| settings.github.repos.first().cloned());
+    let workflow = match repo {
+        Some(repo) => Some(check_workflow(&settings, &repo).await),
+        None => None,
+    };
+    let status = HealthStatus {
+        checked_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default(),
+        healthy: server.as_ref().is_none_or(|s| s.ok)
+            && workflow.as_ref().is_none_or(|w| !w.failed()),
+        server,
+        workflow,
+    };
+
+    let previous = app
+        .state::<HealthState>()
+        .0
+        .lock()
+        .ok()
+        .and_then(|mut last| last.replace(status.clone()));
+    if let Err(e) = log(app, &status) {
+        log!("{}", e);
+    }
+    events::emit(app, &status);
+    if settings.health.announce {
+        for message in announcements(previous.as_ref(), &status) {
+            tts::speak(&settings.tts, &message, None);
+        }
+    }
+    status
+}
+
+/// Check on the configured interval for as long as the app runs. Settings
+/// are read each time, so turning monitoring on or off needs no restart.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = app.state::<SettingsState>().get().health;
+            if settings.enabled {
+                check(&app).await;
+            }
+            let interval = settings.interval_secs.max(MIN_INTERVAL_SECS);
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    });
+}
+
+/// The latest check, or `None` before the first one.
+#[tauri::command]
+pub fn get_health_status(health: State<'_, HealthState>) -> Option<HealthStatus> {
+    health.0.lock().ok().and_then(|last| last.clone())
+}
+
+/// Check now, whether or not monitoring is enabled.
+#[tauri::command]
+pub async fn check_health(app: AppHandle) -> HealthStatus {
+    check(&app).await
+}
@@ -0,0 +1,460 @@
+//! Transcript history: every successful transcription is stored in a SQLite
+//! database in the app data dir, with an FTS5 index for `search_history`,
+//! the server's confidence scores when it sent any and, with the
+//! `embeddings` feature, sentence vectors for `semantic_search`.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use voice_core::transcript::{self, Confidence};
+
+use crate::error::CommandError;
+use crate::summary::Summary;
+
+const DB_FILE: &str = "history.sqlite3";
+const DEFAULT_SEARCH_LIMIT: u32 = 20;
+/// Tokens of context around each match in a snippet.
+const SNIPPET_TOKENS: u32 = 12;
+
+/// `transcripts_fts` is an external-content index kept in sync by triggers,
+/// so the text is stored once.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS transcripts (
+        id INTEGER PRIMARY KEY,
+        created_at INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        endpoint TEXT
+    );
+    CREATE VIRTUAL TABLE IF NOT EXISTS transcripts_fts USING fts5(
+        text, content='transcripts', content_rowid='id'
+    );
+    CREATE TRIGGER IF NOT EXISTS transcripts_ai AFTER INSERT ON transcripts BEGIN
+        INSERT INTO transcripts_fts(rowid, text) VALUES (new.id, new.text);
+    END;
+    CREATE TRIGGER IF NOT EXISTS transcripts_ad AFTER DELETE ON transcripts BEGIN
+        INSERT INTO transcripts_fts(transcripts_fts, rowid, text)
+            VALUES ('delete', old.id, old.text);
+    END;
+    CREATE TRIGGER IF NOT EXISTS transcripts_au AFTER UPDATE OF text ON transcripts BEGIN
+        INSERT INTO transcripts_fts(transcripts_fts, rowid, text)
+            VALUES ('delete', old.id, old.text);
+        INSERT INTO transcripts_fts(rowid, text) VALUES (new.id, new.text);
+    END;
+    CREATE TABLE IF NOT EXISTS transcript_vectors (
+        transcript_id INTEGER PRIMARY KEY,
+        vector BLOB NOT NULL
+    );
+    CREATE TRIGGER IF NOT EXISTS transcript_vectors_ad AFTER DELETE ON transcripts BEGIN
+        DELETE FROM transcript_vectors WHERE transcript_id = old.id;
+    END;
+    CREATE TABLE IF NOT EXISTS transcript_summaries (
+        transcript_id INTEGER PRIMARY KEY,
+        summary TEXT NOT NULL,
+        action_items TEXT NOT NULL
+    );
+    CREATE TRIGGER IF NOT EXISTS transcript_summaries_ad AFTER DELETE ON transcripts BEGIN
+        DELETE FROM transcript_summaries WHERE transcript_id = old.id;
+    END;
+    CREATE TABLE IF NOT EXISTS transcript_confidence (
+        transcript_id INTEGER PRIMARY KEY,
+        overall REAL NOT NULL,
+        segments TEXT NOT NULL
+    );
+    CREATE TRIGGER IF NOT EXISTS transcript_confidence_ad AFTER DELETE ON transcripts BEGIN
+        DELETE FROM transcript_confidence WHERE transcript_id = old.id;
+    END;
+";
+
+pub struct HistoryState(Mutex<Connection>);
+
+impl HistoryState {
+    /// Open the on-disk history, or an in-memory one if that fails so the
+    /// rest of the app keeps working (history just won't survive a restart).
+    pub fn open(app: &AppHandle) -> Self {
+        let conn = open_file(app).unwrap_or_else(|e| {
+            log!("Transcript history unavailable, using memory: {}", e);
+            let conn = Connection::open_in_memory().expect("in-memory SQLite");
+            conn.execute_batch(SCHEMA).expect("history schema");
+            conn
+        });
+        Self(Mutex::new(conn))
+    }
+
+    fn insert(&self, text: &str, endpoint: Option<&str>) -> Result<i64, String> {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default();
+        let conn = self.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO transcripts (created_at, text, endpoint) VALUES (?1, ?2, ?3)",
+            params![created_at, text, endpoint],
+        )
+        .map_err(|e| format!("Failed to store transcript: {}", e))?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Swap in another transcript for `id`. Its confidence and vector were
+    /// for the old text and are dropped, for indexing to embed it again.
+    pub fn replace_text(&self, id: i64, text: &str) -> Result<(), String> {
+        let conn = self.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE transcripts SET text = ?2 WHERE id = ?1",
+            params![id, text],
+        )
+        .and_then(|_| {
+            conn.execute(
+                "DELETE FROM transcript_confidence WHERE transcript_id = ?1",
+                params![id],
+            )
+        })
+        .and_then(|_| {
+            conn.execute(
+                "DELETE FROM transcript_vectors WHERE transcript_id = ?1",
+                params![id],
+            )
+        })
+        .map(|_| ())
+        .map_err(|e| format!("Failed to update transcript: {}", e))
+    }
+
+    /// Up to `limit` transcripts stored in the last `within`, oldest first.
+    pub fn recent(&self, limit: usize, within: Duration) -> Result<Vec<String>, String> {
+        let since = SystemTime::now()
+            .checked_sub(within)
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default();
+        let conn = self.0.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT text FROM transcripts WHERE created_at >= ?1
+                 ORDER BY created_at DESC, id DESC LIMIT ?2",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut rows = stmt
+            .query_map(params![since, limit as i64], |row| row.get(0))
+            .and_then(|rows| rows.collect::<Result<Vec<String>, _>>())
+            .map_err(|e| format!("Failed to read transcripts: {}", e))?;
+        rows.reverse();
+        Ok(rows)
+    }
+
+    /// Action items are stored as a JSON array.
+    pub fn store_summary(&self, id: i64, summary: &Summary) -> Result<(), String> {
+        let action_items =
+            serde_json::to_string(&summary.action_items).map_err(|e| e.to_string())?;
+        self.0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .execute(
+                "INSERT OR REPLACE INTO transcript_summaries (transcript_id, summary, action_items)
+                 VALUES (?1, ?2, ?3)",
+                params![id, summary.summary, action_items],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store summary: {}", e))
+    }
+
+    /// Segments are stored as JSON.
+    fn store_confidence(&self, id: i64, confidence: &Confidence) -> Result<(), String> {
+        let segments = serde_json::to_string(&confidence.segments).map_err(|e| e.to_string())?;
+        self.0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .execute(
+                "INSERT OR REPLACE INTO transcript_confidence (transcript_id, overall, segments)
+                 VALUES (?1, ?2, ?3)",
+                params![id, confidence.overall, segments],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store confidence: {}", e))
+    }
+
+    pub fn confidence(&self, id: i64) -> Result<Option<Confidence>, String> {
+        let conn = self.0.lock().map_err(|e| e.to_string())?;
+        let row = conn.query_row(
+            "SELECT overall, segments FROM transcript_confidence WHERE transcript_id = ?1",
+            params![id],
+            |row| Ok((row.get::<_, f32>(0)?, row.get::<_, String>(1)?)),
+        );
+        let (overall, segments) = match row {
+            Ok(row) => row,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(format!("Failed to read confidence: {}", e)),
+        };
+        let segments = serde_json::from_str(&segments)
+            .map_err(|e| format!("Stored confidence is unreadable: {}", e))?;
+        Ok(Some(Confidence { overall, segments }))
+    }
+
+    pub fn search(&self, query: &str, limit: u32) -> Result<Vec<HistoryHit>, String> {
+        let Some(query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let conn = self.0.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT t.id, t.created_at, t.text,
+                        snippet(transcripts_fts, 0, '[', ']', '…', ?3), bm25(transcripts_fts),
+                        c.overall
+                 FROM transcripts_fts
+                 JOIN transcripts t ON t.id = transcripts_fts.rowid
+                 LEFT JOIN transcript_confidence c ON c.transcript_id = t.id
+                 WHERE transcripts_fts MATCH ?1
+                 ORDER BY bm25(transcripts_fts)
+                 LIMIT ?2",
+            )
+            .map_err(|e| format!("Failed to prepare history search: {}", e))?;
+        let hits = stmt
+            .query_map(params![query, limit, SNIPPET_TOKENS], |row| {
+                Ok(HistoryHit {
+                    id: row.get(0)?,
+                    created_at: row.get(1)?,
+                    text: row.get(2)?,
+                    snippet: row.get(3)?,
+                    score: row.get(4)?,
+                    confidence: row.get(5)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("History search failed: {}", e))?;
+        Ok(hits)
+    }
+}
+
+/// Whole-database operations for export and import.
+impl HistoryState {
+    pub fn count(&self) -> Result<usize, String> {
+        self.0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .query_row("SELECT COUNT(*) FROM transcripts", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count transcripts: {}", e))
+    }
+
+    /// Write a consistent copy of the database to `path`, which must not
+    /// exist yet.
+    pub fn snapshot(&self, path: &Path) -> Result<(), String> {
+        self.0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .execute("VACUUM INTO ?1", params![path.to_string_lossy()])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to snapshot history: {}", e))
+    }
+
+    /// Add the transcripts of another history database, skipping ones that
+    /// are already here, so importing the same backup twice is harmless.
+    /// Returns how many were added. Vectors and summaries are not copied;
+    /// the embeddings index rebuilds vectors, summaries stay behind.
+    pub fn merge_from(&self, path: &Path) -> Result<usize, String> {
+        let conn = self.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "ATTACH DATABASE ?1 AS imported",
+            params![path.to_string_lossy()],
+        )
+        .map_err(|e| format!("Failed to open imported history: {}", e))?;
+        let merged = conn
+            .execute(
+                "INSERT INTO transcripts (created_at, text, endpoint)
+                 SELECT i.created_at, i.text, i.endpoint FROM imported.transcripts i
+                 WHERE NOT EXISTS (
+                     SELECT 1 FROM transcripts t
+                     WHERE t.created_at = i.created_at AND t.text = i.text
+                 )
+                 ORDER BY i.created_at",
+                [],
+            )
+            .map_err(|e| format!("Failed to import transcripts: {}", e));
+        let _ = conn.execute("DETACH DATABASE imported", []);
+        merged
+    }
+}
+
+/// Vector storage for the embeddings index: little-endian f32 blobs.
+#[cfg(feature = "embeddings")]
+impl HistoryState {
+    /// Transcripts without a stored vector.
+    pub fn unindexed(&self) -> Result<Vec<(i64, String)>, String> {
+        let conn = self.0.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, text FROM transcripts
+                 WHERE id NOT IN (SELECT transcript_id FROM transcript_vectors)",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read transcripts: {}", e))?;
+        Ok(rows)
+    }
+
+    pub fn store_vector(&self, id: i64, vector: &[f32]) -> Result<(), String> {
+        let blob: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .execute(
+                "INSERT OR REPLACE INTO transcript_vectors (transcript_id, vector) VALUES (?1, ?2)",
+                params![id, blob],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to store vector: {}", e))
+    }
+
+    pub fn vectors(&self) -> Result<Vec<(Transcript, Vec<f32>)>, String> {
+        let conn = self.0.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT t.id, t.created_at, t.text, v.vector
+                 FROM transcript_vectors v JOIN transcripts t ON t.id = v.transcript_id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                let blob: Vec<u8> = row.get(3)?;
+                let vector = blob
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect();
+                let transcript = Transcript {
+                    id: row.get(0)?,
+                    created_at: row.get(1)?,
+                    text: row.get(2)?,
+                };
+                Ok((transcript, vector))
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read vectors: {}", e))?;
+        Ok(rows)
+    }
+}
+
+#[cfg(feature = "embeddings")]
+pub struct Transcript {
+    pub id: i64,
+    /// Unix time in milliseconds.
+    pub created_at: i64,
+    pub text: String,
+}
+
+fn open_file(app: &AppHandle) -> Result<Connection, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("No data directory: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(DB_FILE);
+    let conn =
+        Connection::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    conn.execute_batch(SCHEMA)
+        .map_err(|e| format!("Failed to create history schema: {}", e))?;
+    Ok(conn)
+}
+
+#[derive(Serialize)]
+pub struct HistoryHit {
+    id: i64,
+    /// Unix time in milliseconds.
+    created_at: i64,
+    text: String,
+    /// Matched terms wrapped in `[` `]`, trimmed to the surrounding context.
+    snippet: String,
+    /// BM25; lower is a better match.
+    score: f64,
+    /// The server's overall confidence (0–1), when it sent scores.
+    confidence: Option<f32>,
+}
+
+/// Turn free text into an FTS5 query: every word must appear, the last one
+/// as a prefix so results keep up with typing. Words are quoted so input
+/// like `deploy-checklist` or `AND` is never parsed as query syntax.
+fn fts_query(input: &str) -> Option<String> {
+    let words: Vec<String> = input
+        .split_whitespace()
+        .map(|w| format!("\"{}\"", w.replace('"', "\"\"")))
+        .collect();
+    let (last, rest) = words.split_last()?;
+    let mut query = rest.join(" ");
+    if !query.is_empty() {
+        query.push(' ');
+    }
+    query.push_str(last);
+    query.push('*');
+    Some(query)
+}
+
+/// The text the server sent back: the pipeline route's `transcribed_text`,
+/// or `text` from the plain transcription fallback.
+pub fn transcript_text(result: &serde_json::Value) -> Option<&str> {
+    result
+        .get("transcribed_text")
+        .or_else(|| result.get("text"))
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+/// Add the confidence scores in `result`, if the server sent any, as
+/// `_confidence`: the overall score and per-segment and per-word scores in
+/// one shape whatever the engine, for highlighting shaky words.
+pub fn attach_confidence(result: &mut serde_json::Value) {
+    let Some(confidence) = transcript::confidence(result) else {
+        return;
+    };
+    if let (Some(obj), Ok(value)) = (result.as_object_mut(), serde_json::to_value(&confidence)) {
+        obj.insert("_confidence".to_string(), value);
+    }
+}
+
+/// The scores `attach_confidence` added to `result`.
+pub fn attached_confidence(result: &serde_json::Value) -> Option<Confidence> {
+    serde_json::from_value(result.get("_confidence")?.clone()).ok()
+}
+
+/// Store the transcript of a successful upload, if it has one, with its
+/// confidence, and return its id.
+pub fn record(app: &AppHandle, result: &serde_json::Value) -> Option<i64> {
+    let text = transcript_text(result)?;
+    let endpoint = result.get("_endpoint_used").and_then(|v| v.as_str());
+    let history = app.state::<HistoryState>();
+    let id = history
+        .insert(text, endpoint)
+        .inspect_err(|e| log!("{}", e))
+        .ok()?;
+    if let Some(confidence) = attached_confidence(result) {
+        if let Err(e) = history.store_confidence(id, &confidence) {
+            log!("{}", e);
+        }
+    }
+    #[cfg(feature = "embeddings")]
+    crate::embeddings::index_in_background(app);
+    Some(id)
+}
+
+/// Ranked matches for `query`, best first.
+#[tauri::command]
+pub fn search_history(
+    history: State<'_, HistoryState>,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<HistoryHit>, CommandError> {
+    Ok(history.search(&query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))?)
+}
+
+/// Per-segment and per-word confidence of transcript `id`; `None` when the
+/// server sent no scores for it.
+#[tauri::command]
+pub fn get_transcript_confidence(
+    history: State<'_, HistoryState>,
+    id: i64,
+) -> Result<Option<Confidence>, CommandError> {
+    Ok(history.confidence(id)?)
+}
@@ -0,0 +1,39 @@
+use tauri::{App, AppHandle};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::captions;
+use crate::voice_loop;
+
+/// Install the global-shortcut plugin and bind the configured toggle key.
+/// Every shortcut registered with the plugin toggles recording, including
+/// the media key from `triggers`, except the captions hotkey.
+pub fn register(app: &App, shortcut: &str) -> Result<(), Box<dyn std::error::Error>> {
+    app.handle().plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(|app, shortcut, event| {
+                if event.state != ShortcutState::Pressed {
+                    return;
+                }
+                if !captions::is_hotkey(app, shortcut) {
+                    voice_loop::toggle(app);
+                } else if let Err(e) = captions::toggle(app) {
+                    log!("{}", e);
+                }
+            })
+            .build(),
+    )?;
+
+    if let Err(e) = app.global_shortcut().register(shortcut) {
+        // A taken or malformed shortcut shouldn't keep the app from starting.
+        log!("Failed to register hotkey {}: {}", shortcut, e);
+    }
+    Ok(())
+}
+
+pub fn rebind(app: &AppHandle, old: &str, new: &str) -> Result<(), String> {
+    let shortcuts = app.global_shortcut();
+    let _ = shortcuts.unregister(old);
+    shortcuts
+        .register(new)
+        .map_err(|e| format!("Failed to register hotkey {}: {}", new, e))
+}
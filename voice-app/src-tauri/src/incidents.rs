@@ -0,0 +1,195 @@
+//! Client-side failures reported back to the repository: after
+//! `incidents.threshold` failed sends in a row, a GitHub issue with
+//! diagnostics is opened, or the open one is commented on, the same way
+//! the Jules health-check workflow tracks its failures. One report per
+//! streak; a successful send starts over.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::github::{self, GithubClient};
+use crate::health;
+use crate::net;
+use crate::settings::{Settings, SettingsState};
+
+const ISSUE_TITLE: &str = "Voice client uploads failing";
+/// Errors quoted in a report, newest last.
+const MAX_ERRORS: usize = 10;
+const MAX_ERROR_CHARS: usize = 500;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IncidentSettings {
+    pub enabled: bool,
+    /// Failed sends in a row before an issue is filed.
+    pub threshold: u32,
+    /// `owner/name`; the first of `github.repos` when unset.
+    pub repo: Option<String>,
+    pub label: String,
+}
+
+impl Default for IncidentSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 3,
+            repo: None,
+            label: "voice-client-failure".into(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Streak {
+    /// `(unix seconds, error)` of the current run of failures.
+    errors: Vec<(u64, String)>,
+    failures: u32,
+    reported: bool,
+}
+
+#[derive(Default)]
+pub struct IncidentState(Mutex<Streak>);
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn record_success(app: &AppHandle) {
+    if let Ok(mut streak) = app.state::<IncidentState>().0.lock() {
+        *streak = Streak::default();
+    }
+}
+
+/// Count a failed send and file a report in the background once the streak
+/// reaches the threshold.
+pub fn record_failure(app: &AppHandle, error: &str) {
+    let settings = app.state::<SettingsState>().get();
+    let state = app.state::<IncidentState>();
+    let (failures, errors) = {
+        let Ok(mut streak) = state.0.lock() else {
+            return;
+        };
+        streak.failures += 1;
+        let error: String = error.chars().take(MAX_ERROR_CHARS).collect();
+        streak.errors.push((now_secs(), error));
+        if streak.errors.len() > MAX_ERRORS {
+            streak.errors.remove(0);
+        }
+        if !settings.incidents.enabled
+            || streak.reported
+            || streak.failures < settings.incidents.threshold.max(1)
+        {
+            return;
+        }
+        streak.reported = true;
+        (streak.failures, streak.errors.clone())
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = report(&app, &settings, failures, &errors).await {
+            log!("Failed to report upload failures: {}", e);
+            // Let the next failure try again.
+            if let Ok(mut streak) = app.state::<IncidentState>().0.lock() {
+                streak.reported = false;
+            }
+        }
+    });
+}
+
+async fn diagnostics(
+    app: &AppHandle,
+    settings: &Settings,
+    failures: u32,
+    errors: &[(u64, String)],
+) -> String {
+    let server = health::check_server(&net::client(app), &settings.server_url).await;
+    let server_health = match &server.error {
+        None => format!("ok ({} ms)", server.latency_ms),
+        Some(e) => e.clone(),
+    };
+    let mut body = format!(
+        "Voice client sends failed {} times in a row.\n\n\
+         - App version: {}\n\
+         - OS: {} {}\n\
+         - Server: {}\n\
+         - Server /health: {}\n\
+         - Voice commands: {}\n\n\
+         Recent errors:\n",
+        failures,
+        app.package_info().version,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        settings.server_url,
+        server_health,
+        if settings.intents.enabled {
+            "on"
+        } else {
+            "off"
+        },
+    );
+    for (at, error) in errors {
+        body.push_str(&format!(
+            "- {}: `{}`\n",
+            github::iso8601(*at),
+            error.replace('`', "'")
+        ));
+    }
+    body
+}
+
+async fn report(
+    app: &AppHandle,
+    settings: &Settings,
+    failures: u32,
+    errors: &[(u64, String)],
+) -> Result<(), String> {
+    let incidents = &settings.incidents;
+    let repo = incidents
+        .repo
+        .clone()
+        .or_else(|| settings.github.repos.first().cloned())
+        .ok_or("No repository for failure reports; set incidents.repo")?;
+    github::check_repo(&repo)?;
+    let client = GithubClient::new(&settings.github)?;
+    let body = diagnostics(app, settings, failures, errors).await;
+
+    let open = client
+        .get_query(
+            &format!("/repos/{}/issues", repo),
+            &[
+                ("labels", &incidents.label),
+                ("state", "open"),
+                ("per_page", "1"),
+            ],
+        )
+        .await?;
+    match open[0]["number"].as_u64() {
+        Some(number) => {
+            client
+                .post(
+                    &format!("/repos/{}/issues/{}/comments", repo, number),
+                    &serde_json::json!({ "body": body }),
+                )
+                .await?;
+        }
+        None => {
+            client
+                .post(
+                    &format!("/repos/{}/issues", repo),
+                    &serde_json::json!({
+                        "title": ISSUE_TITLE,
+                        "body": body,
+                        "labels": [incidents.label],
+                    }),
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
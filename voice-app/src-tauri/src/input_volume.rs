@@ -0,0 +1,247 @@
+//! OS input volume and mute for the default capture device, so a muted or
+//! zeroed mic can be caught before a silent recording: PulseAudio/PipeWire
+//! via `pactl` on Linux, `osascript` on macOS, the WASAPI endpoint volume on
+//! Windows. Devices other than the OS default are not covered.
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::devices::{AudioSource, SourceKind};
+use crate::error::CommandError;
+use crate::events;
+use crate::settings::SettingsState;
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct InputVolume {
+    /// 0.0–1.0
+    pub level: f32,
+    pub muted: bool,
+}
+
+impl events::Event for InputVolume {
+    const NAME: &'static str = "mic-silent";
+}
+
+impl InputVolume {
+    pub fn is_silent(&self) -> bool {
+        self.muted || self.level <= 0.0
+    }
+}
+
+/// Only the OS default input is controllable; anything else would silently
+/// read or change the wrong device.
+fn ensure_default(source: &AudioSource) -> Result<(), String> {
+    if source.kind != SourceKind::Input || source.device.is_some() {
+        return Err("Input volume is only available for the default input device".into());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::InputVolume;
+    use std::process::Command;
+
+    const SOURCE: &str = "@DEFAULT_SOURCE@";
+
+    fn pactl(args: &[&str]) -> Result<String, String> {
+        let output = Command::new("pactl")
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run pactl: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "pactl {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    pub fn get() -> Result<InputVolume, String> {
+        // "Volume: front-left: 65536 / 100% / 0.00 dB, ..." — first channel wins.
+        let volume = pactl(&["get-source-volume", SOURCE])?;
+        let percent = volume
+            .split_whitespace()
+            .find_map(|word| word.strip_suffix('%')?.parse::<f32>().ok())
+            .ok_or_else(|| format!("Unexpected pactl output: {}", volume.trim()))?;
+        let mute = pactl(&["get-source-mute", SOURCE])?;
+        Ok(InputVolume {
+            level: percent / 100.0,
+            muted: mute.trim().ends_with("yes"),
+        })
+    }
+
+    pub fn set_level(level: f32) -> Result<(), String> {
+        let percent = format!("{}%", (level * 100.0).round() as u32);
+        pactl(&["set-source-volume", SOURCE, &percent]).map(|_| ())
+    }
+
+    pub fn set_muted(muted: bool) -> Result<(), String> {
+        pactl(&["set-source-mute", SOURCE, if muted { "1" } else { "0" }]).map(|_| ())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::InputVolume;
+    use std::process::Command;
+
+    fn osascript(script: &str) -> Result<String, String> {
+        let output = Command::new("osascript")
+            .args(["-e", script])
+            .output()
+            .map_err(|e| format!("Failed to run osascript: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "osascript failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// macOS has no input mute switch; a zero input volume is what mutes.
+    pub fn get() -> Result<InputVolume, String> {
+        let raw = osascript("input volume of (get volume settings)")?;
+        let percent: f32 = raw
+            .parse()
+            .map_err(|_| format!("No input volume available ({})", raw))?;
+        Ok(InputVolume {
+            level: percent / 100.0,
+            muted: percent <= 0.0,
+        })
+    }
+
+    pub fn set_level(level: f32) -> Result<(), String> {
+        let percent = (level * 100.0).round() as u32;
+        osascript(&format!("set volume input volume {}", percent)).map(|_| ())
+    }
+
+    pub fn set_muted(_muted: bool) -> Result<(), String> {
+        Err("macOS has no input mute; set the level instead".into())
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::InputVolume;
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
+    use windows::Win32::Media::Audio::{
+        eCapture, eConsole, IMMDeviceEnumerator, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED,
+    };
+
+    fn endpoint() -> Result<IAudioEndpointVolume, String> {
+        let err = |e: windows::core::Error| format!("Windows audio error: {}", e);
+        // SAFETY: plain COM calls on this thread; initializing an already
+        // initialized thread is harmless (S_FALSE / RPC_E_CHANGED_MODE).
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).map_err(err)?;
+            let device = enumerator
+                .GetDefaultAudioEndpoint(eCapture, eConsole)
+                .map_err(err)?;
+            device.Activate(CLSCTX_ALL, None).map_err(err)
+        }
+    }
+
+    pub fn get() -> Result<InputVolume, String> {
+        let volume = endpoint()?;
+        // SAFETY: COM interface obtained above, used on the same thread.
+        unsafe {
+            Ok(InputVolume {
+                level: volume
+                    .GetMasterVolumeLevelScalar()
+                    .map_err(|e| e.to_string())?,
+                muted: volume.GetMute().map_err(|e| e.to_string())?.as_bool(),
+            })
+        }
+    }
+
+    pub fn set_level(level: f32) -> Result<(), String> {
+        let volume = endpoint()?;
+        // SAFETY: as in `get`; a null event context is allowed.
+        unsafe {
+            volume
+                .SetMasterVolumeLevelScalar(level, std::ptr::null())
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    pub fn set_muted(muted: bool) -> Result<(), String> {
+        let volume = endpoint()?;
+        // SAFETY: as in `get`; a null event context is allowed.
+        unsafe {
+            volume
+                .SetMute(BOOL::from(muted), std::ptr::null())
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::InputVolume;
+
+    const UNSUPPORTED: &str = "Input volume control is not supported on this platform";
+
+    pub fn get() -> Result<InputVolume, String> {
+        Err(UNSUPPORTED.into())
+    }
+
+    pub fn set_level(_level: f32) -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+
+    pub fn set_muted(_muted: bool) -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+}
+
+/// Off the calling thread: emit `mic-silent` if the input is muted or at
+/// zero, so the UI can offer to fix it while the recording is still young.
+pub fn warn_if_silent(app: &AppHandle, source: &AudioSource) {
+    if ensure_default(source).is_err() {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Ok(volume) = platform::get() {
+            if volume.is_silent() {
+                events::emit(&app, &volume);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_input_volume(settings: State<'_, SettingsState>) -> Result<InputVolume, CommandError> {
+    ensure_default(&settings.get().source)?;
+    Ok(platform::get()?)
+}
+
+/// Either field may be omitted to leave it unchanged.
+#[tauri::command]
+pub fn set_input_volume(
+    settings: State<'_, SettingsState>,
+    level: Option<f32>,
+    muted: Option<bool>,
+) -> Result<InputVolume, CommandError> {
+    ensure_default(&settings.get().source)?;
+    if let Some(level) = level {
+        if !(0.0..=1.0).contains(&level) {
+            return Err(format!("Volume must be between 0 and 1, got {}", level).into());
+        }
+        platform::set_level(level)?;
+    }
+    if let Some(muted) = muted {
+        platform::set_muted(muted)?;
+    }
+    Ok(platform::get()?)
+}
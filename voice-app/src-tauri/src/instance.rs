@@ -0,0 +1,70 @@
+//! Launch intents. A second launch is forwarded to the running instance by
+//! the single-instance plugin instead of opening another app that would
+//! fight over the microphone; the first launch honours the same flags.
+//!
+//!     agentic-devops-voice --record | --toggle | --transcribe <file.wav>
+
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::api;
+use crate::controller;
+use crate::voice_loop;
+
+pub enum LaunchIntent {
+    Record,
+    Toggle,
+    Transcribe(PathBuf),
+    Show,
+}
+
+/// `args` excludes the binary name. Deep links are routed by the deep-link
+/// plugin and never reach this.
+pub fn parse_args(args: &[String]) -> LaunchIntent {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--record" => return LaunchIntent::Record,
+            "--toggle" => return LaunchIntent::Toggle,
+            "--transcribe" => {
+                if let Some(path) = iter.next() {
+                    return LaunchIntent::Transcribe(PathBuf::from(path));
+                }
+            }
+            _ => {}
+        }
+    }
+    LaunchIntent::Show
+}
+
+pub fn handle(app: &AppHandle, intent: LaunchIntent) {
+    match intent {
+        LaunchIntent::Record => {
+            if let Err(e) = controller::start_recording(app) {
+                log!("Failed to start recording: {}", e);
+            }
+        }
+        LaunchIntent::Toggle => voice_loop::toggle(app),
+        LaunchIntent::Transcribe(path) => match api::read_wav_file(&path) {
+            Ok(samples) => voice_loop::send_in_background(app, samples.into()),
+            Err(e) => log!("Cannot transcribe {}: {}", path.display(), e),
+        },
+        LaunchIntent::Show => {
+            if let Err(e) = crate::show_main_window(app) {
+                log!("Failed to open window: {}", e);
+            }
+        }
+    }
+}
+
+/// Single-instance callback: `argv` is the second launch's full command line,
+/// `cwd` its working directory (relative paths are resolved against it).
+pub fn forward(app: &AppHandle, argv: Vec<String>, cwd: String) {
+    let intent = match parse_args(argv.get(1..).unwrap_or_default()) {
+        LaunchIntent::Transcribe(path) if path.is_relative() => {
+            LaunchIntent::Transcribe(PathBuf::from(cwd).join(path))
+        }
+        intent => intent,
+    };
+    handle(app, intent);
+}
@@ -0,0 +1,445 @@
+//! Voice commands. With routing on, a send is transcribed first and the
+//! text matched against the configured patterns; a match is dispatched to
+//! the handler registered for its action instead of going to the pipeline,
+//! anything else carries on as plain dictation.
+//!
+//! Patterns are words and `{slot}`s, matched case-insensitively against the
+//! whole transcript: `"open {target}"` matches "Open github.com." with
+//! `target = "github.com"`. A slot takes at least one word.
+//!
+//! Users can add their own commands in settings as phrase mappings, tried
+//! before the built-in patterns: a phrase in the same syntax and an action
+//! to run a program, open a URL, press keys or call the pipeline, with the
+//! phrase's slots filled into it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::process::Command;
+use tauri::{AppHandle, Manager};
+
+use crate::activity;
+use crate::api::{self, TranscribeOptions};
+use crate::containers;
+use crate::error::CommandError;
+use crate::history;
+use crate::net;
+use crate::settings::SettingsState;
+use crate::transcription;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct IntentPattern {
+    pub pattern: String,
+    /// Name of a registered handler, e.g. `open`.
+    pub action: String,
+}
+
+impl IntentPattern {
+    fn new(pattern: &str, action: &str) -> Self {
+        Self {
+            pattern: pattern.into(),
+            action: action.into(),
+        }
+    }
+}
+
+/// What a phrase mapping does. `{slot}`s from the phrase can be used in
+/// every string.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MappedAction {
+    /// Program and arguments. Slots are filled in per argument and nothing
+    /// goes through a shell unless the command itself starts one.
+    Shell { command: Vec<String> },
+    /// Slots are percent-encoded.
+    Url { url: String },
+    /// A key macro, e.g. `ctrl+l, "{query}", enter`; slots are filled into
+    /// the quoted text.
+    Keys { keys: String },
+    /// Run the pipeline on `text`, or on the whole transcript without it.
+    Pipeline { text: Option<String> },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PhraseMapping {
+    /// A pattern, e.g. `"search docs for {query}"`.
+    pub phrase: String,
+    pub action: MappedAction,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntentSettings {
+    pub enabled: bool,
+    /// Tried in order; the first match wins.
+    pub patterns: Vec<IntentPattern>,
+    /// What "run <name>" may start: name to program and arguments. Nothing
+    /// outside this map is ever executed.
+    pub commands: BTreeMap<String, Vec<String>>,
+    /// User-defined commands, tried in order before `patterns`.
+    pub mappings: Vec<PhraseMapping>,
+}
+
+impl Default for IntentSettings {
+    fn default() -> Self {
+        #[allow(unused_mut)]
+        let mut patterns = vec![
+            IntentPattern::new("open {target}", "open"),
+            IntentPattern::new("run {command}", "run"),
+            IntentPattern::new("create issue {text}", "create_issue"),
+            IntentPattern::new("what happened overnight", "repo_activity"),
+            IntentPattern::new("what happened overnight in {repo}", "repo_activity"),
+            IntentPattern::new("what happened in {repo}", "repo_activity"),
+            IntentPattern::new("what's wrong with the {container} container", "container_logs"),
+            IntentPattern::new("summarize the {container} logs", "container_logs"),
+        ];
+        #[cfg(feature = "k8s")]
+        patterns.extend([
+            IntentPattern::new("pod status", "k8s_pods"),
+            IntentPattern::new("pod status in {namespace}", "k8s_pods"),
+            IntentPattern::new("recent restarts", "k8s_restarts"),
+            IntentPattern::new("recent restarts in {namespace}", "k8s_restarts"),
+        ]);
+        Self {
+            enabled: false,
+            patterns,
+            commands: BTreeMap::new(),
+            mappings: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Intent {
+    pub action: String,
+    pub slots: BTreeMap<String, String>,
+    /// The full transcript.
+    pub text: String,
+    /// Set for phrase mappings, whose action is `mapping`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapping: Option<MappedAction>,
+}
+
+impl Intent {
+    fn slot(&self, name: &str) -> Result<&str, String> {
+        self.slots
+            .get(name)
+            .map(String::as_str)
+            .ok_or_else(|| format!("Pattern for {} has no {{{}}} slot", self.action, name))
+    }
+}
+
+enum Part {
+    Word(String),
+    Slot(String),
+}
+
+fn parse_pattern(pattern: &str) -> Vec<Part> {
+    pattern
+        .split_whitespace()
+        .map(
+            |token| match token.strip_prefix('{').and_then(|t| t.strip_suffix('}')) {
+                Some(name) => Part::Slot(name.to_string()),
+                None => Part::Word(token.to_lowercase()),
+            },
+        )
+        .collect()
+}
+
+/// Whether a spoken name refers to the identifier `name`, ignoring case,
+/// spacing and punctuation: "agentic devops loop" is `agentic-devops-loop`.
+pub fn same_name(spoken: &str, name: &str) -> bool {
+    let simplify = |s: &str| -> String {
+        s.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    };
+    simplify(spoken) == simplify(name)
+}
+
+/// Transcribers punctuate and capitalize; neither should stop a match.
+fn words(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .map(|w| w.trim_matches(|c: char| matches!(c, '.' | ',' | '!' | '?' | ';' | ':' | '"')))
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+fn match_parts(parts: &[Part], words: &[&str], slots: &mut BTreeMap<String, String>) -> bool {
+    match parts.split_first() {
+        None => words.is_empty(),
+        Some((Part::Word(word), rest)) => {
+            words.first().is_some_and(|w| w.to_lowercase() == *word)
+                && match_parts(rest, &words[1..], slots)
+        }
+        // Shortest capture first, so a literal after the slot can still match.
+        Some((Part::Slot(name), rest)) => (1..=words.len()).any(|end| {
+            let matched = match_parts(rest, &words[end..], slots);
+            if matched {
+                slots.insert(name.clone(), words[..end].join(" "));
+            }
+            matched
+        }),
+    }
+}
+
+fn match_pattern(pattern: &str, words: &[&str]) -> Option<BTreeMap<String, String>> {
+    let mut slots = BTreeMap::new();
+    match_parts(&parse_pattern(pattern), words, &mut slots).then_some(slots)
+}
+
+/// Replace `{name}` with the slot's value, passed through `encode`. Unknown
+/// names are left as written.
+fn fill(template: &str, slots: &BTreeMap<String, String>, encode: fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start..];
+        match after.find('}').and_then(|end| Some((slots.get(&after[1..end])?, end))) {
+            Some((value, end)) => {
+                out.push_str(&encode(value));
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &after[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Key macros split on `"`, so a slot value can't close the quoted text.
+fn macro_text(value: &str) -> String {
+    value.replace('"', "'")
+}
+
+/// Handler action for every phrase mapping; the intent carries what to do.
+const MAPPING_ACTION: &str = "mapping";
+
+pub type HandlerFuture =
+    Pin<Box<dyn Future<Output = Result<serde_json::Value, CommandError>> + Send>>;
+
+/// Runs an intent and returns what the send reports back, shaped like a
+/// server response. Gets the server URL of the send that produced it.
+pub type Handler = fn(AppHandle, Intent, String) -> HandlerFuture;
+
+pub struct IntentRouter {
+    handlers: HashMap<&'static str, Handler>,
+}
+
+impl Default for IntentRouter {
+    fn default() -> Self {
+        let mut router = Self {
+            handlers: HashMap::new(),
+        };
+        router.register("open", open);
+        router.register("run", run);
+        router.register("create_issue", create_issue);
+        router.register("repo_activity", activity::repo_activity_intent);
+        router.register("container_logs", containers::container_logs_intent);
+        #[cfg(feature = "k8s")]
+        router.register("k8s_pods", crate::k8s::pods_intent);
+        #[cfg(feature = "k8s")]
+        router.register("k8s_restarts", crate::k8s::restarts_intent);
+        router.register(MAPPING_ACTION, run_mapping);
+        router
+    }
+}
+
+impl IntentRouter {
+    pub fn register(&mut self, action: &'static str, handler: Handler) {
+        self.handlers.insert(action, handler);
+    }
+
+    /// The first phrase mapping, or else the first pattern with a handler,
+    /// that matches `text`.
+    pub fn recognize(&self, settings: &IntentSettings, text: &str) -> Option<(Intent, Handler)> {
+        let words = words(text);
+        let mapped = settings.mappings.iter().find_map(|m| {
+            let slots = match_pattern(&m.phrase, &words)?;
+            let intent = Intent {
+                action: MAPPING_ACTION.to_string(),
+                slots,
+                text: text.to_string(),
+                mapping: Some(m.action.clone()),
+            };
+            Some((intent, self.handlers[MAPPING_ACTION]))
+        });
+        mapped.or_else(|| {
+            settings.patterns.iter().find_map(|p| {
+                let Some(&handler) = self.handlers.get(p.action.as_str()) else {
+                    log!("No handler for intent action {:?}", p.action);
+                    return None;
+                };
+                let intent = Intent {
+                    action: p.action.clone(),
+                    slots: match_pattern(&p.pattern, &words)?,
+                    text: text.to_string(),
+                    mapping: None,
+                };
+                Some((intent, handler))
+            })
+        })
+    }
+}
+
+/// Result for handlers that act locally: the transcript, the way the
+/// transcription-only route reports it.
+fn dictation(intent: &Intent) -> serde_json::Value {
+    serde_json::json!({ "text": intent.text })
+}
+
+fn open(_app: AppHandle, intent: Intent, _server_url: String) -> HandlerFuture {
+    Box::pin(async move {
+        let target = intent.slot("target")?;
+        // "open github.com" means the website, not a file of that name.
+        let target = if !target.contains("://") && target.contains('.') && !target.contains(' ') {
+            format!("https://{}", target)
+        } else {
+            target.to_string()
+        };
+        open::that_detached(&target).map_err(|e| format!("Failed to open {}: {}", target, e))?;
+        Ok(dictation(&intent))
+    })
+}
+
+fn run(app: AppHandle, intent: Intent, _server_url: String) -> HandlerFuture {
+    Box::pin(async move {
+        let name = intent.slot("command")?;
+        let commands = app.state::<SettingsState>().get().intents.commands;
+        let argv = commands
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, argv)| argv)
+            .ok_or_else(|| format!("No command named {:?}", name))?;
+        let (program, args) = argv
+            .split_first()
+            .ok_or_else(|| format!("Command {:?} is empty", name))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        // Reap it whenever it exits; the send doesn't wait for it.
+        std::thread::spawn(move || child.wait());
+        Ok(dictation(&intent))
+    })
+}
+
+/// Only the part after "create issue" goes to the pipeline.
+fn create_issue(app: AppHandle, intent: Intent, server_url: String) -> HandlerFuture {
+    Box::pin(async move {
+        let text = intent.slot("text")?;
+        let signing = app.state::<SettingsState>().get().signing;
+        let options = transcription::options(&app);
+        Ok(
+            api::run_pipeline_text(&net::client(&app), text, &server_url, &signing, &options)
+                .await?
+                .ok_or("The server has no pipeline to create issues with")?,
+        )
+    })
+}
+
+/// Runs the action of a phrase mapping.
+fn run_mapping(app: AppHandle, intent: Intent, server_url: String) -> HandlerFuture {
+    Box::pin(async move {
+        let action = intent
+            .mapping
+            .clone()
+            .ok_or("Mapping intent without an action")?;
+        let slots = &intent.slots;
+        match action {
+            MappedAction::Shell { command } => {
+                let argv: Vec<String> = command
+                    .iter()
+                    .map(|arg| fill(arg, slots, str::to_string))
+                    .collect();
+                let (program, args) = argv.split_first().ok_or("Mapped command is empty")?;
+                let mut child = Command::new(program)
+                    .args(args)
+                    .spawn()
+                    .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+                std::thread::spawn(move || child.wait());
+            }
+            MappedAction::Url { url } => {
+                let url = fill(&url, slots, percent_encode);
+                open::that_detached(&url).map_err(|e| format!("Failed to open {}: {}", url, e))?;
+            }
+            #[cfg(desktop)]
+            MappedAction::Keys { keys } => {
+                let keys = fill(&keys, slots, macro_text);
+                tauri::async_runtime::spawn_blocking(move || crate::keyboard::run_macro(&keys))
+                    .await
+                    .map_err(|e| format!("Key macro task failed: {}", e))??;
+            }
+            #[cfg(mobile)]
+            MappedAction::Keys { .. } => {
+                return Err("Key macros are only supported on desktop".into());
+            }
+            MappedAction::Pipeline { text } => {
+                let text = match text {
+                    Some(template) => fill(&template, slots, str::to_string),
+                    None => intent.text.clone(),
+                };
+                let signing = app.state::<SettingsState>().get().signing;
+                let options = transcription::options(&app);
+                let client = net::client(&app);
+                return Ok(
+                    api::run_pipeline_text(&client, &text, &server_url, &signing, &options)
+                        .await?
+                        .ok_or("The server has no pipeline to run")?,
+                );
+            }
+        }
+        Ok(dictation(&intent))
+    })
+}
+
+/// Upload for sends with routing on: transcribe, then dispatch a matching
+/// intent or run the pipeline on the text. The routed intent is reported
+/// in the result as `_intent`.
+pub async fn upload(
+    app: &AppHandle,
+    wav_bytes: &[u8],
+    server_url: &str,
+    options: &TranscribeOptions,
+) -> Result<serde_json::Value, CommandError> {
+    let signing = app.state::<SettingsState>().get().signing;
+    let client = net::client(app);
+    let transcription =
+        api::transcribe_wav(&client, wav_bytes, server_url, &signing, options).await?;
+    let Some(text) = history::transcript_text(&transcription).map(str::to_owned) else {
+        return Ok(transcription);
+    };
+
+    let settings = app.state::<SettingsState>().get().intents;
+    let recognized = app.state::<IntentRouter>().recognize(&settings, &text);
+    let Some((intent, handler)) = recognized else {
+        return Ok(api::run_pipeline_text(&client, &text, server_url, &signing, options)
+            .await?
+            .unwrap_or(transcription));
+    };
+
+    let report = serde_json::to_value(&intent).map_err(|e| e.to_string())?;
+    let mut result = handler(app.clone(), intent, server_url.to_string()).await?;
+    if let Some(obj) = result.as_object_mut() {
+        obj.insert("_intent".to_string(), report);
+    }
+    Ok(result)
+}
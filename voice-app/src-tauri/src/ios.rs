@@ -0,0 +1,112 @@
+//! iOS glue for the mic path. cpal captures through a RemoteIO unit on the
+//! shared `AVAudioSession`, which is configured once at launch: play and
+//! record (cues and spoken responses play through the same session) in
+//! measurement mode, so the OS skips the voice-call processing, preferring
+//! 16 kHz. A call or Siri interrupts the session and silences the unit; the
+//! recording is suspended for the interruption and resumed into the same
+//! take when it ends, instead of being rebuilt by the stall watchdog
+//! against a session it can't use.
+
+use block2::RcBlock;
+use objc2::rc::Retained;
+use objc2_avf_audio::{
+    AVAudioSession, AVAudioSessionCategoryOptions, AVAudioSessionCategoryPlayAndRecord,
+    AVAudioSessionInterruptionNotification, AVAudioSessionInterruptionType,
+    AVAudioSessionInterruptionTypeKey, AVAudioSessionModeMeasurement,
+};
+use objc2_foundation::{NSNotification, NSNotificationCenter, NSNumber};
+use std::ptr::NonNull;
+use tauri::{AppHandle, Manager};
+
+use crate::mic::{MicState, TARGET_SAMPLE_RATE};
+
+/// Set the session's category, mode and preferred rate, and activate it.
+pub fn configure_audio_session() -> Result<(), String> {
+    // SAFETY: the session singleton and the category and mode constants are
+    // provided by AVFAudio and live for the whole process.
+    unsafe {
+        let session = AVAudioSession::sharedInstance();
+        let (Some(category), Some(mode)) =
+            (AVAudioSessionCategoryPlayAndRecord, AVAudioSessionModeMeasurement)
+        else {
+            return Err("Audio session constants unavailable".into());
+        };
+        session
+            .setCategory_mode_options_error(
+                category,
+                mode,
+                AVAudioSessionCategoryOptions::DefaultToSpeaker
+                    | AVAudioSessionCategoryOptions::AllowBluetooth,
+            )
+            .map_err(|e| format!("Failed to configure audio session: {}", e))?;
+        // Only a preference; capture resamples whatever the hardware picks.
+        session
+            .setPreferredSampleRate_error(TARGET_SAMPLE_RATE as f64)
+            .map_err(|e| format!("Failed to set preferred sample rate: {}", e))?;
+        session
+            .setActive_error(true)
+            .map_err(|e| format!("Failed to activate audio session: {}", e))
+    }
+}
+
+fn interruption_type(notification: &NSNotification) -> Option<AVAudioSessionInterruptionType> {
+    // SAFETY: the key is an AVFAudio constant, and the value under it is an
+    // `NSNumber` per the notification's documentation.
+    unsafe {
+        let info = notification.userInfo()?;
+        let value = info.objectForKey(AVAudioSessionInterruptionTypeKey?)?;
+        let number = value.downcast::<NSNumber>().ok()?;
+        Some(AVAudioSessionInterruptionType(number.unsignedIntegerValue()))
+    }
+}
+
+/// Suspend every recording session when an interruption begins and resume
+/// them when it ends, reactivating the audio session first. Both show up as
+/// `mic-suspended`. Recordings are resumed even when the OS doesn't suggest
+/// it: the user was mid-sentence and only the recording knows that.
+pub fn observe_interruptions(app: &AppHandle) {
+    let handle = app.clone();
+    let block = RcBlock::new(move |notification: NonNull<NSNotification>| {
+        // SAFETY: the center passes a valid notification for the block's
+        // duration.
+        let notification = unsafe { notification.as_ref() };
+        let began = match interruption_type(notification) {
+            Some(AVAudioSessionInterruptionType::Began) => true,
+            Some(AVAudioSessionInterruptionType::Ended) => false,
+            _ => return,
+        };
+        let recording: Vec<_> = handle
+            .state::<MicState>()
+            .sessions()
+            .into_iter()
+            .filter(|(_, mic)| mic.is_recording())
+            .collect();
+        if recording.is_empty() {
+            return;
+        }
+        if !began {
+            if let Err(e) = configure_audio_session() {
+                log!("Audio interruption: {}", e);
+                return;
+            }
+        }
+        for (id, mic) in recording {
+            let result = if began { mic.suspend() } else { mic.resume() };
+            if let Err(e) = result {
+                log!("Audio interruption ({}): {}", id, e);
+            }
+        }
+    });
+    // SAFETY: the notification name is an AVFAudio constant; the block is
+    // copied by the center and called on the posting thread.
+    let observer: Retained<_> = unsafe {
+        NSNotificationCenter::defaultCenter().addObserverForName_object_queue_usingBlock(
+            AVAudioSessionInterruptionNotification,
+            None,
+            None,
+            &block,
+        )
+    };
+    // Registered for the app's lifetime.
+    std::mem::forget(observer);
+}
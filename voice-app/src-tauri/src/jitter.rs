@@ -0,0 +1,88 @@
+//! Adaptive jitter buffer between capture and a paced streaming sender.
+//! Capture callbacks arrive in irregular sizes and the sender can stall on
+//! the network; frames are released on a fixed tick only once a few are
+//! queued, so the stream the server sees has no holes in it.
+
+use std::collections::VecDeque;
+
+const MIN_DEPTH: usize = 2;
+const MAX_DEPTH: usize = 10;
+/// Drop the oldest frames beyond this so a long stall can't grow unbounded.
+const MAX_QUEUED: usize = MAX_DEPTH * 4;
+/// Ticks without an underrun before the target depth shrinks by one frame.
+const STABLE_TICKS: u32 = 250; // 5s of 20ms frames
+
+pub struct JitterBuffer {
+    frame_len: usize,
+    partial: Vec<i16>,
+    frames: VecDeque<Vec<i16>>,
+    /// Frames to hold before (re)starting playout.
+    target: usize,
+    playing: bool,
+    stable_ticks: u32,
+}
+
+impl JitterBuffer {
+    pub fn new(frame_len: usize) -> Self {
+        Self {
+            frame_len,
+            partial: Vec::with_capacity(frame_len),
+            frames: VecDeque::new(),
+            target: MIN_DEPTH,
+            playing: false,
+            stable_ticks: 0,
+        }
+    }
+
+    /// Queue captured samples, splitting them into whole frames.
+    pub fn push(&mut self, samples: &[i16]) {
+        self.partial.extend_from_slice(samples);
+        while self.partial.len() >= self.frame_len {
+            let rest = self.partial.split_off(self.frame_len);
+            let frame = std::mem::replace(&mut self.partial, rest);
+            self.frames.push_back(frame);
+        }
+        while self.frames.len() > MAX_QUEUED {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Next frame for this tick, or `None` while (re)filling. An underrun
+    /// pauses playout and deepens the buffer instead of sending a short
+    /// frame, so the stream just resumes later rather than skipping audio.
+    pub fn pop(&mut self) -> Option<Vec<i16>> {
+        if !self.playing {
+            if self.frames.len() < self.target {
+                return None;
+            }
+            self.playing = true;
+        }
+
+        match self.frames.pop_front() {
+            Some(frame) => {
+                self.stable_ticks += 1;
+                if self.stable_ticks >= STABLE_TICKS && self.target > MIN_DEPTH {
+                    self.target -= 1;
+                    self.stable_ticks = 0;
+                }
+                Some(frame)
+            }
+            None => {
+                self.playing = false;
+                self.stable_ticks = 0;
+                self.target = (self.target + 1).min(MAX_DEPTH);
+                None
+            }
+        }
+    }
+
+    /// Everything left at end of stream, the last frame padded with silence.
+    pub fn drain(&mut self) -> impl Iterator<Item = Vec<i16>> + '_ {
+        if !self.partial.is_empty() {
+            let mut last = std::mem::take(&mut self.partial);
+            last.resize(self.frame_len, 0);
+            self.frames.push_back(last);
+        }
+        self.frames.drain(..)
+    }
+}
@@ -0,0 +1,272 @@
+//! Spoken Kubernetes status for the `k8s` feature: pod health and recent
+//! container restarts in a namespace of the configured kubeconfig context,
+//! answered through TTS from the intent router ("pod status", "recent
+//! restarts in staging") or the matching commands.
+
+use k8s_openapi::api::core::v1::Pod;
+use k8s_openapi::chrono::{Duration, Utc};
+use kube::api::ListParams;
+use kube::config::KubeConfigOptions;
+use kube::{Api, Client, Config};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Manager};
+
+use crate::error::CommandError;
+use crate::intents::{HandlerFuture, Intent};
+use crate::settings::SettingsState;
+use crate::tts;
+
+/// Pods or workloads named per answer; the rest are only counted.
+const MAX_NAMED: usize = 5;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct K8sSettings {
+    /// Kubeconfig context; the current one when unset.
+    pub context: Option<String>,
+    /// Used when a query doesn't name one.
+    pub namespace: String,
+    /// How far back "recent restarts" looks.
+    pub restart_hours: u32,
+}
+
+impl Default for K8sSettings {
+    fn default() -> Self {
+        Self {
+            context: None,
+            namespace: "default".into(),
+            restart_hours: 24,
+        }
+    }
+}
+
+async fn list_pods(settings: &K8sSettings, namespace: &str) -> Result<Vec<Pod>, String> {
+    let config = Config::from_kubeconfig(&KubeConfigOptions {
+        context: settings.context.clone(),
+        ..Default::default()
+    })
+    .await
+    .map_err(|e| format!("Failed to load kubeconfig: {}", e))?;
+    let client =
+        Client::try_from(config).map_err(|e| format!("Failed to create k8s client: {}", e))?;
+    let pods = Api::<Pod>::namespaced(client, namespace)
+        .list(&ListParams::default())
+        .await
+        .map_err(|e| format!("Failed to list pods in {}: {}", namespace, e))?;
+    Ok(pods.items)
+}
+
+/// Generated pod names don't read well; say the app they belong to.
+fn spoken_name(pod: &Pod) -> String {
+    let labels = pod.metadata.labels.as_ref();
+    ["app.kubernetes.io/name", "app"]
+        .iter()
+        .find_map(|key| labels.and_then(|l| l.get(*key)).cloned())
+        .or_else(|| pod.metadata.name.clone())
+        .unwrap_or_else(|| "unnamed pod".into())
+}
+
+fn phase(pod: &Pod) -> &str {
+    pod.status
+        .as_ref()
+        .and_then(|s| s.phase.as_deref())
+        .unwrap_or("Unknown")
+}
+
+/// Why a pod isn't healthy, or `None` if it is running and ready (or has
+/// finished successfully).
+fn problem(pod: &Pod) -> Option<String> {
+    let status = pod.status.as_ref()?;
+    let waiting = status
+        .container_statuses
+        .iter()
+        .flatten()
+        .find_map(|c| c.state.as_ref()?.waiting.as_ref()?.reason.clone());
+    if waiting.is_some() {
+        return waiting;
+    }
+    match phase(pod) {
+        "Succeeded" => None,
+        "Running" => {
+            let ready = status
+                .conditions
+                .iter()
+                .flatten()
+                .any(|c| c.type_ == "Ready" && c.status == "True");
+            (!ready).then(|| "not ready".to_string())
+        }
+        other => Some(other.to_string()),
+    }
+}
+
+fn plural(n: usize, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+/// e.g. "5 pods in default: 4 running, 1 pending. Not healthy: api
+/// (CrashLoopBackOff)."
+fn status_text(namespace: &str, pods: &[Pod]) -> String {
+    if pods.is_empty() {
+        return format!("No pods in {}.", namespace);
+    }
+    let mut phases: BTreeMap<String, usize> = BTreeMap::new();
+    for pod in pods {
+        *phases.entry(phase(pod).to_lowercase()).or_default() += 1;
+    }
+    let phases: Vec<String> = phases
+        .iter()
+        .map(|(phase, n)| format!("{} {}", n, phase))
+        .collect();
+    let mut text = format!(
+        "{} in {}: {}.",
+        plural(pods.len(), "pod", "pods"),
+        namespace,
+        phases.join(", ")
+    );
+
+    let mut problems: Vec<String> = Vec::new();
+    for pod in pods {
+        if let Some(reason) = problem(pod) {
+            let entry = format!("{} ({})", spoken_name(pod), reason);
+            if !problems.contains(&entry) {
+                problems.push(entry);
+            }
+        }
+    }
+    if problems.is_empty() {
+        text.push_str(" All healthy.");
+    } else {
+        problems.truncate(MAX_NAMED);
+        text.push_str(&format!(" Not healthy: {}.", problems.join(", ")));
+    }
+    text
+}
+
+fn ago(minutes: i64) -> String {
+    if minutes < 60 {
+        format!(
+            "{} ago",
+            plural(minutes.max(1) as usize, "minute", "minutes")
+        )
+    } else {
+        format!("{} ago", plural((minutes / 60) as usize, "hour", "hours"))
+    }
+}
+
+/// Containers whose last termination was within `hours`, most recent
+/// first, e.g. "api restarted 5 times, last 12 minutes ago (OOMKilled)".
+fn restarts_text(namespace: &str, pods: &[Pod], hours: u32) -> String {
+    let now = Utc::now();
+    let since = now - Duration::hours(i64::from(hours));
+    let mut restarts = Vec::new();
+    for pod in pods {
+        let statuses = pod
+            .status
+            .as_ref()
+            .and_then(|s| s.container_statuses.as_ref());
+        for container in statuses.into_iter().flatten() {
+            let Some(terminated) = container
+                .last_state
+                .as_ref()
+                .and_then(|s| s.terminated.as_ref())
+            else {
+                continue;
+            };
+            let Some(finished) = terminated.finished_at.as_ref().map(|t| t.0) else {
+                continue;
+            };
+            if container.restart_count > 0 && finished >= since {
+                restarts.push((
+                    finished,
+                    spoken_name(pod),
+                    container.restart_count,
+                    terminated.reason.clone(),
+                ));
+            }
+        }
+    }
+    if restarts.is_empty() {
+        return format!(
+            "No restarts in {} in the last {}.",
+            namespace,
+            plural(hours as usize, "hour", "hours")
+        );
+    }
+
+    restarts.sort_by_key(|(finished, ..)| std::cmp::Reverse(*finished));
+    let described: Vec<String> = restarts
+        .iter()
+        .take(MAX_NAMED)
+        .map(|(finished, name, count, reason)| {
+            let mut entry = format!(
+                "{} restarted {}, last {}",
+                name,
+                plural(*count as usize, "time", "times"),
+                ago((now - *finished).num_minutes())
+            );
+            if let Some(reason) = reason {
+                entry.push_str(&format!(" ({})", reason));
+            }
+            entry
+        })
+        .collect();
+    format!("Restarts in {}: {}.", namespace, described.join("; "))
+}
+
+enum Query {
+    Status,
+    Restarts,
+}
+
+async fn answer(app: &AppHandle, query: Query, namespace: Option<&str>) -> Result<String, String> {
+    let settings = app.state::<SettingsState>().get();
+    let namespace = namespace.unwrap_or(&settings.k8s.namespace).to_string();
+    let pods = list_pods(&settings.k8s, &namespace).await?;
+    let text = match query {
+        Query::Status => status_text(&namespace, &pods),
+        Query::Restarts => restarts_text(&namespace, &pods, settings.k8s.restart_hours),
+    };
+    tts::speak(&settings.tts, &text, None);
+    Ok(text)
+}
+
+async fn answer_intent(
+    app: AppHandle,
+    intent: Intent,
+    query: Query,
+) -> Result<serde_json::Value, CommandError> {
+    // Namespaces are lowercase; transcripts often aren't.
+    let namespace = intent.slots.get("namespace").map(|n| n.to_lowercase());
+    let text = answer(&app, query, namespace.as_deref()).await?;
+    Ok(serde_json::json!({ "text": intent.text, "_k8s": text }))
+}
+
+/// Handler for the `k8s_pods` action; the `namespace` slot is optional.
+pub fn pods_intent(app: AppHandle, intent: Intent, _server_url: String) -> HandlerFuture {
+    Box::pin(answer_intent(app, intent, Query::Status))
+}
+
+/// Handler for the `k8s_restarts` action; the `namespace` slot is optional.
+pub fn restarts_intent(app: AppHandle, intent: Intent, _server_url: String) -> HandlerFuture {
+    Box::pin(answer_intent(app, intent, Query::Restarts))
+}
+
+/// Speak and return a summary of pod health in `namespace` (default:
+/// `k8s.namespace`).
+#[tauri::command]
+pub async fn k8s_pod_status(
+    app: AppHandle,
+    namespace: Option<String>,
+) -> Result<String, CommandError> {
+    Ok(answer(&app, Query::Status, namespace.as_deref()).await?)
+}
+
+/// Speak and return the containers restarted within `k8s.restart_hours`.
+#[tauri::command]
+pub async fn k8s_recent_restarts(
+    app: AppHandle,
+    namespace: Option<String>,
+) -> Result<String, CommandError> {
+    Ok(answer(&app, Query::Restarts, namespace.as_deref()).await?)
+}
@@ -0,0 +1,125 @@
+//! Synthetic keyboard input into whatever app has focus, for voice macros.
+//!
+//! A macro is a comma-separated list of steps: a chord such as `ctrl+l` or
+//! `enter`, or quoted text to type, e.g. `ctrl+l, "example.com", enter`.
+
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+
+fn enigo() -> Result<Enigo, String> {
+    Enigo::new(&Settings::default()).map_err(|e| format!("Keyboard input unavailable: {}", e))
+}
+
+fn parse_key(name: &str) -> Result<Key, String> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "ctrl" | "control" => Key::Control,
+        "shift" => Key::Shift,
+        "alt" | "option" => Key::Alt,
+        "meta" | "super" | "win" | "cmd" | "command" => Key::Meta,
+        "enter" | "return" => Key::Return,
+        "tab" => Key::Tab,
+        "esc" | "escape" => Key::Escape,
+        "space" => Key::Space,
+        "backspace" => Key::Backspace,
+        "delete" | "del" => Key::Delete,
+        "up" => Key::UpArrow,
+        "down" => Key::DownArrow,
+        "left" => Key::LeftArrow,
+        "right" => Key::RightArrow,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "f1" => Key::F1,
+        "f2" => Key::F2,
+        "f3" => Key::F3,
+        "f4" => Key::F4,
+        "f5" => Key::F5,
+        "f6" => Key::F6,
+        "f7" => Key::F7,
+        "f8" => Key::F8,
+        "f9" => Key::F9,
+        "f10" => Key::F10,
+        "f11" => Key::F11,
+        "f12" => Key::F12,
+        other => {
+            let mut chars = other.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Key::Unicode(c),
+                _ => return Err(format!("Unknown key: {}", name)),
+            }
+        }
+    };
+    Ok(key)
+}
+
+/// Hold the modifiers, click the last key, release in reverse order.
+fn chord(enigo: &mut Enigo, chord: &str) -> Result<(), String> {
+    let keys = chord
+        .split('+')
+        .map(|k| parse_key(k.trim()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (last, modifiers) = keys.split_last().ok_or("Empty key chord")?;
+    let err = |e: enigo::InputError| format!("Key press failed: {}", e);
+    for &key in modifiers {
+        enigo.key(key, Direction::Press).map_err(err)?;
+    }
+    let clicked = enigo.key(*last, Direction::Click).map_err(err);
+    for &key in modifiers.iter().rev() {
+        enigo.key(key, Direction::Release).map_err(err)?;
+    }
+    clicked
+}
+
+enum Step<'a> {
+    Chord(&'a str),
+    Text(&'a str),
+}
+
+fn parse_macro(input: &str) -> Result<Vec<Step<'_>>, String> {
+    let mut steps = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let (step, after) = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted
+                .find('"')
+                .ok_or_else(|| format!("Unclosed quote in key macro: {}", input))?;
+            (Step::Text(&quoted[..end]), &quoted[end + 1..])
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            (Step::Chord(rest[..end].trim()), &rest[end..])
+        };
+        steps.push(step);
+        rest = after.trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+    Ok(steps)
+}
+
+pub fn type_text(text: &str) -> Result<(), String> {
+    enigo()?
+        .text(text)
+        .map_err(|e| format!("Typing failed: {}", e))
+}
+
+/// Press Backspace `count` times, to take back typed text.
+pub fn erase(count: usize) -> Result<(), String> {
+    let mut enigo = enigo()?;
+    for _ in 0..count {
+        enigo
+            .key(Key::Backspace, Direction::Click)
+            .map_err(|e| format!("Key press failed: {}", e))?;
+    }
+    Ok(())
+}
+
+pub fn run_macro(keys: &str) -> Result<(), String> {
+    let steps = parse_macro(keys)?;
+    let mut enigo = enigo()?;
+    for step in steps {
+        match step {
+            Step::Chord(c) => chord(&mut enigo, c)?,
+            Step::Text(t) => enigo.text(t).map_err(|e| format!("Typing failed: {}", e))?,
+        }
+    }
+    Ok(())
+}
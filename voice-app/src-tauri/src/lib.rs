@@ -1,25 +1,371 @@
-mod api;
-mod mic;
+//! The Tauri desktop app (`app` feature, on by default). Without it the
+//! crate is a plain library re-exporting `voice_core`, whose `controller`
+//! runs the same record/send lifecycle without a window or commands.
 
+#[cfg(not(feature = "app"))]
+pub use voice_core::*;
+
+/// `#[cfg(feature = "app")]` on each item.
+macro_rules! app {
+    ($($item:item)*) => {
+        $(#[cfg(feature = "app")] $item)*
+    };
+}
+
+/// `eprintln!` that also keeps the line for crash reports.
+#[cfg(feature = "app")]
+macro_rules! log {
+    ($($arg:tt)*) => {
+        $crate::crash::log(format!($($arg)*))
+    };
+}
+
+app! {
+    pub mod api;
+    mod activity;
+    #[cfg(target_os = "android")]
+    mod android;
+    mod archive;
+    mod auth;
+    mod cancel;
+    #[cfg(desktop)]
+    mod autostart;
+    #[cfg(desktop)]
+    mod captions;
+    mod circuit;
+    #[cfg(desktop)]
+    mod control;
+    mod containers;
+    mod controller;
+    mod crash;
+    mod cues;
+    mod deep_link;
+    pub mod devices;
+    mod ducking;
+    #[cfg(feature = "embeddings")]
+    mod embeddings;
+    mod error;
+    mod events;
+    #[cfg(desktop)]
+    mod focus;
+    mod github;
+    mod health;
+    mod history;
+    mod incidents;
+    #[cfg(desktop)]
+    mod hotkey;
+    mod input_volume;
+    mod intents;
+    #[cfg(target_os = "ios")]
+    mod ios;
+    #[cfg(desktop)]
+    mod instance;
+    #[cfg(feature = "webrtc")]
+    mod jitter;
+    #[cfg(feature = "k8s")]
+    mod k8s;
+    #[cfg(desktop)]
+    mod keyboard;
+    #[cfg(desktop)]
+    mod midi;
+    #[cfg(target_os = "macos")]
+    mod macos;
+    mod metrics;
+    pub mod mic;
+    mod net;
+    #[cfg(feature = "opus")]
+    mod ogg_opus;
+    #[cfg(feature = "webrtc")]
+    mod rtc;
+    mod playback;
+    mod privacy;
+    mod processing;
+    mod queue;
+    mod review;
+    mod secrets;
+    mod self_check;
+    mod settings;
+    mod shutdown;
+    pub mod signing;
+    mod summary;
+    mod terminal;
+    mod transcription;
+    #[cfg(desktop)]
+    mod tray;
+    #[cfg(desktop)]
+    mod triggers;
+    mod tts;
+    #[cfg(all(desktop, feature = "updater"))]
+    mod updater;
+    mod uploads;
+    mod voice_loop;
+    mod wake_word;
+    mod webhooks;
+}
+
+#[cfg(feature = "app")]
 use mic::MicState;
-use tauri::Manager;
+#[cfg(feature = "app")]
+use settings::SettingsState;
+#[cfg(feature = "app")]
+use tauri::{AppHandle, Manager, RunEvent, WebviewWindowBuilder, WindowEvent};
 
+/// Show the main window, creating it from config on first use (it is not
+/// created at startup so headless mode never loads the webview).
+#[cfg(feature = "app")]
+pub(crate) fn show_main_window(app: &AppHandle) -> tauri::Result<()> {
+    let window = match app.get_webview_window("main") {
+        Some(window) => window,
+        None => {
+            let config = app
+                .config()
+                .app
+                .windows
+                .iter()
+                .find(|w| w.label == "main")
+                .ok_or(tauri::Error::WindowNotFound)?;
+            WebviewWindowBuilder::from_config(app, config)?.build()?
+        }
+    };
+    window.show()?;
+    window.set_focus()
+}
+
+#[cfg(feature = "app")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let context = tauri::generate_context!();
+    crash::install(&context.config().identifier);
+
+    let headless_flag = std::env::args().any(|arg| arg == "--headless");
+
+    let builder = tauri::Builder::default();
+
+    // Must be the first plugin so a second launch exits before doing any work.
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+        instance::forward(app, argv, cwd)
+    }));
+
+    #[cfg(target_os = "android")]
+    let builder = builder.plugin(tauri_plugin_background_capture::init());
+
+    let app = builder
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_deep_link::init())
         .manage(MicState::default())
-        .setup(|app| {
-            // Give MicState access to the AppHandle for emitting events
-            let mic_state = app.state::<MicState>();
-            mic_state.set_app_handle(app.handle().clone());
+        .manage(controller::Controller::default())
+        .manage(intents::IntentRouter::default())
+        .manage(review::ReviewState::default())
+        .manage(health::HealthState::default())
+        .manage(self_check::StartupState::default())
+        .manage(incidents::IncidentState::default())
+        .manage(webhooks::WebhookState::default())
+        .manage(auth::AuthState::default())
+        .manage(circuit::CircuitBreakers::default())
+        .manage(net::HttpClient::default())
+        .manage(api::RouteCache::default())
+        .manage(metrics::MetricsState::default())
+        .manage(metrics::MicMetricsState::default())
+        .manage(metrics::WakeWordMetricsState::default())
+        .manage(wake_word::WakeWordState::default())
+        .manage(playback::ReviewPlayback::default())
+        .manage(events::EventChannel::default())
+        .manage(uploads::UploadWorker::default())
+        .manage(cancel::Cancellation::default())
+        .manage(transcription::TranscriptionState::default())
+        .setup(move |app| {
+            crash::prune();
+            mic::forward_events(app.handle());
+
+            let settings = settings::load(app.handle());
+            let headless = cfg!(desktop) && (headless_flag || settings.headless);
+
+            #[cfg(desktop)]
+            {
+                #[cfg(feature = "updater")]
+                {
+                    app.handle()
+                        .plugin(tauri_plugin_updater::Builder::new().build())?;
+                    app.manage(updater::PendingUpdate::default());
+                }
+
+                tray::register(app)?;
+                hotkey::register(app, &settings.hotkey)?;
+                app.manage(triggers::TriggerState::default());
+                app.manage(midi::MidiState::default());
+                triggers::register(app.handle(), &settings.triggers);
+                app.manage(captions::CaptionState::default());
+                captions::register(app.handle(), &settings.captions);
+                autostart::register(app, settings.autostart)?;
+            }
+            // Asked up front so the first recording doesn't hit the prompt.
+            #[cfg(target_os = "android")]
+            if let Err(e) = android::request_record_permission() {
+                log!("{}", e);
+            }
+            #[cfg(target_os = "macos")]
+            if let Err(e) = macos::request_record_permission() {
+                log!("{}", e);
+            }
+            #[cfg(target_os = "ios")]
+            {
+                if let Err(e) = ios::configure_audio_session() {
+                    log!("{}", e);
+                }
+                ios::observe_interruptions(app.handle());
+            }
+            events::apply(app.handle(), &settings.events);
+            ducking::apply(&settings.ducking);
+            webhooks::apply(app.handle(), &settings.webhooks);
+            if let Err(e) = net::apply(app.handle(), &settings.network) {
+                log!("{}", e);
+            }
+            net::warm_up(app.handle(), &settings.network, &settings.server_url);
+            app.manage(SettingsState::new(settings));
+            mic::apply_standby(app.handle());
+            app.manage(history::HistoryState::open(app.handle()));
+            #[cfg(feature = "embeddings")]
+            {
+                app.manage(embeddings::EmbeddingsState::default());
+                embeddings::index_in_background(app.handle());
+            }
+
+            #[cfg(desktop)]
+            control::spawn(app.handle());
+
+            uploads::spawn(app.handle());
+            tauri::async_runtime::spawn(queue::replay(app.handle().clone()));
+            health::start(app.handle());
+            self_check::check_on_startup(app.handle());
+            auth::start_refresh(app.handle());
+
+            #[cfg(feature = "webrtc")]
+            app.manage(rtc::RtcState::default());
+
+            if headless {
+                #[cfg(target_os = "macos")]
+                app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+            } else {
+                show_main_window(app.handle())?;
+            }
+
+            deep_link::register(app)?;
+
+            #[cfg(desktop)]
+            {
+                let args: Vec<String> = std::env::args().skip(1).collect();
+                match instance::parse_args(&args) {
+                    instance::LaunchIntent::Show => {}
+                    intent => instance::handle(app.handle(), intent),
+                }
+            }
             Ok(())
         })
+        .on_window_event(|window, event| {
+            // Closing the main window hides it to the tray so recording,
+            // the hotkey and in-flight uploads keep running.
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                let close_to_tray = cfg!(desktop)
+                    && window.label() == "main"
+                    && window.state::<SettingsState>().get().close_to_tray;
+                if close_to_tray {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
+            activity::read_repo_activity,
+            archive::export_history,
+            archive::import_history,
+            auth::cancel_device_login,
+            auth::logout,
+            auth::start_device_login,
+            cancel::cancel_all,
+            containers::summarize_container_logs,
+            crash::dismiss_crash_report,
+            crash::get_crash_reports,
+            devices::list_audio_hosts,
+            devices::list_audio_sources,
+            health::check_health,
+            health::get_health_status,
+            history::get_transcript_confidence,
+            history::search_history,
+            input_volume::get_input_volume,
+            input_volume::set_input_volume,
+            metrics::get_mic_metrics,
+            metrics::get_upload_metrics,
+            metrics::get_wake_word_metrics,
+            mic::get_mic_status,
+            mic::list_sessions,
+            mic::set_muted,
             mic::start_mic,
+            mic::start_session,
             mic::stop_mic,
+            mic::stop_session,
+            mic::test_microphone,
             api::send_audio,
+            api::send_last_recording,
+            api::stop_and_send,
+            uploads::get_upload_status,
+            controller::cancel_send,
+            controller::discard_recording,
+            controller::get_app_state,
+            events::get_event_schemas,
+            review::start_review_comment,
+            review::post_review_comment,
+            review::discard_review_comment,
+            secrets::set_secret,
+            secrets::delete_secret,
+            self_check::get_startup_issues,
+            self_check::run_self_check,
+            settings::get_settings,
+            settings::update_settings,
+            playback::review_recording,
+            playback::seek_review,
+            playback::stop_review,
+            privacy::open_mic_privacy_settings,
+            processing::list_capture_presets,
+            processing::set_capture_preset,
+            transcription::choose_alternative,
+            tts::list_tts_voices,
+            tts::speak_text,
+            wake_word::report_wake_word,
+            #[cfg(feature = "embeddings")]
+            embeddings::semantic_search,
+            #[cfg(feature = "k8s")]
+            k8s::k8s_pod_status,
+            #[cfg(feature = "k8s")]
+            k8s::k8s_recent_restarts,
+            #[cfg(feature = "webrtc")]
+            rtc::start_rtc_stream,
+            #[cfg(feature = "webrtc")]
+            rtc::stop_rtc_stream,
+            #[cfg(desktop)]
+            captions::toggle_captions,
+            #[cfg(all(desktop, feature = "updater"))]
+            updater::check_for_update,
+            #[cfg(all(desktop, feature = "updater"))]
+            updater::install_update,
         ])
-        .run(tauri::generate_context!())
+        .build(context)
         .expect("error while running tauri application");
+
+    app.run(move |app, event| match event {
+        // In headless mode the tray owns the app; closing a window opened
+        // from it must not quit.
+        RunEvent::ExitRequested { code: None, api, .. }
+            if headless_flag || app.state::<SettingsState>().get().headless =>
+        {
+            api.prevent_exit();
+        }
+        RunEvent::Exit => shutdown::run(app),
+        // Dock icon click after the window was hidden to the tray.
+        #[cfg(target_os = "macos")]
+        RunEvent::Reopen { .. } => {
+            let _ = show_main_window(app);
+        }
+        _ => {}
+    });
 }
@@ -0,0 +1,73 @@
+//! macOS microphone access. The TCC prompt is driven from here rather than
+//! left to the first stream open, where a denial only shows up as digital
+//! silence: the status is checked before every capture and a missing grant
+//! fails with a message saying where to change it. A bundle without
+//! `NSMicrophoneUsageDescription` is refused up front, since macOS kills
+//! the process on its first mic access instead of prompting.
+
+use block2::RcBlock;
+use objc2::runtime::Bool;
+use objc2_av_foundation::{AVAuthorizationStatus, AVCaptureDevice, AVMediaTypeAudio};
+use objc2_foundation::{ns_string, NSBundle};
+use voice_core::capture::MicError;
+
+/// `open_mic_privacy_settings` opens this page.
+const DENIED: &str = "Permission denied: allow microphone access in System Settings > \
+                      Privacy & Security > Microphone and try again";
+
+fn has_usage_description() -> bool {
+    NSBundle::mainBundle()
+        .objectForInfoDictionaryKey(ns_string!("NSMicrophoneUsageDescription"))
+        .is_some()
+}
+
+fn status() -> Result<AVAuthorizationStatus, String> {
+    // SAFETY: the media type is an AVFoundation constant.
+    unsafe {
+        let audio = AVMediaTypeAudio.ok_or("AVFoundation unavailable")?;
+        Ok(AVCaptureDevice::authorizationStatusForMediaType(audio))
+    }
+}
+
+/// Show the system prompt if the user hasn't been asked yet. Returns
+/// without waiting for the answer.
+pub fn request_record_permission() -> Result<(), String> {
+    if !has_usage_description() || status()? != AVAuthorizationStatus::NotDetermined {
+        return Ok(());
+    }
+    let answered = RcBlock::new(|granted: Bool| {
+        if !granted.as_bool() {
+            log!("Microphone access was not granted");
+        }
+    });
+    // SAFETY: as in `status`; the block is copied by AVFoundation.
+    unsafe {
+        let audio = AVMediaTypeAudio.ok_or("AVFoundation unavailable")?;
+        AVCaptureDevice::requestAccessForMediaType_completionHandler(audio, &answered);
+    }
+    Ok(())
+}
+
+/// Fail unless microphone access is granted, asking for it when the user
+/// hasn't been asked yet so the next attempt can succeed.
+pub fn ensure_record_permission() -> Result<(), MicError> {
+    if !has_usage_description() {
+        return Err(MicError::Misconfigured(
+            "NSMicrophoneUsageDescription is missing from Info.plist; macOS would \
+             terminate the app on mic access"
+                .into(),
+        ));
+    }
+    let message = match status().map_err(MicError::Failed)? {
+        AVAuthorizationStatus::Authorized => return Ok(()),
+        AVAuthorizationStatus::NotDetermined => {
+            request_record_permission().map_err(MicError::Failed)?;
+            "Permission denied: answer the microphone prompt and try again"
+        }
+        AVAuthorizationStatus::Restricted => {
+            "Permission denied: microphone access is restricted by a device policy"
+        }
+        _ => DENIED,
+    };
+    Err(MicError::PermissionDenied(message.into()))
+}
@@ -0,0 +1,146 @@
+//! Upload counters since launch, so operators can see which route answers
+//! and notice when the pipeline route silently broke and every send is
+//! going through the `/api/transcribe` fallback. Counted from the metadata
+//! each response carries (`_endpoint_used`, `_fallback`).
+//!
+//! Also the time each recording took from `start` to its first audio, so a
+//! device or config change that makes starting slower shows up, and every
+//! wake-word detection with its confidence.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::wake_word::WakeWordOutcome;
+
+/// Wake-word detections kept in `recent`.
+const WAKE_WORD_RECENT: usize = 50;
+
+#[derive(Clone, Default, Serialize)]
+pub struct UploadMetrics {
+    pub uploads: u64,
+    /// Successful uploads per `_endpoint_used`.
+    pub endpoints: BTreeMap<String, u64>,
+    pub fallbacks: u64,
+    /// The pipeline route's status behind each fallback, e.g. `404`.
+    pub fallback_statuses: BTreeMap<u16, u64>,
+    /// Fallbacks taken without asking, because the route was already known
+    /// to be missing.
+    pub cached_fallbacks: u64,
+}
+
+#[derive(Default)]
+pub struct MetricsState(Mutex<UploadMetrics>);
+
+/// Count a successful upload from its result.
+pub fn record_upload(app: &AppHandle, result: &serde_json::Value) {
+    let state = app.state::<MetricsState>();
+    let Ok(mut metrics) = state.0.lock() else {
+        return;
+    };
+    metrics.uploads += 1;
+    if let Some(endpoint) = result["_endpoint_used"].as_str() {
+        *metrics.endpoints.entry(endpoint.to_string()).or_default() += 1;
+    }
+    let fallback = &result["_fallback"];
+    if let Some(status) = fallback["status"].as_u64() {
+        metrics.fallbacks += 1;
+        *metrics.fallback_statuses.entry(status as u16).or_default() += 1;
+        if fallback["cached"].as_bool() == Some(true) {
+            metrics.cached_fallbacks += 1;
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_upload_metrics(metrics: State<'_, MetricsState>) -> UploadMetrics {
+    metrics.0.lock().map(|m| m.clone()).unwrap_or_default()
+}
+
+/// Start-to-first-sample times of the main recording since launch, in ms.
+#[derive(Clone, Default, Serialize)]
+pub struct MicMetrics {
+    pub starts: u64,
+    pub last_start_ms: Option<f32>,
+    pub min_start_ms: Option<f32>,
+    pub max_start_ms: Option<f32>,
+    pub mean_start_ms: Option<f32>,
+}
+
+#[derive(Default)]
+pub struct MicMetricsState(Mutex<MicMetrics>);
+
+pub fn record_start_latency(app: &AppHandle, ms: f32) {
+    let state = app.state::<MicMetricsState>();
+    let Ok(mut metrics) = state.0.lock() else {
+        return;
+    };
+    let total = metrics.mean_start_ms.unwrap_or(0.0) * metrics.starts as f32 + ms;
+    metrics.starts += 1;
+    metrics.last_start_ms = Some(ms);
+    metrics.min_start_ms = Some(metrics.min_start_ms.map_or(ms, |min| min.min(ms)));
+    metrics.max_start_ms = Some(metrics.max_start_ms.map_or(ms, |max| max.max(ms)));
+    metrics.mean_start_ms = Some(total / metrics.starts as f32);
+}
+
+#[tauri::command]
+pub fn get_mic_metrics(metrics: State<'_, MicMetricsState>) -> MicMetrics {
+    metrics.0.lock().map(|m| m.clone()).unwrap_or_default()
+}
+
+#[derive(Clone, Serialize)]
+pub struct WakeWordDetection {
+    pub keyword: String,
+    pub confidence: f32,
+    pub outcome: WakeWordOutcome,
+}
+
+/// Wake-word detections since launch.
+#[derive(Clone, Default, Serialize)]
+pub struct WakeWordMetrics {
+    pub detections: u64,
+    pub accepted: u64,
+    pub below_threshold: u64,
+    pub cooldown: u64,
+    /// The last detections, oldest first.
+    pub recent: VecDeque<WakeWordDetection>,
+}
+
+#[derive(Default)]
+pub struct WakeWordMetricsState(Mutex<WakeWordMetrics>);
+
+/// Count a detection and keep it in `recent`; also logged, for tuning from
+/// the crash log.
+pub fn record_wake_word(app: &AppHandle, keyword: &str, confidence: f32, outcome: WakeWordOutcome) {
+    log!(
+        "Wake word {:?} at confidence {:.2}: {:?}",
+        keyword,
+        confidence,
+        outcome
+    );
+    let state = app.state::<WakeWordMetricsState>();
+    let Ok(mut metrics) = state.0.lock() else {
+        return;
+    };
+    metrics.detections += 1;
+    match outcome {
+        WakeWordOutcome::Accepted => metrics.accepted += 1,
+        WakeWordOutcome::BelowThreshold => metrics.below_threshold += 1,
+        WakeWordOutcome::Cooldown => metrics.cooldown += 1,
+        WakeWordOutcome::Disabled => {}
+    }
+    if metrics.recent.len() == WAKE_WORD_RECENT {
+        metrics.recent.pop_front();
+    }
+    metrics.recent.push_back(WakeWordDetection {
+        keyword: keyword.to_string(),
+        confidence,
+        outcome,
+    });
+}
+
+#[tauri::command]
+pub fn get_wake_word_metrics(metrics: State<'_, WakeWordMetricsState>) -> WakeWordMetrics {
+    metrics.0.lock().map(|m| m.clone()).unwrap_or_default()
+}
@@ -1,320 +1,506 @@
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{SampleFormat, StreamConfig};
-use serde::Serialize;
+//! The app's microphone: `voice_core::capture` as managed state, its events
+//! forwarded to the webview and cue player, and the mic commands.
+//!
+//! Besides the main recording, which the controller drives through record →
+//! send, named sessions can record alongside it, e.g. a loopback session
+//! taking the other side of a call while the mic takes ours. Each is a
+//! capture of its own with its own buffer, started and stopped by id, and
+//! its events carry that id.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
-use tauri::{AppHandle, Emitter, State};
-
+use std::time::Duration;
+use tauri::ipc::Response;
+use tauri::{AppHandle, Manager};
+
+pub use voice_core::capture::{Capture, CaptureSettings, InputLatency, MicError, Recording};
+use voice_core::capture::{CaptureEvent, EventCallback};
+pub use voice_core::TARGET_SAMPLE_RATE;
+
+use crate::api;
+use crate::controller::{self, Controller};
+use crate::cues::{self, Cue};
+use crate::devices::AudioSource;
+use crate::error::CommandError;
+use crate::events;
+use crate::metrics;
+use crate::processing::Preset;
+use crate::settings::SettingsState;
+
+/// Id of the controller's recording in events and session commands.
+pub const MAIN_SESSION: &str = "main";
+
+const MIC_TEST_MAX_SECS: f64 = 10.0;
+const MIC_TEST_FILE: &str = "agentic-devops-voice-mic-test.wav";
+
+/// The main capture and the named sessions by id.
+#[derive(Default)]
 pub struct MicState {
-    recording: Arc<Mutex<bool>>,
-    buffer: Arc<Mutex<Vec<i16>>>,
-    stream: Arc<Mutex<Option<cpal::Stream>>>,
-    input_sample_rate: Arc<Mutex<u32>>,
-    app_handle: Arc<Mutex<Option<AppHandle>>>,
+    main: Capture,
+    sessions: Mutex<HashMap<String, Capture>>,
 }
 
-impl Default for MicState {
-    fn default() -> Self {
-        Self {
-            recording: Arc::new(Mutex::new(false)),
-            buffer: Arc::new(Mutex::new(Vec::new())),
-            stream: Arc::new(Mutex::new(None)),
-            input_sample_rate: Arc::new(Mutex::new(TARGET_SAMPLE_RATE)),
-            app_handle: Arc::new(Mutex::new(None)),
-        }
+impl MicState {
+    /// The controller's recording.
+    pub fn main(&self) -> &Capture {
+        &self.main
     }
-}
 
-// SAFETY: cpal::Stream is Send on all platforms we target.
-// The stream is behind Arc<Mutex<>> and only accessed from one thread at a time.
-unsafe impl Send for MicState {}
-unsafe impl Sync for MicState {}
+    /// The capture behind `id`, including `"main"`.
+    pub fn session(&self, id: &str) -> Option<Capture> {
+        if id == MAIN_SESSION {
+            return Some(self.main.clone());
+        }
+        self.sessions.lock().ok()?.get(id).cloned()
+    }
 
-impl MicState {
-    pub fn set_app_handle(&self, handle: AppHandle) {
-        if let Ok(mut h) = self.app_handle.lock() {
-            *h = Some(handle);
+    /// Every session, main first.
+    pub fn sessions(&self) -> Vec<(String, Capture)> {
+        let mut all = vec![(MAIN_SESSION.to_string(), self.main.clone())];
+        if let Ok(sessions) = self.sessions.lock() {
+            let mut named: Vec<_> = sessions
+                .iter()
+                .map(|(id, capture)| (id.clone(), capture.clone()))
+                .collect();
+            named.sort_by(|a, b| a.0.cmp(&b.0));
+            all.extend(named);
         }
+        all
     }
 }
 
-const TARGET_SAMPLE_RATE: u32 = 16_000;
-const RMS_WINDOW: usize = 800; // ~50ms at 16kHz
-const MIN_EMIT_INTERVAL_MS: u128 = 50; // Max 20 events/s
-
 #[derive(Clone, Serialize)]
-struct MicLevelPayload {
+pub struct MicLevelPayload {
+    session: String,
     rms: f32,
 }
 
-fn to_i16(sample: f32) -> i16 {
-    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+impl events::Event for MicLevelPayload {
+    const NAME: &'static str = "mic-level";
 }
 
-fn interleaved_f32_to_mono(data: &[f32], channels: usize) -> Vec<f32> {
-    if channels <= 1 {
-        return data.iter().map(|s| s.clamp(-1.0, 1.0)).collect();
-    }
-    data.chunks(channels)
-        .map(|frame| {
-            let sum: f32 = frame.iter().map(|s| s.clamp(-1.0, 1.0)).sum();
-            (sum / channels as f32).clamp(-1.0, 1.0)
-        })
-        .collect()
+/// `source` is `"stream"` when a chunk subscriber fell behind, `"recording"`
+/// when the recording hit the length cap and stopped growing.
+#[derive(Clone, Serialize)]
+pub struct MicOverrunPayload {
+    session: String,
+    source: &'static str,
 }
 
-fn interleaved_i16_to_mono(data: &[i16], channels: usize) -> Vec<f32> {
-    if channels <= 1 {
-        return data
-            .iter()
-            .map(|s| (*s as f32 / i16::MAX as f32).clamp(-1.0, 1.0))
-            .collect();
-    }
-    data.chunks(channels)
-        .map(|frame| {
-            let sum: f32 = frame
-                .iter()
-                .map(|s| (*s as f32 / i16::MAX as f32).clamp(-1.0, 1.0))
-                .sum();
-            (sum / channels as f32).clamp(-1.0, 1.0)
-        })
-        .collect()
+impl events::Event for MicOverrunPayload {
+    const NAME: &'static str = "mic-overrun";
 }
 
-fn interleaved_u16_to_mono(data: &[u16], channels: usize) -> Vec<f32> {
-    let to_f32 = |s: u16| ((s as f32 / u16::MAX as f32) * 2.0 - 1.0).clamp(-1.0, 1.0);
-    if channels <= 1 {
-        return data.iter().map(|s| to_f32(*s)).collect();
-    }
-    data.chunks(channels)
-        .map(|frame| {
-            let sum: f32 = frame.iter().map(|s| to_f32(*s)).sum();
-            (sum / channels as f32).clamp(-1.0, 1.0)
-        })
-        .collect()
+#[derive(Clone, Serialize)]
+pub struct MicLatencyPayload {
+    session: String,
+    #[serde(flatten)]
+    latency: InputLatency,
 }
 
-fn handle_mono_samples(
-    mono: &[f32],
-    buffer: &Arc<Mutex<Vec<i16>>>,
-    rms_buffer: &Arc<Mutex<Vec<f32>>>,
-    last_emit: &Arc<Mutex<Instant>>,
-    app_handle: &Arc<Mutex<Option<AppHandle>>>,
-) {
-    if mono.is_empty() {
-        return;
-    }
+impl events::Event for MicLatencyPayload {
+    const NAME: &'static str = "mic-latency";
+}
 
-    if let Ok(mut buf) = buffer.lock() {
-        buf.extend(mono.iter().map(|s| to_i16(*s)));
-    }
+#[derive(Clone, Serialize)]
+pub struct MicRestartedPayload {
+    session: String,
+    /// The configured device failed to reopen and the default was used.
+    fallback: bool,
+}
 
-    if let Ok(mut rms_buf) = rms_buffer.lock() {
-        rms_buf.extend_from_slice(mono);
+impl events::Event for MicRestartedPayload {
+    const NAME: &'static str = "mic-restarted";
+}
 
-        if rms_buf.len() >= RMS_WINDOW {
-            let should_emit = last_emit
-                .lock()
-                .map(|t| t.elapsed().as_millis() >= MIN_EMIT_INTERVAL_MS)
-                .unwrap_or(true);
+/// The recording was suspended while the OS gave the mic to something else
+/// (a call, Siri), or resumed afterwards.
+#[derive(Clone, Serialize)]
+pub struct MicSuspendedPayload {
+    session: String,
+    suspended: bool,
+    /// Resumed on the default device because the configured one failed to
+    /// reopen.
+    fallback: bool,
+}
 
-            if should_emit {
-                let sum_sq: f32 = rms_buf.iter().map(|&s| s * s).sum();
-                let rms = (sum_sq / rms_buf.len() as f32).sqrt();
+impl events::Event for MicSuspendedPayload {
+    const NAME: &'static str = "mic-suspended";
+}
 
-                if let Ok(handle) = app_handle.lock() {
-                    if let Some(ref h) = *handle {
-                        let _ = h.emit("mic-level", MicLevelPayload { rms });
-                    }
-                }
+/// A named session started or stopped. The main recording shows up as
+/// `state-changed` instead.
+#[derive(Clone, Serialize)]
+pub struct MicSessionPayload {
+    session: String,
+    recording: bool,
+}
+
+impl events::Event for MicSessionPayload {
+    const NAME: &'static str = "mic-session";
+}
 
-                if let Ok(mut t) = last_emit.lock() {
-                    *t = Instant::now();
+/// Fail, and ask the user, where the OS gates the mic behind a runtime
+/// permission that isn't granted yet (Android, macOS), or a privacy switch
+/// blocks it (Windows). Called before every capture.
+pub fn ensure_access() -> Result<(), MicError> {
+    #[cfg(target_os = "android")]
+    crate::android::ensure_record_permission()?;
+    #[cfg(target_os = "macos")]
+    crate::macos::ensure_record_permission()?;
+    #[cfg(windows)]
+    crate::privacy::ensure_record_permission()?;
+    Ok(())
+}
+
+/// Capture events of session `id` as webview events. Only the main session
+/// plays the start and stop cues.
+fn event_handler(app: &AppHandle, id: &str) -> EventCallback {
+    let handle = app.clone();
+    let session = id.to_string();
+    let main = id == MAIN_SESSION;
+    Arc::new(move |event| {
+        let session = session.clone();
+        match event {
+            CaptureEvent::Started if main => cues::play(&handle, Cue::Start),
+            CaptureEvent::Stopped if main => cues::play(&handle, Cue::Stop),
+            CaptureEvent::Started | CaptureEvent::Stopped => events::emit(
+                &handle,
+                &MicSessionPayload {
+                    session,
+                    recording: matches!(event, CaptureEvent::Started),
+                },
+            ),
+            CaptureEvent::Level { rms } => events::emit(&handle, &MicLevelPayload { session, rms }),
+            CaptureEvent::Overrun { source } => {
+                events::emit(&handle, &MicOverrunPayload { session, source })
+            }
+            CaptureEvent::Latency(latency) => {
+                events::emit(&handle, &MicLatencyPayload { session, latency })
+            }
+            CaptureEvent::FirstSample { ms } => {
+                if main {
+                    metrics::record_start_latency(&handle, ms);
                 }
             }
+            CaptureEvent::Restarted { fallback } => {
+                events::emit(&handle, &MicRestartedPayload { session, fallback })
+            }
+            CaptureEvent::Suspended => events::emit(
+                &handle,
+                &MicSuspendedPayload {
+                    session,
+                    suspended: true,
+                    fallback: false,
+                },
+            ),
+            CaptureEvent::Resumed { fallback } => events::emit(
+                &handle,
+                &MicSuspendedPayload {
+                    session,
+                    suspended: false,
+                    fallback,
+                },
+            ),
+        }
+    })
+}
 
-            rms_buf.clear();
+/// Open or close the main capture's standby stream to match the `standby`
+/// setting, on a thread of its own since opening the device takes a while.
+pub fn apply_standby(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let settings = app.state::<SettingsState>().get();
+        let mic = app.state::<MicState>();
+        if !settings.standby {
+            mic.main().close_standby();
+            return;
         }
-    }
+        if let Err(e) =
+            ensure_access().and_then(|()| mic.main().standby(&settings.source, &settings.capture))
+        {
+            log!("Mic standby failed: {}", e);
+        }
+    });
 }
 
-fn resample_linear_i16(input: &[i16], input_rate: u32, output_rate: u32) -> Vec<i16> {
-    if input.is_empty() || input_rate == output_rate {
-        return input.to_vec();
-    }
+/// Send the main mic's capture events to the webview, and play the start
+/// and stop cues.
+pub fn forward_events(app: &AppHandle) {
+    app.state::<MicState>()
+        .main()
+        .set_events(event_handler(app, MAIN_SESSION));
+}
 
-    let step = input_rate as f64 / output_rate as f64;
-    let mut pos = 0.0_f64;
-    let mut output = Vec::new();
-
-    while (pos as usize) + 1 < input.len() {
-        let i = pos.floor() as usize;
-        let frac = (pos - i as f64) as f32;
-        let a = input[i] as f32;
-        let b = input[i + 1] as f32;
-        output.push((a + (b - a) * frac).clamp(i16::MIN as f32, i16::MAX as f32) as i16);
-        pos += step;
-    }
+#[derive(Clone, Serialize)]
+pub struct MicMutedPayload {
+    session: String,
+    muted: bool,
+}
 
-    if output.is_empty() {
-        output.push(input[0]);
-    }
-    output
-}
-
-fn build_stream(
-    device: &cpal::Device,
-    config: &StreamConfig,
-    sample_format: SampleFormat,
-    buffer: Arc<Mutex<Vec<i16>>>,
-    recording_flag: Arc<Mutex<bool>>,
-    app_handle: Arc<Mutex<Option<AppHandle>>>,
-    rms_buffer: Arc<Mutex<Vec<f32>>>,
-    last_emit: Arc<Mutex<Instant>>,
-) -> Result<cpal::Stream, String> {
-    let channels = config.channels as usize;
-    if channels == 0 {
-        return Err("Input device reports zero channels".into());
-    }
+impl events::Event for MicMutedPayload {
+    const NAME: &'static str = "mic-muted";
+}
 
-    let err_fn = move |err| {
-        eprintln!("Audio stream error: {}", err);
-    };
-
-    match sample_format {
-        SampleFormat::F32 => device
-            .build_input_stream(
-                config,
-                move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    let is_recording = recording_flag.lock().map(|r| *r).unwrap_or(false);
-                    if !is_recording {
-                        return;
-                    }
-                    let mono = interleaved_f32_to_mono(data, channels);
-                    handle_mono_samples(&mono, &buffer, &rms_buffer, &last_emit, &app_handle);
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| format!("Failed to build input stream: {}", e)),
-        SampleFormat::I16 => device
-            .build_input_stream(
-                config,
-                move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                    let is_recording = recording_flag.lock().map(|r| *r).unwrap_or(false);
-                    if !is_recording {
-                        return;
-                    }
-                    let mono = interleaved_i16_to_mono(data, channels);
-                    handle_mono_samples(&mono, &buffer, &rms_buffer, &last_emit, &app_handle);
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| format!("Failed to build input stream: {}", e)),
-        SampleFormat::U16 => device
-            .build_input_stream(
-                config,
-                move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                    let is_recording = recording_flag.lock().map(|r| *r).unwrap_or(false);
-                    if !is_recording {
-                        return;
-                    }
-                    let mono = interleaved_u16_to_mono(data, channels);
-                    handle_mono_samples(&mono, &buffer, &rms_buffer, &last_emit, &app_handle);
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| format!("Failed to build input stream: {}", e)),
-        _ => Err(format!("Unsupported sample format: {:?}", sample_format)),
+/// Capture overrides for one recording, applied on top of the configured
+/// source and capture settings without changing them.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StartOptions {
+    pub device: Option<String>,
+    /// Device rate to open the input at; the recording is 16 kHz regardless.
+    pub sample_rate: Option<u32>,
+    /// Device channel count to open the input with.
+    pub channels: Option<u16>,
+    /// 0-based channel to record instead of downmixing.
+    pub channel: Option<u16>,
+    pub dither: Option<bool>,
+    pub preset: Option<Preset>,
+    pub avfoundation: Option<bool>,
+}
+
+impl StartOptions {
+    pub fn apply(
+        &self,
+        source: &AudioSource,
+        capture: &CaptureSettings,
+    ) -> (AudioSource, CaptureSettings) {
+        let mut source = source.clone();
+        let mut capture = capture.clone();
+        if self.device.is_some() {
+            source.device = self.device.clone();
+        }
+        source.sample_rate = self.sample_rate.or(source.sample_rate);
+        source.channels = self.channels.or(source.channels);
+        source.channel = self.channel.or(source.channel);
+        capture.dither = self.dither.unwrap_or(capture.dither);
+        capture.preset = self.preset.unwrap_or(capture.preset);
+        capture.avfoundation = self.avfoundation.unwrap_or(capture.avfoundation);
+        (source, capture)
     }
 }
 
+/// `source` replaces the one in settings for this recording only, and
+/// `options` override parts of it and of the capture settings.
 #[tauri::command]
-pub fn start_mic(state: State<'_, MicState>) -> Result<String, String> {
-    let mut recording = state.recording.lock().map_err(|e| e.to_string())?;
-    if *recording {
-        return Err("Already recording".into());
-    }
+pub fn start_mic(
+    app: AppHandle,
+    source: Option<AudioSource>,
+    options: Option<StartOptions>,
+) -> Result<String, CommandError> {
+    let settings = app.state::<SettingsState>().get();
+    let (source, capture) = options.unwrap_or_default().apply(
+        source.as_ref().unwrap_or(&settings.source),
+        &settings.capture,
+    );
+    controller::start_recording_with(&app, &source, &capture)?;
+    Ok("Recording started".into())
+}
 
-    // Clear previous buffer
-    {
-        let mut buf = state.buffer.lock().map_err(|e| e.to_string())?;
-        buf.clear();
-    }
+#[derive(Serialize)]
+pub struct MicStatus {
+    session: String,
+    recording: bool,
+    muted: bool,
+    /// A standby stream is open, waiting for the next recording.
+    standby: bool,
+    sample_rate: u32,
+    latency: Option<InputLatency>,
+    /// From `start` to the first audio of the current or last recording.
+    start_latency_ms: Option<f32>,
+}
 
-    let host = cpal::default_host();
-    let device = host
-        .default_input_device()
-        .ok_or("No input device available")?;
-
-    let supported_config = device
-        .default_input_config()
-        .map_err(|e| format!("Failed to get default input config: {}", e))?;
-    let sample_format = supported_config.sample_format();
-    let config: StreamConfig = supported_config.config();
-
-    {
-        let mut sr = state
-            .input_sample_rate
-            .lock()
-            .map_err(|e| e.to_string())?;
-        *sr = config.sample_rate.0;
+impl MicStatus {
+    fn of(session: String, capture: &Capture) -> Self {
+        Self {
+            session,
+            recording: capture.is_recording(),
+            muted: capture.is_muted(),
+            standby: capture.is_standby(),
+            sample_rate: capture.input_sample_rate(),
+            latency: capture.latency(),
+            start_latency_ms: capture.start_latency().map(|d| d.as_secs_f32() * 1000.0),
+        }
     }
+}
 
-    let buffer = Arc::clone(&state.buffer);
-    let recording_flag = Arc::clone(&state.recording);
-    let app_handle = Arc::clone(&state.app_handle);
+fn unknown_session(id: &str) -> CommandError {
+    CommandError::invalid_state(format!("No session {}", id))
+}
 
-    // State for RMS calculation + throttling
-    let rms_buffer: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::with_capacity(RMS_WINDOW)));
-    let last_emit = Arc::new(Mutex::new(Instant::now()));
+/// Status of `session`, the main recording by default.
+#[tauri::command]
+pub fn get_mic_status(
+    state: tauri::State<'_, MicState>,
+    session: Option<String>,
+) -> Result<MicStatus, CommandError> {
+    let id = session.unwrap_or_else(|| MAIN_SESSION.to_string());
+    let capture = state.session(&id).ok_or_else(|| unknown_session(&id))?;
+    Ok(MicStatus::of(id, &capture))
+}
 
-    let stream = build_stream(
-        &device,
-        &config,
-        sample_format,
-        buffer,
-        recording_flag,
-        app_handle,
-        rms_buffer,
-        last_emit,
-    )?;
+/// Mute or unmute a recording in progress, the main one by default: silence
+/// is recorded in place of the input, and the stream keeps running. Every
+/// recording starts unmuted.
+#[tauri::command]
+pub fn set_muted(app: AppHandle, muted: bool, session: Option<String>) -> Result<(), CommandError> {
+    let session = session.unwrap_or_else(|| MAIN_SESSION.to_string());
+    let mic = app
+        .state::<MicState>()
+        .session(&session)
+        .filter(Capture::is_recording)
+        .ok_or(MicError::NotRecording)?;
+    mic.set_muted(muted);
+    events::emit(&app, &MicMutedPayload { session, muted });
+    Ok(())
+}
 
-    stream.play().map_err(|e| format!("Failed to start stream: {}", e))?;
+fn to_bytes(recording: &Recording) -> Vec<u8> {
+    recording
+        .samples
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect()
+}
 
-    *recording = true;
-    let mut stream_holder = state.stream.lock().map_err(|e| e.to_string())?;
-    *stream_holder = Some(stream);
+/// Returns the samples as raw little-endian i16 bytes (an `ArrayBuffer` on
+/// the JS side) instead of a JSON array, which is several times larger and
+/// slow to serialize for long recordings. The samples also stay in Rust for
+/// `send_last_recording`, so the preview copy never has to come back.
+#[tauri::command]
+pub fn stop_mic(app: AppHandle) -> Result<Response, CommandError> {
+    let recording = controller::stop_recording(&app)?;
+    let bytes = to_bytes(&recording);
+    app.state::<Controller>().keep_recording(Some(recording));
+    Ok(Response::new(bytes))
+}
 
-    Ok("Recording started".into())
+/// Start named session `id` recording from `source` (the configured source
+/// when unset) with `options` as for `start_mic`, next to the main
+/// recording and any other sessions. A session that was stopped can be
+/// started again under the same id.
+#[tauri::command]
+pub fn start_session(
+    app: AppHandle,
+    state: tauri::State<'_, MicState>,
+    id: String,
+    source: Option<AudioSource>,
+    options: Option<StartOptions>,
+) -> Result<(), CommandError> {
+    if id == MAIN_SESSION {
+        return Err("The main session is started with start_mic".into());
+    }
+    let mut sessions = state
+        .sessions
+        .lock()
+        .map_err(|_| "Session list unavailable")?;
+    if sessions.get(&id).is_some_and(Capture::is_recording) {
+        return Err(CommandError::invalid_state(format!(
+            "Session {} is already recording",
+            id
+        )));
+    }
+    ensure_access()?;
+    let settings = app.state::<SettingsState>().get();
+    let (source, capture_settings) = options.unwrap_or_default().apply(
+        source.as_ref().unwrap_or(&settings.source),
+        &settings.capture,
+    );
+    let capture = Capture::default();
+    capture.set_events(event_handler(&app, &id));
+    capture.start(&source, &capture_settings)?;
+    sessions.insert(id, capture);
+    Ok(())
 }
 
+/// Stop named session `id` and return its samples, as `stop_mic` does.
+/// The session is forgotten afterwards.
 #[tauri::command]
-pub fn stop_mic(state: State<'_, MicState>) -> Result<Vec<i16>, String> {
-    let mut recording = state.recording.lock().map_err(|e| e.to_string())?;
-    if !*recording {
-        return Err("Not recording".into());
+pub fn stop_session(
+    state: tauri::State<'_, MicState>,
+    id: String,
+) -> Result<Response, CommandError> {
+    if id == MAIN_SESSION {
+        return Err("The main session is stopped with stop_mic".into());
     }
+    let capture = state
+        .sessions
+        .lock()
+        .map_err(|_| "Session list unavailable")?
+        .remove(&id)
+        .ok_or_else(|| unknown_session(&id))?;
+    let recording = capture.stop()?;
+    Ok(Response::new(to_bytes(&recording)))
+}
 
-    *recording = false;
+/// Every session, the main one first.
+#[tauri::command]
+pub fn list_sessions(state: tauri::State<'_, MicState>) -> Vec<MicStatus> {
+    state
+        .sessions()
+        .into_iter()
+        .map(|(id, capture)| MicStatus::of(id, &capture))
+        .collect()
+}
 
-    // Drop the stream to stop recording
-    {
-        let mut stream_holder = state.stream.lock().map_err(|e| e.to_string())?;
-        *stream_holder = None;
-    }
+/// Result of `test_microphone`. Levels are linear (0 to 1) and in dBFS.
+#[derive(Serialize)]
+pub struct MicTest {
+    duration_secs: f64,
+    rms: f32,
+    peak: f32,
+    rms_dbfs: f32,
+    peak_dbfs: f32,
+    /// The clip as a 16 kHz WAV, for playing it back; overwritten by the
+    /// next test.
+    path: PathBuf,
+}
 
-    let input_rate = *state
-        .input_sample_rate
-        .lock()
-        .map_err(|e| e.to_string())?;
-    let buf = state.buffer.lock().map_err(|e| e.to_string())?.clone();
+fn dbfs(level: f32) -> f32 {
+    20.0 * level.max(1e-5).log10()
+}
 
-    if input_rate == TARGET_SAMPLE_RATE {
-        return Ok(buf);
+/// Record `seconds` (up to 10) from the configured source through the same
+/// capture path as a real recording, without touching the app's recording
+/// state, and report the levels.
+#[tauri::command]
+pub async fn test_microphone(app: AppHandle, seconds: f64) -> Result<MicTest, CommandError> {
+    if app.state::<Controller>().state() == controller::AppState::Recording {
+        return Err(CommandError::invalid_state(
+            "Stop the current recording first",
+        ));
     }
-
-    Ok(resample_linear_i16(&buf, input_rate, TARGET_SAMPLE_RATE))
+    let settings = app.state::<SettingsState>().get();
+    let seconds = seconds.clamp(0.1, MIC_TEST_MAX_SECS);
+
+    let samples = tauri::async_runtime::spawn_blocking(move || {
+        ensure_access()?;
+        let mic = Capture::default();
+        mic.start(&settings.source, &settings.capture)?;
+        std::thread::sleep(Duration::from_secs_f64(seconds));
+        mic.stop()
+    })
+    .await
+    .map_err(|e| format!("Mic test failed: {}", e))??
+    .samples;
+
+    let to_f32 = |s: i16| s as f32 / i16::MAX as f32;
+    let peak = samples.iter().map(|&s| to_f32(s).abs()).fold(0.0, f32::max);
+    let sum_sq: f32 = samples.iter().map(|&s| to_f32(s).powi(2)).sum();
+    let rms = (sum_sq / samples.len().max(1) as f32).sqrt();
+
+    let path = std::env::temp_dir().join(MIC_TEST_FILE);
+    std::fs::write(&path, api::encode_wav(&samples)?)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(MicTest {
+        duration_secs: samples.len() as f64 / TARGET_SAMPLE_RATE as f64,
+        rms,
+        peak,
+        rms_dbfs: dbfs(rms),
+        peak_dbfs: dbfs(peak),
+        path,
+    })
 }
@@ -0,0 +1,178 @@
+//! MIDI triggers: foot pedals and pad controllers that present as MIDI
+//! devices, with notes or control changes mapped to start, stop, send or
+//! toggle. The input port is opened with midir, whose callback runs on its
+//! own thread, so this works while the app is unfocused. Actions go
+//! through the same paths as the control socket and the tray.
+
+use midir::{Ignore, MidiInput, MidiInputConnection};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::control::{self, Command};
+use crate::voice_loop;
+
+const CLIENT_NAME: &str = "agentic-devops-voice";
+/// CC values from here up count as pressed, as for sustain pedals.
+const CC_PRESSED: u8 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MidiAction {
+    Start,
+    /// Stop and keep the recording for a later `send`.
+    Stop,
+    /// Send the kept recording, stopping first if still recording.
+    Send,
+    /// Start, or stop and send, like the hotkey.
+    Toggle,
+}
+
+/// One note or control change (set exactly one of `note` and `cc`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MidiMapping {
+    pub note: Option<u8>,
+    pub cc: Option<u8>,
+    /// 1–16; any channel when unset.
+    pub channel: Option<u8>,
+    /// On note on, or a CC value of 64 and up.
+    pub press: Option<MidiAction>,
+    /// On note off, or a CC value below 64; e.g. `send` after `start` on
+    /// press for a hold-to-dictate pedal.
+    pub release: Option<MidiAction>,
+}
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MidiSettings {
+    pub enabled: bool,
+    /// Case-insensitive substring of the input port's name; the first port
+    /// when unset.
+    pub port: Option<String>,
+    pub mappings: Vec<MidiMapping>,
+}
+
+/// The open input port; dropping the connection closes it.
+#[derive(Default)]
+pub struct MidiState(Mutex<Option<MidiInputConnection<()>>>);
+
+/// A note or CC message, with its 1-based channel and whether it counts as
+/// a press.
+struct Event {
+    note: Option<u8>,
+    cc: Option<u8>,
+    channel: u8,
+    pressed: bool,
+}
+
+fn parse(message: &[u8]) -> Option<Event> {
+    let &[status, number, value, ..] = message else {
+        return None;
+    };
+    let channel = (status & 0x0f) + 1;
+    let (note, cc, pressed) = match status & 0xf0 {
+        0x90 => (Some(number), None, value > 0),
+        0x80 => (Some(number), None, false),
+        0xb0 => (None, Some(number), value >= CC_PRESSED),
+        _ => return None,
+    };
+    Some(Event {
+        note,
+        cc,
+        channel,
+        pressed,
+    })
+}
+
+impl MidiMapping {
+    fn action(&self, event: &Event) -> Option<MidiAction> {
+        let matches = ((self.note.is_some() && self.note == event.note)
+            || (self.cc.is_some() && self.cc == event.cc))
+            && self.channel.is_none_or(|ch| ch == event.channel);
+        if !matches {
+            return None;
+        }
+        if event.pressed {
+            self.press
+        } else {
+            self.release
+        }
+    }
+}
+
+fn run(app: &AppHandle, action: MidiAction) {
+    let command = match action {
+        MidiAction::Toggle => return voice_loop::toggle(app),
+        MidiAction::Start => Command::Start,
+        MidiAction::Stop => Command::Stop,
+        MidiAction::Send => Command::Send,
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = control::execute(&app, command).await;
+        match action {
+            MidiAction::Send => voice_loop::report(&app, result),
+            _ => {
+                if let Err(e) = result {
+                    log!("MIDI {:?} failed: {}", action, e);
+                }
+            }
+        }
+    });
+}
+
+fn connect(app: &AppHandle, settings: &MidiSettings) -> Result<MidiInputConnection<()>, String> {
+    let mut input =
+        MidiInput::new(CLIENT_NAME).map_err(|e| format!("MIDI unavailable: {}", e))?;
+    input.ignore(Ignore::All);
+    let wanted = settings.port.as_deref().map(str::to_lowercase);
+    let port = input
+        .ports()
+        .into_iter()
+        .find(|port| match &wanted {
+            None => true,
+            Some(wanted) => input
+                .port_name(port)
+                .is_ok_and(|name| name.to_lowercase().contains(wanted)),
+        })
+        .ok_or_else(|| match &settings.port {
+            Some(name) => format!("MIDI port not found: {}", name),
+            None => "No MIDI input port available".to_string(),
+        })?;
+
+    let app = app.clone();
+    let mappings = settings.mappings.clone();
+    input
+        .connect(
+            &port,
+            CLIENT_NAME,
+            move |_, message, _| {
+                let Some(event) = parse(message) else {
+                    return;
+                };
+                for action in mappings.iter().filter_map(|m| m.action(&event)) {
+                    run(&app, action);
+                }
+            },
+            (),
+        )
+        .map_err(|e| format!("Failed to open MIDI port: {}", e))
+}
+
+/// Close the open port, if any, and open one for `settings` if enabled.
+pub fn apply(app: &AppHandle, settings: &MidiSettings) {
+    let state = app.state::<MidiState>();
+    let Ok(mut open) = state.0.lock() else {
+        return;
+    };
+    if let Some(connection) = open.take() {
+        connection.close();
+    }
+    if !settings.enabled {
+        return;
+    }
+    match connect(app, settings) {
+        Ok(connection) => *open = Some(connection),
+        Err(e) => log!("{}", e),
+    }
+}
@@ -0,0 +1,183 @@
+//! The HTTP client shared by everything that talks to the pipeline server,
+//! rebuilt when the `network` settings change. Sharing it keeps idle
+//! connections in one pool, so repeated uploads to the same server skip the
+//! TCP and TLS handshakes. `host_overrides` pins hostnames to addresses for
+//! lab setups whose server isn't in DNS, without editing the hosts file;
+//! TLS still checks the certificate against the hostname. Servers that
+//! require mutual TLS get the certificate in the `client_identity` secret.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::secrets;
+
+const WARM_UP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpVersion {
+    /// HTTP/2 where TLS negotiates it, HTTP/1.1 otherwise.
+    #[default]
+    Auto,
+    Http1,
+    /// HTTP/2 without negotiation, also over plain `http://`. Only for
+    /// servers known to speak it.
+    Http2,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    /// Hostname to IP address, e.g. `"pipeline.lab": "10.0.0.12"`. The port
+    /// still comes from the URL.
+    pub host_overrides: HashMap<String, String>,
+    /// TCP keep-alive probe interval; 0 turns it off.
+    pub keep_alive_secs: u64,
+    /// How long an unused connection is kept for the next request.
+    pub pool_idle_secs: u64,
+    pub http_version: HttpVersion,
+    /// Connect to the server when settings load, so the first send doesn't
+    /// pay for DNS and handshakes.
+    pub warm_up: bool,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            host_overrides: HashMap::new(),
+            keep_alive_secs: 60,
+            pool_idle_secs: 90,
+            http_version: HttpVersion::Auto,
+            warm_up: false,
+        }
+    }
+}
+
+/// Split PEM text into its certificates and its private key.
+fn split_pem(pem: &str) -> Result<(String, String), String> {
+    let mut certs = String::new();
+    let mut key = String::new();
+    let mut in_key = None;
+    for line in pem.lines().map(str::trim) {
+        if line.starts_with("-----BEGIN ") {
+            in_key = Some(line.contains("PRIVATE KEY"));
+        }
+        match in_key {
+            Some(true) => key.push_str(line),
+            Some(false) => certs.push_str(line),
+            None => continue,
+        }
+        if in_key == Some(true) {
+            key.push('\n');
+        } else {
+            certs.push('\n');
+        }
+        if line.starts_with("-----END ") {
+            in_key = None;
+        }
+    }
+    if certs.is_empty() || key.is_empty() {
+        return Err("client_identity needs a certificate and its PKCS#8 private key".into());
+    }
+    Ok((certs, key))
+}
+
+/// The client certificate from the keychain, if one is stored. A keychain
+/// that can't be read is logged and counts as none, like the API key.
+pub fn identity() -> Result<Option<reqwest::Identity>, String> {
+    let stored = secrets::get(secrets::CLIENT_IDENTITY).unwrap_or_else(|e| {
+        log!("{}; connecting without a client certificate", e);
+        None
+    });
+    let Some(stored) = stored.filter(|s| !s.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let identity = if stored.trim_start().starts_with("-----BEGIN ") {
+        let (certs, key) = split_pem(&stored)?;
+        reqwest::Identity::from_pkcs8_pem(certs.as_bytes(), key.as_bytes())
+    } else {
+        let der = BASE64
+            .decode(stored.trim())
+            .map_err(|e| format!("client_identity is neither PEM nor base64: {}", e))?;
+        let password = secrets::get(secrets::CLIENT_IDENTITY_PASSWORD)?.unwrap_or_default();
+        reqwest::Identity::from_pkcs12_der(&der, &password)
+    };
+    identity
+        .map(Some)
+        .map_err(|e| format!("Invalid client_identity: {}", e))
+}
+
+pub fn build_client(
+    settings: &NetworkSettings,
+    identity: Option<reqwest::Identity>,
+) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder()
+        .pool_idle_timeout(Duration::from_secs(settings.pool_idle_secs))
+        .tcp_keepalive(
+            Some(Duration::from_secs(settings.keep_alive_secs)).filter(|d| !d.is_zero()),
+        );
+    builder = match settings.http_version {
+        HttpVersion::Auto => builder,
+        HttpVersion::Http1 => builder.http1_only(),
+        HttpVersion::Http2 => builder.http2_prior_knowledge(),
+    };
+    if let Some(identity) = identity {
+        builder = builder.identity(identity);
+    }
+    for (host, ip) in &settings.host_overrides {
+        let ip: IpAddr = ip
+            .parse()
+            .map_err(|_| format!("Not an IP address for {}: {:?}", host, ip))?;
+        builder = builder.resolve(host, SocketAddr::new(ip, 0));
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+#[derive(Default)]
+pub struct HttpClient(Mutex<reqwest::Client>);
+
+/// The shared client. Clones share its connection pool.
+pub fn client(app: &AppHandle) -> reqwest::Client {
+    app.state::<HttpClient>()
+        .0
+        .lock()
+        .map(|client| client.clone())
+        .unwrap_or_default()
+}
+
+/// Open a pooled connection to `server_url` in the background if
+/// `warm_up` is on.
+pub fn warm_up(app: &AppHandle, settings: &NetworkSettings, server_url: &str) {
+    if settings.warm_up {
+        connect(app, server_url);
+    }
+}
+
+/// Open a pooled connection to `server_url` in the background. Any response
+/// will do; a failure is left for the first real request to report.
+pub fn connect(app: &AppHandle, server_url: &str) {
+    let client = client(app);
+    let url = format!("{}/health", server_url.trim_end_matches('/'));
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = client.get(&url).timeout(WARM_UP_TIMEOUT).send().await {
+            log!("Warm-up request to {} failed: {}", url, e);
+        }
+    });
+}
+
+/// Replace the shared client with one built from `settings` and the stored
+/// client certificate. On error the current client stays in place.
+pub fn apply(app: &AppHandle, settings: &NetworkSettings) -> Result<(), String> {
+    let client = build_client(settings, identity()?)?;
+    let state = app.state::<HttpClient>();
+    *state.0.lock().map_err(|e| e.to_string())? = client;
+    Ok(())
+}
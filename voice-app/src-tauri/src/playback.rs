@@ -0,0 +1,323 @@
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, StreamConfig};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::controller::Controller;
+use crate::error::CommandError;
+use crate::events;
+use crate::mic::TARGET_SAMPLE_RATE;
+use crate::settings::SettingsState;
+
+/// How often a reviewed recording reports its position.
+const REVIEW_TICK: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaybackSettings {
+    /// Output device for `review_recording`, by name; `None` is the system
+    /// default.
+    pub device: Option<String>,
+}
+
+fn resample_linear_f32(input: &[f32], input_rate: u32, output_rate: u32) -> Vec<f32> {
+    if input.is_empty() || input_rate == output_rate {
+        return input.to_vec();
+    }
+
+    let step = input_rate as f64 / output_rate as f64;
+    let out_len = (input.len() as f64 / step) as usize;
+    (0..out_len)
+        .map(|n| {
+            let pos = n as f64 * step;
+            let i = pos as usize;
+            let frac = (pos - i as f64) as f32;
+            let a = input[i];
+            let b = input.get(i + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+fn build_output<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    samples: Vec<f32>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels.max(1) as usize;
+    let mut pos = 0;
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                for frame in data.chunks_mut(channels) {
+                    let value = samples.get(pos).copied().unwrap_or(0.0);
+                    pos += 1;
+                    for out in frame.iter_mut() {
+                        *out = T::from_sample(value);
+                    }
+                }
+            },
+            |err| log!("Audio output error: {}", err),
+            None,
+        )
+        .map_err(|e| format!("Failed to build output stream: {}", e))
+}
+
+fn play_blocking(samples: Vec<f32>, sample_rate: u32) -> Result<(), String> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or("No output device available")?;
+    let supported = device
+        .default_output_config()
+        .map_err(|e| format!("Failed to get default output config: {}", e))?;
+    let config: StreamConfig = supported.config();
+
+    let samples = resample_linear_f32(&samples, sample_rate, config.sample_rate.0);
+    let duration = Duration::from_secs_f64(samples.len() as f64 / config.sample_rate.0 as f64);
+
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => build_output::<f32>(&device, &config, samples),
+        SampleFormat::I16 => build_output::<i16>(&device, &config, samples),
+        SampleFormat::U16 => build_output::<u16>(&device, &config, samples),
+        other => Err(format!("Unsupported output sample format: {:?}", other)),
+    }?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start output stream: {}", e))?;
+
+    // Let the device drain its buffer before the stream is dropped.
+    std::thread::sleep(duration + Duration::from_millis(100));
+    Ok(())
+}
+
+/// Play mono f32 samples on the default output device without blocking the
+/// caller. The stream lives on its own thread since cpal streams aren't Send.
+pub fn play(samples: Vec<f32>, sample_rate: u32) {
+    std::thread::spawn(move || {
+        if let Err(e) = play_blocking(samples, sample_rate) {
+            log!("Playback failed: {}", e);
+        }
+    });
+}
+
+fn output_device(name: Option<&str>) -> Result<cpal::Device, String> {
+    let host = cpal::default_host();
+    let Some(name) = name else {
+        return host
+            .default_output_device()
+            .ok_or_else(|| "No output device available".to_string());
+    };
+    host.output_devices()
+        .map_err(|e| format!("Failed to list output devices: {}", e))?
+        .find(|d| d.name().is_ok_and(|n| n == name))
+        .ok_or_else(|| format!("Output device not found: {}", name))
+}
+
+/// A kept recording being played back. Position is in output frames, so the
+/// callback only has to count; seeking just moves it.
+struct Review {
+    frame: AtomicU64,
+    output_rate: u32,
+    duration_secs: f64,
+    stop: AtomicBool,
+}
+
+impl Review {
+    fn position_secs(&self) -> f64 {
+        (self.frame.load(Ordering::Relaxed) as f64 / self.output_rate as f64)
+            .min(self.duration_secs)
+    }
+
+    fn status(&self, playing: bool) -> ReviewStatus {
+        ReviewStatus {
+            playing,
+            position_secs: self.position_secs(),
+            duration_secs: self.duration_secs,
+        }
+    }
+}
+
+/// Sent as `review-playback` while a recording plays and once when it stops.
+#[derive(Clone, Serialize)]
+pub struct ReviewStatus {
+    playing: bool,
+    position_secs: f64,
+    duration_secs: f64,
+}
+
+impl events::Event for ReviewStatus {
+    const NAME: &'static str = "review-playback";
+}
+
+#[derive(Default)]
+pub struct ReviewPlayback(Mutex<Option<Arc<Review>>>);
+
+impl ReviewPlayback {
+    fn current(&self) -> Option<Arc<Review>> {
+        self.0.lock().ok().and_then(|r| r.clone())
+    }
+
+    fn stop(&self) {
+        if let Some(review) = self.0.lock().ok().and_then(|mut r| r.take()) {
+            review.stop.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn build_review_output<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    samples: Vec<i16>,
+    review: Arc<Review>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels.max(1) as usize;
+    let step = TARGET_SAMPLE_RATE as f64 / review.output_rate as f64;
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                for frame in data.chunks_mut(channels) {
+                    let pos = review.frame.fetch_add(1, Ordering::Relaxed) as f64 * step;
+                    let i = pos as usize;
+                    let value = match samples.get(i) {
+                        Some(&a) => {
+                            let b = samples.get(i + 1).copied().unwrap_or(a);
+                            let frac = (pos - i as f64) as f32;
+                            (a as f32 + (b as f32 - a as f32) * frac) / i16::MAX as f32
+                        }
+                        None => 0.0,
+                    };
+                    for out in frame.iter_mut() {
+                        *out = T::from_sample(value);
+                    }
+                }
+            },
+            |err| log!("Audio output error: {}", err),
+            None,
+        )
+        .map_err(|e| format!("Failed to build output stream: {}", e))
+}
+
+/// Open the output and play on this thread until the end or a stop,
+/// reporting through `ready` whether the stream started.
+fn run_review(
+    app: &AppHandle,
+    device: Option<&str>,
+    samples: Vec<i16>,
+    start_secs: f64,
+    ready: mpsc::Sender<Result<Arc<Review>, String>>,
+) {
+    let duration_secs = samples.len() as f64 / TARGET_SAMPLE_RATE as f64;
+    let opened = output_device(device).and_then(|device| {
+        let supported = device
+            .default_output_config()
+            .map_err(|e| format!("Failed to get default output config: {}", e))?;
+        let config: StreamConfig = supported.config();
+        let review = Arc::new(Review {
+            frame: AtomicU64::new((start_secs * config.sample_rate.0 as f64) as u64),
+            output_rate: config.sample_rate.0,
+            duration_secs,
+            stop: AtomicBool::new(false),
+        });
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => {
+                build_review_output::<f32>(&device, &config, samples, Arc::clone(&review))
+            }
+            SampleFormat::I16 => {
+                build_review_output::<i16>(&device, &config, samples, Arc::clone(&review))
+            }
+            SampleFormat::U16 => {
+                build_review_output::<u16>(&device, &config, samples, Arc::clone(&review))
+            }
+            other => Err(format!("Unsupported output sample format: {:?}", other)),
+        }?;
+        stream
+            .play()
+            .map_err(|e| format!("Failed to start output stream: {}", e))?;
+        Ok((stream, review))
+    });
+    let (stream, review) = match opened {
+        Ok(opened) => opened,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(Arc::clone(&review)));
+
+    while !review.stop.load(Ordering::Relaxed) && review.position_secs() < duration_secs {
+        events::emit(app, &review.status(true));
+        std::thread::sleep(REVIEW_TICK);
+    }
+    drop(stream);
+    events::emit(app, &review.status(false));
+
+    let state = app.state::<ReviewPlayback>();
+    if let Ok(mut current) = state.0.lock() {
+        if current.as_ref().is_some_and(|r| Arc::ptr_eq(r, &review)) {
+            *current = None;
+        }
+    };
+}
+
+/// Play the recording kept by the last `stop_mic` on the configured output
+/// device, from `start_secs` if given, so a long dictation can be checked
+/// before it is sent. Replaces any review already playing.
+#[tauri::command]
+pub fn review_recording(
+    app: AppHandle,
+    start_secs: Option<f64>,
+) -> Result<ReviewStatus, CommandError> {
+    let samples = app
+        .state::<Controller>()
+        .kept_samples()
+        .ok_or_else(|| CommandError::invalid_state("Nothing recorded to review"))?;
+    let device = app.state::<SettingsState>().get().playback.device;
+    let state = app.state::<ReviewPlayback>();
+    state.stop();
+
+    let (ready, started) = mpsc::channel();
+    let handle = app.clone();
+    let start_secs = start_secs.unwrap_or(0.0).max(0.0);
+    // cpal streams aren't Send, so the stream stays on the thread that
+    // opened it.
+    std::thread::spawn(move || {
+        run_review(&handle, device.as_deref(), samples, start_secs, ready);
+    });
+    let review = started
+        .recv()
+        .map_err(|_| "Review playback thread exited".to_string())??;
+    *state.0.lock().map_err(|e| e.to_string())? = Some(Arc::clone(&review));
+    Ok(review.status(true))
+}
+
+/// Jump to `position_secs` in the recording being reviewed.
+#[tauri::command]
+pub fn seek_review(
+    state: State<'_, ReviewPlayback>,
+    position_secs: f64,
+) -> Result<ReviewStatus, CommandError> {
+    let review = state
+        .current()
+        .ok_or_else(|| CommandError::invalid_state("Nothing is playing"))?;
+    let secs = position_secs.clamp(0.0, review.duration_secs);
+    review
+        .frame
+        .store((secs * review.output_rate as f64) as u64, Ordering::Relaxed);
+    Ok(review.status(true))
+}
+
+#[tauri::command]
+pub fn stop_review(state: State<'_, ReviewPlayback>) {
+    state.stop();
+}
@@ -0,0 +1,96 @@
+//! The OS microphone privacy switches. On Windows, "Microphone access" and
+//! "Let desktop apps access your microphone" in Settings block capture
+//! without any prompt: cpal then finds no input device, or opens one that
+//! only delivers silence. Their registry values are checked before every
+//! capture so the failure says which switch to flip, and
+//! `open_mic_privacy_settings` takes the user there.
+
+#[cfg(windows)]
+use voice_core::capture::MicError;
+
+use crate::error::CommandError;
+
+#[cfg(windows)]
+const SETTINGS_PAGE: &str = "ms-settings:privacy-microphone";
+#[cfg(target_os = "macos")]
+const SETTINGS_PAGE: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone";
+
+#[cfg(windows)]
+mod consent {
+    use windows::core::{w, PCWSTR};
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Registry::{
+        RegGetValueW, HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ,
+    };
+
+    const MICROPHONE: PCWSTR = w!(
+        "Software\\Microsoft\\Windows\\CurrentVersion\\CapabilityAccessManager\\ConsentStore\\microphone"
+    );
+    const NON_PACKAGED: PCWSTR = w!(
+        "Software\\Microsoft\\Windows\\CurrentVersion\\CapabilityAccessManager\\ConsentStore\\microphone\\NonPackaged"
+    );
+
+    /// Whether the consent value under `key` is `Deny`. A missing value
+    /// means the switch was never touched, which allows access.
+    fn denied(root: HKEY, key: PCWSTR) -> bool {
+        let mut buf = [0u16; 16];
+        let mut size = std::mem::size_of_val(&buf) as u32;
+        // SAFETY: `buf` and `size` describe a writable buffer that outlives
+        // the call.
+        let status = unsafe {
+            RegGetValueW(
+                root,
+                key,
+                w!("Value"),
+                RRF_RT_REG_SZ,
+                None,
+                Some(buf.as_mut_ptr().cast()),
+                Some(&mut size),
+            )
+        };
+        if status != ERROR_SUCCESS {
+            return false;
+        }
+        let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        String::from_utf16_lossy(&buf[..len]).eq_ignore_ascii_case("Deny")
+    }
+
+    /// The first switch, from the outermost in, that blocks this app.
+    pub fn blocked_by() -> Option<&'static str> {
+        if denied(HKEY_LOCAL_MACHINE, MICROPHONE) {
+            Some("\"Microphone access\" is off for this device")
+        } else if denied(HKEY_CURRENT_USER, MICROPHONE) {
+            Some("\"Microphone access\" is off")
+        } else if denied(HKEY_CURRENT_USER, NON_PACKAGED) {
+            Some("\"Let desktop apps access your microphone\" is off")
+        } else {
+            None
+        }
+    }
+}
+
+/// Fail when a Windows privacy switch keeps this app from the mic.
+#[cfg(windows)]
+pub fn ensure_record_permission() -> Result<(), MicError> {
+    match consent::blocked_by() {
+        Some(switch) => Err(MicError::PermissionDenied(format!(
+            "Permission denied: {} in Settings > Privacy & security > Microphone",
+            switch
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Open the OS page with the microphone privacy switches.
+#[tauri::command]
+pub fn open_mic_privacy_settings() -> Result<(), CommandError> {
+    #[cfg(any(windows, target_os = "macos"))]
+    {
+        open::that_detached(SETTINGS_PAGE)
+            .map_err(|e| format!("Failed to open {}: {}", SETTINGS_PAGE, e))?;
+        Ok(())
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    Err("Microphone privacy settings are only supported on Windows and macOS".into())
+}
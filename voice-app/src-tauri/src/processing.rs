@@ -0,0 +1,57 @@
+//! Switching the environment presets of `voice_core::processing`. Presets
+//! apply from the next recording; switching is a command and a tray submenu.
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+pub use voice_core::processing::{Preset, Processing};
+
+use crate::error::CommandError;
+use crate::events;
+use crate::settings::{self, SettingsState};
+
+#[derive(Clone, Serialize)]
+pub struct PresetChanged {
+    preset: Preset,
+}
+
+impl events::Event for PresetChanged {
+    const NAME: &'static str = "capture-preset-changed";
+}
+
+#[derive(Serialize)]
+pub struct PresetInfo {
+    preset: Preset,
+    label: &'static str,
+    processing: Option<Processing>,
+}
+
+/// The preset with the serialized name `id`, e.g. `noisy_cafe`.
+pub fn find(id: &str) -> Option<Preset> {
+    Preset::ALL.into_iter().find(|preset| preset.id() == id)
+}
+
+/// Switch presets and save the choice. Emits `capture-preset-changed`.
+pub fn select(app: &AppHandle, preset: Preset) -> Result<(), String> {
+    settings::modify(app, |settings| settings.capture.preset = preset)?;
+    events::emit(app, &PresetChanged { preset });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_capture_preset(app: AppHandle, preset: Preset) -> Result<(), CommandError> {
+    Ok(select(&app, preset)?)
+}
+
+#[tauri::command]
+pub fn list_capture_presets(app: AppHandle) -> Vec<PresetInfo> {
+    let custom = app.state::<SettingsState>().get().capture.custom;
+    Preset::ALL
+        .iter()
+        .map(|&preset| PresetInfo {
+            preset,
+            label: preset.label(),
+            processing: preset.processing(&custom),
+        })
+        .collect()
+}
@@ -0,0 +1,167 @@
+//! Offline queue: uploads that couldn't finish before exit are written here
+//! as WAV files and replayed against the configured server on next launch.
+//! Recordings interrupted by exit go to `recordings/` instead — they were
+//! never meant to be sent, so they are kept but not uploaded. They are WAV,
+//! or Opus when `recordings.format` asks for it and the `opus` feature is
+//! built in.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::api::{self, RouteCache};
+use crate::circuit::CircuitBreakers;
+use crate::error::CommandError;
+use crate::events;
+use crate::metrics;
+use crate::net;
+#[cfg(feature = "opus")]
+use crate::ogg_opus;
+use crate::settings::SettingsState;
+use crate::transcription;
+use crate::voice_loop::PipelineResult;
+
+const QUEUE_DIR: &str = "queue";
+const RECORDINGS_DIR: &str = "recordings";
+/// Extensions of files in `recordings/`.
+pub const RECORDING_EXTENSIONS: [&str; 2] = ["wav", "opus"];
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingFormat {
+    #[default]
+    Wav,
+    /// Opus in Ogg, about a tenth of the size. Needs the `opus` feature;
+    /// builds without it save WAV.
+    Opus,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingSettings {
+    pub format: RecordingFormat,
+    /// Opus bitrate in bits/s.
+    pub opus_bitrate: u32,
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self {
+            format: RecordingFormat::Wav,
+            opus_bitrate: 24_000,
+        }
+    }
+}
+
+fn data_subdir(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("No data directory: {}", e))?
+        .join(name);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+fn timestamped(dir: &Path, prefix: &str, extension: &str) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    dir.join(format!("{}-{}.{}", prefix, millis, extension))
+}
+
+/// Where interrupted recordings are kept; also exported with the history.
+pub fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    data_subdir(app, RECORDINGS_DIR)
+}
+
+/// Queue an encoded upload for the next launch.
+pub fn persist_upload(app: &AppHandle, wav_bytes: &[u8]) -> Result<PathBuf, String> {
+    let path = timestamped(&data_subdir(app, QUEUE_DIR)?, "upload", "wav");
+    std::fs::write(&path, wav_bytes)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// File contents and extension for a recording in the configured format.
+fn encode_recording(
+    samples: &[i16],
+    settings: &RecordingSettings,
+) -> Result<(Vec<u8>, &'static str), String> {
+    match settings.format {
+        RecordingFormat::Wav => Ok((api::encode_wav(samples)?, "wav")),
+        #[cfg(feature = "opus")]
+        RecordingFormat::Opus => Ok((ogg_opus::encode(samples, settings.opus_bitrate)?, "opus")),
+        #[cfg(not(feature = "opus"))]
+        RecordingFormat::Opus => {
+            log!("Built without Opus support; saving the recording as WAV");
+            Ok((api::encode_wav(samples)?, "wav"))
+        }
+    }
+}
+
+/// Save an interrupted recording without queueing it for upload.
+pub fn save_recording(app: &AppHandle, samples: &[i16]) -> Result<PathBuf, String> {
+    let settings = app.state::<SettingsState>().get().recordings;
+    let (bytes, extension) = encode_recording(samples, &settings)?;
+    let path = timestamped(&recordings_dir(app)?, "recovered", extension);
+    std::fs::write(&path, bytes)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Upload everything left in the queue, oldest first. Files are removed only
+/// after the server accepted them, so a failed replay is retried next launch.
+pub async fn replay(app: AppHandle) {
+    let Ok(dir) = data_subdir(&app, QUEUE_DIR) else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wav"))
+        .collect();
+    files.sort();
+
+    let settings = app.state::<SettingsState>().get();
+    let client = net::client(&app);
+    let routes = app.state::<RouteCache>();
+    for path in files {
+        let Ok(wav_bytes) = std::fs::read(&path) else {
+            continue;
+        };
+        let upload = async {
+            api::upload_wav(
+                &client,
+                &routes,
+                &wav_bytes,
+                &settings.server_url,
+                &settings.signing,
+                &transcription::options(&app),
+            )
+            .await
+            .map_err(CommandError::from)
+        };
+        match app
+            .state::<CircuitBreakers>()
+            .call(&settings.circuit, &settings.server_url, upload)
+            .await
+        {
+            Ok(result) => {
+                let _ = std::fs::remove_file(&path);
+                metrics::record_upload(&app, &result);
+                events::emit(&app, &PipelineResult(result));
+            }
+            Err(e) => {
+                log!("Queued upload {} failed: {}", path.display(), e);
+                // Server is likely still down; keep the rest for next time.
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Spoken pull-request review comments. `start_review_comment` arms a
+//! target (a PR, or a line of a file in it); the next send is transcribed
+//! only, not run through the pipeline, and its text becomes a draft shown
+//! to the user via `review-comment-draft`. Nothing reaches GitHub until
+//! `post_review_comment` confirms the draft, optionally with edited text.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::api::{self, TranscribeOptions};
+use crate::error::CommandError;
+use crate::events;
+use crate::github::{self, GithubClient};
+use crate::history;
+use crate::net;
+use crate::settings::SettingsState;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReviewTarget {
+    /// `owner/name`.
+    pub repo: String,
+    pub pull_number: u64,
+    /// File and line (on the new side of the diff) for an inline comment;
+    /// without them the comment goes on the conversation.
+    pub path: Option<String>,
+    pub line: Option<u64>,
+}
+
+impl ReviewTarget {
+    fn check(&self) -> Result<(), String> {
+        github::check_repo(&self.repo)?;
+        if self.pull_number == 0 {
+            return Err("Pull request numbers start at 1".into());
+        }
+        match (&self.path, self.line) {
+            (Some(path), Some(line)) if !path.is_empty() && line > 0 => Ok(()),
+            (None, None) => Ok(()),
+            _ => Err("An inline comment needs both a path and a line".into()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReviewDraft {
+    pub target: ReviewTarget,
+    pub body: String,
+}
+
+impl events::Event for ReviewDraft {
+    const NAME: &'static str = "review-comment-draft";
+}
+
+#[derive(Default)]
+pub struct ReviewState {
+    /// Where the next send's transcript goes.
+    target: Mutex<Option<ReviewTarget>>,
+    /// Dictated, waiting for confirmation.
+    draft: Mutex<Option<ReviewDraft>>,
+}
+
+impl ReviewState {
+    pub fn is_armed(&self) -> bool {
+        self.target.lock().is_ok_and(|t| t.is_some())
+    }
+}
+
+/// Upload for a send while a review target is armed: transcribe and turn
+/// the text into a draft, reported in the result as `_review_draft`.
+pub async fn upload(
+    app: &AppHandle,
+    wav_bytes: &[u8],
+    server_url: &str,
+    options: &TranscribeOptions,
+) -> Result<serde_json::Value, CommandError> {
+    let signing = app.state::<SettingsState>().get().signing;
+    let mut result =
+        api::transcribe_wav(&net::client(app), wav_bytes, server_url, &signing, options).await?;
+    let Some(body) = history::transcript_text(&result).map(str::to_owned) else {
+        return Ok(result);
+    };
+    let state = app.state::<ReviewState>();
+    let Some(target) = state.target.lock().map_err(|e| e.to_string())?.take() else {
+        return Ok(result);
+    };
+
+    let draft = ReviewDraft { target, body };
+    *state.draft.lock().map_err(|e| e.to_string())? = Some(draft.clone());
+    events::emit(app, &draft);
+    if let (Some(obj), Ok(value)) = (result.as_object_mut(), serde_json::to_value(&draft)) {
+        obj.insert("_review_draft".to_string(), value);
+    }
+    Ok(result)
+}
+
+async fn post(app: &AppHandle, draft: &ReviewDraft) -> Result<String, CommandError> {
+    let client = GithubClient::new(&app.state::<SettingsState>().get().github)?;
+    let target = &draft.target;
+    let posted = match (&target.path, target.line) {
+        (Some(path), Some(line)) => {
+            // Inline comments are anchored to a commit; use the PR's head.
+            let pull = client
+                .get(&format!(
+                    "/repos/{}/pulls/{}",
+                    target.repo, target.pull_number
+                ))
+                .await?;
+            let commit_id = pull["head"]["sha"]
+                .as_str()
+                .ok_or("Pull request has no head commit")?;
+            client
+                .post(
+                    &format!(
+                        "/repos/{}/pulls/{}/comments",
+                        target.repo, target.pull_number
+                    ),
+                    &serde_json::json!({
+                        "body": draft.body,
+                        "commit_id": commit_id,
+                        "path": path,
+                        "line": line,
+                        "side": "RIGHT",
+                    }),
+                )
+                .await?
+        }
+        _ => {
+            client
+                .post(
+                    &format!(
+                        "/repos/{}/issues/{}/comments",
+                        target.repo, target.pull_number
+                    ),
+                    &serde_json::json!({ "body": draft.body }),
+                )
+                .await?
+        }
+    };
+    Ok(posted["html_url"].as_str().unwrap_or_default().to_string())
+}
+
+/// Send the next recording to `target` as a review comment draft.
+#[tauri::command]
+pub fn start_review_comment(
+    review: State<'_, ReviewState>,
+    target: ReviewTarget,
+) -> Result<(), CommandError> {
+    target.check()?;
+    *review.draft.lock().map_err(|e| e.to_string())? = None;
+    *review.target.lock().map_err(|e| e.to_string())? = Some(target);
+    Ok(())
+}
+
+/// Post the pending draft, with `body` replacing the dictated text if
+/// given. Returns the comment's URL. The draft is kept if posting fails.
+#[tauri::command]
+pub async fn post_review_comment(
+    app: AppHandle,
+    body: Option<String>,
+) -> Result<String, CommandError> {
+    let state = app.state::<ReviewState>();
+    let mut draft = state
+        .draft
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("No review comment waiting for confirmation")?;
+    if let Some(body) = body {
+        draft.body = body;
+    }
+    if draft.body.trim().is_empty() {
+        return Err("Review comment is empty".into());
+    }
+
+    let url = post(&app, &draft).await?;
+    *state.draft.lock().map_err(|e| e.to_string())? = None;
+    Ok(url)
+}
+
+/// Drop the armed target and any unconfirmed draft.
+#[tauri::command]
+pub fn discard_review_comment(review: State<'_, ReviewState>) -> Result<(), CommandError> {
+    *review.target.lock().map_err(|e| e.to_string())? = None;
+    *review.draft.lock().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
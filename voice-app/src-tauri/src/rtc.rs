@@ -0,0 +1,335 @@
+//! WebRTC transport (feature `webrtc`): streams the microphone as an Opus
+//! track while recording and receives transcripts on a data channel.
+//! Signaling is a single WHIP-style exchange: the SDP offer is POSTed to
+//! `/api/webrtc/offer` and the response body is the SDP answer.
+
+use audiopus::coder::Encoder;
+use audiopus::{Application, Channels, SampleRate};
+use bytes::Bytes;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::mpsc::Receiver;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+use voice_core::dsp;
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::{MediaEngine, MIME_TYPE_OPUS};
+use webrtc::api::APIBuilder;
+use webrtc::data_channel::data_channel_message::DataChannelMessage;
+use webrtc::ice_transport::ice_server::RTCIceServer;
+use webrtc::interceptor::registry::Registry;
+use webrtc::media::Sample;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
+use webrtc::track::track_local::TrackLocal;
+
+use crate::api::{self, UploadError};
+use crate::cancel::Cancellation;
+use crate::controller;
+use crate::error::CommandError;
+use crate::events;
+use crate::jitter::JitterBuffer;
+use crate::mic::{MicState, TARGET_SAMPLE_RATE};
+use crate::net;
+
+const STUN_SERVER: &str = "stun:stun.l.google.com:19302";
+const FRAME_DURATION: Duration = Duration::from_millis(20);
+const FRAME_SAMPLES: usize = TARGET_SAMPLE_RATE as usize / 50; // 20ms at 16kHz
+const MAX_PACKET: usize = 4000; // Recommended max Opus packet size
+
+pub struct RtcSession {
+    peer: Arc<RTCPeerConnection>,
+    pump: tauri::async_runtime::JoinHandle<()>,
+    /// Cancelled by `cancel_all`, which hangs up without flushing.
+    cancel: CancellationToken,
+}
+
+#[derive(Default)]
+pub struct RtcState(tokio::sync::Mutex<Option<RtcSession>>);
+
+/// A data-channel message from the server: JSON as sent, anything else as a
+/// string.
+#[derive(Clone, Serialize)]
+pub struct TranscriptPartial {
+    data: serde_json::Value,
+}
+
+impl events::Event for TranscriptPartial {
+    const NAME: &'static str = "transcript-partial";
+}
+
+#[derive(Clone, Serialize)]
+pub struct RtcStateChanged {
+    state: String,
+}
+
+impl events::Event for RtcStateChanged {
+    const NAME: &'static str = "rtc-state-changed";
+}
+
+fn rtc_err(e: webrtc::Error) -> String {
+    format!("WebRTC error: {}", e)
+}
+
+struct OpusSender {
+    encoder: Encoder,
+    packet: Vec<u8>,
+    track: Arc<TrackLocalStaticSample>,
+}
+
+impl OpusSender {
+    async fn send(&mut self, frame: &[i16]) -> Result<(), String> {
+        let len = self
+            .encoder
+            .encode(frame, &mut self.packet)
+            .map_err(|e| format!("Opus encode error: {}", e))?;
+        self.track
+            .write_sample(&Sample {
+                data: Bytes::copy_from_slice(&self.packet[..len]),
+                duration: FRAME_DURATION,
+                ..Default::default()
+            })
+            .await
+            .map_err(rtc_err)
+    }
+}
+
+async fn pump_audio(
+    mut chunks: Receiver<Vec<i16>>,
+    input_rate: u32,
+    track: Arc<TrackLocalStaticSample>,
+) -> Result<(), String> {
+    let mut sender = OpusSender {
+        encoder: Encoder::new(SampleRate::Hz16000, Channels::Mono, Application::Voip)
+            .map_err(|e| format!("Opus encoder error: {}", e))?,
+        packet: vec![0u8; MAX_PACKET],
+        track,
+    };
+    let mut jitter = JitterBuffer::new(FRAME_SAMPLES);
+    let mut tick = tokio::time::interval(FRAME_DURATION);
+    // After a network stall, catch up on missed frames instead of falling
+    // permanently behind capture.
+    tick.set_missed_tick_behavior(MissedTickBehavior::Burst);
+
+    loop {
+        tokio::select! {
+            chunk = chunks.recv() => match chunk {
+                Some(chunk) => jitter.push(&dsp::resample_i16(&chunk, input_rate, TARGET_SAMPLE_RATE)),
+                // stop_recording closed the channel
+                None => break,
+            },
+            _ = tick.tick() => {
+                if let Some(frame) = jitter.pop() {
+                    sender.send(&frame).await?;
+                }
+            }
+        }
+    }
+
+    for frame in jitter.drain() {
+        sender.send(&frame).await?;
+    }
+    Ok(())
+}
+
+async fn exchange_sdp(
+    client: &reqwest::Client,
+    server_url: &str,
+    offer_sdp: String,
+) -> Result<String, UploadError> {
+    let url = format!("{}/api/webrtc/offer", server_url.trim_end_matches('/'));
+    let mut request = client
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/sdp")
+        .body(offer_sdp);
+    if let Some(key) = api::api_key() {
+        request = request.bearer_auth(key);
+    }
+
+    let response = request.send().await.map_err(|e| {
+        let (url, message) = (url.clone(), e.to_string());
+        if e.is_timeout() {
+            UploadError::Timeout { url, message }
+        } else {
+            UploadError::Unreachable { url, message }
+        }
+    })?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(UploadError::Status {
+            status,
+            route: Some("/api/webrtc/offer"),
+            body,
+        });
+    }
+    response
+        .text()
+        .await
+        .map_err(|e| UploadError::InvalidResponse {
+            endpoint: "webrtc_offer".into(),
+            message: e.to_string(),
+        })
+}
+
+async fn connect(
+    app: &AppHandle,
+    server_url: &str,
+    chunks: Receiver<Vec<i16>>,
+    input_rate: u32,
+    cancel: CancellationToken,
+) -> Result<RtcSession, CommandError> {
+    let mut media = MediaEngine::default();
+    media.register_default_codecs().map_err(rtc_err)?;
+    let registry = register_default_interceptors(Registry::new(), &mut media).map_err(rtc_err)?;
+    let api = APIBuilder::new()
+        .with_media_engine(media)
+        .with_interceptor_registry(registry)
+        .build();
+
+    let peer = Arc::new(
+        api.new_peer_connection(RTCConfiguration {
+            ice_servers: vec![RTCIceServer {
+                urls: vec![STUN_SERVER.to_string()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+        .await
+        .map_err(rtc_err)?,
+    );
+
+    let track = Arc::new(TrackLocalStaticSample::new(
+        RTCRtpCodecCapability {
+            mime_type: MIME_TYPE_OPUS.to_owned(),
+            clock_rate: 48_000, // RTP clock for Opus is always 48kHz
+            channels: 1,
+            ..Default::default()
+        },
+        "audio".to_owned(),
+        "agentic-devops-voice".to_owned(),
+    ));
+    peer.add_track(Arc::clone(&track) as Arc<dyn TrackLocal + Send + Sync>)
+        .await
+        .map_err(rtc_err)?;
+
+    let channel = peer
+        .create_data_channel("transcripts", None)
+        .await
+        .map_err(rtc_err)?;
+    let handle = app.clone();
+    channel.on_message(Box::new(move |msg: DataChannelMessage| {
+        let payload = serde_json::from_slice::<serde_json::Value>(&msg.data).unwrap_or_else(|_| {
+            serde_json::Value::String(String::from_utf8_lossy(&msg.data).into_owned())
+        });
+        #[cfg(desktop)]
+        crate::captions::show_partial(&handle, &payload);
+        events::emit(&handle, &TranscriptPartial { data: payload });
+        Box::pin(async {})
+    }));
+
+    let handle = app.clone();
+    peer.on_peer_connection_state_change(Box::new(move |state: RTCPeerConnectionState| {
+        events::emit(
+            &handle,
+            &RtcStateChanged {
+                state: state.to_string(),
+            },
+        );
+        Box::pin(async {})
+    }));
+
+    // Non-trickle: wait for all candidates so one POST carries the full offer.
+    let offer = peer.create_offer(None).await.map_err(rtc_err)?;
+    let mut gathered = peer.gathering_complete_promise().await;
+    peer.set_local_description(offer).await.map_err(rtc_err)?;
+    let _ = gathered.recv().await;
+    let local = peer
+        .local_description()
+        .await
+        .ok_or("WebRTC produced no local description")?;
+
+    let answer_sdp = exchange_sdp(&net::client(app), server_url, local.sdp).await?;
+    let answer = RTCSessionDescription::answer(answer_sdp).map_err(rtc_err)?;
+    peer.set_remote_description(answer).await.map_err(rtc_err)?;
+
+    let handle = app.clone();
+    let (connection, token) = (Arc::clone(&peer), cancel.clone());
+    let pump = tauri::async_runtime::spawn(async move {
+        let pumped = tokio::select! {
+            pumped = pump_audio(chunks, input_rate, track) => pumped,
+            _ = token.cancelled() => connection.close().await.map_err(rtc_err),
+        };
+        if let Err(e) = pumped {
+            log!("WebRTC audio stream failed: {}", e);
+            events::emit(
+                &handle,
+                &RtcStateChanged {
+                    state: "failed".into(),
+                },
+            );
+        }
+    });
+
+    Ok(RtcSession { peer, pump, cancel })
+}
+
+#[tauri::command]
+pub async fn start_rtc_stream(
+    app: AppHandle,
+    mic_state: State<'_, MicState>,
+    rtc: State<'_, RtcState>,
+    server_url: String,
+) -> Result<(), CommandError> {
+    let mut slot = rtc.0.lock().await;
+    // A cancelled session has already hung up and is just replaced.
+    if slot.as_ref().is_some_and(|s| !s.cancel.is_cancelled()) {
+        return Err(CommandError::invalid_state("WebRTC stream already running"));
+    }
+
+    // Subscribe first so nothing captured during connection setup is lost.
+    let chunks = mic_state.main().subscribe_chunks();
+    controller::start_recording(&app)?;
+
+    let cancel = app.state::<Cancellation>().child();
+    match connect(
+        &app,
+        &server_url,
+        chunks,
+        mic_state.main().input_sample_rate(),
+        cancel,
+    )
+    .await
+    {
+        Ok(session) => {
+            *slot = Some(session);
+            Ok(())
+        }
+        Err(e) => {
+            let _ = controller::stop_recording(&app);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn stop_rtc_stream(app: AppHandle, rtc: State<'_, RtcState>) -> Result<(), CommandError> {
+    let session = rtc
+        .0
+        .lock()
+        .await
+        .take()
+        .ok_or_else(|| CommandError::invalid_state("No WebRTC stream running"))?;
+
+    // Stopping closes the chunk channel; let the pump flush what's queued
+    // before hanging up so the tail of the utterance reaches the server.
+    let _ = controller::stop_recording(&app);
+    let _ = session.pump.await;
+    Ok(session.peer.close().await.map_err(rtc_err)?)
+}
@@ -0,0 +1,100 @@
+use keyring::Entry;
+use tauri::{AppHandle, Manager};
+
+use crate::error::{CommandError, ErrorCode};
+use crate::net;
+use crate::settings::SettingsState;
+
+// All secrets live under the app identifier so they show up as one group in
+// Keychain Access / Credential Manager / Secret Service.
+const SERVICE: &str = "dev.agentic.devops.voice";
+
+/// Bearer token for the pipeline server, attached to uploads when present.
+pub const PIPELINE_API_KEY: &str = "pipeline_api_key";
+
+/// GitHub token for the flows that call the GitHub API directly.
+pub const GITHUB_TOKEN: &str = "github_token";
+
+/// Shared secret GitHub signs webhook deliveries with.
+pub const WEBHOOK_SECRET: &str = "webhook_secret";
+
+/// Shared secret for HMAC-signed uploads (`signing.enabled`).
+pub const SIGNING_SECRET: &str = "upload_signing_secret";
+
+/// Refresh token and expiry from a device-flow login to the pipeline
+/// server, as JSON. The access token itself is `pipeline_api_key`.
+pub const PIPELINE_OAUTH: &str = "pipeline_oauth";
+
+/// Same as `PIPELINE_OAUTH`, for the `github_token` from a GitHub login.
+pub const GITHUB_OAUTH: &str = "github_oauth";
+
+/// Client certificate for servers that require mutual TLS: PEM with the
+/// certificate chain and its PKCS#8 key, or a base64 PKCS#12 bundle.
+pub const CLIENT_IDENTITY: &str = "client_identity";
+
+/// Password of a PKCS#12 `client_identity`.
+pub const CLIENT_IDENTITY_PASSWORD: &str = "client_identity_password";
+
+fn keychain_error(message: String) -> CommandError {
+    CommandError::new(ErrorCode::KeychainUnavailable, message)
+}
+
+fn entry(name: &str) -> Result<Entry, CommandError> {
+    if name.trim().is_empty() {
+        return Err(CommandError::new(
+            ErrorCode::ConfigInvalid,
+            "Secret name must not be empty",
+        ));
+    }
+    Entry::new(SERVICE, name)
+        .map_err(|e| keychain_error(format!("Keychain error for {}: {}", name, e)))
+}
+
+/// Read a secret from the OS keychain. Returns `Ok(None)` when it was never set.
+pub fn get(name: &str) -> Result<Option<String>, CommandError> {
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(keychain_error(format!(
+            "Failed to read secret {}: {}",
+            name, e
+        ))),
+    }
+}
+
+pub fn set(name: &str, value: &str) -> Result<(), CommandError> {
+    entry(name)?
+        .set_password(value)
+        .map_err(|e| keychain_error(format!("Failed to store secret {}: {}", name, e)))
+}
+
+/// Remove a secret; removing one that was never set is not an error.
+pub fn delete(name: &str) -> Result<(), CommandError> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(keychain_error(format!(
+            "Failed to delete secret {}: {}",
+            name, e
+        ))),
+    }
+}
+
+/// Rebuild the HTTP client when its certificate changed.
+fn changed(app: &AppHandle, name: &str) -> Result<(), CommandError> {
+    if name == CLIENT_IDENTITY || name == CLIENT_IDENTITY_PASSWORD {
+        net::apply(app, &app.state::<SettingsState>().get().network)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_secret(app: AppHandle, name: String, value: String) -> Result<(), CommandError> {
+    set(&name, &value)?;
+    changed(&app, &name)
+}
+
+#[tauri::command]
+pub fn delete_secret(app: AppHandle, name: String) -> Result<(), CommandError> {
+    delete(&name)?;
+    changed(&app, &name)
+}
@@ -0,0 +1,344 @@
+//! `run_self_check`: the first things to look at when dictation doesn't
+//! work, checked in one go and returned as a checklist. Every check runs;
+//! a failing one doesn't stop the others.
+//!
+//! A lighter pass runs at launch (`check_on_startup`): it resolves the
+//! capture device without opening it, so there is no permission prompt, and
+//! reports what is wrong before the first `start_mic` runs into it. The
+//! result is kept for `get_startup_issues`, since the check may finish
+//! before the webview listens for `startup-issues`.
+
+use cpal::traits::DeviceTrait;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::controller::{AppState, Controller};
+use crate::devices;
+use crate::error::{CommandError, ErrorCode};
+use crate::events;
+use crate::health;
+use crate::mic::{self, Capture, MicError, TARGET_SAMPLE_RATE};
+use crate::net;
+use crate::queue;
+use crate::settings::SettingsState;
+
+/// How long the capture device is held open.
+const MIC_CHECK: Duration = Duration::from_millis(300);
+/// Below this the next recordings may not fit.
+const DISK_MIN_BYTES: u64 = 100 * 1024 * 1024;
+/// Below this, warn.
+const DISK_LOW_BYTES: u64 = 1024 * 1024 * 1024;
+const PROBE_FILE: &str = ".self-check";
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    /// Couldn't be checked right now, e.g. the mic while recording.
+    Skipped,
+}
+
+#[derive(Clone, Serialize)]
+pub struct Check {
+    /// Stable id: `mic`, `mic_access`, `server_url`, `server`, `disk` or
+    /// `data_dir`.
+    pub id: &'static str,
+    pub status: CheckStatus,
+    /// What was found, in English; `code` is there for a localized one.
+    pub detail: String,
+    pub code: Option<ErrorCode>,
+}
+
+impl Check {
+    fn new(id: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            id,
+            status,
+            detail: detail.into(),
+            code: None,
+        }
+    }
+
+    fn failed(id: &'static str, error: CommandError) -> Self {
+        Self {
+            id,
+            status: CheckStatus::Fail,
+            detail: error.message,
+            code: Some(error.code),
+        }
+    }
+}
+
+/// What `check_on_startup` found: only the checks that didn't pass.
+#[derive(Clone, Serialize)]
+pub struct StartupIssues {
+    pub issues: Vec<Check>,
+}
+
+impl events::Event for StartupIssues {
+    const NAME: &'static str = "startup-issues";
+}
+
+/// The result of `check_on_startup`, for `get_startup_issues`.
+#[derive(Default)]
+pub struct StartupState(Mutex<Option<StartupIssues>>);
+
+#[derive(Clone, Serialize)]
+pub struct SelfCheckReport {
+    /// No check failed; warnings still pass.
+    pub ok: bool,
+    pub checks: Vec<Check>,
+}
+
+/// Open the configured source briefly. Returns the device check and the
+/// access check, which is judged from what it captured.
+async fn check_mic(app: &AppHandle) -> [Check; 2] {
+    if app.state::<Controller>().state() == AppState::Recording {
+        let busy = "Recording in progress";
+        return [
+            Check::new("mic", CheckStatus::Skipped, busy),
+            Check::new("mic_access", CheckStatus::Skipped, busy),
+        ];
+    }
+    let settings = app.state::<SettingsState>().get();
+    let captured = tauri::async_runtime::spawn_blocking(move || {
+        mic::ensure_access()?;
+        let mic = Capture::default();
+        mic.start(&settings.source, &settings.capture)?;
+        std::thread::sleep(MIC_CHECK);
+        mic.stop()
+    })
+    .await
+    .map_err(|e| MicError::Failed(format!("Mic check failed: {}", e)))
+    .and_then(|recording| recording)
+    .map_err(CommandError::from);
+
+    let samples = match captured {
+        Ok(recording) => recording.samples,
+        Err(e) => {
+            return [
+                Check::failed("mic", e),
+                Check::new("mic_access", CheckStatus::Skipped, "The device didn't open"),
+            ];
+        }
+    };
+    if samples.is_empty() {
+        let silent = CommandError::new(ErrorCode::MicFailed, "The device opened but sent no audio");
+        return [
+            Check::failed("mic", silent),
+            Check::new("mic_access", CheckStatus::Skipped, "No audio to judge"),
+        ];
+    }
+    let millis = samples.len() as u64 * 1000 / TARGET_SAMPLE_RATE as u64;
+    let device = Check::new("mic", CheckStatus::Pass, format!("Captured {} ms", millis));
+    // A denied permission (macOS) or a muted input delivers exact zeros;
+    // a live mic always has some noise.
+    let access = if samples.iter().all(|&s| s == 0) {
+        Check::new(
+            "mic_access",
+            CheckStatus::Warn,
+            "Only digital silence was captured; microphone access may be denied or the input muted",
+        )
+    } else {
+        Check::new("mic_access", CheckStatus::Pass, "Audio is coming through")
+    };
+    [device, access]
+}
+
+/// Resolve the configured source to a device and config, as `start_mic`
+/// would, without opening a stream.
+async fn check_source(app: &AppHandle) -> Check {
+    let source = app.state::<SettingsState>().get().source;
+    let resolved = tauri::async_runtime::spawn_blocking(move || -> Result<String, MicError> {
+        let (device, _) = devices::open(&source)?;
+        Ok(device.name().unwrap_or_default())
+    })
+    .await
+    .map_err(|e| MicError::Failed(format!("Mic check failed: {}", e)))
+    .and_then(|name| name);
+    match resolved {
+        Ok(name) => Check::new("mic", CheckStatus::Pass, format!("Using {}", name)),
+        Err(e) => Check::failed("mic", e.into()),
+    }
+}
+
+fn check_server_url(server_url: &str) -> Check {
+    let valid = reqwest::Url::parse(server_url)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host());
+    if valid {
+        Check::new("server_url", CheckStatus::Pass, server_url)
+    } else if server_url.trim().is_empty() {
+        let missing = CommandError::new(ErrorCode::ConfigInvalid, "No server URL is set");
+        Check::failed("server_url", missing)
+    } else {
+        let invalid = CommandError::new(
+            ErrorCode::ConfigInvalid,
+            format!("Not an http(s) URL: {:?}", server_url),
+        );
+        Check::failed("server_url", invalid)
+    }
+}
+
+async fn check_server(app: &AppHandle) -> Check {
+    let server_url = app.state::<SettingsState>().get().server_url;
+    let health = health::check_server(&net::client(app), &server_url).await;
+    match health.error {
+        None => Check::new(
+            "server",
+            CheckStatus::Pass,
+            format!("{} answered in {} ms", server_url, health.latency_ms),
+        ),
+        Some(e) => Check::failed("server", e.into()),
+    }
+}
+
+fn check_disk(dir: &Path) -> Check {
+    let free = match platform::available_bytes(dir) {
+        Ok(free) => free,
+        Err(e) => return Check::new("disk", CheckStatus::Skipped, e),
+    };
+    let detail = format!("{:.1} GB free in {}", free as f64 / 1e9, dir.display());
+    let status = if free < DISK_MIN_BYTES {
+        CheckStatus::Fail
+    } else if free < DISK_LOW_BYTES {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    };
+    Check::new("disk", status, detail)
+}
+
+fn check_data_dir(dir: &Path) -> Check {
+    let probe = dir.join(PROBE_FILE);
+    let written = std::fs::write(&probe, b"ok").and_then(|_| std::fs::remove_file(&probe));
+    match written {
+        Ok(()) => Check::new(
+            "data_dir",
+            CheckStatus::Pass,
+            format!("{} is writable", dir.display()),
+        ),
+        Err(e) => Check::failed(
+            "data_dir",
+            format!("Failed to write {}: {}", probe.display(), e).into(),
+        ),
+    }
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    pub fn available_bytes(path: &Path) -> Result<u64, String> {
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| format!("Invalid path {}: {}", path.display(), e))?;
+        let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: `c_path` is NUL-terminated and `stat` is only read after
+        // statvfs reports it filled in.
+        let stat = unsafe {
+            if libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) != 0 {
+                return Err(format!(
+                    "Failed to read free space of {}: {}",
+                    path.display(),
+                    std::io::Error::last_os_error()
+                ));
+            }
+            stat.assume_init()
+        };
+        Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::path::Path;
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    pub fn available_bytes(path: &Path) -> Result<u64, String> {
+        let mut free = 0u64;
+        // SAFETY: `free` outlives the call; the other outputs are not asked for.
+        unsafe {
+            GetDiskFreeSpaceExW(
+                &HSTRING::from(path.as_os_str()),
+                Some(&mut free),
+                None,
+                None,
+            )
+        }
+        .map_err(|e| format!("Failed to read free space of {}: {}", path.display(), e))?;
+        Ok(free)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::path::Path;
+
+    pub fn available_bytes(_path: &Path) -> Result<u64, String> {
+        Err("Free space is not available on this platform".into())
+    }
+}
+
+/// Check that the capture device opens and delivers audio, the server
+/// answers, and recordings can be written with room to spare.
+#[tauri::command]
+pub async fn run_self_check(app: AppHandle) -> SelfCheckReport {
+    let ([mic, mic_access], server) = tokio::join!(check_mic(&app), check_server(&app));
+    let mut checks = vec![mic, mic_access, server];
+    match queue::recordings_dir(&app) {
+        Ok(dir) => {
+            checks.push(check_disk(&dir));
+            checks.push(check_data_dir(&dir));
+        }
+        Err(e) => {
+            checks.push(Check::new("disk", CheckStatus::Skipped, e.clone()));
+            checks.push(Check::failed("data_dir", e.into()));
+        }
+    }
+    SelfCheckReport {
+        ok: checks.iter().all(|c| c.status != CheckStatus::Fail),
+        checks,
+    }
+}
+
+/// Probe the audio setup, server URL and data folder in the background, keep
+/// the result and emit `startup-issues` if anything is wrong. Nothing is recorded and the
+/// server isn't contacted; `health` does that on its own schedule.
+pub fn check_on_startup(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let server_url = app.state::<SettingsState>().get().server_url;
+        let mut checks = vec![check_source(&app).await, check_server_url(&server_url)];
+        match queue::recordings_dir(&app) {
+            Ok(dir) => checks.push(check_data_dir(&dir)),
+            Err(e) => checks.push(Check::failed("data_dir", e.into())),
+        }
+        let issues: Vec<Check> = checks
+            .into_iter()
+            .filter(|c| c.status != CheckStatus::Pass)
+            .collect();
+        for issue in &issues {
+            log!("Startup issue ({}): {}", issue.id, issue.detail);
+        }
+        let report = StartupIssues { issues };
+        if let Ok(mut last) = app.state::<StartupState>().0.lock() {
+            *last = Some(report.clone());
+        }
+        if !report.issues.is_empty() {
+            events::emit(&app, &report);
+        }
+    });
+}
+
+/// What the launch check found, or `None` while it is still running.
+#[tauri::command]
+pub fn get_startup_issues(startup: State<'_, StartupState>) -> Option<StartupIssues> {
+    startup.0.lock().ok().and_then(|last| last.clone())
+}
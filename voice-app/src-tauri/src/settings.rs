@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+#[cfg(desktop)]
+use crate::captions::{self, CaptionSettings};
+#[cfg(desktop)]
+use crate::triggers::{self, TriggerSettings};
+#[cfg(desktop)]
+use crate::{autostart, hotkey};
+use crate::api::HedgeSettings;
+use crate::auth::AuthSettings;
+use crate::circuit::CircuitSettings;
+use crate::cues::CueSettings;
+use crate::devices::AudioSource;
+use crate::ducking::{self, DuckingSettings};
+use crate::error::CommandError;
+use crate::events::{self, EventSettings};
+use crate::github::GithubSettings;
+use crate::health::HealthSettings;
+use crate::incidents::IncidentSettings;
+use crate::intents::IntentSettings;
+use crate::mic::{self, CaptureSettings};
+#[cfg(feature = "k8s")]
+use crate::k8s::K8sSettings;
+use crate::net::{self, NetworkSettings};
+use crate::playback::PlaybackSettings;
+use crate::queue::RecordingSettings;
+use crate::signing::SigningSettings;
+use crate::summary::SummarySettings;
+use crate::terminal::TerminalSettings;
+use crate::transcription::{DictationSettings, NumberSettings, TranscriptionSettings};
+use crate::tts::TtsSettings;
+use crate::wake_word::WakeWordSettings;
+use crate::webhooks::{self, WebhookSettings};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Rust-side settings. The UI keeps its own copy of the server URL in
+/// localStorage; this one drives the tray/hotkey loop that runs without it.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub server_url: String,
+    /// Start without a webview: tray + hotkey + pipeline only.
+    pub headless: bool,
+    /// Global shortcut that toggles recording, e.g. "CmdOrCtrl+Shift+Space".
+    pub hotkey: String,
+    /// Media key and HID button triggers, alongside the hotkey.
+    #[cfg(desktop)]
+    pub triggers: TriggerSettings,
+    /// The live captions window.
+    #[cfg(desktop)]
+    pub captions: CaptionSettings,
+    /// Hide the main window to the tray on close instead of quitting.
+    pub close_to_tray: bool,
+    /// Launch at login (in headless mode).
+    pub autostart: bool,
+    /// Earcons for start/stop/upload failure.
+    pub cues: CueSettings,
+    /// Where recordings come from unless `start_mic` names a source.
+    pub source: AudioSource,
+    /// Processing applied to captured audio.
+    pub capture: CaptureSettings,
+    /// Keep `source` open, gated, while idle, so a recording starts without
+    /// waiting for the device to open. Keeps the OS mic indicator on.
+    pub standby: bool,
+    /// File format of recordings kept on disk.
+    pub recordings: RecordingSettings,
+    /// Voices, rate and pitch for spoken responses.
+    pub tts: TtsSettings,
+    /// Lowering other applications while recording or speaking.
+    pub ducking: DuckingSettings,
+    /// Output device for reviewing recordings before sending.
+    pub playback: PlaybackSettings,
+    /// LLM summaries of long recordings.
+    pub summary: SummarySettings,
+    /// Voice commands matched before a transcript goes to the pipeline.
+    pub intents: IntentSettings,
+    /// Typing transcripts into a terminal as shell-safe commands.
+    pub terminal: TerminalSettings,
+    /// What is asked of the transcriber with each recording.
+    pub transcription: TranscriptionSettings,
+    /// Spoken punctuation and formatting commands, and their table.
+    pub dictation: DictationSettings,
+    /// Spoken numbers, dates, times and versions written as the locale does.
+    pub numbers: NumberSettings,
+    /// GitHub API access for spoken review comments and activity digests.
+    pub github: GithubSettings,
+    /// OAuth clients for device-flow login to the server and GitHub.
+    pub auth: AuthSettings,
+    /// HMAC signatures on uploads, for servers that verify them.
+    pub signing: SigningSettings,
+    /// Failing fast against a server that keeps failing.
+    pub circuit: CircuitSettings,
+    /// The HTTP client used for the pipeline server.
+    pub network: NetworkSettings,
+    /// Racing a second request against a slow upload.
+    pub hedge: HedgeSettings,
+    /// Background checks of the server and the Jules health-check workflow.
+    pub health: HealthSettings,
+    /// GitHub issues for repeated send failures.
+    pub incidents: IncidentSettings,
+    /// Local listener for GitHub webhook deliveries.
+    pub webhooks: WebhookSettings,
+    /// Prefix and envelope channel for events sent to the webview.
+    pub events: EventSettings,
+    /// Sensitivity, cooldown and chime for wake-word detections.
+    pub wake_word: WakeWordSettings,
+    /// Cluster context and namespace for spoken pod status.
+    #[cfg(feature = "k8s")]
+    pub k8s: K8sSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            server_url: "http://localhost:8000".into(),
+            headless: false,
+            hotkey: "CmdOrCtrl+Shift+Space".into(),
+            #[cfg(desktop)]
+            triggers: TriggerSettings::default(),
+            #[cfg(desktop)]
+            captions: CaptionSettings::default(),
+            close_to_tray: true,
+            autostart: false,
+            cues: CueSettings::default(),
+            source: AudioSource::default(),
+            capture: CaptureSettings::default(),
+            standby: false,
+            recordings: RecordingSettings::default(),
+            tts: TtsSettings::default(),
+            ducking: DuckingSettings::default(),
+            playback: PlaybackSettings::default(),
+            summary: SummarySettings::default(),
+            intents: IntentSettings::default(),
+            terminal: TerminalSettings::default(),
+            transcription: TranscriptionSettings::default(),
+            dictation: DictationSettings::default(),
+            numbers: NumberSettings::default(),
+            github: GithubSettings::default(),
+            auth: AuthSettings::default(),
+            signing: SigningSettings::default(),
+            circuit: CircuitSettings::default(),
+            network: NetworkSettings::default(),
+            hedge: HedgeSettings::default(),
+            health: HealthSettings::default(),
+            incidents: IncidentSettings::default(),
+            webhooks: WebhookSettings::default(),
+            events: EventSettings::default(),
+            wake_word: WakeWordSettings::default(),
+            #[cfg(feature = "k8s")]
+            k8s: K8sSettings::default(),
+        }
+    }
+}
+
+pub struct SettingsState(Mutex<Settings>);
+
+impl SettingsState {
+    pub fn new(settings: Settings) -> Self {
+        Self(Mutex::new(settings))
+    }
+
+    pub fn get(&self) -> Settings {
+        self.0.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(|e| format!("No config directory: {}", e))
+}
+
+/// Load settings from disk, falling back to defaults if missing or unreadable.
+pub fn load(app: &AppHandle) -> Settings {
+    let Ok(path) = settings_path(app) else {
+        return Settings::default();
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+            log!("Ignoring invalid {}: {}", path.display(), e);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    }
+}
+
+fn save(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let raw = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    std::fs::write(&path, raw).map_err(|e| format!("Failed to write settings: {}", e))
+}
+
+/// Change a few fields in place and save, for callers outside the settings
+/// window such as the tray menu.
+pub fn modify(app: &AppHandle, change: impl FnOnce(&mut Settings)) -> Result<(), String> {
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    let mut changed = settings.clone();
+    change(&mut changed);
+    save(app, &changed)?;
+    *settings = changed;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_settings(state: State<'_, SettingsState>) -> Settings {
+    state.get()
+}
+
+#[tauri::command]
+pub fn update_settings(
+    app: AppHandle,
+    state: State<'_, SettingsState>,
+    settings: Settings,
+) -> Result<(), CommandError> {
+    let previous = state.get();
+    #[cfg(desktop)]
+    {
+        if previous.hotkey != settings.hotkey {
+            hotkey::rebind(&app, &previous.hotkey, &settings.hotkey)?;
+        }
+        if previous.autostart != settings.autostart {
+            autostart::apply(&app, settings.autostart)?;
+        }
+        if previous.triggers != settings.triggers {
+            triggers::apply(&app, &previous.triggers, &settings.triggers);
+        }
+        if previous.captions != settings.captions {
+            captions::apply(&app, &previous.captions, &settings.captions);
+        }
+    }
+
+    if previous.network != settings.network {
+        net::apply(&app, &settings.network)?;
+    }
+    if previous.network != settings.network || previous.server_url != settings.server_url {
+        net::warm_up(&app, &settings.network, &settings.server_url);
+    }
+    if previous.webhooks != settings.webhooks {
+        webhooks::apply(&app, &settings.webhooks);
+    }
+    if previous.events != settings.events {
+        events::apply(&app, &settings.events);
+    }
+    if previous.ducking != settings.ducking {
+        ducking::apply(&settings.ducking);
+    }
+    let standby_changed = previous.standby != settings.standby
+        || previous.source != settings.source
+        || previous.capture != settings.capture;
+
+    save(&app, &settings)?;
+    *state.0.lock().map_err(|e| e.to_string())? = settings;
+    if standby_changed {
+        mic::apply_standby(&app);
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::api;
+use crate::cancel;
+use crate::controller::{self, AppState, Controller};
+use crate::ducking;
+use crate::queue;
+use crate::uploads::UploadWorker;
+
+/// How long exit may block waiting for an in-flight upload to finish.
+const UPLOAD_GRACE: Duration = Duration::from_secs(3);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Called once on `RunEvent::Exit`: stop capture, give an in-flight upload a
+/// short chance to finish, then cancel what is still running and persist it
+/// so nothing is lost.
+pub fn run(app: &AppHandle) {
+    let controller = app.state::<Controller>();
+
+    if controller.state() == AppState::Recording {
+        // Without reopening the standby stream, which exit would only close.
+        match controller::stop_recording_on_exit(app) {
+            Ok(recording) if !recording.samples.is_empty() => {
+                match queue::save_recording(app, &recording.samples) {
+                    Ok(path) => log!("Saved interrupted recording to {}", path.display()),
+                    Err(e) => log!("Failed to save interrupted recording: {}", e),
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log!("Failed to stop recording on exit: {}", e),
+        }
+    }
+
+    let deadline = Instant::now() + UPLOAD_GRACE;
+    while controller.send_phase().is_some() && Instant::now() < deadline {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    // Taken before cancelling; the cancelled send clears its pending upload
+    // as it unwinds.
+    let pending = controller.take_pending_upload();
+    let queued = app.state::<UploadWorker>().take_queued();
+    cancel::cancel_everything(app);
+    ducking::restore_now();
+
+    if let Some(wav_bytes) = pending {
+        match queue::persist_upload(app, &wav_bytes) {
+            Ok(path) => log!("Queued unfinished upload at {}", path.display()),
+            Err(e) => log!("Failed to queue unfinished upload: {}", e),
+        }
+    }
+    // Sends that were waiting behind the one in flight.
+    for recording in queued {
+        let queued = recording
+            .wav
+            .map_or_else(|| api::encode_wav(&recording.samples), Ok)
+            .and_then(|wav_bytes| queue::persist_upload(app, &wav_bytes));
+        match queued {
+            Ok(path) => log!("Queued unsent upload at {}", path.display()),
+            Err(e) => log!("Failed to queue unsent upload: {}", e),
+        }
+    }
+}
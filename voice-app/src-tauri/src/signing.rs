@@ -0,0 +1,18 @@
+//! Upload signing from `voice_core::signing`, keyed with the
+//! `upload_signing_secret` secret from the keychain.
+
+pub use voice_core::signing::*;
+
+use crate::secrets;
+
+/// `None` when signing is off. Enabled without a stored secret is an error
+/// rather than a silently unsigned upload.
+pub fn load(settings: &SigningSettings) -> Result<Option<Signer>, String> {
+    if !settings.enabled {
+        return Ok(None);
+    }
+    match secrets::get(secrets::SIGNING_SECRET)? {
+        Some(secret) if !secret.is_empty() => Ok(Some(Signer::new(&secret, settings))),
+        _ => Err("Request signing is enabled but no upload_signing_secret is stored".into()),
+    }
+}
@@ -0,0 +1,148 @@
+//! Terminal dictation: the transcript of a send is made shell-safe and typed
+//! into the focused window, for dictating commands. Shell metacharacters are
+//! backslash-escaped, so a line does exactly what it literally says —
+//! "ls; rm" lists two files instead of running `rm`.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::history;
+use crate::settings::SettingsState;
+
+/// Characters a POSIX shell (or zsh) would treat as anything but text.
+const SHELL_SPECIAL: &[char] = &[
+    '\\', '\'', '"', '`', '$', '&', '|', ';', '<', '>', '(', ')', '[', ']', '{', '}', '*', '?',
+    '~', '!', '#', '^',
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalSettings {
+    pub enabled: bool,
+    pub lowercase: bool,
+    /// Join words with `-` instead of spaces, for branch and file names.
+    pub join_with_dashes: bool,
+    /// Leave the typed line for the user to check and submit; otherwise
+    /// Enter is pressed after it.
+    pub confirm_enter: bool,
+    /// With `confirm_enter` off, still leave the line unsubmitted when the
+    /// server's confidence in it is below this (0–1).
+    pub confirm_below: f32,
+}
+
+impl Default for TerminalSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lowercase: false,
+            join_with_dashes: false,
+            confirm_enter: true,
+            confirm_below: 0.6,
+        }
+    }
+}
+
+/// The line to type for `text`: a single line, without the full stop
+/// transcribers end sentences with, with metacharacters escaped.
+pub fn format(settings: &TerminalSettings, text: &str) -> String {
+    let text = text.trim().trim_end_matches(['.', '!', '?']);
+    let text = if settings.lowercase {
+        text.to_lowercase()
+    } else {
+        text.to_string()
+    };
+    // Splitting on whitespace also drops newlines, which would submit early.
+    let words: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || c.is_control())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let line = words.join(if settings.join_with_dashes { "-" } else { " " });
+
+    let mut escaped = String::with_capacity(line.len());
+    for c in line.chars() {
+        if SHELL_SPECIAL.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Whether the line typed for `result` is submitted with Enter.
+pub fn submits(settings: &TerminalSettings, result: &serde_json::Value) -> bool {
+    let shaky =
+        history::attached_confidence(result).is_some_and(|c| c.overall < settings.confirm_below);
+    !settings.confirm_enter && !shaky
+}
+
+#[cfg(desktop)]
+fn type_line(line: &str, submit: bool) -> Result<(), String> {
+    crate::keyboard::type_text(line)?;
+    if submit {
+        crate::keyboard::run_macro("enter")?;
+    }
+    Ok(())
+}
+
+#[cfg(mobile)]
+fn type_line(_line: &str, _submit: bool) -> Result<(), String> {
+    Err("Terminal dictation is only supported on desktop".into())
+}
+
+#[cfg(desktop)]
+fn replace_line(previous: &str, line: &str) -> Result<(), String> {
+    crate::keyboard::erase(previous.chars().count())?;
+    crate::keyboard::type_text(line)
+}
+
+#[cfg(mobile)]
+fn replace_line(_previous: &str, _line: &str) -> Result<(), String> {
+    Err("Terminal dictation is only supported on desktop".into())
+}
+
+/// Erase `previous`, a line typed by `inject` and not submitted, and type
+/// the line for `text` instead. Returns the new line.
+pub async fn retype(app: &AppHandle, previous: &str, text: &str) -> Result<String, String> {
+    let settings = app.state::<SettingsState>().get().terminal;
+    let line = format(&settings, text);
+    let (previous, typed) = (previous.to_string(), line.clone());
+    tauri::async_runtime::spawn_blocking(move || replace_line(&previous, &typed))
+        .await
+        .map_err(|e| format!("Typing task failed: {}", e))??;
+    Ok(line)
+}
+
+/// Type the transcript in `result` if terminal mode is on, and report the
+/// typed line as `_terminal`. Sends routed to a voice command or dictating
+/// a review comment are not typed. Failures are logged; the send itself
+/// already succeeded.
+pub async fn inject(app: &AppHandle, result: &mut serde_json::Value) {
+    let settings = app.state::<SettingsState>().get().terminal;
+    let handled = ["_intent", "_review_draft"]
+        .iter()
+        .any(|key| result.get(key).is_some());
+    if !settings.enabled || handled {
+        return;
+    }
+    let Some(text) = history::transcript_text(result) else {
+        return;
+    };
+    let line = format(&settings, text);
+    if line.is_empty() {
+        return;
+    }
+
+    let typed = line.clone();
+    let submit = submits(&settings, result);
+    let outcome = tauri::async_runtime::spawn_blocking(move || type_line(&typed, submit))
+        .await
+        .map_err(|e| format!("Typing task failed: {}", e))
+        .and_then(|r| r);
+    if let Err(e) = outcome {
+        log!("Terminal dictation failed: {}", e);
+        return;
+    }
+    if let Some(obj) = result.as_object_mut() {
+        obj.insert("_terminal".to_string(), serde_json::Value::String(line));
+    }
+}
@@ -0,0 +1,284 @@
+//! What is asked of the transcriber with each recording, and what comes
+//! back beyond the text. The user's vocabulary (project names, CLI tools,
+//! teammates) goes with every request so jargon comes out spelled right,
+//! and optionally what the user was just doing (recent transcripts, the
+//! focused window, the git branch), which also reaches the pipeline's
+//! intent extraction so "rerun that workflow" resolves. Alternative
+//! transcripts come back from servers that offer them. Before a transcript
+//! is stored or typed, spoken commands in it ("comma", "new line") are
+//! carried out per `dictation`, then spoken numbers, dates and versions are
+//! written out per `numbers`. The last send's alternatives are kept so
+//! `choose_alternative` can swap one in for the text, in history and in the
+//! terminal line it typed, which helps with technical terms the transcriber
+//! wasn't sure about.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use voice_core::client::TranscribeOptions;
+use voice_core::dictation;
+pub use voice_core::dictation::DictationSettings;
+use voice_core::numbers;
+pub use voice_core::numbers::NumberSettings;
+use voice_core::transcript::{self, Alternative};
+
+use crate::error::CommandError;
+use crate::history::{self, HistoryState};
+use crate::settings::SettingsState;
+use crate::terminal;
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptionSettings {
+    /// Alternative transcripts to ask the server for; none when 0.
+    pub alternatives: u32,
+    /// Words and phrases the transcriber should favour.
+    pub vocabulary: Vec<String>,
+    /// What is sent as context with live recordings.
+    pub context: ContextSettings,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextSettings {
+    /// Earlier transcripts to include; none when 0.
+    pub recent: usize,
+    /// Only transcripts from this long ago or less.
+    pub recent_within_secs: u64,
+    /// The title of the focused window (desktop only).
+    pub window_title: bool,
+    /// A repository whose checked-out branch is included.
+    pub git_repo: Option<PathBuf>,
+}
+
+impl Default for ContextSettings {
+    fn default() -> Self {
+        Self {
+            recent: 0,
+            recent_within_secs: 10 * 60,
+            window_title: false,
+            git_repo: None,
+        }
+    }
+}
+
+/// The options sent with every recording.
+pub fn options(app: &AppHandle) -> TranscribeOptions {
+    let settings = app.state::<SettingsState>().get().transcription;
+    TranscribeOptions {
+        alternatives: settings.alternatives,
+        vocabulary: settings.vocabulary,
+        context: Vec::new(),
+        language: None,
+    }
+}
+
+/// `options` with the context of a recording made just now. Replayed
+/// recordings from the queue go without it.
+pub async fn options_with_context(app: &AppHandle) -> TranscribeOptions {
+    let mut options = options(app);
+    let settings = app.state::<SettingsState>().get().transcription.context;
+    let handle = app.clone();
+    options.context = tauri::async_runtime::spawn_blocking(move || context(&handle, &settings))
+        .await
+        .unwrap_or_default();
+    options
+}
+
+fn context(app: &AppHandle, settings: &ContextSettings) -> Vec<String> {
+    let mut context = Vec::new();
+    if settings.recent > 0 {
+        let within = Duration::from_secs(settings.recent_within_secs);
+        match app.state::<HistoryState>().recent(settings.recent, within) {
+            Ok(recent) => context.extend(recent),
+            Err(e) => log!("{}", e),
+        }
+    }
+    #[cfg(desktop)]
+    if settings.window_title {
+        if let Some(title) = crate::focus::window_title() {
+            context.push(format!("Window: {}", title));
+        }
+    }
+    if let Some(repo) = &settings.git_repo {
+        match git_branch(repo) {
+            Ok(branch) => context.push(format!("Git branch: {}", branch)),
+            Err(e) => log!("{}", e),
+        }
+    }
+    context
+}
+
+fn git_branch(repo: &Path) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git rev-parse in {} failed: {}",
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+struct LastTranscript {
+    /// The history row, when it was stored.
+    id: Option<i64>,
+    text: String,
+    alternatives: Vec<Alternative>,
+    /// The terminal line typed for it and not submitted yet.
+    typed: Option<String>,
+}
+
+#[derive(Default)]
+pub struct TranscriptionState(Mutex<Option<LastTranscript>>);
+
+/// Add the alternatives in `result`, if the server sent any, as
+/// `_alternatives`.
+pub fn attach_alternatives(result: &mut serde_json::Value) {
+    let alternatives = transcript::alternatives(result);
+    if alternatives.is_empty() {
+        return;
+    }
+    if let (Some(obj), Ok(value)) = (result.as_object_mut(), serde_json::to_value(&alternatives)) {
+        obj.insert("_alternatives".to_string(), value);
+    }
+}
+
+/// Replace the transcript in `result`, and its alternatives, with `f` of
+/// them.
+fn rewrite(result: &mut serde_json::Value, f: impl Fn(&str) -> String) {
+    for key in ["transcribed_text", "text"] {
+        if let Some(text) = result.get(key).and_then(|v| v.as_str()).map(&f) {
+            result[key] = serde_json::Value::String(text);
+        }
+    }
+    let alternatives = result
+        .get_mut("_alternatives")
+        .and_then(|v| v.as_array_mut());
+    for alternative in alternatives.into_iter().flatten() {
+        if let Some(text) = alternative["text"].as_str().map(&f) {
+            alternative["text"] = serde_json::Value::String(text);
+        }
+    }
+}
+
+/// Carry out spoken punctuation and formatting commands in the transcript
+/// of `result`, when `dictation.enabled`, then write its numbers, dates and
+/// versions for the locale, when `numbers.enabled`. Commands go first so a
+/// spoken "comma" ends a number.
+pub fn format(app: &AppHandle, result: &mut serde_json::Value) {
+    let settings = app.state::<SettingsState>().get();
+    if settings.dictation.enabled {
+        rewrite(result, |text| dictation::apply(&settings.dictation, text));
+    }
+    if settings.numbers.enabled {
+        rewrite(result, |text| numbers::apply(&settings.numbers, text));
+    }
+}
+
+/// Keep a finished send for `choose_alternative`, once it has been stored
+/// and typed.
+pub fn remember(app: &AppHandle, result: &serde_json::Value, id: Option<i64>) {
+    let Some(text) = history::transcript_text(result) else {
+        return;
+    };
+    let alternatives = result
+        .get("_alternatives")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let settings = app.state::<SettingsState>().get().terminal;
+    let typed = result["_terminal"]
+        .as_str()
+        .filter(|_| !terminal::submits(&settings, result))
+        .map(str::to_owned);
+    if let Ok(mut last) = app.state::<TranscriptionState>().0.lock() {
+        *last = Some(LastTranscript {
+            id,
+            text: text.to_string(),
+            alternatives,
+            typed,
+        });
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct ChosenAlternative {
+    pub text: String,
+    /// The remaining alternatives, now including the replaced text.
+    pub alternatives: Vec<Alternative>,
+    /// The terminal line typed in place of the old one.
+    pub typed: Option<String>,
+}
+
+/// Swap alternative `index` of the last send in for its text: in history
+/// and, when terminal dictation typed it and left it unsubmitted, on the
+/// command line. The replaced text becomes an alternative, so choosing it
+/// again undoes the swap.
+#[tauri::command]
+pub async fn choose_alternative(
+    app: AppHandle,
+    state: State<'_, TranscriptionState>,
+    index: usize,
+) -> Result<ChosenAlternative, CommandError> {
+    let (id, chosen, alternatives, typed) = {
+        let mut guard = state.0.lock().map_err(|e| e.to_string())?;
+        let last = guard
+            .as_mut()
+            .ok_or_else(|| CommandError::invalid_state("Nothing has been transcribed yet"))?;
+        if index >= last.alternatives.len() {
+            return Err(format!(
+                "No alternative {}; the last transcript has {}",
+                index,
+                last.alternatives.len()
+            )
+            .into());
+        }
+        let chosen = last.alternatives.remove(index);
+        let previous = std::mem::replace(&mut last.text, chosen.text.clone());
+        last.alternatives.insert(
+            index,
+            Alternative {
+                text: previous,
+                confidence: None,
+            },
+        );
+        (
+            last.id,
+            chosen.text,
+            last.alternatives.clone(),
+            last.typed.clone(),
+        )
+    };
+
+    if let Some(id) = id {
+        app.state::<HistoryState>().replace_text(id, &chosen)?;
+        #[cfg(feature = "embeddings")]
+        crate::embeddings::index_in_background(&app);
+    }
+    let typed = match typed {
+        Some(previous) => {
+            let line = terminal::retype(&app, &previous, &chosen).await?;
+            if let Ok(mut last) = state.0.lock() {
+                if let Some(last) = last.as_mut() {
+                    last.typed = Some(line.clone());
+                }
+            }
+            Some(line)
+        }
+        None => None,
+    };
+    Ok(ChosenAlternative {
+        text: chosen,
+        alternatives,
+        typed,
+    })
+}
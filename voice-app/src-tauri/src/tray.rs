@@ -0,0 +1,55 @@
+use tauri::menu::{IsMenuItem, Menu, MenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{App, Wry};
+
+use crate::processing::{self, Preset};
+use crate::voice_loop;
+
+const PRESET_PREFIX: &str = "preset:";
+
+pub fn register(app: &App) -> tauri::Result<()> {
+    let toggle = MenuItem::with_id(app, "toggle", "Start/Stop Recording", true, None::<&str>)?;
+    let presets = Preset::ALL
+        .iter()
+        .map(|preset| {
+            let id = format!("{}{}", PRESET_PREFIX, preset.id());
+            MenuItem::with_id(app, id, preset.label(), true, None::<&str>)
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let preset_items: Vec<&dyn IsMenuItem<Wry>> = presets
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let environment = Submenu::with_items(app, "Environment", true, &preset_items)?;
+    let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&toggle, &environment, &show, &quit])?;
+
+    let mut builder = TrayIconBuilder::with_id("main")
+        .tooltip("Agentic DevOps Voice")
+        .menu(&menu)
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            "toggle" => voice_loop::toggle(app),
+            "show" => {
+                if let Err(e) = crate::show_main_window(app) {
+                    log!("Failed to open window: {}", e);
+                }
+            }
+            "quit" => app.exit(0),
+            id => {
+                let Some(name) = id.strip_prefix(PRESET_PREFIX) else {
+                    return;
+                };
+                if let Some(&preset) = Preset::ALL.iter().find(|p| p.id() == name) {
+                    if let Err(e) = processing::select(app, preset) {
+                        log!("Failed to switch preset: {}", e);
+                    }
+                }
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
@@ -0,0 +1,166 @@
+//! Recording triggers beyond the hotkey, handled in Rust so they work while
+//! the app is unfocused: a media key (a headset's play/pause button arrives
+//! as one) bound through the global-shortcut plugin, and a button on a
+//! specific HID device such as a macro pad, read with hidapi on a thread of
+//! its own. Both drive the same record → send toggle as the tray. MIDI
+//! pedals and pads live in `midi`.
+
+use hidapi::{HidApi, HidDevice};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::GlobalShortcutExt;
+
+use crate::controller::{AppState, Controller};
+use crate::midi::{self, MidiSettings};
+use crate::voice_loop;
+
+/// How long to wait before looking for an unplugged device again.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+/// How often the reader checks whether it was replaced.
+const READ_TIMEOUT_MS: i32 = 500;
+const REPORT_SIZE: usize = 64;
+
+/// A button on one HID device: the bits of `mask` in byte `byte` of its
+/// input reports are set while it is held. With numbered reports, byte 0
+/// is the report id.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HidButton {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub byte: usize,
+    pub mask: u8,
+    /// Record while held and send on release, instead of toggling on each
+    /// press.
+    #[serde(default)]
+    pub push_to_talk: bool,
+}
+
+impl HidButton {
+    fn pressed(&self, report: &[u8]) -> bool {
+        report.get(self.byte).is_some_and(|b| b & self.mask != 0)
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriggerSettings {
+    /// Media key that toggles recording, e.g. "MediaPlayPause"; while
+    /// bound, other apps don't receive it.
+    pub media_key: Option<String>,
+    pub hid: Option<HidButton>,
+    pub midi: MidiSettings,
+}
+
+/// Cleared to stop the running HID reader.
+#[derive(Default)]
+pub struct TriggerState(Mutex<Option<Arc<AtomicBool>>>);
+
+/// Bind the media key, start the HID reader and open the MIDI port for
+/// `settings`.
+pub fn register(app: &AppHandle, settings: &TriggerSettings) {
+    if let Some(key) = &settings.media_key {
+        if let Err(e) = app.global_shortcut().register(key.as_str()) {
+            log!("Failed to register media key {}: {}", key, e);
+        }
+    }
+    start_hid(app, settings.hid.clone());
+    midi::apply(app, &settings.midi);
+}
+
+/// Swap the bindings from `previous` to `settings`.
+pub fn apply(app: &AppHandle, previous: &TriggerSettings, settings: &TriggerSettings) {
+    if previous.media_key != settings.media_key {
+        if let Some(key) = &previous.media_key {
+            let _ = app.global_shortcut().unregister(key.as_str());
+        }
+        if let Some(key) = &settings.media_key {
+            if let Err(e) = app.global_shortcut().register(key.as_str()) {
+                log!("Failed to register media key {}: {}", key, e);
+            }
+        }
+    }
+    if previous.hid != settings.hid {
+        start_hid(app, settings.hid.clone());
+    }
+    if previous.midi != settings.midi {
+        midi::apply(app, &settings.midi);
+    }
+}
+
+/// Stop the running reader, if any, and start one for `button`.
+fn start_hid(app: &AppHandle, button: Option<HidButton>) {
+    let state = app.state::<TriggerState>();
+    let Ok(mut running) = state.0.lock() else {
+        return;
+    };
+    if let Some(running) = running.take() {
+        running.store(false, Ordering::Relaxed);
+    }
+    let Some(button) = button else {
+        return;
+    };
+    let active = Arc::new(AtomicBool::new(true));
+    *running = Some(Arc::clone(&active));
+    let app = app.clone();
+    std::thread::spawn(move || read_hid(&app, &button, &active));
+}
+
+fn open(button: &HidButton) -> Result<HidDevice, String> {
+    HidApi::new()
+        .and_then(|api| api.open(button.vendor_id, button.product_id))
+        .map_err(|e| {
+            format!(
+                "HID device {:04x}:{:04x} unavailable: {}",
+                button.vendor_id, button.product_id, e
+            )
+        })
+}
+
+/// Reader thread: act on press (and, for push-to-talk, release) edges
+/// until `active` is cleared, reopening the device when it goes away.
+fn read_hid(app: &AppHandle, button: &HidButton, active: &AtomicBool) {
+    let mut logged = false;
+    while active.load(Ordering::Relaxed) {
+        let device = match open(button) {
+            Ok(device) => device,
+            Err(e) => {
+                // Once per outage, not every retry.
+                if !std::mem::replace(&mut logged, true) {
+                    log!("{}", e);
+                }
+                std::thread::sleep(RECONNECT_INTERVAL);
+                continue;
+            }
+        };
+        logged = false;
+
+        let mut held = false;
+        let mut report = [0u8; REPORT_SIZE];
+        while active.load(Ordering::Relaxed) {
+            let len = match device.read_timeout(&mut report, READ_TIMEOUT_MS) {
+                Ok(0) => continue,
+                Ok(len) => len,
+                Err(e) => {
+                    log!("HID read failed: {}", e);
+                    break;
+                }
+            };
+            let pressed = button.pressed(&report[..len]);
+            if pressed == std::mem::replace(&mut held, pressed) {
+                continue;
+            }
+            let recording = app.state::<Controller>().state() == AppState::Recording;
+            let act = if button.push_to_talk {
+                pressed != recording
+            } else {
+                pressed
+            };
+            if act {
+                voice_loop::toggle(app);
+            }
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Spoken responses through the OS speech engine: `say` on macOS,
+//! `espeak-ng` on Linux, System.Speech on Windows. Voices are picked per
+//! language from settings, falling back to the engine's default voice.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::process::Command;
+use tauri::State;
+
+use crate::ducking;
+use crate::error::CommandError;
+use crate::settings::SettingsState;
+
+const MIN_FACTOR: f32 = 0.5;
+const MAX_FACTOR: f32 = 2.0;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TtsSettings {
+    /// Voice id per language tag, e.g. `{"sv": "Alva", "en-US": "Samantha"}`.
+    pub voices: BTreeMap<String, String>,
+    /// Speaking rate relative to the engine default, 0.5–2.0.
+    pub rate: f32,
+    /// Pitch relative to the voice default, 0.5–2.0. Ignored by `say`.
+    pub pitch: f32,
+}
+
+impl Default for TtsSettings {
+    fn default() -> Self {
+        Self {
+            voices: BTreeMap::new(),
+            rate: 1.0,
+            pitch: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Voice {
+    /// What to store in `TtsSettings::voices`.
+    id: String,
+    name: String,
+    /// BCP 47 tag, e.g. `en-US`.
+    language: String,
+}
+
+/// Engines disagree on `en_US`/`en-us`/`en-US`; compare them as one form.
+fn normalize_language(tag: &str) -> String {
+    tag.replace('_', "-").to_ascii_lowercase()
+}
+
+/// The configured voice for `language`: an exact tag first, then the
+/// primary language, so `sv-SE` text uses a voice set for `sv`.
+fn voice_for<'a>(settings: &'a TtsSettings, language: &str) -> Option<&'a str> {
+    let wanted = normalize_language(language);
+    let primary = wanted.split('-').next().unwrap_or(&wanted);
+    let lookup = |tag: &str| {
+        settings
+            .voices
+            .iter()
+            .find(|(lang, _)| normalize_language(lang) == tag)
+            .map(|(_, voice)| voice.as_str())
+    };
+    lookup(&wanted).or_else(|| lookup(primary))
+}
+
+fn run(command: &mut Command, program: &str) -> Result<String, String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(target_os = "macos")]
+mod engine {
+    use super::{run, Voice};
+    use std::process::Command;
+
+    const DEFAULT_WPM: f32 = 175.0;
+
+    /// `say -v ?` lines look like `Bad News    en_US    # The light...`;
+    /// names may contain spaces, the language never does.
+    pub fn voices() -> Result<Vec<Voice>, String> {
+        let out = run(Command::new("say").args(["-v", "?"]), "say")?;
+        Ok(out
+            .lines()
+            .filter_map(|line| {
+                let described = line.split('#').next()?.trim_end();
+                let (name, language) = described.rsplit_once(char::is_whitespace)?;
+                let name = name.trim().to_string();
+                Some(Voice {
+                    id: name.clone(),
+                    name,
+                    language: language.replace('_', "-"),
+                })
+            })
+            .collect())
+    }
+
+    pub fn command(text: &str, voice: Option<&str>, rate: f32, _pitch: f32) -> Command {
+        let mut cmd = Command::new("say");
+        if let Some(voice) = voice {
+            cmd.args(["-v", voice]);
+        }
+        cmd.args(["-r", &((DEFAULT_WPM * rate).round() as u32).to_string()]);
+        cmd.arg("--").arg(text);
+        cmd
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod engine {
+    use super::{run, Voice};
+    use std::process::Command;
+
+    const DEFAULT_WPM: f32 = 175.0;
+    const DEFAULT_PITCH: f32 = 50.0;
+
+    /// `espeak-ng --voices` is a table:
+    /// `Pty Language Age/Gender VoiceName File Other Languages`.
+    pub fn voices() -> Result<Vec<Voice>, String> {
+        let out = run(Command::new("espeak-ng").arg("--voices"), "espeak-ng")?;
+        Ok(out
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let mut cols = line.split_whitespace();
+                let language = cols.nth(1)?.to_string();
+                let name = cols.nth(1)?.replace('_', " ");
+                Some(Voice {
+                    id: language.clone(),
+                    name,
+                    language,
+                })
+            })
+            .collect())
+    }
+
+    pub fn command(text: &str, voice: Option<&str>, rate: f32, pitch: f32) -> Command {
+        let mut cmd = Command::new("espeak-ng");
+        if let Some(voice) = voice {
+            cmd.args(["-v", voice]);
+        }
+        cmd.args(["-s", &((DEFAULT_WPM * rate).round() as u32).to_string()]);
+        cmd.args([
+            "-p",
+            &((DEFAULT_PITCH * pitch).round().min(99.0) as u32).to_string(),
+        ]);
+        cmd.arg("--").arg(text);
+        cmd
+    }
+}
+
+#[cfg(windows)]
+mod engine {
+    use super::{run, Voice};
+    use std::process::Command;
+
+    const LOAD: &str = "Add-Type -AssemblyName System.Speech; \
+        $s = New-Object System.Speech.Synthesis.SpeechSynthesizer;";
+
+    fn powershell(script: &str) -> Command {
+        let mut cmd = Command::new("powershell");
+        cmd.args(["-NoProfile", "-NonInteractive", "-Command"])
+            .arg(format!("{} {}", LOAD, script));
+        cmd
+    }
+
+    pub fn voices() -> Result<Vec<Voice>, String> {
+        let out = run(
+            &mut powershell(
+                "$s.GetInstalledVoices() | ForEach-Object { \
+                 $_.VoiceInfo.Name + '|' + $_.VoiceInfo.Culture.Name }",
+            ),
+            "powershell",
+        )?;
+        Ok(out
+            .lines()
+            .filter_map(|line| {
+                let (name, language) = line.trim().split_once('|')?;
+                Some(Voice {
+                    id: name.to_string(),
+                    name: name.to_string(),
+                    language: language.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    /// Text and voice travel in environment variables so nothing the user
+    /// dictated is ever parsed as PowerShell; pitch needs SSML.
+    pub fn command(text: &str, voice: Option<&str>, rate: f32, pitch: f32) -> Command {
+        // SpeechSynthesizer.Rate is -10..10 with 0 as normal speed.
+        let rate = ((rate - 1.0) * 10.0).round().clamp(-10.0, 10.0) as i32;
+        let pitch = ((pitch - 1.0) * 100.0).round() as i32;
+        let mut cmd = powershell(&format!(
+            "if ($env:TTS_VOICE) {{ $s.SelectVoice($env:TTS_VOICE) }}; \
+             $s.Rate = {}; \
+             $t = [Security.SecurityElement]::Escape($env:TTS_TEXT); \
+             $s.SpeakSsml(\"<speak version='1.0' xml:lang='$($s.Voice.Culture.Name)' \
+             xmlns='http://www.w3.org/2001/10/synthesis'>\
+             <prosody pitch='{:+}%'>$t</prosody></speak>\")",
+            rate, pitch
+        ));
+        cmd.env("TTS_TEXT", text)
+            .env("TTS_VOICE", voice.unwrap_or_default());
+        cmd
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+mod engine {
+    use super::Voice;
+    use std::process::Command;
+
+    pub fn voices() -> Result<Vec<Voice>, String> {
+        Err("Text-to-speech is not supported on this platform".into())
+    }
+
+    pub fn command(_text: &str, _voice: Option<&str>, _rate: f32, _pitch: f32) -> Command {
+        Command::new("false")
+    }
+}
+
+pub fn list_voices() -> Result<Vec<Voice>, String> {
+    engine::voices()
+}
+
+/// Speak `text` without blocking the caller, in the voice configured for
+/// `language` (or the engine default).
+pub fn speak(settings: &TtsSettings, text: &str, language: Option<&str>) {
+    let voice = language.and_then(|lang| voice_for(settings, lang));
+    let rate = settings.rate.clamp(MIN_FACTOR, MAX_FACTOR);
+    let pitch = settings.pitch.clamp(MIN_FACTOR, MAX_FACTOR);
+    let mut command = engine::command(text, voice, rate, pitch);
+    std::thread::spawn(move || {
+        let _duck = ducking::while_speaking();
+        if let Err(e) = run(&mut command, "speech engine") {
+            log!("Speech failed: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_tts_voices() -> Result<Vec<Voice>, CommandError> {
+    Ok(list_voices()?)
+}
+
+/// `language` is a BCP 47 tag such as `sv` or `en-US`.
+#[tauri::command]
+pub fn speak_text(settings: State<'_, SettingsState>, text: String, language: Option<String>) {
+    speak(&settings.get().tts, &text, language.as_deref());
+}
@@ -0,0 +1,84 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::error::CommandError;
+use crate::events;
+
+/// Update found by the last `check_for_update`, kept until `install_update`.
+#[derive(Default)]
+pub struct PendingUpdate(Mutex<Option<Update>>);
+
+#[derive(Clone, Serialize)]
+pub struct UpdateInfo {
+    version: String,
+    current_version: String,
+    notes: Option<String>,
+    date: Option<String>,
+}
+
+impl From<&Update> for UpdateInfo {
+    fn from(update: &Update) -> Self {
+        Self {
+            version: update.version.clone(),
+            current_version: update.current_version.clone(),
+            notes: update.body.clone(),
+            date: update.date.map(|d| d.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct UpdateStagedPayload {
+    version: String,
+}
+
+impl events::Event for UpdateStagedPayload {
+    const NAME: &'static str = "update-staged";
+}
+
+#[tauri::command]
+pub async fn check_for_update(
+    app: AppHandle,
+    pending: State<'_, PendingUpdate>,
+) -> Result<Option<UpdateInfo>, CommandError> {
+    let update = app
+        .updater()
+        .map_err(|e| format!("Updater unavailable: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Update check failed: {}", e))?;
+
+    let info = update.as_ref().map(UpdateInfo::from);
+    *pending.0.lock().map_err(|e| e.to_string())? = update;
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn install_update(
+    app: AppHandle,
+    pending: State<'_, PendingUpdate>,
+) -> Result<(), CommandError> {
+    let update = pending
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or("No update pending, call check_for_update first")?;
+
+    // Signature verification against the configured pubkey happens inside
+    // download_and_install; a tampered artifact fails here, not at restart.
+    update
+        .download_and_install(|_, _| {}, || {})
+        .await
+        .map_err(|e| format!("Update install failed: {}", e))?;
+
+    events::emit(
+        &app,
+        &UpdateStagedPayload {
+            version: update.version.clone(),
+        },
+    );
+    Ok(())
+}
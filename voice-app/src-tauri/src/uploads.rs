@@ -0,0 +1,228 @@
+//! Upload worker: every send becomes a job on one long-lived task, so sends
+//! from the UI, hotkey, control socket and voice loop queue behind each
+//! other instead of racing for the controller. Callers still await their
+//! own result; `get_upload_status` lists the jobs the worker knows about.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::controller;
+use crate::error::{CommandError, ErrorCode};
+use crate::mic::Recording;
+
+/// Finished jobs kept for `get_upload_status`; older ones are forgotten.
+const FINISHED_KEPT: usize = 20;
+
+pub type JobId = u64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Clone, Serialize)]
+pub struct UploadJob {
+    pub id: JobId,
+    pub status: JobStatus,
+    /// When the job was submitted, in Unix milliseconds.
+    pub queued_at_ms: u64,
+    pub error: Option<String>,
+    /// `error`'s code, for a localized message.
+    pub error_code: Option<ErrorCode>,
+}
+
+struct Entry {
+    job: UploadJob,
+    /// Held here until the worker picks the job up, so shutdown can queue
+    /// what never started.
+    recording: Option<Recording>,
+}
+
+struct Job {
+    id: JobId,
+    server_url: String,
+    /// When the send was requested, for `send-progress` timings.
+    started: Instant,
+    reply: oneshot::Sender<Result<serde_json::Value, CommandError>>,
+}
+
+pub struct UploadWorker {
+    tx: mpsc::UnboundedSender<Job>,
+    /// Taken by `spawn`.
+    rx: Mutex<Option<mpsc::UnboundedReceiver<Job>>>,
+    next_id: AtomicU64,
+    jobs: Mutex<BTreeMap<JobId, Entry>>,
+}
+
+impl Default for UploadWorker {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            rx: Mutex::new(Some(rx)),
+            next_id: AtomicU64::new(1),
+            jobs: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl UploadWorker {
+    fn start_job(&self, id: JobId) -> Option<Recording> {
+        let mut jobs = self.jobs.lock().ok()?;
+        let entry = jobs.get_mut(&id)?;
+        let recording = entry.recording.take()?;
+        entry.job.status = JobStatus::Running;
+        Some(recording)
+    }
+
+    fn finish_job(&self, id: JobId, result: &Result<serde_json::Value, CommandError>) {
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return;
+        };
+        if let Some(entry) = jobs.get_mut(&id) {
+            match result {
+                Ok(_) => entry.job.status = JobStatus::Done,
+                Err(e) => {
+                    entry.job.status = JobStatus::Failed;
+                    entry.job.error = Some(e.message.clone());
+                    entry.job.error_code = Some(e.code);
+                }
+            }
+        }
+        let finished: Vec<JobId> = jobs
+            .values()
+            .filter(|e| matches!(e.job.status, JobStatus::Done | JobStatus::Failed))
+            .map(|e| e.job.id)
+            .collect();
+        for id in finished
+            .iter()
+            .take(finished.len().saturating_sub(FINISHED_KEPT))
+        {
+            jobs.remove(id);
+        }
+    }
+
+    /// Fail every job that hasn't started; the worker skips them.
+    pub fn cancel_queued(&self) {
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return;
+        };
+        for entry in jobs.values_mut() {
+            if entry.recording.take().is_some() {
+                entry.job.status = JobStatus::Failed;
+                entry.job.error = Some("Cancelled".into());
+                entry.job.error_code = Some(ErrorCode::Cancelled);
+            }
+        }
+    }
+
+    /// Recordings of jobs that haven't started, removing them from the
+    /// queue; the worker skips their jobs when it gets to them.
+    pub fn take_queued(&self) -> Vec<Recording> {
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return Vec::new();
+        };
+        let queued: Vec<JobId> = jobs
+            .values()
+            .filter(|e| e.recording.is_some())
+            .map(|e| e.job.id)
+            .collect();
+        queued
+            .into_iter()
+            .filter_map(|id| jobs.remove(&id).and_then(|e| e.recording))
+            .collect()
+    }
+}
+
+async fn run(app: &AppHandle, job: Job) {
+    let worker = app.state::<UploadWorker>();
+    // Cancelled, or taken by shutdown.
+    let Some(recording) = worker.start_job(job.id) else {
+        return;
+    };
+    let result = controller::run_send(app, recording, &job.server_url, job.started).await;
+    worker.finish_job(job.id, &result);
+    // The caller may have gone away; the job is still done.
+    let _ = job.reply.send(result);
+}
+
+/// Start the worker. Jobs submitted before this wait in the channel.
+pub fn spawn(app: &AppHandle) {
+    let Some(mut rx) = app
+        .state::<UploadWorker>()
+        .rx
+        .lock()
+        .ok()
+        .and_then(|mut rx| rx.take())
+    else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        while let Some(job) = rx.recv().await {
+            run(&app, job).await;
+        }
+    });
+}
+
+/// Queue `recording` for upload to `server_url` and wait for its turn and
+/// result.
+pub async fn submit(
+    app: &AppHandle,
+    recording: Recording,
+    server_url: &str,
+    started: Instant,
+) -> Result<serde_json::Value, CommandError> {
+    let worker = app.state::<UploadWorker>();
+    let id = worker.next_id.fetch_add(1, Ordering::Relaxed);
+    let queued_at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    worker.jobs.lock().map_err(|e| e.to_string())?.insert(
+        id,
+        Entry {
+            job: UploadJob {
+                id,
+                status: JobStatus::Queued,
+                queued_at_ms,
+                error: None,
+                error_code: None,
+            },
+            recording: Some(recording),
+        },
+    );
+
+    let (reply, result) = oneshot::channel();
+    worker
+        .tx
+        .send(Job {
+            id,
+            server_url: server_url.to_string(),
+            started,
+            reply,
+        })
+        .map_err(|_| "Upload worker stopped")?;
+    result
+        .await
+        .map_err(|_| CommandError::cancelled("Upload cancelled before it started"))?
+}
+
+/// Queued, running and recently finished uploads, oldest first.
+#[tauri::command]
+pub fn get_upload_status(worker: State<'_, UploadWorker>) -> Vec<UploadJob> {
+    worker
+        .jobs
+        .lock()
+        .map(|jobs| jobs.values().map(|e| e.job.clone()).collect())
+        .unwrap_or_default()
+}
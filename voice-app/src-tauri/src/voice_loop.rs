@@ -0,0 +1,79 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::controller::{self, AppState, Controller};
+use crate::error::{CommandError, ErrorCode};
+use crate::events;
+use crate::mic::Recording;
+use crate::settings::SettingsState;
+
+/// Record/send toggle for triggers outside the webview (tray, hotkey).
+/// Stopping uploads straight to the configured server and reports back via
+/// `pipeline-result` / `pipeline-error` for whichever window is listening.
+pub fn toggle(app: &AppHandle) {
+    if app.state::<Controller>().state() != AppState::Recording {
+        if let Err(e) = controller::start_recording(app) {
+            log!("Failed to start recording: {}", e);
+        }
+        return;
+    }
+
+    let recording = match controller::stop_recording(app) {
+        Ok(recording) => recording,
+        Err(e) => {
+            log!("Failed to stop recording: {}", e);
+            return;
+        }
+    };
+
+    send_in_background(app, recording);
+}
+
+/// A send's result: the server's JSON plus the `_`-prefixed fields added
+/// on the way (`_endpoint_used`, `_fallback`, `_summary`, ...).
+#[derive(Clone, Serialize)]
+#[serde(transparent)]
+pub struct PipelineResult(pub serde_json::Value);
+
+impl events::Event for PipelineResult {
+    const NAME: &'static str = "pipeline-result";
+}
+
+#[derive(Clone, Serialize)]
+pub struct PipelineError {
+    error: String,
+    code: ErrorCode,
+}
+
+impl events::Event for PipelineError {
+    const NAME: &'static str = "pipeline-error";
+}
+
+/// Upload to the configured server without blocking the caller.
+pub fn send_in_background(app: &AppHandle, recording: Recording) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let server_url = app.state::<SettingsState>().get().server_url;
+        let result = controller::send(&app, recording, &server_url).await;
+        report(&app, result);
+    });
+}
+
+/// Emit a send's outcome as `pipeline-result` or `pipeline-error`.
+pub fn report(app: &AppHandle, result: Result<serde_json::Value, CommandError>) {
+    match result {
+        Ok(result) => {
+            events::emit(app, &PipelineResult(result));
+        }
+        Err(e) => {
+            log!("Pipeline upload failed: {}", e);
+            events::emit(
+                app,
+                &PipelineError {
+                    error: e.message,
+                    code: e.code,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Wake-word tuning. The app ships no detection engine; whichever one is
+//! plugged in reports each keyword it spots, with its confidence, through
+//! `detected` (or the `report_wake_word` command, for engines running in the
+//! webview). Detections below the `sensitivity` threshold, or within
+//! `cooldown_ms` of the last accepted one, are dropped; the rest start a
+//! recording after an optional confirmation chime. Every detection goes to
+//! `metrics` with its confidence and outcome, so false triggers can be tuned
+//! away by looking at what they scored.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::controller::{self, AppState, Controller};
+use crate::cues::{self, Cue};
+use crate::error::CommandError;
+use crate::metrics;
+use crate::settings::SettingsState;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WakeWordSettings {
+    pub enabled: bool,
+    /// 0.0–1.0: a detection counts when its confidence is at least
+    /// `1 - sensitivity`. Lower it when the app wakes by itself.
+    pub sensitivity: f32,
+    /// Detections this soon after an accepted one are ignored, so one
+    /// utterance doesn't trigger twice.
+    pub cooldown_ms: u64,
+    /// Play the `wake` cue when a detection is accepted.
+    pub chime: bool,
+}
+
+impl Default for WakeWordSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sensitivity: 0.5,
+            cooldown_ms: 2000,
+            chime: true,
+        }
+    }
+}
+
+impl WakeWordSettings {
+    fn threshold(&self) -> f32 {
+        1.0 - self.sensitivity.clamp(0.0, 1.0)
+    }
+}
+
+/// What became of one detection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WakeWordOutcome {
+    /// Started a recording, or found one already running.
+    Accepted,
+    /// Confidence under the `sensitivity` threshold.
+    BelowThreshold,
+    /// Within `cooldown_ms` of the last accepted detection.
+    Cooldown,
+    /// `wake_word.enabled` is off.
+    Disabled,
+}
+
+/// When the last detection was accepted, for the cooldown.
+#[derive(Default)]
+pub struct WakeWordState(Mutex<Option<Instant>>);
+
+/// Handle a detection of `keyword` with `confidence` (0.0–1.0) from the
+/// engine.
+pub fn detected(app: &AppHandle, keyword: &str, confidence: f32) -> WakeWordOutcome {
+    let settings = app.state::<SettingsState>().get().wake_word;
+    let outcome = if !settings.enabled {
+        WakeWordOutcome::Disabled
+    } else if confidence < settings.threshold() {
+        WakeWordOutcome::BelowThreshold
+    } else {
+        let cooldown = Duration::from_millis(settings.cooldown_ms);
+        let state = app.state::<WakeWordState>();
+        let cooling = state.0.lock().is_ok_and(|mut last| {
+            if last.is_some_and(|at| at.elapsed() < cooldown) {
+                return true;
+            }
+            *last = Some(Instant::now());
+            false
+        });
+        if cooling {
+            WakeWordOutcome::Cooldown
+        } else {
+            WakeWordOutcome::Accepted
+        }
+    };
+    metrics::record_wake_word(app, keyword, confidence, outcome);
+
+    if outcome == WakeWordOutcome::Accepted {
+        if settings.chime {
+            cues::play(app, Cue::Wake);
+        }
+        if app.state::<Controller>().state() != AppState::Recording {
+            if let Err(e) = controller::start_recording(app) {
+                log!("Wake word could not start recording: {}", e);
+            }
+        }
+    }
+    outcome
+}
+
+/// `detected`, for an engine running in the webview.
+#[tauri::command]
+pub fn report_wake_word(
+    app: AppHandle,
+    keyword: String,
+    confidence: f32,
+) -> Result<WakeWordOutcome, CommandError> {
+    if !(0.0..=1.0).contains(&confidence) {
+        return Err(format!("Confidence must be between 0 and 1, got {}", confidence).into());
+    }
+    Ok(detected(&app, &keyword, confidence))
+}
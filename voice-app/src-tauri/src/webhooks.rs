@@ -0,0 +1,290 @@
+//! Optional local listener for GitHub webhooks, so CI failures and issue
+//! changes show up as they happen instead of on the next poll. Point a
+//! repository webhook (through a tunnel if the machine isn't reachable) at
+//! `http://<bind>:<port>/` with content type `application/json` and the
+//! secret stored as `webhook_secret`. `workflow_run` and `issues` deliveries
+//! are emitted as `webhook-event`; failed runs are also spoken.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::events;
+use crate::health;
+use crate::secrets;
+use crate::settings::SettingsState;
+use crate::tts;
+
+/// GitHub caps deliveries at 25 MB; the events handled here are far smaller.
+const MAX_BODY: usize = 5 * 1024 * 1024;
+const MAX_HEADER_LINES: usize = 100;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    pub enabled: bool,
+    /// Address to listen on; loopback unless a tunnel needs otherwise.
+    pub bind: String,
+    pub port: u16,
+    /// Speak failed workflow runs.
+    pub announce_failures: bool,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: "127.0.0.1".into(),
+            port: 8787,
+            announce_failures: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct WebhookEvent {
+    /// The `X-GitHub-Event` name, e.g. `workflow_run`.
+    pub kind: String,
+    pub repo: String,
+    /// One line for a notification, e.g. "CI failure on main".
+    pub title: String,
+    pub url: Option<String>,
+    pub failed: bool,
+}
+
+impl events::Event for WebhookEvent {
+    const NAME: &'static str = "webhook-event";
+}
+
+/// The running listener, replaced when the settings change.
+#[derive(Default)]
+pub struct WebhookState(Mutex<Option<JoinHandle<()>>>);
+
+struct Request {
+    method: String,
+    /// Lowercased names.
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .await
+        .map_err(|e| e.to_string())?;
+    let method = line
+        .split_whitespace()
+        .next()
+        .ok_or("Empty request")?
+        .to_string();
+
+    let mut headers = HashMap::new();
+    for _ in 0..MAX_HEADER_LINES {
+        line.clear();
+        reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| e.to_string())?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .ok_or("Missing Content-Length")?;
+    if length > MAX_BODY {
+        return Err(format!("Body of {} bytes is too large", length));
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Request {
+        method,
+        headers,
+        body,
+    })
+}
+
+/// `X-Hub-Signature-256` is `sha256=<hex HMAC of the body>`.
+fn verify(secret: &str, signature: Option<&String>, body: &[u8]) -> bool {
+    let Some(expected) = signature
+        .and_then(|s| s.strip_prefix("sha256="))
+        .and_then(|hex| hex::decode(hex).ok())
+    else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// The events worth surfacing; everything else is acknowledged and dropped.
+fn parse_event(kind: &str, payload: &serde_json::Value) -> Option<WebhookEvent> {
+    let repo = payload["repository"]["full_name"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let action = payload["action"].as_str().unwrap_or_default();
+    match kind {
+        "workflow_run" if action == "completed" => {
+            let run = &payload["workflow_run"];
+            let conclusion = run["conclusion"].as_str().unwrap_or("unknown");
+            let mut title = format!(
+                "{} {}",
+                run["name"].as_str().unwrap_or("Workflow"),
+                conclusion
+            );
+            if let Some(branch) = run["head_branch"].as_str() {
+                title.push_str(&format!(" on {}", branch));
+            }
+            Some(WebhookEvent {
+                kind: kind.to_string(),
+                repo,
+                title,
+                url: run["html_url"].as_str().map(str::to_string),
+                failed: health::is_failure(conclusion),
+            })
+        }
+        "issues" if matches!(action, "opened" | "closed" | "reopened") => {
+            let issue = &payload["issue"];
+            Some(WebhookEvent {
+                kind: kind.to_string(),
+                repo,
+                title: format!(
+                    "Issue #{} {}: {}",
+                    issue["number"].as_u64().unwrap_or_default(),
+                    action,
+                    issue["title"].as_str().unwrap_or_default()
+                ),
+                url: issue["html_url"].as_str().map(str::to_string),
+                failed: false,
+            })
+        }
+        _ => None,
+    }
+}
+
+async fn handle(app: &AppHandle, stream: &mut TcpStream, secret: &str) -> &'static str {
+    let request = match read_request(stream).await {
+        Ok(request) => request,
+        Err(e) => {
+            log!("Bad webhook request: {}", e);
+            return "400 Bad Request";
+        }
+    };
+    if request.method != "POST" {
+        return "405 Method Not Allowed";
+    }
+    if !verify(
+        secret,
+        request.headers.get("x-hub-signature-256"),
+        &request.body,
+    ) {
+        return "401 Unauthorized";
+    }
+    let Ok(payload) = serde_json::from_slice::<serde_json::Value>(&request.body) else {
+        return "400 Bad Request";
+    };
+
+    let kind = request
+        .headers
+        .get("x-github-event")
+        .map(String::as_str)
+        .unwrap_or_default();
+    if let Some(event) = parse_event(kind, &payload) {
+        let settings = app.state::<SettingsState>().get();
+        if event.failed && settings.webhooks.announce_failures {
+            tts::speak(
+                &settings.tts,
+                &format!("{} in {}.", event.title, event.repo),
+                None,
+            );
+        }
+        events::emit(app, &event);
+    }
+    "204 No Content"
+}
+
+async fn serve(app: AppHandle, settings: WebhookSettings, secret: String) {
+    let address = format!("{}:{}", settings.bind, settings.port);
+    let listener = match TcpListener::bind(&address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log!("Webhook listener unavailable on {}: {}", address, e);
+            return;
+        }
+    };
+    loop {
+        let mut stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                log!("Webhook accept failed: {}", e);
+                continue;
+            }
+        };
+        let app = app.clone();
+        let secret = secret.clone();
+        tokio::spawn(async move {
+            let status = tokio::time::timeout(REQUEST_TIMEOUT, handle(&app, &mut stream, &secret))
+                .await
+                .unwrap_or("408 Request Timeout");
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+/// Stop the running listener, if any, and start one for `settings` if
+/// enabled. Unsigned deliveries are never accepted, so there is no listener
+/// without a `webhook_secret`.
+pub fn apply(app: &AppHandle, settings: &WebhookSettings) {
+    let state = app.state::<WebhookState>();
+    let Ok(mut running) = state.0.lock() else {
+        return;
+    };
+    if let Some(listener) = running.take() {
+        listener.abort();
+    }
+    if !settings.enabled {
+        return;
+    }
+    let secret = match secrets::get(secrets::WEBHOOK_SECRET) {
+        Ok(Some(secret)) if !secret.is_empty() => secret,
+        Ok(_) => {
+            log!("Webhooks enabled but no webhook_secret is stored");
+            return;
+        }
+        Err(e) => {
+            log!("{}", e);
+            return;
+        }
+    };
+    *running = Some(tauri::async_runtime::spawn(serve(
+        app.clone(),
+        settings.clone(),
+        secret,
+    )));
+}
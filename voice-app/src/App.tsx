@@ -3,6 +3,12 @@ import { invoke } from "@tauri-apps/api/core";
 import { usePipelineStore } from "./stores/pipelineStore";
 import { connectWebSocket, disconnectWebSocket } from "./lib/ws";
 import type { LoopEvent } from "./lib/ws";
+import {
+  CONNECTION_ERRORS,
+  describeError,
+  errorMessage,
+  isCommandError,
+} from "./lib/errors";
 import { useKeyboardShortcuts } from "./hooks/useKeyboardShortcuts";
 import { useMicLevel } from "./hooks/useMicLevel";
 
@@ -28,16 +34,10 @@ function buildConnectionHelpMessage(serverUrl: string): string {
 }
 
 function formatRequestError(err: unknown, serverUrl: string): string {
-  const raw = String(err);
-  if (
-    raw.includes("HTTP request failed") ||
-    raw.includes("error sending request for url") ||
-    raw.includes("Connection refused") ||
-    raw.includes("timed out")
-  ) {
+  if (isCommandError(err) && CONNECTION_ERRORS.includes(err.code)) {
     return buildConnectionHelpMessage(serverUrl);
   }
-  return `Request failed: ${raw}`;
+  return `Request failed: ${describeError(err)}`;
 }
 
 async function checkBackendHealth(serverUrl: string): Promise<{ ok: boolean; detail: string }> {
@@ -185,16 +185,18 @@ function App() {
     if (status === "recording") {
       try {
         appendLog("[client] Stopping mic...");
-        const samples: number[] = await invoke("stop_mic");
+        // Raw little-endian i16 bytes, not JSON
+        const bytes: ArrayBuffer = await invoke("stop_mic");
+        const samples = Array.from(new Int16Array(bytes));
         appendLog(`[client] Captured ${samples.length} samples`);
 
         // Show preview instead of sending immediately
         setPendingSamples(samples);
         setStatus("previewing");
       } catch (err) {
-        appendLog(`[client] Error: ${err}`);
+        appendLog(`[client] Error: ${errorMessage(err)}`);
         setStatus("error");
-        addToast("error", `Recording failed: ${err}`);
+        addToast("error", `Recording failed: ${describeError(err)}`);
       }
     } else if (status === "idle" || status === "done" || status === "error") {
       try {
@@ -206,9 +208,9 @@ function App() {
         setStatus("recording");
         appendLog("[client] Recording...");
       } catch (err) {
-        appendLog(`[client] Error: ${err}`);
+        appendLog(`[client] Error: ${errorMessage(err)}`);
         setStatus("error");
-        addToast("error", `Failed to start recording: ${err}`);
+        addToast("error", `Failed to start recording: ${describeError(err)}`);
       }
     }
   }, [
@@ -239,9 +241,10 @@ function App() {
 
       setPendingSamples(null);
 
+      // The samples are still held in Rust from stop_mic
       const result = await invoke<Record<string, unknown>>(
-        "send_audio",
-        { samples, serverUrl },
+        "send_last_recording",
+        { serverUrl },
       );
 
       const endpointUsed =
@@ -325,7 +328,7 @@ function App() {
       setStatus("error");
       addToast("error", "Unexpected server response");
     } catch (err) {
-      appendLog(`[client] Error: ${err}`);
+      appendLog(`[client] Error: ${errorMessage(err)}`);
       setStatus("error");
       addToast("error", formatRequestError(err, serverUrl));
     }
@@ -344,11 +347,17 @@ function App() {
     ensureBackendAvailable,
   ]);
 
-  const handleDiscardAudio = useCallback(() => {
+  const handleDiscardAudio = useCallback(async () => {
     setPendingSamples(null);
     setStatus("idle");
     appendLog("[client] Recording discarded");
     addToast("info", "Recording discarded");
+    try {
+      // Drop the Rust-side copy kept for send_last_recording
+      await invoke("discard_recording");
+    } catch (err) {
+      appendLog(`[client] Discard failed: ${err}`);
+    }
   }, [setPendingSamples, setStatus, appendLog, addToast]);
 
   const handleClarifySubmit = async (answer: string) => {
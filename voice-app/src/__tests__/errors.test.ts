@@ -0,0 +1,59 @@
+import { describe, it, expect } from "vitest";
+import { describeError, errorMessage, isCommandError } from "../lib/errors";
+
+describe("isCommandError", () => {
+  it("should recognize structured command errors", () => {
+    expect(
+      isCommandError({
+        code: "SERVER_UNREACHABLE",
+        message: "HTTP request failed",
+        retryable: true,
+      }),
+    ).toBe(true);
+  });
+
+  it("should reject plain strings and other objects", () => {
+    expect(isCommandError("Not recording")).toBe(false);
+    expect(isCommandError(null)).toBe(false);
+    expect(isCommandError({ message: "no code" })).toBe(false);
+  });
+});
+
+describe("errorMessage", () => {
+  it("should use the message of a structured error", () => {
+    expect(
+      errorMessage({
+        code: "MIC_NO_DEVICE",
+        message: "No input device available",
+        retryable: true,
+      }),
+    ).toBe("No input device available");
+  });
+
+  it("should pass plain string errors through", () => {
+    expect(errorMessage("Not recording")).toBe("Not recording");
+  });
+});
+
+describe("describeError", () => {
+  it("should show the localized message for a known code", () => {
+    expect(
+      describeError({
+        code: "SERVER_TIMEOUT",
+        message: "HTTP request timed out for http://x: operation timed out",
+        retryable: true,
+      }),
+    ).toBe("The server took too long to answer. Try again.");
+  });
+
+  it("should fall back to the raw message", () => {
+    expect(
+      describeError({
+        code: "INTERNAL",
+        message: "Failed to encode WAV",
+        retryable: false,
+      }),
+    ).toBe("Failed to encode WAV");
+    expect(describeError("Not recording")).toBe("Not recording");
+  });
+});
@@ -0,0 +1,42 @@
+import { describe, it, expect, vi } from "vitest";
+import { invoke } from "@tauri-apps/api/core";
+import { eventName, readPayload } from "../lib/events";
+
+vi.mock("@tauri-apps/api/core", () => ({
+  invoke: vi.fn(),
+}));
+
+describe("readPayload", () => {
+  it("should strip schema_version from versioned payloads", () => {
+    const payload = readPayload<{ rms: number }>("mic-level", {
+      schema_version: 1,
+      rms: 0.2,
+    });
+    expect(payload).toEqual({ rms: 0.2 });
+  });
+
+  it("should accept payloads sent before versioning", () => {
+    const warn = vi.spyOn(console, "warn").mockImplementation(() => {});
+    expect(readPayload("mic-level", { rms: 0.1 })).toEqual({ rms: 0.1 });
+    expect(warn).not.toHaveBeenCalled();
+    warn.mockRestore();
+  });
+
+  it("should warn once about a newer schema", () => {
+    const warn = vi.spyOn(console, "warn").mockImplementation(() => {});
+    readPayload("mic-level", { schema_version: 2, rms: 0.1 });
+    readPayload("mic-level", { schema_version: 2, rms: 0.1 });
+    expect(warn).toHaveBeenCalledTimes(1);
+    warn.mockRestore();
+  });
+});
+
+describe("eventName", () => {
+  it("should put the configured prefix in front, reading it once", async () => {
+    vi.mocked(invoke).mockResolvedValue({ events: { prefix: "voice:" } });
+    expect(await eventName("mic-level")).toBe("voice:mic-level");
+    expect(await eventName("mic-muted")).toBe("voice:mic-muted");
+    expect(invoke).toHaveBeenCalledTimes(1);
+    expect(invoke).toHaveBeenCalledWith("get_settings");
+  });
+});
@@ -1,10 +1,12 @@
 import { useEffect, useRef, useState } from "react";
+import { eventName, readPayload } from "../lib/events";
 
 const BUFFER_SIZE = 20;
 const IS_TAURI = "__TAURI_INTERNALS__" in window;
 
 /**
- * Listen to `mic-level` events from Tauri and maintain a rolling buffer of RMS values.
+ * Listen to `mic-level` events from Tauri (under `events.prefix`) and maintain a
+ * rolling buffer of RMS values.
  * Falls back to empty array in browser mode.
  */
 export function useMicLevel(active: boolean): number[] {
@@ -19,12 +21,23 @@ export function useMicLevel(active: boolean): number[] {
     }
 
     let unlisten: (() => void) | null = null;
+    let cancelled = false;
 
     // Dynamic import to avoid breaking browser-only dev
-    import("@tauri-apps/api/event").then(({ listen }) => {
-      listen<{ rms: number }>("mic-level", (event) => {
+    Promise.all([
+      import("@tauri-apps/api/event"),
+      eventName("mic-level"),
+    ]).then(([{ listen }, name]) => {
+      if (cancelled) {
+        return;
+      }
+      listen(name, (event) => {
+        const { rms } = readPayload<{ rms: number }>(
+          "mic-level",
+          event.payload,
+        );
         // Normalize RMS: typical speech is ~0.01-0.15, clamp to 0-1
-        const normalized = Math.min(event.payload.rms * 8, 1);
+        const normalized = Math.min(rms * 8, 1);
         const buf = bufferRef.current;
         buf.push(normalized);
         if (buf.length > BUFFER_SIZE) {
@@ -32,11 +45,16 @@ export function useMicLevel(active: boolean): number[] {
         }
         setLevels([...buf]);
       }).then((fn) => {
-        unlisten = fn;
+        if (cancelled) {
+          fn();
+        } else {
+          unlisten = fn;
+        }
       });
     });
 
     return () => {
+      cancelled = true;
       unlisten?.();
       bufferRef.current = [];
       setLevels([]);
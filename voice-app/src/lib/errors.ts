@@ -0,0 +1,78 @@
+/**
+ * `code` of a failed command; see `src-tauri/src/error.rs`. Codes are added,
+ * never renamed, so they are safe to key translations on.
+ */
+export type ErrorCode =
+  | "MIC_NO_DEVICE"
+  | "MIC_DEVICE_NOT_FOUND"
+  | "MIC_UNSUPPORTED"
+  | "MIC_FAILED"
+  | "INVALID_STATE"
+  | "CANCELLED"
+  | "SERVER_UNREACHABLE"
+  | "SERVER_TIMEOUT"
+  | "SERVER_UNAVAILABLE"
+  | "SERVER_REJECTED"
+  | "AUTH_EXPIRED"
+  | "KEYCHAIN_UNAVAILABLE"
+  | "CONFIG_INVALID"
+  | "PERMISSION_DENIED"
+  | "INTERNAL";
+
+/** How every command rejects. */
+export interface CommandError {
+  code: ErrorCode;
+  /** The backend's English message, for logs. */
+  message: string;
+  /** Trying the same request again may succeed. */
+  retryable: boolean;
+}
+
+/**
+ * What to tell the user for each code. The one place to translate; codes
+ * without an actionable message (`INTERNAL`) show the backend's message.
+ */
+export const ERROR_MESSAGES: Partial<Record<ErrorCode, string>> = {
+  MIC_NO_DEVICE: "No microphone found. Connect one and try again.",
+  MIC_DEVICE_NOT_FOUND:
+    "The selected microphone is gone. Pick another in Settings.",
+  MIC_UNSUPPORTED: "This microphone can't be recorded from.",
+  MIC_FAILED: "The microphone stopped working. Try again.",
+  INVALID_STATE: "Not possible right now.",
+  CANCELLED: "Cancelled.",
+  SERVER_UNREACHABLE: "Can't reach the server. Check the Server URL.",
+  SERVER_TIMEOUT: "The server took too long to answer. Try again.",
+  SERVER_UNAVAILABLE: "The server is having trouble. Try again shortly.",
+  SERVER_REJECTED: "The server refused the request.",
+  AUTH_EXPIRED: "Your session has expired. Sign in again.",
+  KEYCHAIN_UNAVAILABLE: "Couldn't access the system keychain.",
+  CONFIG_INVALID: "A setting is missing or invalid. Check Settings.",
+  PERMISSION_DENIED:
+    "The system denied access the app needs. Allow it and try again.",
+};
+
+/** Codes meaning the server couldn't be reached or didn't answer. */
+export const CONNECTION_ERRORS: readonly ErrorCode[] = [
+  "SERVER_UNREACHABLE",
+  "SERVER_TIMEOUT",
+  "SERVER_UNAVAILABLE",
+];
+
+export function isCommandError(err: unknown): err is CommandError {
+  return (
+    typeof err === "object" &&
+    err !== null &&
+    typeof (err as CommandError).code === "string" &&
+    typeof (err as CommandError).message === "string"
+  );
+}
+
+/** The raw message of any command rejection, structured or not. */
+export function errorMessage(err: unknown): string {
+  return isCommandError(err) ? err.message : String(err);
+}
+
+/** The user-facing message for a rejection: its code's, else the raw one. */
+export function describeError(err: unknown): string {
+  return (isCommandError(err) && ERROR_MESSAGES[err.code]) || errorMessage(err);
+}
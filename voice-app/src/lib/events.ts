@@ -0,0 +1,47 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/**
+ * Schema versions of the backend events this frontend understands. Payloads
+ * carry `schema_version`; one sent before versioning has none and reads as
+ * version 0, whose fields are a subset of version 1.
+ */
+export const EVENT_SCHEMAS: Record<string, number> = {
+  "mic-level": 1,
+};
+
+const warned = new Set<string>();
+
+let prefix: Promise<string> | null = null;
+
+/**
+ * `name` as the backend emits it, with `events.prefix` from the settings in
+ * front. The prefix is read once; changing it takes effect after a reload.
+ */
+export function eventName(name: string): Promise<string> {
+  prefix ??= invoke<{ events?: { prefix?: string } }>("get_settings")
+    .then((settings) => settings.events?.prefix ?? "")
+    .catch(() => {
+      prefix = null;
+      return "";
+    });
+  return prefix.then((p) => p + name);
+}
+
+/**
+ * Compatibility shim for event payloads: strips `schema_version` and warns
+ * once per event when the backend sends a newer schema than this frontend
+ * knows, instead of letting renamed fields read as `undefined` unnoticed.
+ */
+export function readPayload<T>(name: string, payload: unknown): T {
+  const { schema_version: version = 0, ...fields } = (payload ?? {}) as {
+    schema_version?: number;
+  };
+  const known = EVENT_SCHEMAS[name];
+  if (known !== undefined && version > known && !warned.has(name)) {
+    warned.add(name);
+    console.warn(
+      `Event "${name}" has schema ${version}, this frontend knows ${known}`,
+    );
+  }
+  return fields as T;
+}
@@ -0,0 +1,41 @@
+package dev.agentic.devops.voice.capture
+
+import android.app.Activity
+import android.content.Intent
+import androidx.core.content.ContextCompat
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.Plugin
+
+@InvokeArg
+class StartArgs {
+    var title: String = "Recording"
+    var text: String = ""
+}
+
+/** Starts and stops [CaptureService]; called from Rust, not the webview. */
+@TauriPlugin
+class BackgroundCapturePlugin(private val activity: Activity) : Plugin(activity) {
+    @Command
+    fun start(invoke: Invoke) {
+        val args = invoke.parseArgs(StartArgs::class.java)
+        val intent = Intent(activity, CaptureService::class.java)
+            .putExtra(CaptureService.EXTRA_TITLE, args.title)
+            .putExtra(CaptureService.EXTRA_TEXT, args.text)
+        try {
+            ContextCompat.startForegroundService(activity, intent)
+            invoke.resolve()
+        } catch (e: Exception) {
+            // Android 12+ refuses to start one from the background.
+            invoke.reject(e.message ?: "Foreground service not allowed")
+        }
+    }
+
+    @Command
+    fun stop(invoke: Invoke) {
+        activity.stopService(Intent(activity, CaptureService::class.java))
+        invoke.resolve()
+    }
+}
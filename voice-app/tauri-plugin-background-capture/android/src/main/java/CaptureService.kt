@@ -0,0 +1,69 @@
+package dev.agentic.devops.voice.capture
+
+import android.app.NotificationChannel
+import android.app.NotificationManager
+import android.app.PendingIntent
+import android.app.Service
+import android.content.Intent
+import android.content.pm.ServiceInfo
+import android.os.Build
+import android.os.IBinder
+import androidx.core.app.NotificationCompat
+import androidx.core.app.ServiceCompat
+
+/**
+ * Foreground service holding the microphone while the app is in the
+ * background. It does no work itself: capture keeps running in the Rust
+ * process, which Android would otherwise cut off from the mic (and soon
+ * kill) once no activity is visible. Starting it again updates the
+ * notification.
+ */
+class CaptureService : Service() {
+    companion object {
+        const val EXTRA_TITLE = "title"
+        const val EXTRA_TEXT = "text"
+        private const val CHANNEL_ID = "capture"
+        private const val NOTIFICATION_ID = 1
+    }
+
+    override fun onBind(intent: Intent?): IBinder? = null
+
+    override fun onStartCommand(intent: Intent?, flags: Int, startId: Int): Int {
+        createChannel()
+        val launch = packageManager.getLaunchIntentForPackage(packageName)
+        val open = launch?.let {
+            PendingIntent.getActivity(
+                this,
+                0,
+                it,
+                PendingIntent.FLAG_IMMUTABLE or PendingIntent.FLAG_UPDATE_CURRENT
+            )
+        }
+        val notification = NotificationCompat.Builder(this, CHANNEL_ID)
+            .setContentTitle(intent?.getStringExtra(EXTRA_TITLE) ?: "Recording")
+            .setContentText(intent?.getStringExtra(EXTRA_TEXT) ?: "")
+            .setSmallIcon(android.R.drawable.ic_btn_speak_now)
+            .setContentIntent(open)
+            .setOngoing(true)
+            .setCategory(NotificationCompat.CATEGORY_SERVICE)
+            .build()
+        val type = if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.R) {
+            ServiceInfo.FOREGROUND_SERVICE_TYPE_MICROPHONE
+        } else {
+            0
+        }
+        ServiceCompat.startForeground(this, NOTIFICATION_ID, notification, type)
+        // Capture died with the process; nothing to resume.
+        return START_NOT_STICKY
+    }
+
+    private fun createChannel() {
+        if (Build.VERSION.SDK_INT < Build.VERSION_CODES.O) return
+        val channel = NotificationChannel(
+            CHANNEL_ID,
+            "Recording",
+            NotificationManager.IMPORTANCE_LOW
+        )
+        getSystemService(NotificationManager::class.java).createNotificationChannel(channel)
+    }
+}
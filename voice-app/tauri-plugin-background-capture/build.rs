@@ -0,0 +1,8 @@
+// Nothing is exposed to the webview; the app drives the plugin from Rust.
+const COMMANDS: &[&str] = &[];
+
+fn main() {
+    tauri_plugin::Builder::new(COMMANDS)
+        .android_path("android")
+        .build();
+}
@@ -0,0 +1,79 @@
+//! Keeps capture running while the app is in the background on Android: a
+//! foreground service with an ongoing notification (`CaptureService.kt`),
+//! started when recording begins and stopped when the app is idle again.
+//! On other platforms the calls do nothing.
+
+use tauri::plugin::{Builder, TauriPlugin};
+use tauri::{Manager, Runtime};
+
+#[cfg(target_os = "android")]
+const PLUGIN_IDENTIFIER: &str = "dev.agentic.devops.voice.capture";
+
+pub struct BackgroundCapture<R: Runtime> {
+    #[cfg(target_os = "android")]
+    handle: tauri::plugin::PluginHandle<R>,
+    #[cfg(not(target_os = "android"))]
+    _runtime: std::marker::PhantomData<fn() -> R>,
+}
+
+#[cfg(target_os = "android")]
+#[derive(serde::Serialize)]
+struct StartArgs<'a> {
+    title: &'a str,
+    text: &'a str,
+}
+
+impl<R: Runtime> BackgroundCapture<R> {
+    /// Start the service, or update its notification if it is running.
+    pub fn start(&self, title: &str, text: &str) -> Result<(), String> {
+        #[cfg(target_os = "android")]
+        return self
+            .handle
+            .run_mobile_plugin::<()>("start", StartArgs { title, text })
+            .map_err(|e| format!("Failed to start the capture service: {}", e));
+        #[cfg(not(target_os = "android"))]
+        {
+            let _ = (title, text);
+            Ok(())
+        }
+    }
+
+    pub fn stop(&self) -> Result<(), String> {
+        #[cfg(target_os = "android")]
+        return self
+            .handle
+            .run_mobile_plugin::<()>("stop", ())
+            .map_err(|e| format!("Failed to stop the capture service: {}", e));
+        #[cfg(not(target_os = "android"))]
+        Ok(())
+    }
+}
+
+/// `app.background_capture()` on anything with an app handle.
+pub trait BackgroundCaptureExt<R: Runtime> {
+    fn background_capture(&self) -> &BackgroundCapture<R>;
+}
+
+impl<R: Runtime, T: Manager<R>> BackgroundCaptureExt<R> for T {
+    fn background_capture(&self) -> &BackgroundCapture<R> {
+        self.state::<BackgroundCapture<R>>().inner()
+    }
+}
+
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("background-capture")
+        .setup(|app, _api| {
+            #[cfg(target_os = "android")]
+            let capture = BackgroundCapture {
+                handle: _api
+                    .register_android_plugin(PLUGIN_IDENTIFIER, "BackgroundCapturePlugin")?,
+            };
+            #[cfg(not(target_os = "android"))]
+            let capture = BackgroundCapture::<R> {
+                _runtime: std::marker::PhantomData,
+            };
+            app.manage(capture);
+            Ok(())
+        })
+        .build()
+}
@@ -0,0 +1,77 @@
+//! Capture-path throughput. Sizes mirror what the audio callback and
+//! `Capture::stop` see in practice: a 10ms stereo callback at 48 kHz, and a
+//! full minute of mono audio at the end of a recording.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use voice_core::dsp::{
+    interleaved_f32_to_mono, interleaved_i16_to_mono, interleaved_u16_to_mono, resample_i16,
+    resample_linear_i16,
+};
+use voice_core::wav::encode_wav;
+
+const CALLBACK_FRAMES: usize = 480; // 10ms at 48kHz
+const MINUTE_16K: usize = 16_000 * 60;
+
+/// Deterministic sawtooth so runs are comparable without pulling in a RNG.
+fn signal_i16(len: usize) -> Vec<i16> {
+    (0..len)
+        .map(|i| ((i * 37) % 65_536) as i32 as i16)
+        .collect()
+}
+
+fn downmix(c: &mut Criterion) {
+    let mut group = c.benchmark_group("downmix");
+    for channels in [1usize, 2] {
+        let len = CALLBACK_FRAMES * channels;
+        let i16_data = signal_i16(len);
+        let f32_data: Vec<f32> = i16_data
+            .iter()
+            .map(|&s| s as f32 / i16::MAX as f32)
+            .collect();
+        let u16_data: Vec<u16> = i16_data
+            .iter()
+            .map(|&s| (s as i32 + 32_768) as u16)
+            .collect();
+        group.throughput(Throughput::Elements(CALLBACK_FRAMES as u64));
+
+        group.bench_with_input(BenchmarkId::new("f32", channels), &f32_data, |b, data| {
+            b.iter(|| interleaved_f32_to_mono(black_box(data), channels))
+        });
+        group.bench_with_input(BenchmarkId::new("i16", channels), &i16_data, |b, data| {
+            b.iter(|| interleaved_i16_to_mono(black_box(data), channels))
+        });
+        group.bench_with_input(BenchmarkId::new("u16", channels), &u16_data, |b, data| {
+            b.iter(|| interleaved_u16_to_mono(black_box(data), channels))
+        });
+    }
+    group.finish();
+}
+
+fn resample(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resample_to_16k");
+    group.sample_size(20);
+    for rate in [44_100u32, 48_000] {
+        let input = signal_i16(rate as usize * 60);
+        group.throughput(Throughput::Elements(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("linear", rate), &input, |b, input| {
+            b.iter(|| resample_linear_i16(black_box(input), rate, 16_000))
+        });
+        // Takes the FIR decimator at 48 kHz, linear at 44.1 kHz.
+        group.bench_with_input(BenchmarkId::new("auto", rate), &input, |b, input| {
+            b.iter(|| resample_i16(black_box(input), rate, 16_000))
+        });
+    }
+    group.finish();
+}
+
+fn wav(c: &mut Criterion) {
+    let samples = signal_i16(MINUTE_16K);
+    let mut group = c.benchmark_group("encode_wav");
+    group.sample_size(20);
+    group.throughput(Throughput::Bytes((MINUTE_16K * 2) as u64));
+    group.bench_function("one_minute", |b| b.iter(|| encode_wav(black_box(&samples))));
+    group.finish();
+}
+
+criterion_group!(benches, downmix, resample, wav);
+criterion_main!(benches);
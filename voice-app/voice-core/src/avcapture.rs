@@ -0,0 +1,175 @@
+//! AVFoundation capture on macOS, as an alternative to cpal's CoreAudio
+//! path for the devices and OS versions where that misbehaves (streams that
+//! open but never call back, aggregate devices that refuse the default
+//! config). An `AVCaptureSession` with an audio data output delivers
+//! buffers on its own dispatch queue; the output converts them to 16 kHz
+//! mono float, so they go straight into the take without a resample.
+
+use objc2::rc::Retained;
+use objc2::runtime::{AnyObject, NSObject, NSObjectProtocol, ProtocolObject};
+use objc2::{define_class, msg_send, AllocAnyThread, DefinedClass};
+use objc2_av_foundation::{
+    AVCaptureAudioDataOutput, AVCaptureAudioDataOutputSampleBufferDelegate, AVCaptureConnection,
+    AVCaptureDevice, AVCaptureDeviceInput, AVCaptureOutput, AVCaptureSession, AVMediaTypeAudio,
+};
+use objc2_avf_audio::{
+    AVFormatIDKey, AVLinearPCMBitDepthKey, AVLinearPCMIsFloatKey, AVLinearPCMIsNonInterleaved,
+    AVNumberOfChannelsKey, AVSampleRateKey,
+};
+use objc2_core_media::CMSampleBuffer;
+use objc2_foundation::{NSDictionary, NSNumber, NSString};
+use parking_lot::Mutex;
+use std::ffi::c_void;
+use std::ptr::NonNull;
+
+use crate::capture::MicError;
+use crate::TARGET_SAMPLE_RATE;
+
+/// `kAudioFormatLinearPCM` (`'lpcm'`).
+const LINEAR_PCM: u32 = u32::from_be_bytes(*b"lpcm");
+
+/// Receives each buffer as mono floats at `TARGET_SAMPLE_RATE`, on the
+/// session's dispatch queue.
+pub type SampleCallback = Box<dyn FnMut(&[f32]) + Send>;
+
+define_class!(
+    #[unsafe(super(NSObject))]
+    #[name = "AgenticVoiceAudioOutputDelegate"]
+    #[ivars = Mutex<SampleCallback>]
+    struct Delegate;
+
+    unsafe impl NSObjectProtocol for Delegate {}
+
+    unsafe impl AVCaptureAudioDataOutputSampleBufferDelegate for Delegate {
+        #[unsafe(method(captureOutput:didOutputSampleBuffer:fromConnection:))]
+        fn did_output(
+            &self,
+            _output: &AVCaptureOutput,
+            buffer: &CMSampleBuffer,
+            _connection: &AVCaptureConnection,
+        ) {
+            if let Some(samples) = read_samples(buffer) {
+                (self.ivars().lock())(&samples);
+            }
+        }
+    }
+);
+
+impl Delegate {
+    fn new(callback: SampleCallback) -> Retained<Self> {
+        let this = Self::alloc().set_ivars(Mutex::new(callback));
+        unsafe { msg_send![super(this), init] }
+    }
+}
+
+/// The buffer's bytes as floats; the output was asked for 32-bit float
+/// interleaved mono, so no layout needs checking.
+fn read_samples(buffer: &CMSampleBuffer) -> Option<Vec<f32>> {
+    // SAFETY: the buffer is valid for the delegate call, and `samples` has
+    // room for the `len` bytes copied into it.
+    unsafe {
+        let block = buffer.data_buffer()?;
+        let len = block.data_length();
+        let mut samples = vec![0f32; len / size_of::<f32>()];
+        let destination = NonNull::new(samples.as_mut_ptr().cast::<c_void>())?;
+        let status = block.copy_data_bytes(0, samples.len() * size_of::<f32>(), destination);
+        (status == 0).then_some(samples)
+    }
+}
+
+/// The default audio capture device, or the one called `name`.
+fn find_device(name: Option<&str>) -> Result<Retained<AVCaptureDevice>, MicError> {
+    // SAFETY: the media type is an AVFoundation constant.
+    #[allow(deprecated)]
+    unsafe {
+        let audio =
+            AVMediaTypeAudio.ok_or_else(|| MicError::Failed("AVFoundation unavailable".into()))?;
+        match name {
+            None => AVCaptureDevice::defaultDeviceWithMediaType(audio).ok_or(MicError::NoDevice),
+            Some(name) => AVCaptureDevice::devicesWithMediaType(audio)
+                .iter()
+                .find(|device| device.localizedName().to_string() == name)
+                .ok_or_else(|| MicError::DeviceNotFound(name.to_string())),
+        }
+    }
+}
+
+/// `audioSettings` for the output: 16 kHz mono 32-bit float PCM.
+fn output_settings() -> Result<Retained<NSDictionary<NSString, AnyObject>>, String> {
+    // SAFETY: the keys are AVFAudio constants.
+    unsafe {
+        let keys = [
+            AVFormatIDKey,
+            AVSampleRateKey,
+            AVNumberOfChannelsKey,
+            AVLinearPCMBitDepthKey,
+            AVLinearPCMIsFloatKey,
+            AVLinearPCMIsNonInterleaved,
+        ]
+        .into_iter()
+        .collect::<Option<Vec<&NSString>>>()
+        .ok_or("AVFoundation unavailable")?;
+        let values = [
+            NSNumber::new_u32(LINEAR_PCM),
+            NSNumber::new_f64(TARGET_SAMPLE_RATE as f64),
+            NSNumber::new_u32(1),
+            NSNumber::new_u32(32),
+            NSNumber::new_bool(true),
+            NSNumber::new_bool(false),
+        ];
+        let values: Vec<&AnyObject> = values.iter().map(|v| v.as_ref()).collect();
+        Ok(NSDictionary::from_slices(&keys, &values))
+    }
+}
+
+/// A running capture session; dropping it stops the session.
+pub struct AvCapture {
+    session: Retained<AVCaptureSession>,
+    /// The output only holds the delegate weakly.
+    _delegate: Retained<Delegate>,
+}
+
+impl AvCapture {
+    /// Open `device` (the default when `None`) and start delivering its
+    /// audio to `callback`.
+    pub fn start(device: Option<&str>, callback: SampleCallback) -> Result<Self, MicError> {
+        let device = find_device(device)?;
+        // SAFETY: plain AVFoundation setup on objects owned here; the
+        // delegate outlives the session it is attached to.
+        unsafe {
+            let input = AVCaptureDeviceInput::deviceInputWithDevice_error(&device)
+                .map_err(|e| MicError::Failed(format!("Failed to open capture device: {}", e)))?;
+            let output = AVCaptureAudioDataOutput::new();
+            output.setAudioSettings(Some(&output_settings().map_err(MicError::Failed)?));
+
+            let session = AVCaptureSession::new();
+            if !session.canAddInput(&input) || !session.canAddOutput(&output) {
+                return Err(MicError::Failed("Failed to build capture session".into()));
+            }
+            session.addInput(&input);
+            session.addOutput(&output);
+
+            let delegate = Delegate::new(callback);
+            let queue = dispatch2::DispatchQueue::new("agentic-devops-voice.capture", None);
+            output.setSampleBufferDelegate_queue(
+                Some(ProtocolObject::from_ref(&*delegate)),
+                Some(&queue),
+            );
+            session.startRunning();
+            if !session.isRunning() {
+                return Err(MicError::Failed("Failed to start capture session".into()));
+            }
+            Ok(Self {
+                session,
+                _delegate: delegate,
+            })
+        }
+    }
+}
+
+impl Drop for AvCapture {
+    fn drop(&mut self) {
+        // SAFETY: stopping is synchronous; no buffer is delivered afterwards.
+        unsafe { self.session.stopRunning() }
+    }
+}
@@ -0,0 +1,1032 @@
+//! Microphone capture on cpal. A `Capture` records an `AudioSource` into a
+//! buffer at the device rate, hands back 16 kHz samples at stop, and
+//! reports levels, overruns and latency to an optional callback. Streams
+//! that stop delivering audio are rebuilt by a watchdog. A recording can be
+//! suspended (the stream closed, the take kept) while the OS lends the mic
+//! to something else, such as a phone call, and resumed into the same take.
+//! A standby stream can be kept open, gated, between recordings, so that
+//! starting one doesn't wait for the device.
+//!
+//! Each recording runs on its own thread, which owns the cpal stream (not
+//! `Send` on every backend) and doubles as the stall watchdog; `suspend` and
+//! `resume` message it, `stop` signals it and joins. Flags and counters
+//! shared with the audio callback are atomics. The remaining locks are
+//! `parking_lot` mutexes. `stream_thread` is held through `start` and
+//! `standby`, and others are only ever taken inside it, never the other
+//! way round; the callback never waits on one that a caller holds for more
+//! than a swap:
+//!
+//! - `take` is only touched by other threads while no stream is running
+//!   (before it opens, during a restart or resume after the old one is
+//!   dropped, and after `stop` has joined the stream thread), or while a
+//!   standby stream is gated; `start` resets it and lifts the gate under
+//!   its lock.
+//! - `chunk_tx` and `latency` are only swapped or copied by callers.
+//! - `events` is read with `try_lock`; an event is skipped rather than wait.
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{SampleFormat, StreamConfig};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc::{self as std_mpsc, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+#[cfg(target_os = "macos")]
+use crate::avcapture::AvCapture;
+#[cfg(target_os = "macos")]
+use crate::devices::SourceKind;
+use crate::devices::{self, AudioSource, Downmix};
+use crate::dsp::{
+    interleaved_f32_to_mono, interleaved_i16_to_mono, interleaved_i32_to_mono,
+    interleaved_u16_to_mono, resample_i16, select_channel, to_i16, weighted_mono, Dither,
+    LoudestChannel, Processor,
+};
+use crate::processing::{Preset, Processing};
+use crate::wav::WavEncoder;
+use crate::TARGET_SAMPLE_RATE;
+
+type ChunkSender = mpsc::Sender<Vec<i16>>;
+
+/// Why the microphone couldn't be opened or used. Shells map each kind to
+/// what they tell the user; the message is for logs.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum MicError {
+    /// No input device is connected.
+    #[error("No input device available")]
+    NoDevice,
+    /// The configured device is gone.
+    #[error("Audio device not found: {0}")]
+    DeviceNotFound(String),
+    /// The device or source can't be captured in this build or format.
+    #[error("{0}")]
+    Unsupported(String),
+    /// The OS refused access to the microphone.
+    #[error("{0}")]
+    PermissionDenied(String),
+    /// The app is set up so the OS won't grant access, e.g. a missing
+    /// usage description.
+    #[error("{0}")]
+    Misconfigured(String),
+    #[error("Already recording")]
+    AlreadyRecording,
+    #[error("Recording from another source")]
+    RecordingOtherSource,
+    #[error("Not recording")]
+    NotRecording,
+    /// The device failed to open, or failed while recording.
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// What a capture reports while it runs.
+#[derive(Clone, Copy, Debug)]
+pub enum CaptureEvent {
+    Started,
+    Stopped,
+    /// RMS of the last ~50ms, at most 20 times a second.
+    Level {
+        rms: f32,
+    },
+    /// Audio was dropped: `"stream"` when a chunk subscriber fell behind,
+    /// `"recording"` when the recording hit `MAX_RECORDING_SECS` and stopped
+    /// growing. Reported once per overrun, not per callback.
+    Overrun {
+        source: &'static str,
+    },
+    /// Smoothed capture latency, about once a second.
+    Latency(InputLatency),
+    /// The first audio of a recording arrived, `ms` after `start` was
+    /// called. Once per recording.
+    FirstSample {
+        ms: f32,
+    },
+    /// A stalled stream was rebuilt; `fallback` when the configured device
+    /// failed to reopen and the default was used.
+    Restarted {
+        fallback: bool,
+    },
+    /// The stream was closed by `suspend`; the take is kept.
+    Suspended,
+    /// A suspended recording is capturing again; `fallback` as for
+    /// `Restarted`.
+    Resumed {
+        fallback: bool,
+    },
+}
+
+/// Called from the audio thread; keep it cheap.
+pub type EventCallback = Arc<dyn Fn(CaptureEvent) + Send + Sync>;
+
+/// The recording so far, at the device rate.
+#[derive(Default)]
+struct Take {
+    samples: Vec<i16>,
+    /// WAV of `samples`, written alongside them. Only used when the device
+    /// already runs at 16 kHz; other rates are resampled at stop and encoded
+    /// at send time.
+    encoder: Option<WavEncoder>,
+}
+
+/// Sent to a recording's stream thread.
+enum StreamCommand {
+    /// Close the stream, keeping the take.
+    Suspend,
+    /// Reopen a suspended stream.
+    Resume,
+}
+
+/// The thread that owns the current recording's stream, or the standby
+/// stream waiting for one.
+struct StreamThread {
+    /// Dropped to stop the thread.
+    commands: std_mpsc::Sender<StreamCommand>,
+    handle: JoinHandle<()>,
+    source: AudioSource,
+    capture: CaptureSettings,
+    /// Opened by `standby`: kept open, gated, when the recording stops.
+    warm: bool,
+}
+
+impl StreamThread {
+    /// Stop the thread and wait for it, closing the stream.
+    fn close(self) {
+        drop(self.commands);
+        let _ = self.handle.join();
+    }
+}
+
+struct Shared {
+    recording: AtomicBool,
+    /// Set by `set_muted`; the callback records silence while it is.
+    muted: AtomicBool,
+    take: Mutex<Take>,
+    input_sample_rate: AtomicU32,
+    events: Mutex<Option<EventCallback>>,
+    chunk_tx: Mutex<Option<ChunkSender>>,
+    latency: Mutex<Option<InputLatency>>,
+    /// When the stream last delivered audio, in milliseconds since `epoch`,
+    /// for the stall watchdog.
+    last_callback: AtomicU64,
+    /// When `start` was called, in microseconds since `epoch`.
+    start_requested: AtomicU64,
+    /// Set by `start`, cleared by the recording's first callback.
+    awaiting_first: AtomicBool,
+    /// Microseconds from `start` to the first callback; 0 until then.
+    start_latency_us: AtomicU64,
+    epoch: Instant,
+    stream_thread: Mutex<Option<StreamThread>>,
+}
+
+impl Shared {
+    fn touch(&self) {
+        let now = self.epoch.elapsed().as_millis() as u64;
+        self.last_callback.store(now, Ordering::Relaxed);
+    }
+
+    fn micros(&self) -> u64 {
+        self.epoch.elapsed().as_micros() as u64
+    }
+
+    /// Note the recording's first callback, once.
+    fn first_sample(&self) {
+        if !self.awaiting_first.load(Ordering::Relaxed)
+            || !self.awaiting_first.swap(false, Ordering::Relaxed)
+        {
+            return;
+        }
+        let since_start = self
+            .micros()
+            .saturating_sub(self.start_requested.load(Ordering::Relaxed))
+            .max(1);
+        self.start_latency_us.store(since_start, Ordering::Relaxed);
+        self.emit(CaptureEvent::FirstSample {
+            ms: since_start as f32 / 1000.0,
+        });
+    }
+
+    fn stalled(&self) -> bool {
+        let now = self.epoch.elapsed().as_millis() as u64;
+        let last = self.last_callback.load(Ordering::Relaxed);
+        now.saturating_sub(last) >= STALL_TIMEOUT.as_millis() as u64
+    }
+
+    fn emit(&self, event: CaptureEvent) {
+        let callback = self.events.try_lock().and_then(|e| e.clone());
+        if let Some(callback) = callback {
+            callback(event);
+        }
+    }
+}
+
+/// Shared handle to one microphone. Clones refer to the same capture.
+#[derive(Clone)]
+pub struct Capture {
+    shared: Arc<Shared>,
+}
+
+impl Default for Capture {
+    fn default() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                recording: AtomicBool::new(false),
+                muted: AtomicBool::new(false),
+                take: Mutex::new(Take::default()),
+                input_sample_rate: AtomicU32::new(TARGET_SAMPLE_RATE),
+                events: Mutex::new(None),
+                chunk_tx: Mutex::new(None),
+                latency: Mutex::new(None),
+                last_callback: AtomicU64::new(0),
+                start_requested: AtomicU64::new(0),
+                awaiting_first: AtomicBool::new(false),
+                start_latency_us: AtomicU64::new(0),
+                epoch: Instant::now(),
+                stream_thread: Mutex::new(None),
+            }),
+        }
+    }
+}
+
+const RMS_WINDOW: usize = 800; // ~50ms at 16kHz
+const MIN_EMIT_INTERVAL_MS: u128 = 50; // Max 20 events/s
+const CHUNK_QUEUE_CAPACITY: usize = 256; // A few seconds of device callbacks
+const MAX_RECORDING_SECS: usize = 600;
+const LATENCY_EMIT_INTERVAL_MS: u128 = 1000;
+/// Weight of each new callback in the smoothed latency figures.
+const LATENCY_SMOOTHING: f32 = 0.1;
+/// A recording stream without callbacks for this long is considered wedged
+/// and rebuilt.
+const STALL_TIMEOUT: Duration = Duration::from_secs(2);
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How captured audio is processed, whatever the source.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureSettings {
+    /// TPDF dither when converting to 16-bit, for ASR models that trip over
+    /// quantization artifacts in quiet passages.
+    pub dither: bool,
+    /// Environment preset for gain, high-pass and noise suppression.
+    pub preset: Preset,
+    /// Used by the `custom` preset.
+    pub custom: Processing,
+    /// macOS: capture through AVFoundation instead of cpal's CoreAudio
+    /// path, for devices where the latter opens but never delivers audio.
+    /// Inputs that CoreAudio fails to open use AVFoundation regardless.
+    pub avfoundation: bool,
+}
+
+impl CaptureSettings {
+    pub fn processing(&self) -> Option<Processing> {
+        self.preset.processing(&self.custom)
+    }
+}
+
+/// How long audio takes from the ADC to our buffer, in milliseconds.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct InputLatency {
+    /// Capture timestamp to callback, as reported by the backend; 0 where
+    /// the backend has no capture timestamp.
+    pub device_ms: f32,
+    /// Duration of one callback's worth of audio, which the first sample
+    /// waits out before the callback runs.
+    pub buffer_ms: f32,
+    /// Time spent in our callback (downmix, buffering, level metering).
+    pub processing_ms: f32,
+    pub total_ms: f32,
+}
+
+impl InputLatency {
+    fn smooth(self, previous: Option<Self>) -> Self {
+        let Some(prev) = previous else {
+            return self;
+        };
+        let mix = |new: f32, old: f32| old + (new - old) * LATENCY_SMOOTHING;
+        let device_ms = mix(self.device_ms, prev.device_ms);
+        let buffer_ms = mix(self.buffer_ms, prev.buffer_ms);
+        let processing_ms = mix(self.processing_ms, prev.processing_ms);
+        Self {
+            device_ms,
+            buffer_ms,
+            processing_ms,
+            total_ms: device_ms + buffer_ms + processing_ms,
+        }
+    }
+}
+
+/// A finished take at 16 kHz.
+pub struct Recording {
+    pub samples: Vec<i16>,
+    /// The samples as WAV, when they were encoded during capture; sending
+    /// then skips the encoder.
+    pub wav: Option<Vec<u8>>,
+    /// The language it is transcribed in (`TranscribeOptions::language`),
+    /// when the take was started for one.
+    pub language: Option<String>,
+}
+
+impl From<Vec<i16>> for Recording {
+    fn from(samples: Vec<i16>) -> Self {
+        Self {
+            samples,
+            wav: None,
+            language: None,
+        }
+    }
+}
+
+/// Everything one stream's audio callback owns, moved into its closure.
+struct CaptureSink {
+    shared: Arc<Shared>,
+    /// Recording length cap in device-rate samples.
+    max_buffer: usize,
+    dither: Option<Dither>,
+    processor: Option<Processor>,
+    mix: Mix,
+    rms_buffer: Vec<f32>,
+    last_emit: Instant,
+    latency: Option<InputLatency>,
+    last_latency_emit: Instant,
+    /// Set while dropping, so each overrun is reported once, not per callback.
+    stream_overrun: bool,
+    recording_full: bool,
+}
+
+fn report_overrun(shared: &Shared, flag: &mut bool, source: &'static str) {
+    if std::mem::replace(flag, true) {
+        return;
+    }
+    eprintln!("Mic overrun ({}): dropping audio", source);
+    shared.emit(CaptureEvent::Overrun { source });
+}
+
+/// Called first in every audio callback: whether the stream is gated, i.e.
+/// a standby stream between recordings, and the callback has nothing to do.
+fn gated(sink: &mut CaptureSink) -> bool {
+    sink.shared.touch();
+    if sink.shared.recording.load(Ordering::Relaxed) {
+        sink.shared.first_sample();
+        return false;
+    }
+    // So the next recording on this stream reports its own overruns.
+    sink.stream_overrun = false;
+    sink.recording_full = false;
+    true
+}
+
+fn handle_mono_samples(mono: &[f32], sink: &mut CaptureSink) {
+    if mono.is_empty() {
+        return;
+    }
+    // Muted audio is kept as silence, so the take still runs in step with
+    // the clock and the stream and timers never notice.
+    let silence;
+    let mono = if sink.shared.muted.load(Ordering::Relaxed) {
+        silence = vec![0.0; mono.len()];
+        &silence[..]
+    } else {
+        mono
+    };
+
+    let samples: Vec<i16> = match sink.dither.as_mut() {
+        Some(dither) => mono.iter().map(|s| dither.to_i16(*s)).collect(),
+        None => mono.iter().map(|s| to_i16(*s)).collect(),
+    };
+    let full = {
+        let mut take = sink.shared.take.lock();
+        // Keep the beginning and stop growing; the user can still stop and
+        // send what was captured.
+        let room = sink.max_buffer.saturating_sub(take.samples.len());
+        let kept = &samples[..room.min(samples.len())];
+        take.samples.extend_from_slice(kept);
+        if let Some(encoder) = take.encoder.as_mut() {
+            encoder.push(kept);
+        }
+        room < samples.len()
+    };
+    if full {
+        report_overrun(&sink.shared, &mut sink.recording_full, "recording");
+    }
+    let sent = sink
+        .shared
+        .chunk_tx
+        .lock()
+        .as_ref()
+        .map(|tx| tx.try_send(samples));
+    match sent {
+        Some(Ok(())) => sink.stream_overrun = false,
+        Some(Err(TrySendError::Full(_))) => {
+            report_overrun(&sink.shared, &mut sink.stream_overrun, "stream");
+        }
+        Some(Err(TrySendError::Closed(_))) | None => {}
+    }
+
+    sink.rms_buffer.extend_from_slice(mono);
+    if sink.rms_buffer.len() >= RMS_WINDOW {
+        if sink.last_emit.elapsed().as_millis() >= MIN_EMIT_INTERVAL_MS {
+            let sum_sq: f32 = sink.rms_buffer.iter().map(|&s| s * s).sum();
+            let rms = (sum_sq / sink.rms_buffer.len() as f32).sqrt();
+
+            sink.shared.emit(CaptureEvent::Level { rms });
+            sink.last_emit = Instant::now();
+        }
+
+        sink.rms_buffer.clear();
+    }
+}
+
+fn record_latency(sink: &mut CaptureSink, sample: InputLatency) {
+    let smoothed = sample.smooth(sink.latency);
+    sink.latency = Some(smoothed);
+    *sink.shared.latency.lock() = Some(smoothed);
+
+    if sink.last_latency_emit.elapsed().as_millis() >= LATENCY_EMIT_INTERVAL_MS {
+        sink.last_latency_emit = Instant::now();
+        sink.shared.emit(CaptureEvent::Latency(smoothed));
+    }
+}
+
+/// How each callback becomes mono, resolved from the source's `channel`
+/// and `downmix`.
+enum Mix {
+    Channel(usize),
+    Average,
+    Weights(Vec<f32>),
+    Loudest(LoudestChannel),
+}
+
+impl Mix {
+    fn for_source(source: &AudioSource) -> Self {
+        match (source.channel, &source.downmix) {
+            (Some(ch), _) => Mix::Channel(ch.into()),
+            (None, Downmix::Average) => Mix::Average,
+            (None, Downmix::Weights(weights)) => Mix::Weights(weights.clone()),
+            (None, Downmix::Loudest) => Mix::Loudest(LoudestChannel::default()),
+        }
+    }
+}
+
+/// Mono floats from one callback. `convert` with one channel only converts
+/// samples, keeping them interleaved for the weighted and loudest mixes.
+fn to_mono<T: Copy>(
+    data: &[T],
+    channels: usize,
+    mix: &mut Mix,
+    convert: fn(&[T], usize) -> Vec<f32>,
+) -> Vec<f32> {
+    match mix {
+        Mix::Channel(ch) => convert(&select_channel(data, channels, *ch), 1),
+        Mix::Average => convert(data, channels),
+        Mix::Weights(weights) => weighted_mono(&convert(data, 1), channels, weights),
+        Mix::Loudest(loudest) => {
+            let samples = convert(data, 1);
+            let ch = loudest.pick(&samples, channels);
+            select_channel(&samples, channels, ch)
+        }
+    }
+}
+
+fn build_input<T: cpal::SizedSample + 'static>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    mut sink: CaptureSink,
+    convert: fn(&[T], usize) -> Vec<f32>,
+) -> Result<cpal::Stream, MicError> {
+    let channels = config.channels as usize;
+    let rate = config.sample_rate.0 as f32;
+    let err_fn = move |err| {
+        eprintln!("Audio stream error: {}", err);
+    };
+
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], info: &cpal::InputCallbackInfo| {
+                if gated(&mut sink) {
+                    return;
+                }
+                let started = Instant::now();
+                let mut mono = to_mono(data, channels, &mut sink.mix, convert);
+                if let Some(processor) = sink.processor.as_mut() {
+                    processor.process(&mut mono);
+                }
+                handle_mono_samples(&mono, &mut sink);
+
+                let timestamp = info.timestamp();
+                let device_ms = timestamp
+                    .callback
+                    .duration_since(&timestamp.capture)
+                    .map_or(0.0, |d| d.as_secs_f32() * 1000.0);
+                let buffer_ms = (data.len() / channels) as f32 * 1000.0 / rate;
+                let processing_ms = started.elapsed().as_secs_f32() * 1000.0;
+                record_latency(
+                    &mut sink,
+                    InputLatency {
+                        device_ms,
+                        buffer_ms,
+                        processing_ms,
+                        total_ms: device_ms + buffer_ms + processing_ms,
+                    },
+                );
+            },
+            err_fn,
+            None,
+        )
+        .map_err(|e| MicError::Failed(format!("Failed to build input stream: {}", e)))
+}
+
+fn build_stream(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    sample_format: SampleFormat,
+    sink: CaptureSink,
+) -> Result<cpal::Stream, MicError> {
+    let channels = config.channels as usize;
+    if channels == 0 {
+        return Err(MicError::Unsupported(
+            "Input device reports zero channels".into(),
+        ));
+    }
+    if let Mix::Channel(ch) = sink.mix {
+        if ch >= channels {
+            return Err(MicError::Unsupported(format!(
+                "Channel {} is out of range; the device has {} channels",
+                ch, channels
+            )));
+        }
+    }
+
+    match sample_format {
+        SampleFormat::F32 => build_input(device, config, sink, interleaved_f32_to_mono),
+        SampleFormat::I16 => build_input(device, config, sink, interleaved_i16_to_mono),
+        SampleFormat::U16 => build_input(device, config, sink, interleaved_u16_to_mono),
+        SampleFormat::I32 => build_input(device, config, sink, interleaved_i32_to_mono),
+        _ => Err(MicError::Unsupported(format!(
+            "Unsupported sample format: {:?}",
+            sample_format
+        ))),
+    }
+}
+
+/// An open input stream; dropping it closes the stream.
+#[allow(dead_code)] // Only held, never read.
+enum Stream {
+    Cpal(cpal::Stream),
+    #[cfg(target_os = "macos")]
+    AvFoundation(AvCapture),
+}
+
+/// Get the shared take ready for a stream at `rate` and build that stream's
+/// sink. When the take already holds audio at another rate (a restart that
+/// fell back to a different device), it is resampled to the new one first.
+fn prepare_sink(
+    shared: &Arc<Shared>,
+    source: &AudioSource,
+    capture: &CaptureSettings,
+    rate: u32,
+) -> CaptureSink {
+    {
+        let mut take = shared.take.lock();
+        let previous = shared.input_sample_rate.swap(rate, Ordering::Relaxed);
+        if take.samples.is_empty() {
+            take.encoder = (rate == TARGET_SAMPLE_RATE).then(WavEncoder::new);
+        } else if previous != rate {
+            take.samples = resample_i16(&take.samples, previous, rate);
+            take.encoder = None;
+        }
+    }
+    shared.touch();
+
+    CaptureSink {
+        shared: Arc::clone(shared),
+        max_buffer: rate as usize * MAX_RECORDING_SECS,
+        dither: capture.dither.then(Dither::default),
+        processor: capture.processing().map(|p| p.processor(rate as f32)),
+        mix: Mix::for_source(source),
+        rms_buffer: Vec::with_capacity(RMS_WINDOW),
+        last_emit: Instant::now(),
+        latency: None,
+        last_latency_emit: Instant::now(),
+        stream_overrun: false,
+        recording_full: false,
+    }
+}
+
+fn open_cpal_stream(
+    shared: &Arc<Shared>,
+    source: &AudioSource,
+    capture: &CaptureSettings,
+) -> Result<Stream, MicError> {
+    let (device, supported_config) = devices::open(source)?;
+    let sample_format = supported_config.sample_format();
+    let config: StreamConfig = supported_config.config();
+    let sink = prepare_sink(shared, source, capture, config.sample_rate.0);
+    let stream = build_stream(&device, &config, sample_format, sink)?;
+
+    stream
+        .play()
+        .map_err(|e| MicError::Failed(format!("Failed to start stream: {}", e)))?;
+    devices::connect(source, &device)?;
+    Ok(Stream::Cpal(stream))
+}
+
+/// Capture `source`'s device through AVFoundation. Its output is already
+/// 16 kHz mono, so a channel pick or downmix mode doesn't apply.
+#[cfg(target_os = "macos")]
+fn open_av_stream(
+    shared: &Arc<Shared>,
+    source: &AudioSource,
+    capture: &CaptureSettings,
+) -> Result<Stream, MicError> {
+    let mono_source = AudioSource {
+        channel: None,
+        downmix: Downmix::Average,
+        ..source.clone()
+    };
+    let mut sink = prepare_sink(shared, &mono_source, capture, TARGET_SAMPLE_RATE);
+    let callback = Box::new(move |mono: &[f32]| {
+        if gated(&mut sink) {
+            return;
+        }
+        let started = Instant::now();
+        let mut mono = mono.to_vec();
+        if let Some(processor) = sink.processor.as_mut() {
+            processor.process(&mut mono);
+        }
+        handle_mono_samples(&mono, &mut sink);
+
+        // No capture timestamps here; the buffer is most of the latency.
+        let buffer_ms = mono.len() as f32 * 1000.0 / TARGET_SAMPLE_RATE as f32;
+        let processing_ms = started.elapsed().as_secs_f32() * 1000.0;
+        record_latency(
+            &mut sink,
+            InputLatency {
+                device_ms: 0.0,
+                buffer_ms,
+                processing_ms,
+                total_ms: buffer_ms + processing_ms,
+            },
+        );
+    });
+    AvCapture::start(source.device.as_deref(), callback).map(Stream::AvFoundation)
+}
+
+/// Open `source` and start a stream that appends to the shared take. On
+/// macOS, `capture.avfoundation` or an input that cpal fails to open goes
+/// through AVFoundation instead.
+fn open_stream(
+    shared: &Arc<Shared>,
+    source: &AudioSource,
+    capture: &CaptureSettings,
+) -> Result<Stream, MicError> {
+    #[cfg(target_os = "macos")]
+    if capture.avfoundation && source.kind == SourceKind::Input {
+        return open_av_stream(shared, source, capture);
+    }
+    match open_cpal_stream(shared, source, capture) {
+        #[cfg(target_os = "macos")]
+        Err(e) if source.kind == SourceKind::Input => {
+            eprintln!("CoreAudio capture failed ({}); trying AVFoundation", e);
+            open_av_stream(shared, source, capture)
+        }
+        result => result,
+    }
+}
+
+/// Replace a wedged stream, on the same source if it reopens and on the
+/// default device otherwise. The new stream appends to the same take, so
+/// the recording continues where the old one stopped.
+fn restart(
+    shared: &Arc<Shared>,
+    source: &AudioSource,
+    capture: &CaptureSettings,
+) -> Result<(Stream, bool), MicError> {
+    match open_stream(shared, source, capture) {
+        Ok(stream) => Ok((stream, false)),
+        Err(e) if source.device.is_some() => {
+            eprintln!(
+                "Reopening {:?} failed ({}); using the default device",
+                source.device, e
+            );
+            let default = AudioSource {
+                device: None,
+                ..source.clone()
+            };
+            Ok((open_stream(shared, &default, capture)?, true))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Body of a recording's stream thread: open the stream, report whether
+/// that worked on `opened`, then follow `commands` and watch the stream for
+/// stalls until the sender is dropped.
+fn run_stream(
+    shared: Arc<Shared>,
+    source: AudioSource,
+    capture: CaptureSettings,
+    opened: std_mpsc::Sender<Result<(), MicError>>,
+    commands: std_mpsc::Receiver<StreamCommand>,
+) {
+    let mut stream = match open_stream(&shared, &source, &capture) {
+        Ok(stream) => Some(stream),
+        Err(e) => {
+            let _ = opened.send(Err(e));
+            return;
+        }
+    };
+    let _ = opened.send(Ok(()));
+
+    let mut suspended = false;
+    loop {
+        match commands.recv_timeout(WATCHDOG_INTERVAL) {
+            Ok(StreamCommand::Suspend) => {
+                if !std::mem::replace(&mut suspended, true) {
+                    drop(stream.take());
+                    shared.emit(CaptureEvent::Suspended);
+                }
+                continue;
+            }
+            Ok(StreamCommand::Resume) if suspended => {
+                match restart(&shared, &source, &capture) {
+                    Ok((resumed, fallback)) => {
+                        suspended = false;
+                        stream = Some(resumed);
+                        shared.emit(CaptureEvent::Resumed { fallback });
+                    }
+                    // Stays suspended; the caller may resume again.
+                    Err(e) => eprintln!("Mic resume failed: {}", e),
+                }
+            }
+            Ok(StreamCommand::Resume) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        if suspended || !shared.stalled() {
+            continue;
+        }
+        eprintln!("Mic stream stalled; restarting it");
+        drop(stream.take());
+        match restart(&shared, &source, &capture) {
+            Ok((restarted, fallback)) => {
+                stream = Some(restarted);
+                shared.emit(CaptureEvent::Restarted { fallback });
+            }
+            // Tried again after the next stall timeout.
+            Err(e) => {
+                eprintln!("Mic restart failed: {}", e);
+                shared.touch();
+            }
+        }
+    }
+}
+
+impl Capture {
+    /// Receive this capture's events from now on, replacing any previous
+    /// callback.
+    pub fn set_events(&self, callback: EventCallback) {
+        *self.shared.events.lock() = Some(callback);
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.shared.recording.load(Ordering::Relaxed)
+    }
+
+    /// Record silence instead of the input, without touching the stream:
+    /// a cough button for long recordings. Cleared by the next `start`.
+    pub fn set_muted(&self, muted: bool) {
+        self.shared.muted.store(muted, Ordering::Relaxed);
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.shared.muted.load(Ordering::Relaxed)
+    }
+
+    /// Sample rate of the open device, i.e. the rate of subscribed chunks.
+    pub fn input_sample_rate(&self) -> u32 {
+        self.shared.input_sample_rate.load(Ordering::Relaxed)
+    }
+
+    /// Time from `start` to the first audio of the current (or last)
+    /// recording, including opening the device unless a standby stream was
+    /// used; `None` until the first callback.
+    pub fn start_latency(&self) -> Option<Duration> {
+        match self.shared.start_latency_us.load(Ordering::Relaxed) {
+            0 => None,
+            us => Some(Duration::from_micros(us)),
+        }
+    }
+
+    /// Smoothed capture latency of the current (or last) recording; `None`
+    /// until the first callback.
+    pub fn latency(&self) -> Option<InputLatency> {
+        *self.shared.latency.lock()
+    }
+
+    /// Live copy of captured audio for streaming transports, at the device
+    /// rate. Replaces any previous subscriber; the channel closes on stop.
+    /// Bounded: chunks are dropped while the subscriber is behind.
+    pub fn subscribe_chunks(&self) -> mpsc::Receiver<Vec<i16>> {
+        let (tx, rx) = mpsc::channel(CHUNK_QUEUE_CAPACITY);
+        *self.shared.chunk_tx.lock() = Some(tx);
+        rx
+    }
+
+    /// Open `source` on a stream thread of its own; its callbacks stay
+    /// gated until `recording` is set.
+    fn spawn_stream(
+        &self,
+        source: &AudioSource,
+        capture: &CaptureSettings,
+        warm: bool,
+    ) -> Result<StreamThread, MicError> {
+        let (opened_tx, opened_rx) = std_mpsc::channel();
+        let (commands_tx, commands_rx) = std_mpsc::channel();
+        let (shared, thread_source, thread_capture) =
+            (Arc::clone(&self.shared), source.clone(), capture.clone());
+        let handle = std::thread::spawn(move || {
+            run_stream(
+                shared,
+                thread_source,
+                thread_capture,
+                opened_tx,
+                commands_rx,
+            )
+        });
+        let opened = opened_rx
+            .recv()
+            .unwrap_or_else(|_| Err(MicError::Failed("Capture thread exited".into())));
+        if let Err(e) = opened {
+            let _ = handle.join();
+            return Err(e);
+        }
+        Ok(StreamThread {
+            commands: commands_tx,
+            handle,
+            source: source.clone(),
+            capture: capture.clone(),
+            warm,
+        })
+    }
+
+    /// Open `source` and start buffering. A standby stream open on the same
+    /// source with the same settings is used as is, so buffering starts
+    /// with its next callback; one on anything else is closed first.
+    pub fn start(&self, source: &AudioSource, capture: &CaptureSettings) -> Result<(), MicError> {
+        let requested = self.shared.micros();
+        // Held until the thread is stored, so a concurrent `stop` waits for
+        // it instead of finding nothing to join.
+        let mut slot = self.shared.stream_thread.lock();
+        if self.is_recording() {
+            return Err(MicError::AlreadyRecording);
+        }
+        let warm = slot
+            .as_ref()
+            .is_some_and(|t| t.warm && t.source == *source && t.capture == *capture);
+        if !warm {
+            if let Some(standby) = slot.take() {
+                standby.close();
+            }
+        }
+        *self.shared.latency.lock() = None;
+        self.shared.muted.store(false, Ordering::Relaxed);
+        self.shared
+            .start_requested
+            .store(requested, Ordering::Relaxed);
+        self.shared.start_latency_us.store(0, Ordering::Relaxed);
+        self.shared.awaiting_first.store(true, Ordering::Relaxed);
+        {
+            // Under the take's lock, so a standby stream's callbacks see the
+            // fresh take and the flag together.
+            let mut take = self.shared.take.lock();
+            let rate = self.shared.input_sample_rate.load(Ordering::Relaxed);
+            *take = Take {
+                samples: Vec::new(),
+                encoder: (warm && rate == TARGET_SAMPLE_RATE).then(WavEncoder::new),
+            };
+            self.shared.recording.store(true, Ordering::Relaxed);
+        }
+
+        if !warm {
+            match self.spawn_stream(source, capture, false) {
+                Ok(thread) => *slot = Some(thread),
+                Err(e) => {
+                    self.shared.recording.store(false, Ordering::Relaxed);
+                    return Err(e);
+                }
+            }
+        }
+        drop(slot);
+
+        self.shared.emit(CaptureEvent::Started);
+        Ok(())
+    }
+
+    /// Open `source` ahead of the next recording and keep it open, gated,
+    /// across recordings, so a `start` with the same source and settings
+    /// skips the device open (hundreds of milliseconds on some drivers).
+    /// Idle callbacks only feed the stall watchdog. A recording already
+    /// running from that source keeps its stream open when it stops.
+    pub fn standby(&self, source: &AudioSource, capture: &CaptureSettings) -> Result<(), MicError> {
+        let mut slot = self.shared.stream_thread.lock();
+        if let Some(thread) = slot.as_mut() {
+            if thread.source == *source && thread.capture == *capture {
+                thread.warm = true;
+                return Ok(());
+            }
+        }
+        if self.is_recording() {
+            return Err(MicError::RecordingOtherSource);
+        }
+        if let Some(standby) = slot.take() {
+            standby.close();
+        }
+        *slot = Some(self.spawn_stream(source, capture, true)?);
+        Ok(())
+    }
+
+    /// Close the standby stream; one in use by a recording closes when the
+    /// recording stops.
+    pub fn close_standby(&self) {
+        let mut slot = self.shared.stream_thread.lock();
+        if self.is_recording() {
+            if let Some(thread) = slot.as_mut() {
+                thread.warm = false;
+            }
+        } else if let Some(standby) = slot.take() {
+            standby.close();
+        }
+    }
+
+    /// Whether a standby stream is open and waiting for a recording.
+    pub fn is_standby(&self) -> bool {
+        !self.is_recording()
+            && self
+                .shared
+                .stream_thread
+                .lock()
+                .as_ref()
+                .is_some_and(|t| t.warm)
+    }
+
+    fn command(&self, command: StreamCommand) -> Result<(), MicError> {
+        self.shared
+            .stream_thread
+            .lock()
+            .as_ref()
+            .ok_or(MicError::NotRecording)?
+            .commands
+            .send(command)
+            .map_err(|_| MicError::Failed("Capture thread exited".into()))
+    }
+
+    /// Close the stream without ending the recording, e.g. while a phone
+    /// call holds the mic. Reported as `Suspended`; the watchdog leaves a
+    /// suspended recording alone.
+    pub fn suspend(&self) -> Result<(), MicError> {
+        self.command(StreamCommand::Suspend)
+    }
+
+    /// Reopen a suspended recording's stream; new audio is appended to the
+    /// same take. Reported as `Resumed`, or logged if the device won't open.
+    pub fn resume(&self) -> Result<(), MicError> {
+        self.command(StreamCommand::Resume)
+    }
+
+    /// Stop the stream, unless it is the standby one, and return the take
+    /// resampled to 16 kHz.
+    pub fn stop(&self) -> Result<Recording, MicError> {
+        if !self.shared.recording.swap(false, Ordering::Relaxed) {
+            return Err(MicError::NotRecording);
+        }
+
+        // Taken out first so the stream thread is joined without the lock.
+        // A standby stream stays open, gated again.
+        let thread = self.shared.stream_thread.lock().take_if(|t| !t.warm);
+        if let Some(thread) = thread {
+            thread.close();
+        }
+        // Closing the chunk channel tells streaming consumers the take is over.
+        *self.shared.chunk_tx.lock() = None;
+        self.shared.emit(CaptureEvent::Stopped);
+
+        let input_rate = self.shared.input_sample_rate.load(Ordering::Relaxed);
+        let take = std::mem::take(&mut *self.shared.take.lock());
+
+        if input_rate == TARGET_SAMPLE_RATE {
+            return Ok(Recording {
+                wav: take
+                    .encoder
+                    .filter(|e| e.len() == take.samples.len())
+                    .map(WavEncoder::finish),
+                samples: take.samples,
+                language: None,
+            });
+        }
+
+        Ok(resample_i16(&take.samples, input_rate, TARGET_SAMPLE_RATE).into())
+    }
+}
@@ -0,0 +1,570 @@
+//! HTTP client for the pipeline server. Callers pass the bearer token and
+//! signing key; where they are stored is up to the shell.
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::time::sleep;
+#[cfg(target_arch = "wasm32")]
+use wasmtimer::tokio::sleep;
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::signing::Signer;
+
+/// How long a server stays known as lacking the pipeline route.
+const ROUTE_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Servers that answered 404/405 on `/api/pipeline/run/audio`, so uploads
+/// to them go straight to `/api/transcribe` instead of paying for the
+/// refused request every time. Forgotten after a TTL, in case the server
+/// gains the route.
+pub struct RouteCache {
+    ttl: Duration,
+    /// When the route was refused, and with which status.
+    no_pipeline: Mutex<HashMap<String, (Instant, u16)>>,
+}
+
+impl Default for RouteCache {
+    fn default() -> Self {
+        Self::new(ROUTE_CACHE_TTL)
+    }
+}
+
+impl RouteCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            no_pipeline: Mutex::default(),
+        }
+    }
+
+    /// The status the pipeline route was refused with, if that is still
+    /// remembered.
+    fn missing_pipeline(&self, base_url: &str) -> Option<u16> {
+        let mut servers = self.no_pipeline.lock().ok()?;
+        match servers.get(base_url) {
+            Some(&(seen, status)) if seen.elapsed() < self.ttl => Some(status),
+            Some(_) => {
+                servers.remove(base_url);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn set_missing_pipeline(&self, base_url: &str, status: Option<u16>) {
+        if let Ok(mut servers) = self.no_pipeline.lock() {
+            match status {
+                Some(status) => servers.insert(base_url.to_string(), (Instant::now(), status)),
+                None => servers.remove(base_url),
+            };
+        }
+    }
+}
+
+/// Racing a second request against a slow first one.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HedgeSettings {
+    pub enabled: bool,
+    /// How long the first request gets before the second starts.
+    pub delay_ms: u64,
+    /// Send the second request here instead of to this server's
+    /// `/api/transcribe`.
+    pub second_server: Option<String>,
+}
+
+impl Default for HedgeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay_ms: 1_500,
+            second_server: None,
+        }
+    }
+}
+
+/// Hints sent as form fields next to the audio. Servers that don't know a
+/// field ignore it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscribeOptions {
+    /// Alternative transcripts to ask for (`n_best`); none when 0.
+    pub alternatives: u32,
+    /// Words and phrases to favour: sent as `hotwords` for engines that
+    /// boost phrases, and as a glossary in the Whisper prompt.
+    pub vocabulary: Vec<String>,
+    /// What the user was just doing, oldest first: earlier transcripts,
+    /// the focused window, the git branch. Starts the Whisper prompt and is
+    /// sent as `context` for the pipeline's intent extraction, so "rerun
+    /// that workflow" can be resolved.
+    pub context: Vec<String>,
+    /// Spoken language as an ISO 639-1 code (`language`), e.g. `sv`; the
+    /// server detects it when `None`.
+    pub language: Option<String>,
+}
+
+impl TranscribeOptions {
+    /// `vocabulary` trimmed, without blanks and repeats.
+    fn words(&self) -> Vec<&str> {
+        let mut words: Vec<&str> = Vec::new();
+        for word in self.vocabulary.iter().map(|w| w.trim()) {
+            if !word.is_empty() && !words.contains(&word) {
+                words.push(word);
+            }
+        }
+        words
+    }
+
+    /// `context` as one block of text, one entry per line.
+    pub fn context_text(&self) -> Option<String> {
+        let lines: Vec<&str> = self
+            .context
+            .iter()
+            .map(|c| c.trim())
+            .filter(|c| !c.is_empty())
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// The prompt Whisper is primed with: the context, then the glossary.
+    /// Whisper keeps only the end of a long prompt, so the glossary goes
+    /// last.
+    pub fn prompt(&self) -> Option<String> {
+        let mut parts: Vec<String> = self.context_text().into_iter().collect();
+        let words = self.words();
+        if !words.is_empty() {
+            parts.push(format!("Glossary: {}.", words.join(", ")));
+        }
+        (!parts.is_empty()).then(|| parts.join("\n"))
+    }
+
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        if self.alternatives > 0 {
+            fields.push(("n_best", self.alternatives.to_string()));
+        }
+        let words = self.words();
+        if !words.is_empty() {
+            fields.push(("hotwords", words.join(", ")));
+        }
+        if let Some(prompt) = self.prompt() {
+            // `prompt` for OpenAI-style servers, `initial_prompt` for
+            // local Whisper.
+            fields.push(("prompt", prompt.clone()));
+            fields.push(("initial_prompt", prompt));
+        }
+        if let Some(context) = self.context_text() {
+            fields.push(("context", context));
+        }
+        if let Some(language) = self.language.as_deref().map(str::trim) {
+            if !language.is_empty() {
+                fields.push(("language", language.to_string()));
+            }
+        }
+        fields
+    }
+}
+
+/// Why an upload or pipeline call failed. The kind, not the message, tells
+/// callers whether to retry, refresh a token or count an outage.
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum UploadError {
+    /// No answer in time.
+    #[error("HTTP request timed out for {url}: {message}")]
+    Timeout { url: String, message: String },
+    /// No connection could be made, or it broke.
+    #[error("HTTP request failed for {url}: {message}")]
+    Unreachable { url: String, message: String },
+    /// The server answered with a failure status. `route` names the route
+    /// when the message should say which one.
+    #[error("Server error {status}{}: {body}", on_route(.route))]
+    Status {
+        status: StatusCode,
+        route: Option<&'static str>,
+        body: String,
+    },
+    /// The server's answer wasn't the JSON expected.
+    #[error("JSON parse error from {endpoint}: {message}")]
+    InvalidResponse { endpoint: String, message: String },
+    /// The request couldn't be built.
+    #[error("JSON encode error: {0}")]
+    Encode(String),
+    /// The shell couldn't load the bearer token or signing key it passes
+    /// in.
+    #[error("{0}")]
+    Credentials(String),
+}
+
+fn on_route(route: &Option<&str>) -> String {
+    route.map(|r| format!(" on {}", r)).unwrap_or_default()
+}
+
+impl UploadError {
+    /// The server refused the bearer token.
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self, Self::Status { status, .. } if *status == StatusCode::UNAUTHORIZED)
+    }
+
+    /// The server didn't answer in time.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Timeout { .. })
+    }
+
+    /// The server is unreachable or broken, as opposed to refusing this
+    /// particular request.
+    pub fn is_outage(&self) -> bool {
+        match self {
+            Self::Timeout { .. } | Self::Unreachable { .. } => true,
+            Self::Status { status, .. } => status.is_server_error(),
+            _ => false,
+        }
+    }
+
+    async fn status(response: reqwest::Response, route: Option<&'static str>) -> Self {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        Self::Status {
+            status,
+            route,
+            body,
+        }
+    }
+}
+
+/// The `audio` form field after the `options` fields, built by hand rather
+/// than with `reqwest::multipart` so the exact body bytes are known for
+/// signing.
+fn multipart_body(wav_bytes: &[u8], options: &TranscribeOptions) -> (String, Vec<u8>) {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let boundary = format!("voice-upload-{:032x}", nanos);
+    let mut body = String::new();
+    for (name, value) in options.fields() {
+        body.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+            boundary, name, value
+        ));
+    }
+    body.push_str(&format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"audio\"; filename=\"recording.wav\"\r\n\
+         Content-Type: audio/wav\r\n\r\n",
+        boundary
+    ));
+    let mut body = body.into_bytes();
+    body.extend_from_slice(wav_bytes);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
+
+/// POST `body` with the bearer token and signature headers when given.
+async fn post(
+    client: &reqwest::Client,
+    url: &str,
+    content_type: &str,
+    body: Vec<u8>,
+    api_key: Option<&str>,
+    signer: Option<&Signer>,
+) -> Result<reqwest::Response, UploadError> {
+    let mut request = client.post(url).header(CONTENT_TYPE, content_type);
+    if let Some(key) = api_key {
+        request = request.bearer_auth(key);
+    }
+    if let Some(signer) = signer {
+        request = signer.sign(request, &body);
+    }
+    request.body(body).send().await.map_err(|e| {
+        let (url, message) = (url.to_string(), e.to_string());
+        if e.is_timeout() {
+            UploadError::Timeout { url, message }
+        } else {
+            UploadError::Unreachable { url, message }
+        }
+    })
+}
+
+async fn post_audio(
+    client: &reqwest::Client,
+    url: &str,
+    wav_bytes: &[u8],
+    api_key: Option<&str>,
+    signer: Option<&Signer>,
+    options: &TranscribeOptions,
+) -> Result<reqwest::Response, UploadError> {
+    let (content_type, body) = multipart_body(wav_bytes, options);
+    post(client, url, &content_type, body, api_key, signer).await
+}
+
+async fn parse_json_response(
+    response: reqwest::Response,
+    endpoint_label: &str,
+) -> Result<serde_json::Value, UploadError> {
+    let mut result: serde_json::Value =
+        response
+            .json()
+            .await
+            .map_err(|e| UploadError::InvalidResponse {
+                endpoint: endpoint_label.to_string(),
+                message: e.to_string(),
+            })?;
+
+    if let Some(obj) = result.as_object_mut() {
+        obj.insert(
+            "_endpoint_used".to_string(),
+            serde_json::Value::String(endpoint_label.to_string()),
+        );
+    }
+
+    Ok(result)
+}
+
+/// Upload a WAV to the pipeline route, falling back to plain transcription
+/// on servers that don't expose it.
+pub async fn upload_wav_with_key(
+    client: &reqwest::Client,
+    routes: &RouteCache,
+    wav_bytes: &[u8],
+    server_url: &str,
+    api_key: Option<&str>,
+    signer: Option<&Signer>,
+) -> Result<serde_json::Value, UploadError> {
+    let options = TranscribeOptions::default();
+    upload_wav_with_options(
+        client, routes, wav_bytes, server_url, api_key, signer, &options,
+    )
+    .await
+}
+
+/// `upload_wav_with_key` with `options` sent next to the audio.
+pub async fn upload_wav_with_options(
+    client: &reqwest::Client,
+    routes: &RouteCache,
+    wav_bytes: &[u8],
+    server_url: &str,
+    api_key: Option<&str>,
+    signer: Option<&Signer>,
+    options: &TranscribeOptions,
+) -> Result<serde_json::Value, UploadError> {
+    let base_url = server_url.trim_end_matches('/');
+    let pipeline_url = format!("{}/api/pipeline/run/audio", base_url);
+    let transcribe_url = format!("{}/api/transcribe", base_url);
+
+    let cached = routes.missing_pipeline(base_url);
+    let fallback_status = match cached {
+        Some(status) => status,
+        None => {
+            let pipeline_response =
+                post_audio(client, &pipeline_url, wav_bytes, api_key, signer, options).await?;
+
+            if pipeline_response.status().is_success() {
+                routes.set_missing_pipeline(base_url, None);
+                return parse_json_response(pipeline_response, "pipeline_run_audio").await;
+            }
+
+            if pipeline_response.status() != StatusCode::NOT_FOUND
+                && pipeline_response.status() != StatusCode::METHOD_NOT_ALLOWED
+            {
+                return Err(UploadError::status(
+                    pipeline_response,
+                    Some("/api/pipeline/run/audio"),
+                )
+                .await);
+            }
+            let status = pipeline_response.status().as_u16();
+            routes.set_missing_pipeline(base_url, Some(status));
+            status
+        }
+    };
+
+    let transcribe_response =
+        post_audio(client, &transcribe_url, wav_bytes, api_key, signer, options).await?;
+
+    if !transcribe_response.status().is_success() {
+        return Err(UploadError::status(transcribe_response, None).await);
+    }
+
+    let mut result = parse_json_response(transcribe_response, "transcribe_fallback").await?;
+    // Why the fallback was used, so a broken pipeline route doesn't go
+    // unnoticed.
+    if let Some(obj) = result.as_object_mut() {
+        obj.insert(
+            "_fallback".to_string(),
+            serde_json::json!({ "status": fallback_status, "cached": cached.is_some() }),
+        );
+    }
+    Ok(result)
+}
+
+/// `upload_wav_with_key`, but if no answer has come within `hedge.delay_ms` a second
+/// request is started alongside: the same upload to `hedge.second_server`,
+/// or this server's `/api/transcribe`. The first success wins and the other
+/// request is dropped, though the server may already be working on it. The
+/// first request's error is reported if both fail.
+pub async fn upload_wav_hedged_with_key(
+    client: &reqwest::Client,
+    routes: &RouteCache,
+    wav_bytes: &[u8],
+    server_url: &str,
+    api_key: Option<&str>,
+    signer: Option<&Signer>,
+    hedge: &HedgeSettings,
+) -> Result<serde_json::Value, UploadError> {
+    let options = TranscribeOptions::default();
+    upload_wav_hedged_with_options(
+        client, routes, wav_bytes, server_url, api_key, signer, hedge, &options,
+    )
+    .await
+}
+
+/// `upload_wav_hedged_with_key` with `options` sent on both requests.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_wav_hedged_with_options(
+    client: &reqwest::Client,
+    routes: &RouteCache,
+    wav_bytes: &[u8],
+    server_url: &str,
+    api_key: Option<&str>,
+    signer: Option<&Signer>,
+    hedge: &HedgeSettings,
+    options: &TranscribeOptions,
+) -> Result<serde_json::Value, UploadError> {
+    let first = upload_wav_with_options(
+        client, routes, wav_bytes, server_url, api_key, signer, options,
+    );
+    if !hedge.enabled {
+        return first.await;
+    }
+
+    let second = async {
+        sleep(Duration::from_millis(hedge.delay_ms)).await;
+        match &hedge.second_server {
+            Some(second_server) => {
+                upload_wav_with_options(
+                    client,
+                    routes,
+                    wav_bytes,
+                    second_server,
+                    api_key,
+                    signer,
+                    options,
+                )
+                .await
+            }
+            None => {
+                transcribe_with_options(
+                    client,
+                    wav_bytes,
+                    server_url,
+                    api_key,
+                    signer,
+                    "transcribe_hedge",
+                    options,
+                )
+                .await
+            }
+        }
+    };
+    tokio::pin!(first, second);
+    tokio::select! {
+        result = &mut first => match result {
+            Ok(value) => Ok(value),
+            Err(e) => second.await.map_err(|_| e),
+        },
+        result = &mut second => match result {
+            Ok(value) => Ok(value),
+            Err(_) => first.await,
+        },
+    }
+}
+
+/// Plain transcription, without the pipeline route.
+pub async fn transcribe_with_key(
+    client: &reqwest::Client,
+    wav_bytes: &[u8],
+    server_url: &str,
+    api_key: Option<&str>,
+    signer: Option<&Signer>,
+    endpoint_label: &str,
+) -> Result<serde_json::Value, UploadError> {
+    let options = TranscribeOptions::default();
+    transcribe_with_options(
+        client,
+        wav_bytes,
+        server_url,
+        api_key,
+        signer,
+        endpoint_label,
+        &options,
+    )
+    .await
+}
+
+/// `transcribe_with_key` with `options` sent next to the audio.
+pub async fn transcribe_with_options(
+    client: &reqwest::Client,
+    wav_bytes: &[u8],
+    server_url: &str,
+    api_key: Option<&str>,
+    signer: Option<&Signer>,
+    endpoint_label: &str,
+    options: &TranscribeOptions,
+) -> Result<serde_json::Value, UploadError> {
+    let url = format!("{}/api/transcribe", server_url.trim_end_matches('/'));
+    let response = post_audio(client, &url, wav_bytes, api_key, signer, options).await?;
+
+    if !response.status().is_success() {
+        return Err(UploadError::status(response, None).await);
+    }
+    parse_json_response(response, endpoint_label).await
+}
+
+/// Run the server pipeline on text that is already transcribed. `None` when
+/// the server has no pipeline route, like the audio fallback in
+/// `upload_wav_with_key`.
+pub async fn run_pipeline_text_with_key(
+    client: &reqwest::Client,
+    text: &str,
+    server_url: &str,
+    api_key: Option<&str>,
+    signer: Option<&Signer>,
+) -> Result<Option<serde_json::Value>, UploadError> {
+    let options = TranscribeOptions::default();
+    run_pipeline_text_with_options(client, text, server_url, api_key, signer, &options).await
+}
+
+/// `run_pipeline_text_with_key` with the `options` context sent along as
+/// `context`.
+pub async fn run_pipeline_text_with_options(
+    client: &reqwest::Client,
+    text: &str,
+    server_url: &str,
+    api_key: Option<&str>,
+    signer: Option<&Signer>,
+    options: &TranscribeOptions,
+) -> Result<Option<serde_json::Value>, UploadError> {
+    let url = format!("{}/api/pipeline/run", server_url.trim_end_matches('/'));
+    let mut request = serde_json::json!({ "text": text });
+    if let Some(context) = options.context_text() {
+        request["context"] = serde_json::Value::String(context);
+    }
+    let body = serde_json::to_vec(&request).map_err(|e| UploadError::Encode(e.to_string()))?;
+    let response = post(client, &url, "application/json", body, api_key, signer).await?;
+
+    if response.status() == StatusCode::NOT_FOUND
+        || response.status() == StatusCode::METHOD_NOT_ALLOWED
+    {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(UploadError::status(response, Some("/api/pipeline/run")).await);
+    }
+    parse_json_response(response, "pipeline_run_text")
+        .await
+        .map(Some)
+}
@@ -0,0 +1,207 @@
+//! The record → encode → upload lifecycle for embedders without a UI shell.
+//! Same state machine as the desktop app: overlapping requests (a second
+//! start, stop during upload) are rejected instead of racing.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::capture::{Capture, CaptureSettings, Recording};
+use crate::client::{upload_wav_hedged_with_options, HedgeSettings, RouteCache, TranscribeOptions};
+use crate::devices::AudioSource;
+use crate::signing::Signer;
+use crate::wav::encode_wav_cancellable;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppState {
+    Idle,
+    Recording,
+    Encoding,
+    Uploading,
+    Done,
+    Error,
+}
+
+pub fn can_transition(from: AppState, to: AppState) -> bool {
+    use AppState::*;
+    matches!(
+        (from, to),
+        (Idle | Done | Error, Recording)
+            // Stopped; the audio is kept for preview or a later send
+            | (Recording, Idle)
+            | (Recording, Error)
+            | (Idle | Done | Error, Encoding)
+            | (Encoding, Uploading)
+            // Send cancelled by the user
+            | (Encoding | Uploading, Idle)
+            | (Encoding | Uploading, Error)
+            | (Uploading, Done)
+    )
+}
+
+/// Called with the new state, the previous one and the error that caused
+/// an `Error` state.
+pub type StateCallback = Arc<dyn Fn(AppState, AppState, Option<&str>) + Send + Sync>;
+
+/// Where recordings are sent.
+#[derive(Default)]
+pub struct Server {
+    pub url: String,
+    pub api_key: Option<String>,
+    pub signer: Option<Signer>,
+    pub hedge: HedgeSettings,
+    pub options: TranscribeOptions,
+}
+
+pub struct Controller {
+    capture: Capture,
+    state: Mutex<AppState>,
+    /// Set by `cancel_send`; checked by the encoder between chunks.
+    cancel_encode: Mutex<Arc<AtomicBool>>,
+    on_state_change: Mutex<Option<StateCallback>>,
+    http: reqwest::Client,
+    routes: RouteCache,
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        Self::new(Capture::default())
+    }
+}
+
+impl Controller {
+    pub fn new(capture: Capture) -> Self {
+        Self {
+            capture,
+            state: Mutex::new(AppState::Idle),
+            cancel_encode: Mutex::new(Arc::default()),
+            on_state_change: Mutex::new(None),
+            http: reqwest::Client::new(),
+            routes: RouteCache::default(),
+        }
+    }
+
+    /// The microphone, e.g. to subscribe to its events or chunks.
+    pub fn capture(&self) -> &Capture {
+        &self.capture
+    }
+
+    /// Receive state changes from now on, replacing any previous callback.
+    pub fn on_state_change(&self, callback: StateCallback) {
+        if let Ok(mut slot) = self.on_state_change.lock() {
+            *slot = Some(callback);
+        }
+    }
+
+    pub fn state(&self) -> AppState {
+        self.state.lock().map(|s| *s).unwrap_or(AppState::Error)
+    }
+
+    fn transition(&self, to: AppState, error: Option<&str>) -> Result<(), String> {
+        let previous = {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            if !can_transition(*state, to) {
+                return Err(format!("Cannot go from {:?} to {:?}", *state, to));
+            }
+            std::mem::replace(&mut *state, to)
+        };
+        let callback = self.on_state_change.lock().ok().and_then(|c| c.clone());
+        if let Some(callback) = callback {
+            callback(to, previous, error);
+        }
+        Ok(())
+    }
+
+    fn fail(&self, error: &str) {
+        let _ = self.transition(AppState::Error, Some(error));
+    }
+
+    pub fn start(&self, source: &AudioSource, capture: &CaptureSettings) -> Result<(), String> {
+        self.transition(AppState::Recording, None)?;
+        self.capture
+            .start(source, capture)
+            .map_err(|e| e.to_string())
+            .inspect_err(|e| self.fail(e))
+    }
+
+    pub fn stop(&self) -> Result<Recording, String> {
+        self.transition(AppState::Idle, None)?;
+        self.capture
+            .stop()
+            .map_err(|e| e.to_string())
+            .inspect_err(|e| self.fail(e))
+    }
+
+    /// Encode and upload a finished recording. Encoding runs on the tokio
+    /// blocking pool and is skipped when the recording was already encoded
+    /// during capture.
+    pub async fn send(
+        &self,
+        recording: Recording,
+        server: &Server,
+    ) -> Result<serde_json::Value, String> {
+        self.transition(AppState::Encoding, None)?;
+        let cancel = Arc::new(AtomicBool::new(false));
+        if let Ok(mut slot) = self.cancel_encode.lock() {
+            *slot = Arc::clone(&cancel);
+        }
+
+        let encoded = match recording.wav {
+            Some(wav_bytes) => Ok(wav_bytes),
+            None => {
+                let cancel = Arc::clone(&cancel);
+                tokio::task::spawn_blocking(move || {
+                    encode_wav_cancellable(&recording.samples, &cancel)
+                })
+                .await
+                .map_err(|e| format!("Encoder task failed: {}", e))
+                .and_then(|r| r)
+            }
+        };
+        let result = match encoded {
+            Ok(wav_bytes) => {
+                self.transition(AppState::Uploading, None)?;
+                upload_wav_hedged_with_options(
+                    &self.http,
+                    &self.routes,
+                    &wav_bytes,
+                    &server.url,
+                    server.api_key.as_deref(),
+                    server.signer.as_ref(),
+                    &server.hedge,
+                    &server.options,
+                )
+                .await
+                .map_err(|e| e.to_string())
+            }
+            Err(e) => Err(e),
+        };
+
+        match &result {
+            Ok(_) => self.transition(AppState::Done, None)?,
+            Err(_) if cancel.load(Ordering::Relaxed) => self.transition(AppState::Idle, None)?,
+            Err(e) => self.fail(e),
+        }
+        result
+    }
+
+    /// Stop recording and send in one go, for push-to-talk.
+    pub async fn stop_and_send(&self, server: &Server) -> Result<serde_json::Value, String> {
+        let recording = self.stop()?;
+        self.send(recording, server).await
+    }
+
+    /// Abort a send that is still encoding. Uploads already on the wire are
+    /// not affected.
+    pub fn cancel_send(&self) -> Result<(), String> {
+        if self.state() != AppState::Encoding {
+            return Err("Nothing is being encoded".into());
+        }
+        self.cancel_encode
+            .lock()
+            .map_err(|e| e.to_string())?
+            .store(true, Ordering::Relaxed);
+        Ok(())
+    }
+}
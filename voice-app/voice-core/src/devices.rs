@@ -0,0 +1,475 @@
+//! Audio source enumeration and resolution. On Windows, output devices are
+//! also offered as loopback sources: WASAPI captures whatever they play,
+//! which is the main way to transcribe a call. With the `jack` feature on
+//! Linux, JACK output ports are sources too: the app opens its own JACK
+//! client and wires the chosen port into it. With the `asio` feature on
+//! Windows, ASIO drivers are listed alongside WDM devices for interfaces
+//! whose WDM path adds too much latency.
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::{Deserialize, Serialize};
+
+use crate::capture::MicError;
+use crate::TARGET_SAMPLE_RATE;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    #[default]
+    Input,
+    /// A render device recorded as an input (Windows only).
+    Loopback,
+    /// A JACK port; `device` is its full name, e.g. `system:capture_1`.
+    Jack,
+    /// An ASIO driver (Windows only); `device` is the driver name.
+    Asio,
+}
+
+/// How the channels of a multi-channel source become mono.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Downmix {
+    /// Equal-weight average of all channels.
+    #[default]
+    Average,
+    /// A gain per channel, e.g. `[1.0, 0.0]` for the left channel only or
+    /// `[1.0, 1.0]` to sum without halving. Missing channels are dropped.
+    Weights(Vec<f32>),
+    /// Follow whichever channel is loudest, for a mic that may be on any
+    /// input while the rest are silent.
+    Loudest,
+}
+
+/// Name patterns of virtual capture devices that record nothing useful
+/// from a mic: mixes of the system output and PulseAudio/PipeWire monitors.
+const HIDDEN_BY_DEFAULT: [&str; 6] = [
+    "stereo mix",
+    "what u hear",
+    "wave out mix",
+    "monitor of",
+    ".monitor",
+    "loopback",
+];
+
+/// Input devices left out of listings and never picked as the default, by
+/// case-insensitive substring of their name. A device named explicitly in
+/// `device` is still opened.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DeviceFilter(pub Vec<String>);
+
+impl Default for DeviceFilter {
+    fn default() -> Self {
+        Self(HIDDEN_BY_DEFAULT.iter().map(|p| p.to_string()).collect())
+    }
+}
+
+impl DeviceFilter {
+    pub fn hides(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.0.iter().any(|p| name.contains(&p.to_lowercase()))
+    }
+}
+
+/// What to record from. `device: None` means the system default.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSource {
+    /// cpal host for `Input` sources, e.g. "ALSA" or "JACK"; `None` uses the
+    /// platform default.
+    pub host: Option<String>,
+    pub kind: SourceKind,
+    pub device: Option<String>,
+    /// Record only this 0-based channel instead of downmixing all of them;
+    /// multi-input interfaces usually carry the mic on a single one.
+    pub channel: Option<u16>,
+    /// How to combine the channels when `channel` is not set.
+    pub downmix: Downmix,
+    /// Devices skipped in listings and when picking the default.
+    pub hidden: DeviceFilter,
+    /// Open an input at this rate instead of its default config; capture
+    /// resamples to 16 kHz either way.
+    pub sample_rate: Option<u32>,
+    /// Open an input with this many channels instead of its default config.
+    pub channels: Option<u16>,
+}
+
+#[derive(Serialize)]
+pub struct SourceInfo {
+    kind: SourceKind,
+    name: String,
+    is_default: bool,
+    /// Channel count of the default config, for picking `channel`.
+    channels: Option<u16>,
+}
+
+/// PulseAudio and PipeWire have no cpal host of their own; they show up as
+/// the `pulse`/`pipewire` devices of the ALSA host.
+#[derive(Serialize)]
+pub struct HostInfo {
+    name: &'static str,
+    is_default: bool,
+}
+
+fn host(name: Option<&str>) -> Result<cpal::Host, MicError> {
+    let Some(name) = name else {
+        return Ok(cpal::default_host());
+    };
+    let id = cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| MicError::Unsupported(format!("Audio host not available: {}", name)))?;
+    cpal::host_from_id(id)
+        .map_err(|e| MicError::Failed(format!("Failed to open audio host {}: {}", name, e)))
+}
+
+pub fn list_hosts() -> Vec<HostInfo> {
+    let default = cpal::default_host().id();
+    cpal::available_hosts()
+        .into_iter()
+        .map(|id| HostInfo {
+            name: id.name(),
+            is_default: id == default,
+        })
+        .collect()
+}
+
+fn describe(
+    devices: impl Iterator<Item = cpal::Device>,
+    default: Option<cpal::Device>,
+    kind: SourceKind,
+    hidden: &DeviceFilter,
+) -> Vec<SourceInfo> {
+    let default_name = default.and_then(|d| d.name().ok());
+    devices
+        .filter_map(|d| {
+            let name = d.name().ok()?;
+            // Render devices are listed for loopback on purpose.
+            if kind != SourceKind::Loopback && hidden.hides(&name) {
+                return None;
+            }
+            let config = match kind {
+                SourceKind::Loopback => d.default_output_config(),
+                _ => d.default_input_config(),
+            };
+            Some(SourceInfo {
+                kind,
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                channels: config.ok().map(|c| c.channels()),
+                name,
+            })
+        })
+        .collect()
+}
+
+pub fn list_sources(
+    host_name: Option<&str>,
+    hidden: &DeviceFilter,
+) -> Result<Vec<SourceInfo>, MicError> {
+    let host = host(host_name)?;
+    let inputs = host
+        .input_devices()
+        .map_err(|e| MicError::Failed(format!("Failed to list input devices: {}", e)))?;
+    #[allow(unused_mut)]
+    let mut sources = describe(
+        inputs,
+        host.default_input_device(),
+        SourceKind::Input,
+        hidden,
+    );
+
+    #[cfg(all(target_os = "linux", feature = "jack"))]
+    sources.extend(jack_ports::list());
+
+    // A missing or unloadable ASIO driver just means no ASIO sources.
+    #[cfg(all(windows, feature = "asio"))]
+    if let Ok(asio) = cpal::host_from_id(cpal::HostId::Asio) {
+        if let Ok(inputs) = asio.input_devices() {
+            sources.extend(describe(
+                inputs,
+                asio.default_input_device(),
+                SourceKind::Asio,
+                hidden,
+            ));
+        }
+    }
+
+    // Loopback is a WASAPI feature, whichever host the inputs came from.
+    #[cfg(windows)]
+    {
+        let wasapi = cpal::default_host();
+        let outputs = wasapi
+            .output_devices()
+            .map_err(|e| MicError::Failed(format!("Failed to list output devices: {}", e)))?;
+        sources.extend(describe(
+            outputs,
+            wasapi.default_output_device(),
+            SourceKind::Loopback,
+            hidden,
+        ));
+    }
+    Ok(sources)
+}
+
+fn find_device(
+    mut devices: impl Iterator<Item = cpal::Device>,
+    name: &str,
+) -> Result<cpal::Device, MicError> {
+    devices
+        .find(|d| d.name().is_ok_and(|n| n == name))
+        .ok_or_else(|| MicError::DeviceNotFound(name.to_string()))
+}
+
+/// The system default input, or the first visible input when the default is
+/// hidden. Falls back to the default if every input is hidden.
+fn default_input(host: &cpal::Host, hidden: &DeviceFilter) -> Result<cpal::Device, MicError> {
+    let default = host.default_input_device().ok_or(MicError::NoDevice)?;
+    if !default.name().is_ok_and(|n| hidden.hides(&n)) {
+        return Ok(default);
+    }
+    let visible = host
+        .input_devices()
+        .ok()
+        .and_then(|mut devices| devices.find(|d| d.name().is_ok_and(|n| !hidden.hides(&n))));
+    Ok(visible.unwrap_or(default))
+}
+
+fn open_input(
+    host: &cpal::Host,
+    name: Option<&str>,
+    hidden: &DeviceFilter,
+) -> Result<(cpal::Device, cpal::SupportedStreamConfig), MicError> {
+    let device = match name {
+        None => default_input(host, hidden)?,
+        Some(name) => find_device(
+            host.input_devices()
+                .map_err(|e| MicError::Failed(format!("Failed to list input devices: {}", e)))?,
+            name,
+        )?,
+    };
+    let config = device
+        .default_input_config()
+        .map_err(|e| MicError::Failed(format!("Failed to get default input config: {}", e)))?;
+    Ok((device, config))
+}
+
+/// A mode of `device` with `channels` channels at `rate`, in a sample
+/// format capture can read. The default config's sample format wins a tie.
+fn find_config(
+    device: &cpal::Device,
+    default: &cpal::SupportedStreamConfig,
+    channels: u16,
+    rate: u32,
+) -> Option<cpal::SupportedStreamConfig> {
+    let rate = cpal::SampleRate(rate);
+    device
+        .supported_input_configs()
+        .ok()?
+        .filter(|range| {
+            range.channels() == channels
+                && range.min_sample_rate() <= rate
+                && rate <= range.max_sample_rate()
+                && matches!(
+                    range.sample_format(),
+                    cpal::SampleFormat::F32
+                        | cpal::SampleFormat::I16
+                        | cpal::SampleFormat::U16
+                        | cpal::SampleFormat::I32
+                )
+        })
+        .max_by_key(|range| range.sample_format() == default.sample_format())
+        .map(|range| range.with_sample_rate(rate))
+}
+
+/// A mono 16 kHz mode, if the device has one, so capture needs neither a
+/// downmix nor a resample.
+fn native_config(
+    device: &cpal::Device,
+    default: &cpal::SupportedStreamConfig,
+) -> Option<cpal::SupportedStreamConfig> {
+    find_config(device, default, 1, TARGET_SAMPLE_RATE)
+}
+
+/// The mode `source.sample_rate` and `source.channels` ask for, the default
+/// config filling in whichever is unset.
+fn requested_config(
+    device: &cpal::Device,
+    default: &cpal::SupportedStreamConfig,
+    source: &AudioSource,
+) -> Result<cpal::SupportedStreamConfig, MicError> {
+    let channels = source.channels.unwrap_or(default.channels());
+    let rate = source.sample_rate.unwrap_or(default.sample_rate().0);
+    find_config(device, default, channels, rate).ok_or_else(|| {
+        MicError::Unsupported(format!(
+            "Device has no {}-channel mode at {} Hz",
+            channels, rate
+        ))
+    })
+}
+
+/// Resolve a source to its device and the config to open it with.
+pub fn open(source: &AudioSource) -> Result<(cpal::Device, cpal::SupportedStreamConfig), MicError> {
+    match source.kind {
+        SourceKind::Input => {
+            let (device, config) = open_input(
+                &host(source.host.as_deref())?,
+                source.device.as_deref(),
+                &source.hidden,
+            )?;
+            // A channel pick refers to the device's own layout; keep it.
+            let config = if source.sample_rate.is_some() || source.channels.is_some() {
+                requested_config(&device, &config, source)?
+            } else if source.channel.is_none() {
+                native_config(&device, &config).unwrap_or(config)
+            } else {
+                config
+            };
+            Ok((device, config))
+        }
+        #[cfg(windows)]
+        SourceKind::Loopback => {
+            let wasapi = cpal::default_host();
+            let device = match &source.device {
+                None => wasapi
+                    .default_output_device()
+                    .ok_or_else(|| MicError::Failed("No output device available".into()))?,
+                Some(name) => find_device(
+                    wasapi.output_devices().map_err(|e| {
+                        MicError::Failed(format!("Failed to list output devices: {}", e))
+                    })?,
+                    name,
+                )?,
+            };
+            // cpal switches WASAPI to loopback when an input stream is built
+            // on a render device; it has to use the device's mix format.
+            let config = device
+                .default_output_config()
+                .map_err(|e| MicError::Failed(format!("Failed to get loopback config: {}", e)))?;
+            Ok((device, config))
+        }
+        #[cfg(not(windows))]
+        SourceKind::Loopback => Err(MicError::Unsupported(
+            "Loopback capture is only supported on Windows".into(),
+        )),
+        #[cfg(all(target_os = "linux", feature = "jack"))]
+        SourceKind::Jack => jack_ports::open(source.device.is_none()).map_err(MicError::Failed),
+        #[cfg(not(all(target_os = "linux", feature = "jack")))]
+        SourceKind::Jack => Err(MicError::Unsupported(
+            "JACK support is not enabled in this build".into(),
+        )),
+        #[cfg(all(windows, feature = "asio"))]
+        SourceKind::Asio => {
+            let asio = cpal::host_from_id(cpal::HostId::Asio)
+                .map_err(|e| MicError::Failed(format!("ASIO unavailable: {}", e)))?;
+            open_input(&asio, source.device.as_deref(), &source.hidden)
+        }
+        #[cfg(not(all(windows, feature = "asio")))]
+        SourceKind::Asio => Err(MicError::Unsupported(
+            "ASIO support is not enabled in this build".into(),
+        )),
+    }
+}
+
+/// Finish setting up a source once its stream is running.
+#[cfg(all(target_os = "linux", feature = "jack"))]
+pub fn connect(source: &AudioSource, device: &cpal::Device) -> Result<(), MicError> {
+    if let (SourceKind::Jack, Some(port)) = (source.kind, &source.device) {
+        let client = device.name().map_err(|e| MicError::Failed(e.to_string()))?;
+        return jack_ports::connect(port, &client).map_err(MicError::Failed);
+    }
+    Ok(())
+}
+
+#[cfg(not(all(target_os = "linux", feature = "jack")))]
+pub fn connect(_source: &AudioSource, _device: &cpal::Device) -> Result<(), MicError> {
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", feature = "jack"))]
+mod jack_ports {
+    use cpal::traits::DeviceTrait;
+    use jack::{Client, ClientOptions, PortFlags};
+
+    use super::{SourceInfo, SourceKind};
+
+    /// cpal appends `_in`, so our capture ports are `agentic-devops-voice_in:in_N`.
+    const CLIENT_NAME: &str = "agentic-devops-voice";
+    const AUDIO_PORT_TYPE: &str = "32 bit float mono audio";
+
+    fn control_client() -> Result<Client, String> {
+        Client::new(
+            &format!("{}-ctl", CLIENT_NAME),
+            ClientOptions::NO_START_SERVER,
+        )
+        .map(|(client, _)| client)
+        .map_err(|e| format!("JACK server unavailable: {}", e))
+    }
+
+    /// Audio output ports of other clients, i.e. everything we could record.
+    /// Empty when no JACK server is running.
+    pub fn list() -> Vec<SourceInfo> {
+        let Ok(client) = control_client() else {
+            return Vec::new();
+        };
+        let mut ports = client.ports(None, Some(AUDIO_PORT_TYPE), PortFlags::IS_OUTPUT);
+        ports.retain(|p| !p.starts_with(CLIENT_NAME));
+        let default = ports
+            .iter()
+            .find(|p| p.starts_with("system:capture_"))
+            .cloned();
+        ports
+            .into_iter()
+            .map(|name| SourceInfo {
+                kind: SourceKind::Jack,
+                is_default: default.as_deref() == Some(name.as_str()),
+                channels: Some(1),
+                name,
+            })
+            .collect()
+    }
+
+    /// Open our JACK input client. Without a specific port it is wired to
+    /// the system capture ports, like any other default input.
+    pub fn open(auto_connect: bool) -> Result<(cpal::Device, cpal::SupportedStreamConfig), String> {
+        let mut host =
+            cpal::platform::JackHost::new().map_err(|_| "JACK host unavailable".to_string())?;
+        host.set_connect_automatically(auto_connect);
+        let device = host
+            .input_device_with_name(CLIENT_NAME)
+            .ok_or("Could not connect to the JACK server")?;
+        let config = device
+            .default_input_config()
+            .map_err(|e| format!("Failed to get JACK input config: {}", e))?;
+        Ok((device.into(), config))
+    }
+
+    /// Feed `port` into every input port of our client (mono source into a
+    /// stereo client; the capture path downmixes anyway).
+    pub fn connect(port: &str, client_name: &str) -> Result<(), String> {
+        let client = control_client()?;
+        let inputs = client.ports(
+            Some(&format!("^{}:", regex_escape(client_name))),
+            Some(AUDIO_PORT_TYPE),
+            PortFlags::IS_INPUT,
+        );
+        if inputs.is_empty() {
+            return Err(format!("JACK client {} has no input ports", client_name));
+        }
+        for input in inputs {
+            client
+                .connect_ports_by_name(port, &input)
+                .map_err(|e| format!("Failed to connect {} to {}: {}", port, input, e))?;
+        }
+        Ok(())
+    }
+
+    /// JACK matches port names as regular expressions.
+    fn regex_escape(s: &str) -> String {
+        s.chars()
+            .flat_map(|c| {
+                let escape = r"\.+*?()|[]{}^$".contains(c);
+                escape.then_some('\\').into_iter().chain(std::iter::once(c))
+            })
+            .collect()
+    }
+}
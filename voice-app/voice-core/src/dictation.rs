@@ -0,0 +1,261 @@
+//! Spoken punctuation and formatting commands in dictated text: "comma",
+//! "new line", "open paren", "all caps on" and so on become what they say.
+//! The command table is data, so users can edit and extend it.
+//!
+//! Phrases are matched case-insensitively on whole words, ignoring the
+//! punctuation a transcriber puts around them, so "Hello, comma, world."
+//! reads as "hello comma world"; the longest phrase wins. Punctuation the
+//! transcriber added before a spoken mark is replaced by it. The escape
+//! word (default "literal") before a phrase keeps its words as text.
+
+use serde::{Deserialize, Serialize};
+
+/// Punctuation a transcriber may end a word with.
+const TRAILING_PUNCTUATION: &[char] = &[',', '.', ';', ':', '!', '?'];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpokenAction {
+    /// Text joined to the word before: "comma" → ",".
+    Attach {
+        text: String,
+    },
+    /// Text joined to the word after: "open paren" → "(".
+    Open {
+        text: String,
+    },
+    /// Text joined on both sides: "new line", "dot".
+    Join {
+        text: String,
+    },
+    /// Text on its own, with spaces around: "ampersand" → "&".
+    Word {
+        text: String,
+    },
+    /// Upper-case what follows until `CapsOff`.
+    CapsOn,
+    CapsOff,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpokenCommand {
+    pub phrase: String,
+    pub action: SpokenAction,
+}
+
+impl SpokenCommand {
+    fn new(phrase: &str, action: SpokenAction) -> Self {
+        Self {
+            phrase: phrase.into(),
+            action,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DictationSettings {
+    pub enabled: bool,
+    /// Said before a phrase to keep its words instead, e.g. "literal comma".
+    pub escape: String,
+    pub commands: Vec<SpokenCommand>,
+}
+
+impl Default for DictationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            escape: "literal".into(),
+            commands: default_commands(),
+        }
+    }
+}
+
+pub fn default_commands() -> Vec<SpokenCommand> {
+    use SpokenAction::*;
+    let text = |t: &str| t.to_string();
+    vec![
+        SpokenCommand::new("period", Attach { text: text(".") }),
+        SpokenCommand::new("full stop", Attach { text: text(".") }),
+        SpokenCommand::new("comma", Attach { text: text(",") }),
+        SpokenCommand::new("question mark", Attach { text: text("?") }),
+        SpokenCommand::new("exclamation mark", Attach { text: text("!") }),
+        SpokenCommand::new("exclamation point", Attach { text: text("!") }),
+        SpokenCommand::new("colon", Attach { text: text(":") }),
+        SpokenCommand::new("semicolon", Attach { text: text(";") }),
+        SpokenCommand::new("ellipsis", Attach { text: text("...") }),
+        SpokenCommand::new("new line", Join { text: text("\n") }),
+        SpokenCommand::new("new paragraph", Join { text: text("\n\n") }),
+        SpokenCommand::new("open paren", Open { text: text("(") }),
+        SpokenCommand::new("close paren", Attach { text: text(")") }),
+        SpokenCommand::new("open bracket", Open { text: text("[") }),
+        SpokenCommand::new("close bracket", Attach { text: text("]") }),
+        SpokenCommand::new("open brace", Open { text: text("{") }),
+        SpokenCommand::new("close brace", Attach { text: text("}") }),
+        SpokenCommand::new("open quote", Open { text: text("\"") }),
+        SpokenCommand::new("close quote", Attach { text: text("\"") }),
+        SpokenCommand::new("dot", Join { text: text(".") }),
+        SpokenCommand::new("hyphen", Join { text: text("-") }),
+        SpokenCommand::new("slash", Join { text: text("/") }),
+        SpokenCommand::new("underscore", Join { text: text("_") }),
+        SpokenCommand::new("at sign", Join { text: text("@") }),
+        SpokenCommand::new("dash", Word { text: text("-") }),
+        SpokenCommand::new("ampersand", Word { text: text("&") }),
+        SpokenCommand::new("all caps on", CapsOn),
+        SpokenCommand::new("all caps off", CapsOff),
+    ]
+}
+
+/// A word as it is matched against phrases.
+fn key(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+struct Token {
+    text: String,
+    /// No space before it.
+    glue_left: bool,
+    /// No space after it.
+    glue_right: bool,
+    /// A dictated word rather than a command's text.
+    spoken: bool,
+}
+
+impl Token {
+    fn new(text: &str, glue_left: bool, glue_right: bool) -> Self {
+        Self {
+            text: text.to_string(),
+            glue_left,
+            glue_right,
+            spoken: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    tokens: Vec<Token>,
+    caps: bool,
+    /// Capitalize the next word, after the end of a sentence or line.
+    capitalize: bool,
+}
+
+impl Writer {
+    fn word(&mut self, word: &str) {
+        let text = if self.caps {
+            word.to_uppercase()
+        } else if self.capitalize {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        } else {
+            word.to_string()
+        };
+        self.capitalize = false;
+        self.tokens.push(Token {
+            text,
+            glue_left: false,
+            glue_right: false,
+            spoken: true,
+        });
+    }
+
+    /// Drop the transcriber's punctuation from the last word, which a
+    /// spoken mark replaces.
+    fn strip_last(&mut self) {
+        if let Some(last) = self.tokens.last_mut().filter(|t| t.spoken) {
+            let kept = last.text.trim_end_matches(TRAILING_PUNCTUATION).len();
+            last.text.truncate(kept);
+        }
+    }
+
+    fn command(&mut self, action: &SpokenAction) {
+        match action {
+            SpokenAction::Attach { text } => {
+                self.strip_last();
+                self.tokens.push(Token::new(text, true, false));
+                self.capitalize = text.ends_with(['.', '?', '!']);
+            }
+            SpokenAction::Open { text } => self.tokens.push(Token::new(text, false, true)),
+            SpokenAction::Join { text } => {
+                self.strip_last();
+                self.tokens.push(Token::new(text, true, true));
+                self.capitalize = text.contains('\n');
+            }
+            SpokenAction::Word { text } => self.tokens.push(Token::new(text, false, false)),
+            SpokenAction::CapsOn => self.caps = true,
+            SpokenAction::CapsOff => self.caps = false,
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::new();
+        let mut glue = true;
+        for token in self.tokens.iter().filter(|t| !t.text.is_empty()) {
+            if !glue && !token.glue_left {
+                out.push(' ');
+            }
+            out.push_str(&token.text);
+            glue = token.glue_right;
+        }
+        out
+    }
+}
+
+/// The longest phrase `keys` starts with: its length in words and action.
+fn longest<'a>(
+    phrases: &'a [(Vec<String>, &'a SpokenAction)],
+    keys: &[String],
+) -> Option<(usize, &'a SpokenAction)> {
+    phrases
+        .iter()
+        .filter(|(phrase, _)| keys.starts_with(phrase))
+        .max_by_key(|(phrase, _)| phrase.len())
+        .map(|(phrase, action)| (phrase.len(), *action))
+}
+
+/// `text` with the spoken commands in `settings` carried out.
+pub fn apply(settings: &DictationSettings, text: &str) -> String {
+    let phrases: Vec<(Vec<String>, &SpokenAction)> = settings
+        .commands
+        .iter()
+        .map(|c| {
+            (
+                c.phrase.split_whitespace().map(key).collect::<Vec<_>>(),
+                &c.action,
+            )
+        })
+        .filter(|(phrase, _)| !phrase.is_empty() && phrase.iter().all(|w| !w.is_empty()))
+        .collect();
+    let escape = key(&settings.escape);
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let keys: Vec<String> = words.iter().map(|w| key(w)).collect();
+
+    let mut writer = Writer::default();
+    let mut i = 0;
+    while i < words.len() {
+        if !escape.is_empty() && keys[i] == escape {
+            if let Some((len, _)) = longest(&phrases, &keys[i + 1..]) {
+                for word in &words[i + 1..i + 1 + len] {
+                    writer.word(word);
+                }
+                i += 1 + len;
+                continue;
+            }
+        }
+        match longest(&phrases, &keys[i..]) {
+            Some((len, action)) => {
+                writer.command(action);
+                i += len;
+            }
+            None => {
+                writer.word(words[i]);
+                i += 1;
+            }
+        }
+    }
+    writer.finish()
+}
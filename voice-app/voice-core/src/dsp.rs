@@ -0,0 +1,373 @@
+//! Sample conversion and resampling used by the capture path. Kept free of
+//! any audio-device or Tauri types so it can be benchmarked and tested alone.
+
+/// Convert a normalized float sample to i16, clamping out-of-range input.
+pub fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// TPDF dither for the conversion to 16-bit: noise of up to ±1 LSB with a
+/// triangular distribution is added before rounding, so quiet passages
+/// turn into low-level noise instead of quantization distortion.
+pub struct Dither {
+    state: u32,
+}
+
+impl Default for Dither {
+    fn default() -> Self {
+        Self { state: 0x9E37_79B9 }
+    }
+}
+
+impl Dither {
+    /// Uniform in `[-0.5, 0.5)` from a xorshift generator; audio noise
+    /// needs no better.
+    fn uniform(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 8) as f32 / (1 << 24) as f32 - 0.5
+    }
+
+    /// As [`to_i16`], with dither.
+    pub fn to_i16(&mut self, sample: f32) -> i16 {
+        let noise = self.uniform() + self.uniform();
+        (sample.clamp(-1.0, 1.0) * i16::MAX as f32 + noise)
+            .round()
+            .clamp(i16::MIN as f32, i16::MAX as f32) as i16
+    }
+}
+
+/// Second-order Butterworth high-pass (RBJ biquad), for rumble and handling
+/// noise below the voice band.
+struct HighPass {
+    b: [f32; 3],
+    a: [f32; 2],
+    x: [f32; 2],
+    y: [f32; 2],
+}
+
+impl HighPass {
+    fn new(cutoff_hz: f32, sample_rate: f32) -> Self {
+        let w0 = 2.0 * std::f32::consts::PI * cutoff_hz / sample_rate;
+        let alpha = w0.sin() / std::f32::consts::SQRT_2;
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha;
+        Self {
+            b: [
+                (1.0 + cos) / 2.0 / a0,
+                -(1.0 + cos) / a0,
+                (1.0 + cos) / 2.0 / a0,
+            ],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+/// Time constants of the noise gate: quick to open so word onsets survive,
+/// slow to close so it doesn't chatter between syllables.
+const GATE_ATTACK_MS: f32 = 5.0;
+const GATE_RELEASE_MS: f32 = 150.0;
+const GATE_SMOOTHING_MS: f32 = 10.0;
+
+/// Capture processing applied before conversion to 16-bit: high-pass, gain,
+/// then a noise gate that turns audio below the voice-activity threshold
+/// down by the suppression strength.
+pub struct Processor {
+    high_pass: Option<HighPass>,
+    gain: f32,
+    threshold: f32,
+    /// Gain applied to audio below the threshold.
+    floor: f32,
+    attack: f32,
+    release: f32,
+    smoothing: f32,
+    envelope: f32,
+    gate: f32,
+}
+
+fn coefficient(ms: f32, sample_rate: f32) -> f32 {
+    1.0 - (-1000.0 / (ms * sample_rate)).exp()
+}
+
+impl Processor {
+    /// A `high_pass_hz` of 0 leaves the filter out; `noise_suppression` runs
+    /// from 0 (gate off) to 1 (mute below the threshold).
+    pub fn new(
+        gain_db: f32,
+        high_pass_hz: f32,
+        noise_suppression: f32,
+        vad_threshold_dbfs: f32,
+        sample_rate: f32,
+    ) -> Self {
+        let high_pass = (high_pass_hz > 0.0 && high_pass_hz < sample_rate / 2.0)
+            .then(|| HighPass::new(high_pass_hz, sample_rate));
+        Self {
+            high_pass,
+            gain: 10f32.powf(gain_db / 20.0),
+            threshold: 10f32.powf(vad_threshold_dbfs / 20.0),
+            floor: 1.0 - noise_suppression.clamp(0.0, 1.0),
+            attack: coefficient(GATE_ATTACK_MS, sample_rate),
+            release: coefficient(GATE_RELEASE_MS, sample_rate),
+            smoothing: coefficient(GATE_SMOOTHING_MS, sample_rate),
+            envelope: 0.0,
+            gate: 1.0,
+        }
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples {
+            let mut value = *sample;
+            if let Some(high_pass) = &mut self.high_pass {
+                value = high_pass.process(value);
+            }
+            value *= self.gain;
+
+            let level = value.abs();
+            let rate = if level > self.envelope {
+                self.attack
+            } else {
+                self.release
+            };
+            self.envelope += (level - self.envelope) * rate;
+            let target = if self.envelope >= self.threshold {
+                1.0
+            } else {
+                self.floor
+            };
+            self.gate += (target - self.gate) * self.smoothing;
+
+            *sample = (value * self.gate).clamp(-1.0, 1.0);
+        }
+    }
+}
+
+/// Downmix interleaved frames to mono floats in `[-1.0, 1.0]`.
+pub fn interleaved_f32_to_mono(data: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return data.iter().map(|s| s.clamp(-1.0, 1.0)).collect();
+    }
+    data.chunks(channels)
+        .map(|frame| {
+            let sum: f32 = frame.iter().map(|s| s.clamp(-1.0, 1.0)).sum();
+            (sum / channels as f32).clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+/// As [`interleaved_f32_to_mono`], for signed 16-bit input.
+pub fn interleaved_i16_to_mono(data: &[i16], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return data
+            .iter()
+            .map(|s| (*s as f32 / i16::MAX as f32).clamp(-1.0, 1.0))
+            .collect();
+    }
+    data.chunks(channels)
+        .map(|frame| {
+            let sum: f32 = frame
+                .iter()
+                .map(|s| (*s as f32 / i16::MAX as f32).clamp(-1.0, 1.0))
+                .sum();
+            (sum / channels as f32).clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+/// As [`interleaved_f32_to_mono`], for unsigned 16-bit input.
+pub fn interleaved_u16_to_mono(data: &[u16], channels: usize) -> Vec<f32> {
+    let to_f32 = |s: u16| ((s as f32 / u16::MAX as f32) * 2.0 - 1.0).clamp(-1.0, 1.0);
+    if channels <= 1 {
+        return data.iter().map(|s| to_f32(*s)).collect();
+    }
+    data.chunks(channels)
+        .map(|frame| {
+            let sum: f32 = frame.iter().map(|s| to_f32(*s)).sum();
+            (sum / channels as f32).clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+/// As [`interleaved_f32_to_mono`], for signed 32-bit input (common on ASIO).
+pub fn interleaved_i32_to_mono(data: &[i32], channels: usize) -> Vec<f32> {
+    let to_f32 = |s: i32| (s as f64 / i32::MAX as f64).clamp(-1.0, 1.0) as f32;
+    if channels <= 1 {
+        return data.iter().map(|s| to_f32(*s)).collect();
+    }
+    data.chunks(channels)
+        .map(|frame| {
+            let sum: f32 = frame.iter().map(|s| to_f32(*s)).sum();
+            (sum / channels as f32).clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+/// Mix interleaved float frames to mono with a gain per channel. Channels
+/// without a weight are left out.
+pub fn weighted_mono(data: &[f32], channels: usize, weights: &[f32]) -> Vec<f32> {
+    data.chunks(channels.max(1))
+        .map(|frame| {
+            let sum: f32 = frame.iter().zip(weights).map(|(s, w)| s * w).sum();
+            sum.clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+/// Weight of each new block in the smoothed per-channel levels.
+const LOUDEST_SMOOTHING: f32 = 0.2;
+/// How much louder another channel must be before it takes over, so two
+/// similar channels don't trade places every callback.
+const LOUDEST_HYSTERESIS: f32 = 2.0;
+
+/// Tracks which channel of a multi-channel stream carries the signal, for
+/// interfaces where the mic may be on any input and the rest are silent.
+#[derive(Default)]
+pub struct LoudestChannel {
+    levels: Vec<f32>,
+    current: usize,
+}
+
+impl LoudestChannel {
+    /// Update the levels with one block of interleaved frames and return the
+    /// channel to use for it.
+    pub fn pick(&mut self, data: &[f32], channels: usize) -> usize {
+        let channels = channels.max(1);
+        if self.levels.len() != channels {
+            self.levels = vec![0.0; channels];
+            self.current = 0;
+        }
+        let frames = (data.len() / channels).max(1) as f32;
+        for (ch, level) in self.levels.iter_mut().enumerate() {
+            let energy: f32 = data.iter().skip(ch).step_by(channels).map(|s| s * s).sum();
+            let rms = (energy / frames).sqrt();
+            *level += (rms - *level) * LOUDEST_SMOOTHING;
+        }
+        let mut loudest = self.current;
+        for (ch, &level) in self.levels.iter().enumerate() {
+            if level > self.levels[loudest] {
+                loudest = ch;
+            }
+        }
+        if self.levels[loudest] > self.levels[self.current] * LOUDEST_HYSTERESIS {
+            self.current = loudest;
+        }
+        self.current
+    }
+}
+
+/// One channel out of interleaved frames.
+pub fn select_channel<T: Copy>(data: &[T], channels: usize, channel: usize) -> Vec<T> {
+    data.iter()
+        .skip(channel)
+        .step_by(channels.max(1))
+        .copied()
+        .collect()
+}
+
+/// Linear-interpolation resampler.
+pub fn resample_linear_i16(input: &[i16], input_rate: u32, output_rate: u32) -> Vec<i16> {
+    if input.is_empty() || input_rate == output_rate {
+        return input.to_vec();
+    }
+
+    let step = input_rate as f64 / output_rate as f64;
+    let mut pos = 0.0_f64;
+    let mut output = Vec::new();
+
+    while (pos as usize) + 1 < input.len() {
+        let i = pos.floor() as usize;
+        let frac = (pos - i as f64) as f32;
+        let a = input[i] as f32;
+        let b = input[i + 1] as f32;
+        output.push((a + (b - a) * frac).clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+        pos += step;
+    }
+
+    if output.is_empty() {
+        output.push(input[0]);
+    }
+    output
+}
+
+/// Taps per unit of decimation factor; 16 gives ~50 dB stopband at 3:1.
+const TAPS_PER_FACTOR: usize = 16;
+
+/// Windowed-sinc (Blackman) low-pass with cutoff just below the output
+/// Nyquist, normalized to unity DC gain.
+fn lowpass_taps(factor: usize) -> Vec<f32> {
+    let len = TAPS_PER_FACTOR * factor + 1;
+    let center = (len / 2) as f64;
+    let cutoff = 0.45 / factor as f64; // cycles per input sample
+    let mut taps: Vec<f64> = (0..len)
+        .map(|n| {
+            let x = n as f64 - center;
+            let sinc = if x == 0.0 {
+                2.0 * cutoff
+            } else {
+                (2.0 * std::f64::consts::PI * cutoff * x).sin() / (std::f64::consts::PI * x)
+            };
+            let phase = 2.0 * std::f64::consts::PI * n as f64 / (len - 1) as f64;
+            let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+            sinc * window
+        })
+        .collect();
+    let sum: f64 = taps.iter().sum();
+    taps.iter_mut().for_each(|t| *t /= sum);
+    taps.into_iter().map(|t| t as f32).collect()
+}
+
+/// Decimate by an integer factor: low-pass FIR evaluated only at the kept
+/// positions. Edges repeat the first/last sample instead of padding with
+/// silence, so a recording doesn't start or end with a fade.
+pub fn decimate_i16(input: &[i16], factor: usize) -> Vec<i16> {
+    if input.is_empty() || factor <= 1 {
+        return input.to_vec();
+    }
+
+    let taps = lowpass_taps(factor);
+    let half = taps.len() / 2;
+    let last = input.len() - 1;
+    let mut output = Vec::with_capacity(input.len().div_ceil(factor));
+
+    for center in (0..input.len()).step_by(factor) {
+        let acc: f32 = if center >= half && center + half <= last {
+            input[center - half..=center + half]
+                .iter()
+                .zip(&taps)
+                .map(|(&s, &t)| s as f32 * t)
+                .sum()
+        } else {
+            taps.iter()
+                .enumerate()
+                .map(|(k, &t)| {
+                    let i = (center + k).saturating_sub(half).min(last);
+                    input[i] as f32 * t
+                })
+                .sum()
+        };
+        output.push(acc.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+    }
+    output
+}
+
+/// Resample for transcription: exact integer ratios down (48 kHz, 32 kHz to
+/// 16 kHz) take the FIR decimator, everything else falls back to linear
+/// interpolation.
+pub fn resample_i16(input: &[i16], input_rate: u32, output_rate: u32) -> Vec<i16> {
+    if input_rate > output_rate && input_rate.is_multiple_of(output_rate) {
+        decimate_i16(input, (input_rate / output_rate) as usize)
+    } else {
+        resample_linear_i16(input, input_rate, output_rate)
+    }
+}
@@ -0,0 +1,26 @@
+//! Capture, DSP, encoding and the pipeline-server client behind the voice
+//! app, with no Tauri dependency: capture reports levels and overruns to a
+//! callback instead of emitting window events, and the client takes its
+//! bearer token and signing key as arguments instead of reading a keychain.
+//! `src-tauri` wraps these in commands and managed state; other shells can
+//! do the same.
+
+#[cfg(all(target_os = "macos", feature = "capture"))]
+mod avcapture;
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod client;
+#[cfg(feature = "capture")]
+pub mod controller;
+#[cfg(feature = "capture")]
+pub mod devices;
+pub mod dictation;
+pub mod dsp;
+pub mod numbers;
+pub mod processing;
+pub mod signing;
+pub mod transcript;
+pub mod wav;
+
+/// The rate recordings are kept and uploaded at.
+pub const TARGET_SAMPLE_RATE: u32 = 16_000;
@@ -0,0 +1,616 @@
+//! Spoken numbers, dates, times, units and versions written the way the
+//! locale writes them: "twenty three" → "23", "three point five" → "3.5"
+//! (or "3,5"), "one point two point three" → "1.2.3", "fifty percent" →
+//! "50%", "five megabytes" → "5 MB", "three thirty pm" → "3:30 PM" (or
+//! "15:30"), "march third twenty twenty four" → "March 3, 2024".
+//!
+//! The words are English, as transcribers write them. Single-word numbers
+//! below ten stay words unless a unit, decimal or time needs them. A
+//! number never spans punctuation or a line break: "one, two" is left
+//! alone. Months that are also common words ("may", "march") only start a
+//! date when a year follows.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumberLocale {
+    #[default]
+    #[serde(rename = "en-US")]
+    EnUs,
+    #[serde(rename = "en-GB")]
+    EnGb,
+    #[serde(rename = "sv-SE")]
+    SvSe,
+}
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+const SWEDISH_MONTHS: [&str; 12] = [
+    "januari",
+    "februari",
+    "mars",
+    "april",
+    "maj",
+    "juni",
+    "juli",
+    "augusti",
+    "september",
+    "oktober",
+    "november",
+    "december",
+];
+
+impl NumberLocale {
+    fn decimal(self) -> char {
+        match self {
+            NumberLocale::EnUs | NumberLocale::EnGb => '.',
+            NumberLocale::SvSe => ',',
+        }
+    }
+
+    /// Between groups of three digits, from 10 000 up.
+    fn group(self) -> char {
+        match self {
+            NumberLocale::EnUs | NumberLocale::EnGb => ',',
+            NumberLocale::SvSe => '\u{a0}',
+        }
+    }
+
+    fn percent(self) -> &'static str {
+        match self {
+            NumberLocale::EnUs | NumberLocale::EnGb => "%",
+            NumberLocale::SvSe => "\u{a0}%",
+        }
+    }
+
+    /// Hour (0–23) and minute as the locale's clock shows them.
+    fn time(self, hour: u64, minute: u64) -> String {
+        let twelve = (hour + 11) % 12 + 1;
+        match self {
+            NumberLocale::EnUs => {
+                let half = if hour < 12 { "AM" } else { "PM" };
+                format!("{}:{:02} {}", twelve, minute, half)
+            }
+            NumberLocale::EnGb => {
+                let half = if hour < 12 { "am" } else { "pm" };
+                format!("{}:{:02} {}", twelve, minute, half)
+            }
+            NumberLocale::SvSe => format!("{}:{:02}", hour, minute),
+        }
+    }
+
+    /// `month` is 1–12.
+    fn date(self, month: usize, day: u64, year: Option<u64>) -> String {
+        let english = {
+            let name = MONTHS[month - 1];
+            let mut chars = name.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        };
+        match (self, year) {
+            (NumberLocale::EnUs, Some(year)) => format!("{} {}, {}", english, day, year),
+            (NumberLocale::EnUs, None) => format!("{} {}", english, day),
+            (NumberLocale::EnGb, Some(year)) => format!("{} {} {}", day, english, year),
+            (NumberLocale::EnGb, None) => format!("{} {}", day, english),
+            (NumberLocale::SvSe, Some(year)) => format!("{}-{:02}-{:02}", year, month, day),
+            (NumberLocale::SvSe, None) => format!("{} {}", day, SWEDISH_MONTHS[month - 1]),
+        }
+    }
+
+    fn integer(self, value: u64) -> String {
+        let digits = value.to_string();
+        if value < 10_000 {
+            return digits;
+        }
+        let mut out = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(self.group());
+            }
+            out.push(c);
+        }
+        out
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NumberSettings {
+    pub enabled: bool,
+    pub locale: NumberLocale,
+}
+
+fn unit(key: &str) -> Option<u64> {
+    let value = match key {
+        "zero" => 0,
+        "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        "eleven" => 11,
+        "twelve" => 12,
+        "thirteen" => 13,
+        "fourteen" => 14,
+        "fifteen" => 15,
+        "sixteen" => 16,
+        "seventeen" => 17,
+        "eighteen" => 18,
+        "nineteen" => 19,
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn tens(key: &str) -> Option<u64> {
+    let value = match key {
+        "twenty" => 20,
+        "thirty" => 30,
+        "forty" => 40,
+        "fifty" => 50,
+        "sixty" => 60,
+        "seventy" => 70,
+        "eighty" => 80,
+        "ninety" => 90,
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn scale(key: &str) -> Option<u64> {
+    match key {
+        "thousand" => Some(1_000),
+        "million" => Some(1_000_000),
+        "billion" => Some(1_000_000_000),
+        _ => None,
+    }
+}
+
+fn ordinal_word(key: &str) -> Option<u64> {
+    let value = match key {
+        "first" => 1,
+        "second" => 2,
+        "third" => 3,
+        "fourth" => 4,
+        "fifth" => 5,
+        "sixth" => 6,
+        "seventh" => 7,
+        "eighth" => 8,
+        "ninth" => 9,
+        "tenth" => 10,
+        "eleventh" => 11,
+        "twelfth" => 12,
+        "thirteenth" => 13,
+        "fourteenth" => 14,
+        "fifteenth" => 15,
+        "sixteenth" => 16,
+        "seventeenth" => 17,
+        "eighteenth" => 18,
+        "nineteenth" => 19,
+        "twentieth" => 20,
+        "thirtieth" => 30,
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn is_number_word(key: &str) -> bool {
+    unit(key).is_some()
+        || tens(key).is_some()
+        || scale(key).is_some()
+        || ordinal_word(key).is_some()
+        || key == "hundred"
+}
+
+fn digits(key: &str) -> Option<u64> {
+    if key.is_empty() || !key.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    key.parse().ok()
+}
+
+/// A single spoken digit, where "oh" is zero.
+fn digit(key: &str) -> Option<u64> {
+    match key {
+        "oh" | "o" => Some(0),
+        _ => unit(key).filter(|&v| v < 10),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Last {
+    Start,
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+    And,
+}
+
+/// A cardinal number at the start of `keys`: words consumed and value.
+fn cardinal(keys: &[&str]) -> Option<(usize, u64)> {
+    if let Some(value) = keys.first().and_then(|k| digits(k)) {
+        return Some((1, value));
+    }
+    let (mut total, mut current) = (0u64, 0u64);
+    let mut last = Last::Start;
+    let mut last_scale = u64::MAX;
+    let mut used = 0;
+    for (i, key) in keys.iter().enumerate() {
+        let after_group = matches!(last, Last::Start | Last::Hundred | Last::Scale | Last::And);
+        if *key == "zero" {
+            if last == Last::Start {
+                return Some((1, 0));
+            }
+            break;
+        } else if let Some(v) = unit(key) {
+            if v < 10 && (after_group || last == Last::Tens) {
+                last = Last::Unit;
+            } else if v >= 10 && after_group {
+                last = Last::Teen;
+            } else {
+                break;
+            }
+            current += v;
+        } else if let Some(v) = tens(key) {
+            if !after_group {
+                break;
+            }
+            current += v;
+            last = Last::Tens;
+        } else if *key == "hundred" {
+            if !matches!(last, Last::Unit | Last::Teen | Last::Tens) || current >= 100 {
+                break;
+            }
+            current *= 100;
+            last = Last::Hundred;
+        } else if let Some(v) = scale(key) {
+            if matches!(last, Last::Start | Last::Scale | Last::And) || v >= last_scale {
+                break;
+            }
+            total += current * v;
+            current = 0;
+            last_scale = v;
+            last = Last::Scale;
+        } else if *key == "and" {
+            let next = keys.get(i + 1).copied().unwrap_or_default();
+            let continues = unit(next).is_some_and(|v| v > 0) || tens(next).is_some();
+            if !matches!(last, Last::Hundred | Last::Scale) || !continues {
+                break;
+            }
+            last = Last::And;
+        } else {
+            break;
+        }
+        used = i + 1;
+    }
+    (used > 0).then_some((used, total + current))
+}
+
+/// Ten to ninety-nine in words.
+fn two_digit(keys: &[&str]) -> Option<(usize, u64)> {
+    let first = *keys.first()?;
+    if let Some(v) = unit(first).filter(|&v| v >= 10) {
+        return Some((1, v));
+    }
+    let t = tens(first)?;
+    match keys
+        .get(1)
+        .and_then(|k| unit(k))
+        .filter(|&v| (1..10).contains(&v))
+    {
+        Some(u) => Some((2, t + u)),
+        None => Some((1, t)),
+    }
+}
+
+/// A year: "nineteen eighty four", "twenty oh five", "two thousand ten"
+/// or four digits.
+fn year(keys: &[&str]) -> Option<(usize, u64)> {
+    if let Some(value) = keys.first().and_then(|k| digits(k)) {
+        return (1000..3000).contains(&value).then_some((1, value));
+    }
+    if let Some((n, century)) = two_digit(keys).filter(|&(_, c)| c == 19 || c == 20) {
+        let rest = &keys[n..];
+        let tail = match rest.first() {
+            Some(&"oh") => rest
+                .get(1)
+                .and_then(|k| digit(k))
+                .filter(|&d| d > 0)
+                .map(|d| (2, d)),
+            _ => two_digit(rest),
+        };
+        if let Some((m, tail)) = tail {
+            return Some((n + m, century * 100 + tail));
+        }
+    }
+    cardinal(keys).filter(|&(n, value)| n >= 2 && (1000..3000).contains(&value))
+}
+
+/// A day of the month: "third", "twenty first", "3rd", or a cardinal.
+fn day(keys: &[&str], ordinal_only: bool) -> Option<(usize, u64)> {
+    let first = *keys.first()?;
+    let parsed = if let Some(v) = ordinal_word(first) {
+        Some((1, v))
+    } else if let Some(t) = tens(first).filter(|&t| t <= 30) {
+        keys.get(1)
+            .and_then(|k| ordinal_word(k))
+            .filter(|&v| v < 10)
+            .map(|v| (2, t + v))
+    } else {
+        ["st", "nd", "rd", "th"]
+            .iter()
+            .find_map(|suffix| first.strip_suffix(suffix))
+            .and_then(digits)
+            .map(|v| (1, v))
+    };
+    let parsed = match parsed {
+        Some(parsed) => Some(parsed),
+        None if ordinal_only => None,
+        None => cardinal(keys).filter(|&(n, _)| n <= 2),
+    };
+    parsed.filter(|&(_, v)| (1..=31).contains(&v))
+}
+
+fn month(key: &str) -> Option<usize> {
+    MONTHS.iter().position(|m| *m == key).map(|i| i + 1)
+}
+
+/// "march third [year]" or "[the] third of march [year]".
+fn date(keys: &[&str], locale: NumberLocale) -> Option<(usize, String)> {
+    let ambiguous = |m: usize| m == 3 || m == 5;
+    if let Some(m) = keys.first().and_then(|k| month(k)) {
+        if let Some((n, d)) = day(&keys[1..], false) {
+            let year = year(&keys[1 + n..]);
+            if year.is_some() || !ambiguous(m) {
+                let used = 1 + n + year.map_or(0, |(y, _)| y);
+                return Some((used, locale.date(m, d, year.map(|(_, y)| y))));
+            }
+        }
+    }
+    let start = usize::from(keys.first() == Some(&"the"));
+    let (n, d) = day(&keys[start..], true)?;
+    let of = start + n;
+    if keys.get(of) != Some(&"of") {
+        return None;
+    }
+    let m = keys.get(of + 1).and_then(|k| month(k))?;
+    let year = year(&keys[of + 2..]);
+    let used = of + 2 + year.map_or(0, |(y, _)| y);
+    Some((used, locale.date(m, d, year.map(|(_, y)| y))))
+}
+
+/// "three thirty pm", "seven oh five am", "ten o'clock".
+fn time(keys: &[&str], locale: NumberLocale) -> Option<(usize, String)> {
+    let hour = keys
+        .first()
+        .and_then(|k| unit(k).or_else(|| digits(k)))
+        .filter(|h| (1..=12).contains(h))?;
+    let rest = &keys[1..];
+    let (n, minute) = match rest.first() {
+        Some(&"oh") => (2, rest.get(1).and_then(|k| digit(k)).filter(|&d| d > 0)?),
+        _ => two_digit(rest).filter(|&(_, m)| m < 60).unwrap_or((0, 0)),
+    };
+    let marker = *rest.get(n)?;
+    let hour = match marker {
+        "am" | "a.m" => hour % 12,
+        "pm" | "p.m" => hour % 12 + 12,
+        "o'clock" if n == 0 => return Some((2, format!("{}:00", hour))),
+        _ => return None,
+    };
+    Some((1 + n + 1, locale.time(hour, minute)))
+}
+
+/// What a unit word is written as, and whether a space goes before it.
+fn unit_symbol(key: &str) -> Option<(&'static str, bool)> {
+    let singular = key.strip_suffix('s').unwrap_or(key);
+    let symbol = match singular {
+        "kilobyte" => ("KB", true),
+        "megabyte" => ("MB", true),
+        "gigabyte" => ("GB", true),
+        "terabyte" => ("TB", true),
+        "millisecond" => ("ms", true),
+        "megahertz" => ("MHz", true),
+        "gigahertz" => ("GHz", true),
+        "millimeter" | "millimetre" => ("mm", true),
+        "centimeter" | "centimetre" => ("cm", true),
+        "meter" | "metre" => ("m", true),
+        "kilometer" | "kilometre" => ("km", true),
+        "kilogram" => ("kg", true),
+        "degree" => ("°", false),
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+/// A number with an optional decimal part or version components and an
+/// optional unit or percent sign.
+fn number(keys: &[&str], locale: NumberLocale, versioned: bool) -> Option<(usize, String)> {
+    let whole = cardinal(keys);
+    let (mut used, value) = year(keys)
+        .filter(|&(n, _)| n >= 2 && whole.map_or(0, |(c, _)| c) < n)
+        .or(whole)?;
+
+    let mut parts: Vec<String> = Vec::new();
+    while keys.get(used) == Some(&"point") {
+        let rest = &keys[used + 1..];
+        let spoken: String = rest
+            .iter()
+            .map_while(|k| digit(k))
+            .map(|d| d.to_string())
+            .collect();
+        let (n, part) = if !spoken.is_empty() {
+            (spoken.len(), spoken)
+        } else if let Some((n, v)) = cardinal(rest) {
+            (n, v.to_string())
+        } else {
+            break;
+        };
+        parts.push(part);
+        used += 1 + n;
+    }
+
+    let mut text = if parts.len() >= 2 || (versioned && !parts.is_empty()) {
+        std::iter::once(value.to_string())
+            .chain(parts.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(".")
+    } else if let Some(fraction) = parts.first() {
+        format!("{}{}{}", locale.integer(value), locale.decimal(), fraction)
+    } else {
+        locale.integer(value)
+    };
+
+    let next = keys.get(used).copied().unwrap_or_default();
+    let suffix = if next == "percent" {
+        Some((1, locale.percent().to_string()))
+    } else if next == "per" && keys.get(used + 1) == Some(&"cent") {
+        Some((2, locale.percent().to_string()))
+    } else {
+        unit_symbol(next).map(|(symbol, spaced)| {
+            let space = if spaced { " " } else { "" };
+            (1, format!("{}{}", space, symbol))
+        })
+    };
+    // Digits the transcriber wrote are left as they are.
+    let small = used == 1 && (value < 10 || digits(keys[0]).is_some());
+    match suffix {
+        Some((n, suffix)) => {
+            text.push_str(&suffix);
+            used += n;
+        }
+        None if small && parts.is_empty() => return None,
+        None => {}
+    }
+    Some((used, text))
+}
+
+struct Word<'a> {
+    /// Punctuation before the word, e.g. an opening parenthesis.
+    lead: &'a str,
+    core: &'a str,
+    /// Punctuation after it.
+    trail: &'a str,
+    /// Whitespace (or a hyphen) up to the next word.
+    sep: &'a str,
+    key: String,
+}
+
+impl Word<'_> {
+    fn original(&self) -> String {
+        [self.lead, self.core, self.trail, self.sep].concat()
+    }
+}
+
+fn push_word<'a>(words: &mut Vec<Word<'a>>, word: &'a str, sep: &'a str) {
+    let is_edge = |c: char| !c.is_alphanumeric();
+    let rest = word.trim_start_matches(is_edge);
+    let lead = &word[..word.len() - rest.len()];
+    let core = rest.trim_end_matches(is_edge);
+    let trail = &rest[core.len()..];
+
+    let parts: Vec<&str> = core.split('-').collect();
+    if parts.len() > 1 && parts.iter().all(|p| is_number_word(&p.to_lowercase())) {
+        let last = parts.len() - 1;
+        for (i, part) in parts.into_iter().enumerate() {
+            words.push(Word {
+                lead: if i == 0 { lead } else { "" },
+                core: part,
+                trail: if i == last { trail } else { "" },
+                sep: if i == last { sep } else { "-" },
+                key: part.to_lowercase(),
+            });
+        }
+        return;
+    }
+    words.push(Word {
+        lead,
+        core,
+        trail,
+        sep,
+        key: core.to_lowercase(),
+    });
+}
+
+/// Words of `text` with what separates them, and the whitespace before the
+/// first.
+fn tokenize(text: &str) -> (&str, Vec<Word<'_>>) {
+    let prefix = &text[..text.len() - text.trim_start().len()];
+    let mut rest = &text[prefix.len()..];
+    let mut words = Vec::new();
+    while !rest.is_empty() {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (word, after) = rest.split_at(end);
+        let (sep, next) = after.split_at(after.len() - after.trim_start().len());
+        push_word(&mut words, word, sep);
+        rest = next;
+    }
+    (prefix, words)
+}
+
+/// The last word a number starting at `start` may take in: numbers stop at
+/// punctuation and line breaks.
+fn run_end(words: &[Word], start: usize) -> usize {
+    let mut end = start;
+    while end + 1 < words.len()
+        && words[end].trail.is_empty()
+        && !words[end].sep.contains('\n')
+        && words[end + 1].lead.is_empty()
+    {
+        end += 1;
+    }
+    end
+}
+
+/// `text` with its spoken numbers, dates, times and versions written out
+/// for `settings.locale`.
+pub fn apply(settings: &NumberSettings, text: &str) -> String {
+    let locale = settings.locale;
+    let (prefix, words) = tokenize(text);
+    let mut out = String::from(prefix);
+    let mut i = 0;
+    while i < words.len() {
+        let end = run_end(&words, i);
+        let keys: Vec<&str> = words[i..=end].iter().map(|w| w.key.as_str()).collect();
+        let versioned = i > 0 && matches!(words[i - 1].key.as_str(), "version" | "v");
+        let parsed = date(&keys, locale)
+            .or_else(|| time(&keys, locale))
+            .or_else(|| number(&keys, locale, versioned));
+        match parsed {
+            Some((n, text)) => {
+                let last = &words[i + n - 1];
+                out.push_str(words[i].lead);
+                out.push_str(&text);
+                out.push_str(last.trail);
+                out.push_str(last.sep);
+                i += n;
+            }
+            None => {
+                out.push_str(&words[i].original());
+                i += 1;
+            }
+        }
+    }
+    out
+}
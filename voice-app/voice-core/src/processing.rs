@@ -0,0 +1,114 @@
+//! Named environment presets for capture processing. Each bundles a gain,
+//! a high-pass filter against rumble, and noise suppression that turns down
+//! audio below a voice-activity threshold.
+
+use serde::{Deserialize, Serialize};
+
+use crate::dsp::Processor;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Processing {
+    pub gain_db: f32,
+    /// High-pass cutoff; 0 turns the filter off.
+    pub high_pass_hz: f32,
+    /// How far audio below `vad_threshold_dbfs` is turned down, from 0 (not
+    /// at all) to 1 (muted).
+    pub noise_suppression: f32,
+    /// Level below which audio counts as background rather than speech.
+    pub vad_threshold_dbfs: f32,
+}
+
+impl Default for Processing {
+    fn default() -> Self {
+        Self {
+            gain_db: 0.0,
+            high_pass_hz: 0.0,
+            noise_suppression: 0.0,
+            vad_threshold_dbfs: -60.0,
+        }
+    }
+}
+
+impl Processing {
+    pub fn processor(&self, sample_rate: f32) -> Processor {
+        Processor::new(
+            self.gain_db,
+            self.high_pass_hz,
+            self.noise_suppression,
+            self.vad_threshold_dbfs,
+            sample_rate,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    /// No processing.
+    #[default]
+    Off,
+    QuietOffice,
+    NoisyCafe,
+    /// Strong high-pass against engine and road rumble.
+    Car,
+    /// `capture.custom`.
+    Custom,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 5] = [
+        Preset::Off,
+        Preset::QuietOffice,
+        Preset::NoisyCafe,
+        Preset::Car,
+        Preset::Custom,
+    ];
+
+    /// Same as the serialized name.
+    pub fn id(self) -> &'static str {
+        match self {
+            Preset::Off => "off",
+            Preset::QuietOffice => "quiet_office",
+            Preset::NoisyCafe => "noisy_cafe",
+            Preset::Car => "car",
+            Preset::Custom => "custom",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Preset::Off => "Off",
+            Preset::QuietOffice => "Quiet office",
+            Preset::NoisyCafe => "Noisy cafe",
+            Preset::Car => "Car",
+            Preset::Custom => "Custom",
+        }
+    }
+
+    /// `None` for `Off`; `custom` for `Custom`.
+    pub fn processing(self, custom: &Processing) -> Option<Processing> {
+        match self {
+            Preset::Off => None,
+            Preset::QuietOffice => Some(Processing {
+                gain_db: 0.0,
+                high_pass_hz: 80.0,
+                noise_suppression: 0.3,
+                vad_threshold_dbfs: -50.0,
+            }),
+            Preset::NoisyCafe => Some(Processing {
+                gain_db: 3.0,
+                high_pass_hz: 120.0,
+                noise_suppression: 0.8,
+                vad_threshold_dbfs: -38.0,
+            }),
+            Preset::Car => Some(Processing {
+                gain_db: 3.0,
+                high_pass_hz: 150.0,
+                noise_suppression: 0.7,
+                vad_threshold_dbfs: -42.0,
+            }),
+            Preset::Custom => Some(*custom),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Typed views of what the server sends back for a recording. Responses
+//! stay `serde_json::Value` end to end, since routes and servers add their
+//! own fields; these pick out the parts the app acts on.
+//!
+//! Confidence comes in several shapes: Whisper segments carry
+//! `avg_logprob` and, with word timestamps, per-word `probability`; other
+//! engines send `confidence` on segments, words or the whole response. All
+//! are read as a 0–1 score.
+//!
+//! Alternative transcripts, asked for with `TranscribeOptions::alternatives`,
+//! come back as `alternatives` or `n_best`: strings, or objects with `text`
+//! or `transcript` and optionally a score.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WordConfidence {
+    pub word: String,
+    /// Seconds from the start of the recording, when the server sent them.
+    pub start: Option<f32>,
+    pub end: Option<f32>,
+    pub confidence: Option<f32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SegmentConfidence {
+    pub text: String,
+    pub start: Option<f32>,
+    pub end: Option<f32>,
+    pub confidence: Option<f32>,
+    pub words: Vec<WordConfidence>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Confidence {
+    /// The response's own score, else the mean over segments.
+    pub overall: f32,
+    pub segments: Vec<SegmentConfidence>,
+}
+
+impl Confidence {
+    /// Words scoring below `threshold`, for highlighting.
+    pub fn low_words(&self, threshold: f32) -> impl Iterator<Item = &WordConfidence> {
+        self.segments
+            .iter()
+            .flat_map(|s| &s.words)
+            .filter(move |w| w.confidence.is_some_and(|c| c < threshold))
+    }
+
+    /// Segments scoring below `threshold`.
+    pub fn low_segments(&self, threshold: f32) -> impl Iterator<Item = &SegmentConfidence> {
+        self.segments
+            .iter()
+            .filter(move |s| s.confidence.is_some_and(|c| c < threshold))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Alternative {
+    pub text: String,
+    pub confidence: Option<f32>,
+}
+
+fn number(value: &Value, key: &str) -> Option<f32> {
+    value.get(key)?.as_f64().map(|v| v as f32)
+}
+
+fn score(value: &Value) -> Option<f32> {
+    number(value, "confidence")
+        .or_else(|| number(value, "probability"))
+        .map(|c| c.clamp(0.0, 1.0))
+}
+
+fn mean(scores: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = scores.fold((0.0, 0), |(sum, n), c| (sum + c, n + 1));
+    (count > 0).then_some(sum / count as f32)
+}
+
+fn word(value: &Value) -> Option<WordConfidence> {
+    let word = value
+        .get("word")
+        .or_else(|| value.get("text"))?
+        .as_str()?
+        .trim()
+        .to_string();
+    Some(WordConfidence {
+        word,
+        start: number(value, "start"),
+        end: number(value, "end"),
+        confidence: score(value),
+    })
+}
+
+fn words(value: &Value) -> Vec<WordConfidence> {
+    value
+        .get("words")
+        .and_then(Value::as_array)
+        .map(|words| words.iter().filter_map(word).collect())
+        .unwrap_or_default()
+}
+
+fn segment(value: &Value) -> Option<SegmentConfidence> {
+    let words = words(value);
+    let confidence = score(value)
+        .or_else(|| number(value, "avg_logprob").map(|lp| lp.exp().clamp(0.0, 1.0)))
+        .or_else(|| mean(words.iter().filter_map(|w| w.confidence)));
+    Some(SegmentConfidence {
+        text: value.get("text")?.as_str()?.trim().to_string(),
+        start: number(value, "start"),
+        end: number(value, "end"),
+        confidence,
+        words,
+    })
+}
+
+/// Confidence in `result`, or `None` when the server sent no scores at all.
+/// Words without segments are treated as one segment of the whole text.
+pub fn confidence(result: &Value) -> Option<Confidence> {
+    let mut segments: Vec<SegmentConfidence> = result
+        .get("segments")
+        .and_then(Value::as_array)
+        .map(|segments| segments.iter().filter_map(segment).collect())
+        .unwrap_or_default();
+    if segments.is_empty() {
+        let words = words(result);
+        if !words.is_empty() {
+            segments.push(SegmentConfidence {
+                text: words
+                    .iter()
+                    .map(|w| w.word.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                start: words.first().and_then(|w| w.start),
+                end: words.last().and_then(|w| w.end),
+                confidence: mean(words.iter().filter_map(|w| w.confidence)),
+                words,
+            });
+        }
+    }
+    let overall = score(result).or_else(|| mean(segments.iter().filter_map(|s| s.confidence)))?;
+    Some(Confidence { overall, segments })
+}
+
+fn alternative(value: &Value) -> Option<Alternative> {
+    let (text, confidence) = match value.as_str() {
+        Some(text) => (text, None),
+        None => (
+            value
+                .get("text")
+                .or_else(|| value.get("transcript"))?
+                .as_str()?,
+            score(value),
+        ),
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| Alternative {
+        text: text.to_string(),
+        confidence,
+    })
+}
+
+/// The alternative transcripts in `result`, best first, without the one
+/// that is already its text and without duplicates.
+pub fn alternatives(result: &Value) -> Vec<Alternative> {
+    let text = result
+        .get("transcribed_text")
+        .or_else(|| result.get("text"))
+        .and_then(Value::as_str)
+        .map(str::trim);
+    let Some(list) = ["alternatives", "n_best"]
+        .iter()
+        .find_map(|key| result.get(*key)?.as_array())
+    else {
+        return Vec::new();
+    };
+    let mut alternatives: Vec<Alternative> = Vec::new();
+    for alternative in list.iter().filter_map(alternative) {
+        let seen = Some(alternative.text.as_str()) == text
+            || alternatives.iter().any(|a| a.text == alternative.text);
+        if !seen {
+            alternatives.push(alternative);
+        }
+    }
+    alternatives
+}
@@ -0,0 +1,143 @@
+//! 16 kHz mono 16-bit WAV, the format the pipeline server accepts.
+
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::dsp;
+use crate::TARGET_SAMPLE_RATE;
+
+const CANCEL_CHECK_INTERVAL: usize = TARGET_SAMPLE_RATE as usize; // Once per second of audio
+
+pub const ENCODE_CANCELLED: &str = "Encoding cancelled";
+
+pub fn encode_wav(samples: &[i16]) -> Result<Vec<u8>, String> {
+    encode_wav_cancellable(samples, &AtomicBool::new(false))
+}
+
+/// As `encode_wav`, checking `cancel` once per second of audio and giving up
+/// with `ENCODE_CANCELLED` when it is set.
+pub fn encode_wav_cancellable(samples: &[i16], cancel: &AtomicBool) -> Result<Vec<u8>, String> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer =
+            WavWriter::new(&mut cursor, spec).map_err(|e| format!("WAV write error: {}", e))?;
+        for chunk in samples.chunks(CANCEL_CHECK_INTERVAL) {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(ENCODE_CANCELLED.into());
+            }
+            for &sample in chunk {
+                writer
+                    .write_sample(sample)
+                    .map_err(|e| format!("WAV sample error: {}", e))?;
+            }
+        }
+        writer
+            .finalize()
+            .map_err(|e| format!("WAV finalize error: {}", e))?;
+    }
+
+    Ok(cursor.into_inner())
+}
+
+const WAV_HEADER_LEN: usize = 44;
+
+/// Builds the same 16 kHz mono WAV as `encode_wav`, a few samples at a time,
+/// so a recording is already encoded when capture stops. The header sizes
+/// are filled in by `finish`.
+pub struct WavEncoder {
+    bytes: Vec<u8>,
+}
+
+impl Default for WavEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WavEncoder {
+    pub fn new() -> Self {
+        let mut bytes = Vec::with_capacity(WAV_HEADER_LEN);
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // Mono
+        bytes.extend_from_slice(&TARGET_SAMPLE_RATE.to_le_bytes());
+        bytes.extend_from_slice(&(TARGET_SAMPLE_RATE * 2).to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        Self { bytes }
+    }
+
+    pub fn push(&mut self, samples: &[i16]) {
+        self.bytes.reserve(samples.len() * 2);
+        for sample in samples {
+            self.bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+
+    /// Samples pushed so far.
+    pub fn len(&self) -> usize {
+        (self.bytes.len() - WAV_HEADER_LEN) / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        let data_len = (self.bytes.len() - WAV_HEADER_LEN) as u32;
+        self.bytes[4..8].copy_from_slice(&(data_len + 36).to_le_bytes());
+        self.bytes[40..44].copy_from_slice(&data_len.to_le_bytes());
+        self.bytes
+    }
+}
+
+/// Read a WAV file as 16 kHz mono, the format `transcribe` expects.
+pub fn read_wav_file(path: &Path) -> Result<Vec<i16>, String> {
+    let mut reader =
+        WavReader::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+
+    let interleaved: Vec<f32> = match (spec.sample_format, spec.bits_per_sample) {
+        (SampleFormat::Float, _) => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("WAV read error: {}", e))?,
+        (SampleFormat::Int, bits) if bits <= 32 => {
+            let scale = (1_i64 << (bits - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("WAV read error: {}", e))?
+        }
+        (_, bits) => return Err(format!("Unsupported WAV bit depth: {}", bits)),
+    };
+
+    let mono: Vec<i16> = interleaved
+        .chunks(channels)
+        .map(|frame| {
+            let avg = frame.iter().sum::<f32>() / frame.len() as f32;
+            (avg.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+        })
+        .collect();
+    Ok(dsp::resample_i16(
+        &mono,
+        spec.sample_rate,
+        TARGET_SAMPLE_RATE,
+    ))
+}
@@ -0,0 +1,75 @@
+//! The embeddable controller's lifecycle without a microphone: sends walk
+//! Encoding → Uploading → Done (or Error) and report each step, and calls
+//! out of order are refused by the state machine.
+
+#![cfg(feature = "capture")]
+
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use voice_core::controller::{can_transition, AppState, Controller, Server};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn recorded(controller: &Controller) -> Arc<Mutex<Vec<AppState>>> {
+    let states = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&states);
+    controller.on_state_change(Arc::new(move |state, _, _| {
+        sink.lock().unwrap().push(state)
+    }));
+    states
+}
+
+#[tokio::test]
+async fn send_reports_each_state() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/pipeline/run/audio"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"text": "hi"})))
+        .mount(&server)
+        .await;
+
+    let controller = Controller::default();
+    let states = recorded(&controller);
+    let target = Server {
+        url: server.uri(),
+        ..Default::default()
+    };
+    let result = controller
+        .send(vec![0, 1_000, -1_000].into(), &target)
+        .await
+        .unwrap();
+
+    assert_eq!(result["text"], "hi");
+    assert_eq!(
+        *states.lock().unwrap(),
+        [AppState::Encoding, AppState::Uploading, AppState::Done]
+    );
+}
+
+#[tokio::test]
+async fn failed_send_ends_in_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let controller = Controller::default();
+    let target = Server {
+        url: server.uri(),
+        ..Default::default()
+    };
+    assert!(controller.send(vec![0; 16].into(), &target).await.is_err());
+    assert_eq!(controller.state(), AppState::Error);
+}
+
+#[test]
+fn out_of_order_calls_are_refused() {
+    let controller = Controller::default();
+    assert!(controller.stop().is_err());
+    assert_eq!(controller.state(), AppState::Idle);
+    assert!(controller.cancel_send().is_err());
+    assert!(!can_transition(AppState::Uploading, AppState::Recording));
+    assert!(can_transition(AppState::Done, AppState::Recording));
+    assert!(can_transition(AppState::Uploading, AppState::Idle));
+}
@@ -0,0 +1,39 @@
+//! The default filter for virtual capture devices, which record the system
+//! output or nothing instead of the mic.
+
+#![cfg(feature = "capture")]
+
+use voice_core::devices::DeviceFilter;
+
+#[test]
+fn default_filter_hides_virtual_inputs() {
+    let filter = DeviceFilter::default();
+    for name in [
+        "Stereo Mix (Realtek High Definition Audio)",
+        "Monitor of Built-in Audio Analog Stereo",
+        "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor",
+        "Loopback Audio",
+    ] {
+        assert!(filter.hides(name), "{} should be hidden", name);
+    }
+}
+
+#[test]
+fn default_filter_keeps_microphones() {
+    let filter = DeviceFilter::default();
+    for name in [
+        "MacBook Pro Microphone",
+        "USB Audio Device",
+        "default",
+        "pulse",
+    ] {
+        assert!(!filter.hides(name), "{} should be listed", name);
+    }
+}
+
+#[test]
+fn patterns_are_case_insensitive() {
+    let filter = DeviceFilter(vec!["Virtual".into()]);
+    assert!(filter.hides("BlackHole VIRTUAL 2ch"));
+    assert!(!filter.hides("Stereo Mix"));
+}
@@ -0,0 +1,62 @@
+//! Spoken punctuation and formatting commands, with the default table and
+//! a user-edited one.
+
+use voice_core::dictation::{apply, DictationSettings, SpokenAction, SpokenCommand};
+
+fn format(text: &str) -> String {
+    apply(&DictationSettings::default(), text)
+}
+
+#[test]
+fn punctuation_joins_the_word_before() {
+    assert_eq!(
+        format("Hello comma world period how are you question mark"),
+        "Hello, world. How are you?"
+    );
+}
+
+#[test]
+fn transcriber_punctuation_around_commands_is_replaced() {
+    assert_eq!(format("Hello, comma, world."), "Hello, world.");
+    assert_eq!(format("Done. Period."), "Done.");
+}
+
+#[test]
+fn brackets_lines_and_joins() {
+    assert_eq!(
+        format("print open paren x close paren new line return"),
+        "print (x)\nReturn"
+    );
+    assert_eq!(format("open main dot rs"), "open main.rs");
+}
+
+#[test]
+fn all_caps_until_turned_off() {
+    assert_eq!(
+        format("all caps on pull request all caps off merged"),
+        "PULL REQUEST merged"
+    );
+}
+
+#[test]
+fn escape_word_keeps_the_phrase() {
+    assert_eq!(
+        format("type literal comma and literal new line"),
+        "type comma and new line"
+    );
+    assert_eq!(format("the literal meaning"), "the literal meaning");
+}
+
+#[test]
+fn user_commands_extend_the_table() {
+    let mut settings = DictationSettings::default();
+    settings.commands.push(SpokenCommand {
+        phrase: "smiley face".into(),
+        action: SpokenAction::Word { text: ":)".into() },
+    });
+    settings.commands.retain(|c| c.phrase != "period");
+    assert_eq!(
+        apply(&settings, "ship it smiley face period"),
+        "ship it :) period"
+    );
+}
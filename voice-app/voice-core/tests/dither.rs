@@ -0,0 +1,32 @@
+//! Tests for the optional TPDF dither on the float-to-16-bit conversion.
+
+use voice_core::dsp::{to_i16, Dither};
+
+#[test]
+fn noise_stays_within_one_lsb() {
+    let mut dither = Dither::default();
+    for _ in 0..10_000 {
+        assert!(dither.to_i16(0.0).abs() <= 1);
+    }
+}
+
+#[test]
+fn dither_is_unbiased() {
+    // A level between two codes averages out to that level, where plain
+    // conversion always lands on the same code.
+    let level = 100.25 / i16::MAX as f32;
+    let mut dither = Dither::default();
+    let n = 100_000;
+    let mean = (0..n).map(|_| dither.to_i16(level) as f64).sum::<f64>() / n as f64;
+    assert!((mean - 100.25).abs() < 0.05, "mean was {}", mean);
+    assert_eq!(to_i16(level), 100);
+}
+
+#[test]
+fn full_scale_does_not_wrap() {
+    let mut dither = Dither::default();
+    for _ in 0..1_000 {
+        assert!(dither.to_i16(1.0) > 32_000);
+        assert!(dither.to_i16(-1.0) < -32_000);
+    }
+}
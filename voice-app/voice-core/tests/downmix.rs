@@ -0,0 +1,52 @@
+//! Tests for the configurable downmixes in `dsp`: per-channel weights, and
+//! following the loudest channel of a multi-input interface.
+
+use voice_core::dsp::{weighted_mono, LoudestChannel};
+
+/// Interleaved stereo with a tone on `live` and silence on the other.
+fn one_live_channel(live: usize, frames: usize) -> Vec<f32> {
+    (0..frames)
+        .flat_map(|i| {
+            let s = if i % 2 == 0 { 0.5 } else { -0.5 };
+            if live == 0 {
+                [s, 0.0]
+            } else {
+                [0.0, s]
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn weights_scale_and_sum_channels() {
+    let data = [0.2, 0.4, -0.2, -0.4];
+    assert_eq!(weighted_mono(&data, 2, &[1.0, 0.0]), vec![0.2, -0.2]);
+    assert_eq!(weighted_mono(&data, 2, &[1.0, 1.0]), vec![0.6, -0.6]);
+    // A missing weight drops the channel.
+    assert_eq!(weighted_mono(&data, 2, &[0.5]), vec![0.1, -0.1]);
+}
+
+#[test]
+fn weighted_sum_is_clamped() {
+    assert_eq!(weighted_mono(&[0.8, 0.8], 2, &[1.0, 1.0]), vec![1.0]);
+}
+
+#[test]
+fn loudest_follows_the_live_channel() {
+    let mut loudest = LoudestChannel::default();
+    assert_eq!(loudest.pick(&one_live_channel(1, 480), 2), 1);
+    for _ in 0..20 {
+        loudest.pick(&one_live_channel(0, 480), 2);
+    }
+    assert_eq!(loudest.pick(&one_live_channel(0, 480), 2), 0);
+}
+
+#[test]
+fn loudest_holds_between_similar_channels() {
+    let mut loudest = LoudestChannel::default();
+    let balanced: Vec<f32> = (0..480).flat_map(|_| [0.5, 0.45]).collect();
+    let first = loudest.pick(&one_live_channel(1, 480), 2);
+    for _ in 0..20 {
+        assert_eq!(loudest.pick(&balanced, 2), first);
+    }
+}
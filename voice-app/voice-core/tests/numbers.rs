@@ -0,0 +1,94 @@
+//! Spoken numbers, dates, times, units and versions, per locale.
+
+use voice_core::numbers::{apply, NumberLocale, NumberSettings};
+
+fn format(locale: NumberLocale, text: &str) -> String {
+    let settings = NumberSettings {
+        enabled: true,
+        locale,
+    };
+    apply(&settings, text)
+}
+
+fn us(text: &str) -> String {
+    format(NumberLocale::EnUs, text)
+}
+
+#[test]
+fn cardinals_become_digits_but_small_numbers_stay_words() {
+    assert_eq!(us("twenty three files changed"), "23 files changed");
+    assert_eq!(us("one hundred and five tests"), "105 tests");
+    assert_eq!(us("twenty-one jobs"), "21 jobs");
+    assert_eq!(us("one of the three builds"), "one of the three builds");
+}
+
+#[test]
+fn large_numbers_are_grouped_by_locale() {
+    let text = "forty two thousand requests";
+    assert_eq!(us(text), "42,000 requests");
+    assert_eq!(format(NumberLocale::SvSe, text), "42\u{a0}000 requests");
+    assert_eq!(us("two thousand five hundred"), "2500");
+}
+
+#[test]
+fn decimals_use_the_locale_separator() {
+    assert_eq!(us("three point one four"), "3.14");
+    assert_eq!(format(NumberLocale::SvSe, "three point five"), "3,5");
+}
+
+#[test]
+fn versions_keep_dots() {
+    assert_eq!(us("bump to one point two point three"), "bump to 1.2.3");
+    assert_eq!(
+        format(NumberLocale::SvSe, "one point twelve point oh"),
+        "1.12.0"
+    );
+    assert_eq!(us("version two point oh"), "version 2.0");
+}
+
+#[test]
+fn units_and_percentages() {
+    assert_eq!(us("five megabytes"), "5 MB");
+    assert_eq!(
+        us("a timeout of two hundred milliseconds."),
+        "a timeout of 200 ms."
+    );
+    assert_eq!(us("fifty percent"), "50%");
+    assert_eq!(format(NumberLocale::SvSe, "fifty percent"), "50\u{a0}%");
+    assert_eq!(us("twenty degrees"), "20°");
+}
+
+#[test]
+fn times_follow_the_locale_clock() {
+    assert_eq!(us("deploy at three thirty pm"), "deploy at 3:30 PM");
+    assert_eq!(format(NumberLocale::EnGb, "seven oh five am"), "7:05 am");
+    assert_eq!(format(NumberLocale::SvSe, "three thirty pm"), "15:30");
+    assert_eq!(us("ten o'clock"), "10:00");
+}
+
+#[test]
+fn dates_follow_the_locale_order() {
+    let text = "due march third twenty twenty four";
+    assert_eq!(us(text), "due March 3, 2024");
+    assert_eq!(format(NumberLocale::EnGb, text), "due 3 March 2024");
+    assert_eq!(format(NumberLocale::SvSe, text), "due 2024-03-03");
+    assert_eq!(us("on the twenty first of june"), "on June 21");
+}
+
+#[test]
+fn ambiguous_months_need_a_year() {
+    assert_eq!(us("I may second that"), "I may second that");
+    assert_eq!(us("may fifth two thousand ten"), "May 5, 2010");
+}
+
+#[test]
+fn numbers_stop_at_punctuation_and_keep_it() {
+    assert_eq!(us("Twenty, thirty."), "20, 30.");
+    assert_eq!(us("(twenty three)"), "(23)");
+    assert_eq!(us("twenty\nthree"), "20\nthree");
+}
+
+#[test]
+fn written_digits_are_left_alone() {
+    assert_eq!(us("PR 12345 is in 2024"), "PR 12345 is in 2024");
+}
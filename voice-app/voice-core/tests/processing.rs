@@ -0,0 +1,66 @@
+//! Tests for the capture processing behind the environment presets.
+
+use voice_core::dsp::Processor;
+
+const RATE: f32 = 16_000.0;
+
+fn sine(freq: f32, amplitude: f32, secs: f32) -> Vec<f32> {
+    (0..(RATE * secs) as usize)
+        .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / RATE).sin())
+        .collect()
+}
+
+/// Peak of the second half, once filters and the gate have settled.
+fn settled_peak(samples: &[f32]) -> f32 {
+    samples[samples.len() / 2..]
+        .iter()
+        .fold(0.0, |peak, s| peak.max(s.abs()))
+}
+
+#[test]
+fn neutral_settings_pass_audio_through() {
+    let mut processor = Processor::new(0.0, 0.0, 0.0, -60.0, RATE);
+    let input = sine(440.0, 0.5, 0.1);
+    let mut output = input.clone();
+    processor.process(&mut output);
+    assert_eq!(input, output);
+}
+
+#[test]
+fn high_pass_removes_rumble_but_keeps_voice() {
+    let mut rumble = sine(30.0, 0.5, 1.0);
+    let mut voice = sine(1_000.0, 0.5, 1.0);
+    Processor::new(0.0, 150.0, 0.0, -60.0, RATE).process(&mut rumble);
+    Processor::new(0.0, 150.0, 0.0, -60.0, RATE).process(&mut voice);
+    assert!(
+        settled_peak(&rumble) < 0.05,
+        "rumble {}",
+        settled_peak(&rumble)
+    );
+    assert!(
+        settled_peak(&voice) > 0.48,
+        "voice {}",
+        settled_peak(&voice)
+    );
+}
+
+#[test]
+fn gate_turns_down_audio_below_threshold() {
+    // -46 dBFS background against a -40 dBFS threshold.
+    let mut background = sine(440.0, 0.005, 1.0);
+    let mut speech = sine(440.0, 0.3, 1.0);
+    Processor::new(0.0, 0.0, 0.8, -40.0, RATE).process(&mut background);
+    Processor::new(0.0, 0.0, 0.8, -40.0, RATE).process(&mut speech);
+    assert!((settled_peak(&background) - 0.001).abs() < 0.0002);
+    assert!(settled_peak(&speech) > 0.29);
+}
+
+#[test]
+fn gain_is_applied_and_clamped() {
+    let mut quiet = sine(440.0, 0.1, 0.1);
+    let mut loud = sine(440.0, 0.9, 0.1);
+    Processor::new(6.0, 0.0, 0.0, -60.0, RATE).process(&mut quiet);
+    Processor::new(6.0, 0.0, 0.0, -60.0, RATE).process(&mut loud);
+    assert!((settled_peak(&quiet) - 0.1995).abs() < 0.002);
+    assert!(settled_peak(&loud) <= 1.0);
+}
@@ -0,0 +1,126 @@
+//! Property tests for the resamplers in `dsp`. Capture rates are whatever
+//! the device reports, so the rate strategy covers the common hardware rates
+//! in both directions around the 16 kHz target.
+
+use proptest::prelude::*;
+use voice_core::dsp::{decimate_i16, resample_i16, resample_linear_i16};
+
+const RATES: [u32; 7] = [8_000, 16_000, 22_050, 32_000, 44_100, 48_000, 96_000];
+
+fn rate() -> impl Strategy<Value = u32> {
+    prop::sample::select(&RATES[..])
+}
+
+fn samples() -> impl Strategy<Value = Vec<i16>> {
+    prop::collection::vec(any::<i16>(), 0..4_000)
+}
+
+proptest! {
+    #[test]
+    fn equal_rates_are_identity(input in samples(), rate in rate()) {
+        prop_assert_eq!(resample_linear_i16(&input, rate, rate), input);
+    }
+
+    #[test]
+    fn output_length_follows_rate_ratio(
+        input in prop::collection::vec(any::<i16>(), 2..4_000),
+        from in rate(),
+        to in rate(),
+    ) {
+        let expected = input.len() as f64 * to as f64 / from as f64;
+        // The last input sample has no right neighbour to interpolate
+        // towards, so up to one input step of output may be missing.
+        let slack = (to as f64 / from as f64).max(1.0) + 1.0;
+        let actual = resample_linear_i16(&input, from, to).len() as f64;
+        prop_assert!(
+            (actual - expected).abs() <= slack,
+            "{} samples {} -> {} Hz gave {}, expected ~{}",
+            input.len(), from, to, actual, expected
+        );
+    }
+
+    #[test]
+    fn output_stays_within_input_range(input in samples(), from in rate(), to in rate()) {
+        prop_assume!(!input.is_empty());
+        let lo = *input.iter().min().unwrap();
+        let hi = *input.iter().max().unwrap();
+        for sample in resample_linear_i16(&input, from, to) {
+            prop_assert!((lo..=hi).contains(&sample));
+        }
+    }
+
+    #[test]
+    fn rising_input_stays_rising(
+        start in -16_000i16..0,
+        len in 2usize..4_000,
+        from in rate(),
+        to in rate(),
+    ) {
+        // A ramp checks that output time only moves forward: any reordering
+        // or repeated read position would break monotonicity.
+        let input: Vec<i16> = (0..len).map(|i| start + i as i16).collect();
+        let output = resample_linear_i16(&input, from, to);
+        for pair in output.windows(2) {
+            prop_assert!(pair[0] <= pair[1], "{} then {}", pair[0], pair[1]);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn decimation_keeps_every_nth_position(input in samples(), factor in 2usize..7) {
+        prop_assert_eq!(decimate_i16(&input, factor).len(), input.len().div_ceil(factor));
+    }
+
+    #[test]
+    fn decimation_passes_dc_unchanged(level in any::<i16>(), len in 1usize..4_000, factor in 2usize..7) {
+        for sample in decimate_i16(&vec![level; len], factor) {
+            prop_assert!((sample as i32 - level as i32).abs() <= 1, "{} became {}", level, sample);
+        }
+    }
+
+    #[test]
+    fn decimation_rejects_energy_above_output_nyquist(amplitude in 1_000i16..i16::MAX, factor in 2usize..7) {
+        // Alternating samples sit at the input Nyquist, far above what the
+        // output can represent; linear interpolation would alias this back.
+        let input: Vec<i16> = (0..2_000).map(|i| if i % 2 == 0 { amplitude } else { -amplitude }).collect();
+        let output = decimate_i16(&input, factor);
+        let interior = &output[20..output.len() - 20];
+        let peak = interior.iter().map(|s| s.unsigned_abs()).max().unwrap();
+        prop_assert!(peak <= amplitude.unsigned_abs() / 100, "peak {} from {}", peak, amplitude);
+    }
+
+    #[test]
+    fn auto_resampler_matches_rate_ratio(input in samples(), from in rate()) {
+        let output = resample_i16(&input, from, 16_000);
+        let expected = input.len() as f64 * 16_000.0 / from as f64;
+        prop_assert!((output.len() as f64 - expected).abs() <= 2.0, "{} vs {}", output.len(), expected);
+    }
+}
+
+#[test]
+fn empty_input_gives_empty_output() {
+    assert!(resample_linear_i16(&[], 48_000, 16_000).is_empty());
+}
+
+#[test]
+fn single_sample_is_kept() {
+    assert_eq!(resample_linear_i16(&[1234], 48_000, 16_000), vec![1234]);
+}
+
+#[test]
+fn integer_ratios_take_the_decimator() {
+    let input: Vec<i16> = (0..4_800).map(|i| (i % 200) as i16).collect();
+    assert_eq!(
+        resample_i16(&input, 48_000, 16_000),
+        decimate_i16(&input, 3)
+    );
+    assert_eq!(
+        resample_i16(&input, 32_000, 16_000),
+        decimate_i16(&input, 2)
+    );
+    assert_eq!(
+        resample_i16(&input, 44_100, 16_000),
+        resample_linear_i16(&input, 44_100, 16_000)
+    );
+}
@@ -0,0 +1,107 @@
+//! Confidence read from the response shapes servers send: Whisper segments
+//! with `avg_logprob` and word `probability`, explicit `confidence` scores,
+//! and bare word lists. Alternatives from `alternatives` or `n_best` lists.
+
+use serde_json::json;
+use voice_core::transcript::{alternatives, confidence};
+
+#[test]
+fn whisper_segments_use_avg_logprob_and_word_probability() {
+    let result = json!({
+        "text": "deploy the branch",
+        "segments": [{
+            "text": " deploy the branch",
+            "start": 0.0,
+            "end": 1.5,
+            "avg_logprob": -0.1,
+            "words": [
+                {"word": " deploy", "start": 0.0, "end": 0.4, "probability": 0.95},
+                {"word": " the", "start": 0.4, "end": 0.6, "probability": 0.9},
+                {"word": " branch", "start": 0.6, "end": 1.5, "probability": 0.3}
+            ]
+        }]
+    });
+    let confidence = confidence(&result).expect("scored");
+    let segment = &confidence.segments[0];
+    assert_eq!(segment.text, "deploy the branch");
+    assert!((segment.confidence.unwrap() - (-0.1f32).exp()).abs() < 1e-6);
+    assert_eq!(segment.words[0].word, "deploy");
+    assert_eq!(confidence.overall, segment.confidence.unwrap());
+
+    let low: Vec<_> = confidence.low_words(0.5).map(|w| w.word.as_str()).collect();
+    assert_eq!(low, ["branch"]);
+}
+
+#[test]
+fn explicit_scores_win_and_are_clamped() {
+    let result = json!({
+        "text": "hi",
+        "confidence": 1.4,
+        "segments": [{"text": "hi", "confidence": 0.4, "avg_logprob": -0.01}]
+    });
+    let confidence = confidence(&result).expect("scored");
+    assert_eq!(confidence.overall, 1.0);
+    assert_eq!(confidence.segments[0].confidence, Some(0.4));
+    assert_eq!(confidence.low_segments(0.5).count(), 1);
+}
+
+#[test]
+fn segment_without_score_averages_its_words() {
+    let result = json!({
+        "segments": [{
+            "text": "a b",
+            "words": [{"word": "a", "confidence": 0.2}, {"word": "b", "confidence": 0.6}]
+        }]
+    });
+    let confidence = confidence(&result).expect("scored");
+    assert!((confidence.overall - 0.4).abs() < 1e-6);
+}
+
+#[test]
+fn bare_words_become_one_segment() {
+    let result = json!({
+        "text": "run tests",
+        "words": [
+            {"word": "run", "start": 0.1, "end": 0.3, "confidence": 0.8},
+            {"word": "tests", "start": 0.3, "end": 0.7, "confidence": 0.6}
+        ]
+    });
+    let confidence = confidence(&result).expect("scored");
+    assert_eq!(confidence.segments.len(), 1);
+    let segment = &confidence.segments[0];
+    assert_eq!(segment.text, "run tests");
+    assert_eq!((segment.start, segment.end), (Some(0.1), Some(0.7)));
+}
+
+#[test]
+fn unscored_responses_have_no_confidence() {
+    assert!(confidence(&json!({"text": "hi"})).is_none());
+    assert!(confidence(&json!({"text": "hi", "segments": [{"text": "hi"}]})).is_none());
+}
+
+#[test]
+fn alternatives_skip_the_chosen_text_and_duplicates() {
+    let result = json!({
+        "text": "check the cube cuddle logs",
+        "alternatives": [
+            "check the cube cuddle logs",
+            {"text": "check the kubectl logs", "confidence": 0.7},
+            {"transcript": " check the kubectl logs "},
+            {"transcript": "check the cube control logs"}
+        ]
+    });
+    let texts: Vec<_> = alternatives(&result).into_iter().map(|a| a.text).collect();
+    assert_eq!(
+        texts,
+        ["check the kubectl logs", "check the cube control logs"]
+    );
+    assert_eq!(alternatives(&result)[0].confidence, Some(0.7));
+}
+
+#[test]
+fn n_best_is_read_when_there_is_no_alternatives_list() {
+    let result = json!({"transcribed_text": "a", "n_best": ["a", "b", ""]});
+    let texts: Vec<_> = alternatives(&result).into_iter().map(|a| a.text).collect();
+    assert_eq!(texts, ["b"]);
+    assert!(alternatives(&json!({"text": "a"})).is_empty());
+}
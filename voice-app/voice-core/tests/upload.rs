@@ -0,0 +1,395 @@
+//! Routing tests for `client::upload_wav_with_key` against a mock server: the
+//! pipeline route is preferred, 404/405 fall back to `/api/transcribe`, and
+//! any other failure is reported without trying the fallback, and a server
+//! without the pipeline route is remembered as such. Timeouts are told
+//! apart from other connection failures. Signed
+//! uploads carry an HMAC of the exact body sent. WAVs encoded during capture
+//! match the ones encoded at send time. Transcription options go out as
+//! form fields next to the audio.
+
+use serde_json::json;
+use voice_core::client::{
+    upload_wav_with_key, upload_wav_with_options, RouteCache, TranscribeOptions,
+};
+use voice_core::signing::{signature, Signer, SigningSettings};
+use voice_core::wav::{encode_wav, WavEncoder};
+use wiremock::matchers::{header, header_regex, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const PIPELINE: &str = "/api/pipeline/run/audio";
+const TRANSCRIBE: &str = "/api/transcribe";
+
+fn client() -> reqwest::Client {
+    reqwest::Client::new()
+}
+
+fn routes() -> RouteCache {
+    RouteCache::default()
+}
+
+fn wav() -> Vec<u8> {
+    encode_wav(&[0, 1_000, -1_000, 0]).expect("encode test WAV")
+}
+
+async fn mount(server: &MockServer, route: &str, response: ResponseTemplate, calls: u64) {
+    Mock::given(method("POST"))
+        .and(path(route))
+        .respond_with(response)
+        .expect(calls)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn pipeline_success_skips_fallback() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        PIPELINE,
+        ResponseTemplate::new(200).set_body_json(json!({"text": "hi"})),
+        1,
+    )
+    .await;
+    mount(&server, TRANSCRIBE, ResponseTemplate::new(200), 0).await;
+
+    let result = upload_wav_with_key(&client(), &routes(), &wav(), &server.uri(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result["text"], "hi");
+    assert_eq!(result["_endpoint_used"], "pipeline_run_audio");
+}
+
+#[tokio::test]
+async fn not_found_falls_back_to_transcribe() {
+    let server = MockServer::start().await;
+    mount(&server, PIPELINE, ResponseTemplate::new(404), 1).await;
+    mount(
+        &server,
+        TRANSCRIBE,
+        ResponseTemplate::new(200).set_body_json(json!({"text": "hi"})),
+        1,
+    )
+    .await;
+
+    let result = upload_wav_with_key(&client(), &routes(), &wav(), &server.uri(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result["text"], "hi");
+    assert_eq!(result["_endpoint_used"], "transcribe_fallback");
+}
+
+#[tokio::test]
+async fn method_not_allowed_falls_back_to_transcribe() {
+    let server = MockServer::start().await;
+    mount(&server, PIPELINE, ResponseTemplate::new(405), 1).await;
+    mount(
+        &server,
+        TRANSCRIBE,
+        ResponseTemplate::new(200).set_body_json(json!({})),
+        1,
+    )
+    .await;
+
+    let result = upload_wav_with_key(&client(), &routes(), &wav(), &server.uri(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result["_endpoint_used"], "transcribe_fallback");
+}
+
+#[tokio::test]
+async fn pipeline_server_error_does_not_fall_back() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        PIPELINE,
+        ResponseTemplate::new(500).set_body_string("boom"),
+        1,
+    )
+    .await;
+    mount(&server, TRANSCRIBE, ResponseTemplate::new(200), 0).await;
+
+    let err = upload_wav_with_key(&client(), &routes(), &wav(), &server.uri(), None, None)
+        .await
+        .unwrap_err();
+    assert!(err.is_outage(), "{}", err);
+    let err = err.to_string();
+    assert!(err.contains("500"), "{}", err);
+    assert!(err.contains("/api/pipeline/run/audio"), "{}", err);
+    assert!(err.contains("boom"), "{}", err);
+}
+
+#[tokio::test]
+async fn fallback_server_error_is_reported() {
+    let server = MockServer::start().await;
+    mount(&server, PIPELINE, ResponseTemplate::new(404), 1).await;
+    mount(
+        &server,
+        TRANSCRIBE,
+        ResponseTemplate::new(503).set_body_string("busy"),
+        1,
+    )
+    .await;
+
+    let err = upload_wav_with_key(&client(), &routes(), &wav(), &server.uri(), None, None)
+        .await
+        .unwrap_err();
+    let err = err.to_string();
+    assert!(err.contains("503"), "{}", err);
+    assert!(err.contains("busy"), "{}", err);
+}
+
+#[tokio::test]
+async fn non_json_body_is_an_error() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        PIPELINE,
+        ResponseTemplate::new(200).set_body_string("ok"),
+        1,
+    )
+    .await;
+
+    let err = upload_wav_with_key(&client(), &routes(), &wav(), &server.uri(), None, None)
+        .await
+        .unwrap_err();
+    assert!(!err.is_outage(), "{}", err);
+    assert!(err.to_string().contains("pipeline_run_audio"), "{}", err);
+}
+
+#[tokio::test]
+async fn non_object_body_is_returned_unannotated() {
+    let server = MockServer::start().await;
+    mount(
+        &server,
+        PIPELINE,
+        ResponseTemplate::new(200).set_body_json(json!(["a", "b"])),
+        1,
+    )
+    .await;
+
+    let result = upload_wav_with_key(&client(), &routes(), &wav(), &server.uri(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(result, json!(["a", "b"]));
+}
+
+#[tokio::test]
+async fn uploads_multipart_audio_with_bearer_token() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(PIPELINE))
+        .and(header("authorization", "Bearer secret"))
+        .and(header_regex(
+            "content-type",
+            "^multipart/form-data; boundary=",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Trailing slash must not produce `//api/...`.
+    let url = format!("{}/", server.uri());
+    upload_wav_with_key(&client(), &routes(), &wav(), &url, Some("secret"), None)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn options_are_sent_as_form_fields() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(PIPELINE))
+        .and(|request: &Request| {
+            let body = String::from_utf8_lossy(&request.body);
+            [
+                "name=\"n_best\"\r\n\r\n3\r\n",
+                "name=\"hotwords\"\r\n\r\nkubectl, Jules\r\n",
+                "name=\"initial_prompt\"\r\n\r\nGlossary: kubectl, Jules.\r\n",
+                "name=\"language\"\r\n\r\nsv\r\n",
+            ]
+            .iter()
+            .all(|field| body.contains(field))
+        })
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let options = TranscribeOptions {
+        alternatives: 3,
+        vocabulary: vec!["kubectl".into(), "Jules".into()],
+        language: Some("sv".into()),
+        ..Default::default()
+    };
+    upload_wav_with_options(
+        &client(),
+        &routes(),
+        &wav(),
+        &server.uri(),
+        None,
+        None,
+        &options,
+    )
+    .await
+    .unwrap();
+}
+
+#[test]
+fn vocabulary_becomes_a_glossary_prompt() {
+    let options = TranscribeOptions {
+        vocabulary: vec![
+            "kubectl".into(),
+            " Jules ".into(),
+            "".into(),
+            "kubectl".into(),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(
+        options.prompt().as_deref(),
+        Some("Glossary: kubectl, Jules.")
+    );
+    assert_eq!(TranscribeOptions::default().prompt(), None);
+}
+
+#[test]
+fn context_comes_before_the_glossary() {
+    let options = TranscribeOptions {
+        vocabulary: vec!["kubectl".into()],
+        context: vec![
+            "rerun the deploy workflow".into(),
+            " ".into(),
+            "Git branch: main".into(),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(
+        options.context_text().as_deref(),
+        Some("rerun the deploy workflow\nGit branch: main")
+    );
+    assert_eq!(
+        options.prompt().as_deref(),
+        Some("rerun the deploy workflow\nGit branch: main\nGlossary: kubectl.")
+    );
+}
+
+#[tokio::test]
+async fn unreachable_server_is_an_error() {
+    // Bind and release a port so nothing is listening on it.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .unwrap()
+        .port();
+    let url = format!("http://127.0.0.1:{}", port);
+
+    let err = upload_wav_with_key(&client(), &routes(), &wav(), &url, None, None)
+        .await
+        .unwrap_err();
+    assert!(err.is_outage() && !err.is_timeout(), "{}", err);
+    assert!(err.to_string().contains("HTTP request failed"), "{}", err);
+}
+
+#[tokio::test]
+async fn slow_server_is_a_timeout() {
+    let server = MockServer::start().await;
+    let slow = ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(2));
+    mount(&server, PIPELINE, slow, 1).await;
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_millis(100))
+        .build()
+        .unwrap();
+
+    let err = upload_wav_with_key(&client, &routes(), &wav(), &server.uri(), None, None)
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout(), "{}", err);
+    assert!(err.is_outage(), "{}", err);
+}
+
+#[test]
+fn signature_covers_timestamp_and_body() {
+    assert_eq!(
+        signature(b"secret", 1_700_000_000, br#"{"text":"hi"}"#),
+        "sha256=3ad1ab8e3e2036926574b48bb349a6926291abb28309d44d18066d4f51de2112"
+    );
+}
+
+#[tokio::test]
+async fn signed_upload_verifies_against_sent_body() {
+    let settings = SigningSettings::default();
+    let server = MockServer::start().await;
+    let (timestamp_header, signature_header) = (
+        settings.timestamp_header.clone(),
+        settings.signature_header.clone(),
+    );
+    Mock::given(method("POST"))
+        .and(path(PIPELINE))
+        .and(move |request: &Request| {
+            let value = |name: &str| {
+                request
+                    .headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+            };
+            let Some(timestamp) = value(&timestamp_header).and_then(|t| t.parse().ok()) else {
+                return false;
+            };
+            value(&signature_header) == Some(signature(b"shared", timestamp, &request.body))
+        })
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let signer = Signer::new("shared", &settings);
+    upload_wav_with_key(
+        &client(),
+        &routes(),
+        &wav(),
+        &server.uri(),
+        None,
+        Some(&signer),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn missing_pipeline_route_is_remembered() {
+    let server = MockServer::start().await;
+    mount(&server, PIPELINE, ResponseTemplate::new(404), 1).await;
+    mount(
+        &server,
+        TRANSCRIBE,
+        ResponseTemplate::new(200).set_body_json(json!({"text": "hi"})),
+        2,
+    )
+    .await;
+
+    let routes = routes();
+    for cached in [false, true] {
+        let result = upload_wav_with_key(&client(), &routes, &wav(), &server.uri(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(result["_endpoint_used"], "transcribe_fallback");
+        assert_eq!(
+            result["_fallback"],
+            json!({"status": 404, "cached": cached})
+        );
+    }
+}
+
+#[test]
+fn incremental_wav_matches_encode_wav() {
+    let samples: Vec<i16> = (0..5_000)
+        .map(|i| (i * 37 % 65_536 - 32_768) as i16)
+        .collect();
+    let mut encoder = WavEncoder::new();
+    for chunk in samples.chunks(441) {
+        encoder.push(chunk);
+    }
+    assert_eq!(encoder.len(), samples.len());
+    assert_eq!(encoder.finish(), encode_wav(&samples).unwrap());
+}
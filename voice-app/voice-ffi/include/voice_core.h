@@ -0,0 +1,58 @@
+/*
+ * C API of the voice app's capture-and-transcribe core (voice-ffi).
+ *
+ * Functions returning int give 0 on success and -1 on failure; the message
+ * is then available from voice_last_error() on the same thread.
+ */
+
+#ifndef VOICE_CORE_H
+#define VOICE_CORE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Rate of the samples returned by voice_capture_samples, in Hz. */
+#define VOICE_SAMPLE_RATE 16000
+
+typedef struct VoiceCapture VoiceCapture;
+
+/* A capture on the default input. Free with voice_capture_free. */
+VoiceCapture *voice_capture_new(void);
+
+/* Stops a running recording and frees the capture. */
+void voice_capture_free(VoiceCapture *capture);
+
+/* Start recording from the input device named device, or the default input
+ * when it is NULL. */
+int voice_capture_start(VoiceCapture *capture, const char *device);
+
+/* Stop recording and keep the take, replacing the previous one. */
+int voice_capture_stop(VoiceCapture *capture);
+
+/* The last take as 16 kHz mono samples, with the count in len. NULL when
+ * nothing has been recorded. Valid until the next voice_capture_stop or
+ * voice_capture_free. */
+const int16_t *voice_capture_samples(const VoiceCapture *capture, size_t *len);
+
+/* Upload the last take to the pipeline server at server_url, with api_key
+ * as bearer token when it is not NULL, and block until the response.
+ * Returns the JSON result, to be freed with voice_string_free, or NULL on
+ * failure. */
+char *voice_capture_send(VoiceCapture *capture, const char *server_url,
+                         const char *api_key);
+
+void voice_string_free(char *s);
+
+/* The message of the last failed call on this thread, or NULL. Valid until
+ * the next failing call on the same thread. */
+const char *voice_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* VOICE_CORE_H */
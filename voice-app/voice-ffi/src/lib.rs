@@ -0,0 +1,222 @@
+//! C ABI over `voice_core`: open the microphone, record, take the 16 kHz
+//! samples and send them to a pipeline server, from any language that can
+//! call C. The declarations are in `include/voice_core.h`.
+//!
+//! Functions returning `int` give 0 on success and -1 on failure; the
+//! message is then available from `voice_last_error` on the same thread.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+
+use voice_core::capture::{Capture, CaptureSettings, Recording};
+use voice_core::client::{upload_wav_with_key, RouteCache};
+use voice_core::devices::AudioSource;
+use voice_core::wav::encode_wav;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(error: String) {
+    let error = CString::new(error).unwrap_or_else(|_| c"Error message contained NUL".into());
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(error));
+}
+
+fn status(result: Result<(), String>) -> c_int {
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            set_error(e);
+            -1
+        }
+    }
+}
+
+/// `None` for a null pointer; an error for invalid UTF-8.
+///
+/// # Safety
+/// `s` is null or a valid NUL-terminated string.
+unsafe fn optional_str<'a>(s: *const c_char, name: &str) -> Result<Option<&'a str>, String> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map(Some)
+        .map_err(|_| format!("{} is not valid UTF-8", name))
+}
+
+/// One microphone and the last recording taken from it.
+pub struct VoiceCapture {
+    capture: Capture,
+    recording: Option<Recording>,
+    client: reqwest::Client,
+    routes: RouteCache,
+}
+
+impl VoiceCapture {
+    fn start(&mut self, device: Option<&str>) -> Result<(), String> {
+        let source = AudioSource {
+            device: device.map(str::to_string),
+            ..Default::default()
+        };
+        self.capture
+            .start(&source, &CaptureSettings::default())
+            .map_err(|e| e.to_string())
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        self.recording = Some(self.capture.stop().map_err(|e| e.to_string())?);
+        Ok(())
+    }
+
+    fn send(&mut self, server_url: &str, api_key: Option<&str>) -> Result<String, String> {
+        let recording = self
+            .recording
+            .as_mut()
+            .ok_or("Nothing recorded yet; call voice_capture_stop first")?;
+        let wav = match recording.wav.take() {
+            Some(wav) => wav,
+            None => encode_wav(&recording.samples)?,
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| format!("Failed to start runtime: {}", e))?;
+        let result = runtime.block_on(upload_wav_with_key(
+            &self.client,
+            &self.routes,
+            &wav,
+            server_url,
+            api_key,
+            None,
+        ));
+        // Kept so a failed send can be retried without re-encoding.
+        recording.wav = Some(wav);
+        Ok(result.map_err(|e| e.to_string())?.to_string())
+    }
+}
+
+/// A capture on the default input. Free with `voice_capture_free`.
+#[no_mangle]
+pub extern "C" fn voice_capture_new() -> *mut VoiceCapture {
+    Box::into_raw(Box::new(VoiceCapture {
+        capture: Capture::default(),
+        recording: None,
+        client: reqwest::Client::new(),
+        routes: RouteCache::default(),
+    }))
+}
+
+/// Stops a running recording and frees the capture.
+///
+/// # Safety
+/// `capture` is null or came from `voice_capture_new` and is not used again.
+#[no_mangle]
+pub unsafe extern "C" fn voice_capture_free(capture: *mut VoiceCapture) {
+    if capture.is_null() {
+        return;
+    }
+    let capture = Box::from_raw(capture);
+    if capture.capture.is_recording() {
+        let _ = capture.capture.stop();
+    }
+}
+
+/// Start recording from the input device named `device`, or the default
+/// input when it is null.
+///
+/// # Safety
+/// `capture` came from `voice_capture_new`; `device` is null or a valid
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn voice_capture_start(
+    capture: *mut VoiceCapture,
+    device: *const c_char,
+) -> c_int {
+    let Some(capture) = capture.as_mut() else {
+        return status(Err("capture is null".into()));
+    };
+    status(optional_str(device, "device").and_then(|device| capture.start(device)))
+}
+
+/// Stop recording and keep the take for `voice_capture_samples` and
+/// `voice_capture_send`, replacing the previous one.
+///
+/// # Safety
+/// `capture` came from `voice_capture_new`.
+#[no_mangle]
+pub unsafe extern "C" fn voice_capture_stop(capture: *mut VoiceCapture) -> c_int {
+    let Some(capture) = capture.as_mut() else {
+        return status(Err("capture is null".into()));
+    };
+    status(capture.stop())
+}
+
+/// The last take as 16 kHz mono samples, with the count in `len`. Null when
+/// nothing has been recorded. The pointer is valid until the next
+/// `voice_capture_stop` or `voice_capture_free`.
+///
+/// # Safety
+/// `capture` came from `voice_capture_new`; `len` is a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn voice_capture_samples(
+    capture: *const VoiceCapture,
+    len: *mut usize,
+) -> *const i16 {
+    let samples = capture
+        .as_ref()
+        .and_then(|c| c.recording.as_ref())
+        .map(|r| r.samples.as_slice());
+    if let Some(len) = len.as_mut() {
+        *len = samples.map_or(0, <[i16]>::len);
+    }
+    samples.map_or(ptr::null(), <[i16]>::as_ptr)
+}
+
+/// Upload the last take to the pipeline server at `server_url`, with
+/// `api_key` as bearer token when it is not null, and block until the
+/// response. Returns the JSON result, to be freed with `voice_string_free`,
+/// or null on failure.
+///
+/// # Safety
+/// `capture` came from `voice_capture_new`; `server_url` and `api_key` are
+/// null or valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn voice_capture_send(
+    capture: *mut VoiceCapture,
+    server_url: *const c_char,
+    api_key: *const c_char,
+) -> *mut c_char {
+    let result = (|| {
+        let capture = capture.as_mut().ok_or("capture is null")?;
+        let server_url = optional_str(server_url, "server_url")?.ok_or("server_url is null")?;
+        let api_key = optional_str(api_key, "api_key")?;
+        let json = capture.send(server_url, api_key)?;
+        CString::new(json).map_err(|e| e.to_string())
+    })();
+    match result {
+        Ok(json) => json.into_raw(),
+        Err(e) => {
+            set_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+/// `s` is null or came from `voice_capture_send` and is not used again.
+#[no_mangle]
+pub unsafe extern "C" fn voice_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// The message of the last failed call on this thread, or null. Valid until
+/// the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn voice_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
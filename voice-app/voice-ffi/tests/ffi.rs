@@ -0,0 +1,60 @@
+//! The C API's error paths, which need no input device: calls out of order
+//! fail with a message from `voice_last_error` instead of crashing.
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+use voice_ffi::*;
+
+fn last_error() -> String {
+    let error = voice_last_error();
+    assert!(!error.is_null());
+    unsafe { CStr::from_ptr(error) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn stop_without_start_fails() {
+    let capture = voice_capture_new();
+    unsafe {
+        assert_eq!(voice_capture_stop(capture), -1);
+        assert_eq!(last_error(), "Not recording");
+        voice_capture_free(capture);
+    }
+}
+
+#[test]
+fn no_samples_before_a_take() {
+    let capture = voice_capture_new();
+    let mut len = 1;
+    unsafe {
+        assert!(voice_capture_samples(capture, &mut len).is_null());
+        assert_eq!(len, 0);
+        voice_capture_free(capture);
+    }
+}
+
+#[test]
+fn send_before_a_take_fails() {
+    let capture = voice_capture_new();
+    let url = CString::new("http://127.0.0.1:1").unwrap();
+    unsafe {
+        assert!(voice_capture_send(capture, url.as_ptr(), ptr::null()).is_null());
+        assert!(last_error().starts_with("Nothing recorded yet"));
+        assert!(voice_capture_send(capture, ptr::null(), ptr::null()).is_null());
+        assert_eq!(last_error(), "server_url is null");
+        voice_capture_free(capture);
+    }
+}
+
+#[test]
+fn null_handles_are_rejected() {
+    unsafe {
+        assert_eq!(voice_capture_start(ptr::null_mut(), ptr::null()), -1);
+        assert_eq!(last_error(), "capture is null");
+        assert_eq!(voice_capture_stop(ptr::null_mut()), -1);
+        voice_capture_free(ptr::null_mut());
+        voice_string_free(ptr::null_mut());
+    }
+}
@@ -0,0 +1,158 @@
+//! Python bindings for the `voice_core` WAV encoder and pipeline-server
+//! client, so scripts and notebooks get the app's exact upload semantics:
+//! the pipeline route with its `/api/transcribe` fallback, the remembered
+//! missing route, hedged second requests and request signing.
+//!
+//! ```python
+//! import agentic_voice
+//!
+//! client = agentic_voice.Client("http://localhost:8000", api_key="...")
+//! result = client.upload(agentic_voice.encode_wav(samples))
+//! ```
+//!
+//! Server and encoder failures raise `agentic_voice.PipelineError` with the
+//! same message the app shows.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use voice_core::client::{
+    run_pipeline_text_with_key, transcribe_with_key, upload_wav_hedged_with_key, HedgeSettings,
+    RouteCache, UploadError,
+};
+use voice_core::signing::{Signer, SigningSettings};
+
+create_exception!(agentic_voice, PipelineError, PyException);
+
+fn to_py(py: Python<'_>, value: &serde_json::Value) -> PyResult<PyObject> {
+    let json = py.import("json")?;
+    Ok(json.call_method1("loads", (value.to_string(),))?.unbind())
+}
+
+/// 16 kHz mono 16-bit samples as a WAV file.
+#[pyfunction]
+fn encode_wav<'py>(py: Python<'py>, samples: Vec<i16>) -> PyResult<Bound<'py, PyBytes>> {
+    let wav = voice_core::wav::encode_wav(&samples).map_err(PipelineError::new_err)?;
+    Ok(PyBytes::new(py, &wav))
+}
+
+/// A pipeline server. Requests block the calling thread, not the
+/// interpreter.
+#[pyclass]
+struct Client {
+    server_url: String,
+    api_key: Option<String>,
+    signer: Option<Signer>,
+    hedge: HedgeSettings,
+    http: reqwest::Client,
+    routes: RouteCache,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Client {
+    fn block_on<T, F>(&self, py: Python<'_>, request: F) -> PyResult<T>
+    where
+        T: Send,
+        F: std::future::Future<Output = Result<T, UploadError>> + Send,
+    {
+        py.allow_threads(|| self.runtime.block_on(request))
+            .map_err(|e| PipelineError::new_err(e.to_string()))
+    }
+}
+
+#[pymethods]
+impl Client {
+    /// `signing_secret` signs every request as the app does with request
+    /// signing enabled. `hedge_delay_ms` races a second request against a
+    /// first one that hasn't answered by then: the same upload to
+    /// `hedge_server`, or this server's `/api/transcribe`.
+    #[new]
+    #[pyo3(signature = (server_url, api_key=None, signing_secret=None, hedge_delay_ms=None, hedge_server=None))]
+    fn new(
+        server_url: String,
+        api_key: Option<String>,
+        signing_secret: Option<String>,
+        hedge_delay_ms: Option<u64>,
+        hedge_server: Option<String>,
+    ) -> PyResult<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| PipelineError::new_err(format!("Failed to start runtime: {}", e)))?;
+        let hedge = HedgeSettings {
+            enabled: hedge_delay_ms.is_some(),
+            delay_ms: hedge_delay_ms.unwrap_or_default(),
+            second_server: hedge_server,
+        };
+        Ok(Self {
+            server_url,
+            api_key,
+            signer: signing_secret.map(|s| Signer::new(&s, &SigningSettings::default())),
+            hedge,
+            http: reqwest::Client::new(),
+            routes: RouteCache::default(),
+            runtime,
+        })
+    }
+
+    /// Run the pipeline on a WAV, falling back to plain transcription on
+    /// servers without the pipeline route. Fallback results carry
+    /// `_fallback` with the status that triggered it.
+    fn upload(&self, py: Python<'_>, wav: &[u8]) -> PyResult<PyObject> {
+        let result = self.block_on(
+            py,
+            upload_wav_hedged_with_key(
+                &self.http,
+                &self.routes,
+                wav,
+                &self.server_url,
+                self.api_key.as_deref(),
+                self.signer.as_ref(),
+                &self.hedge,
+            ),
+        )?;
+        to_py(py, &result)
+    }
+
+    /// Transcription only, without the pipeline route.
+    fn transcribe(&self, py: Python<'_>, wav: &[u8]) -> PyResult<PyObject> {
+        let result = self.block_on(
+            py,
+            transcribe_with_key(
+                &self.http,
+                wav,
+                &self.server_url,
+                self.api_key.as_deref(),
+                self.signer.as_ref(),
+                "transcribe",
+            ),
+        )?;
+        to_py(py, &result)
+    }
+
+    /// The pipeline on text that is already transcribed; `None` when the
+    /// server has no pipeline route.
+    fn run_pipeline_text(&self, py: Python<'_>, text: &str) -> PyResult<Option<PyObject>> {
+        let result = self.block_on(
+            py,
+            run_pipeline_text_with_key(
+                &self.http,
+                text,
+                &self.server_url,
+                self.api_key.as_deref(),
+                self.signer.as_ref(),
+            ),
+        )?;
+        result.map(|value| to_py(py, &value)).transpose()
+    }
+}
+
+#[pymodule]
+fn agentic_voice(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(encode_wav, m)?)?;
+    m.add_class::<Client>()?;
+    m.add("PipelineError", m.py().get_type::<PipelineError>())?;
+    Ok(())
+}
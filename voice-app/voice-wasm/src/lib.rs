@@ -0,0 +1,113 @@
+//! The `voice_core` pipeline client for browsers: built for wasm32 with
+//! `wasm-pack build voice-wasm`, requests go through `fetch`, and uploads
+//! get the app's route fallback, remembered missing route and hedging.
+//! Results are the server's JSON as plain objects, with the same
+//! `_endpoint_used` and `_fallback` fields the app sees.
+//!
+//! Request signing is left out: the shared secret would ship to every
+//! browser.
+
+use js_sys::JSON;
+use wasm_bindgen::prelude::*;
+
+use voice_core::client::{
+    run_pipeline_text_with_key, transcribe_with_key, upload_wav_hedged_with_key, HedgeSettings,
+    RouteCache,
+};
+
+fn to_js(value: &serde_json::Value) -> Result<JsValue, JsError> {
+    JSON::parse(&value.to_string()).map_err(|_| JsError::new("Server returned invalid JSON"))
+}
+
+/// 16 kHz mono 16-bit samples as a WAV file.
+#[wasm_bindgen(js_name = encodeWav)]
+pub fn encode_wav(samples: &[i16]) -> Result<Vec<u8>, JsError> {
+    voice_core::wav::encode_wav(samples).map_err(|e| JsError::new(&e))
+}
+
+/// A pipeline server.
+#[wasm_bindgen]
+pub struct Client {
+    server_url: String,
+    api_key: Option<String>,
+    hedge: HedgeSettings,
+    http: reqwest::Client,
+    routes: RouteCache,
+}
+
+#[wasm_bindgen]
+impl Client {
+    /// `hedgeDelayMs` races a second request against a first one that
+    /// hasn't answered by then: the same upload to `hedgeServer`, or this
+    /// server's `/api/transcribe`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        server_url: String,
+        api_key: Option<String>,
+        hedge_delay_ms: Option<u32>,
+        hedge_server: Option<String>,
+    ) -> Client {
+        Client {
+            server_url,
+            api_key,
+            hedge: HedgeSettings {
+                enabled: hedge_delay_ms.is_some(),
+                delay_ms: hedge_delay_ms.unwrap_or_default().into(),
+                second_server: hedge_server,
+            },
+            http: reqwest::Client::new(),
+            routes: RouteCache::default(),
+        }
+    }
+
+    /// Run the pipeline on a WAV, falling back to plain transcription on
+    /// servers without the pipeline route.
+    pub async fn upload(&self, wav: Vec<u8>) -> Result<JsValue, JsError> {
+        let result = upload_wav_hedged_with_key(
+            &self.http,
+            &self.routes,
+            &wav,
+            &self.server_url,
+            self.api_key.as_deref(),
+            None,
+            &self.hedge,
+        )
+        .await
+        .map_err(|e| JsError::new(&e.to_string()))?;
+        to_js(&result)
+    }
+
+    /// Transcription only, without the pipeline route.
+    pub async fn transcribe(&self, wav: Vec<u8>) -> Result<JsValue, JsError> {
+        let result = transcribe_with_key(
+            &self.http,
+            &wav,
+            &self.server_url,
+            self.api_key.as_deref(),
+            None,
+            "transcribe",
+        )
+        .await
+        .map_err(|e| JsError::new(&e.to_string()))?;
+        to_js(&result)
+    }
+
+    /// The pipeline on text that is already transcribed; `null` when the
+    /// server has no pipeline route.
+    #[wasm_bindgen(js_name = runPipelineText)]
+    pub async fn run_pipeline_text(&self, text: String) -> Result<JsValue, JsError> {
+        let result = run_pipeline_text_with_key(
+            &self.http,
+            &text,
+            &self.server_url,
+            self.api_key.as_deref(),
+            None,
+        )
+        .await
+        .map_err(|e| JsError::new(&e.to_string()))?;
+        match result {
+            Some(value) => to_js(&value),
+            None => Ok(JsValue::NULL),
+        }
+    }
+}